@@ -0,0 +1,10 @@
+-- 帖子转发与引用
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS post_type VARCHAR(10) NOT NULL DEFAULT 'original';
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS repost_of_id UUID REFERENCES posts(id);
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS repost_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS is_hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- 纯转发不允许重复
+CREATE UNIQUE INDEX IF NOT EXISTS idx_posts_unique_repost
+    ON posts(user_id, repost_of_id) WHERE post_type = 'repost';
+CREATE INDEX IF NOT EXISTS idx_posts_repost_of_id ON posts(repost_of_id);
@@ -0,0 +1,26 @@
+-- 交易凭证表，记录帖子引用的链上交易解析结果
+CREATE TABLE IF NOT EXISTS trade_proofs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    chain VARCHAR(10) NOT NULL,
+    tx_hash VARCHAR(100) NOT NULL,
+    from_address VARCHAR(100) NOT NULL,
+    to_address VARCHAR(100) NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_time BIGINT NOT NULL,
+    token_in VARCHAR(100),
+    token_in_symbol VARCHAR(50),
+    amount_in DOUBLE PRECISION,
+    token_out VARCHAR(100),
+    token_out_symbol VARCHAR(50),
+    amount_out DOUBLE PRECISION,
+    dex VARCHAR(50),
+    value_usd DOUBLE PRECISION,
+    status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CONSTRAINT trade_proofs_unique_tx UNIQUE (chain, tx_hash)
+);
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS trade_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_trade_proofs_user_id ON trade_proofs(user_id);
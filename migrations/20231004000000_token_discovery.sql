@@ -0,0 +1,17 @@
+-- 钱包持有过的代币合约（通过Transfer日志发现）
+CREATE TABLE IF NOT EXISTS wallet_tokens (
+    wallet_address VARCHAR(100) NOT NULL,
+    chain_id INTEGER NOT NULL,
+    contract_address VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, chain_id, contract_address)
+);
+
+-- 每个钱包的日志扫描进度
+CREATE TABLE IF NOT EXISTS token_scan_checkpoints (
+    wallet_address VARCHAR(100) NOT NULL,
+    chain_id INTEGER NOT NULL,
+    last_scanned_block BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, chain_id)
+);
@@ -0,0 +1,27 @@
+-- 钱包当前持有的NFT
+CREATE TABLE IF NOT EXISTS nft_assets (
+    wallet_address VARCHAR(100) NOT NULL,
+    chain_id INTEGER NOT NULL,
+    contract_address VARCHAR(100) NOT NULL,
+    token_id VARCHAR(100) NOT NULL,
+    token_type VARCHAR(20) NOT NULL, -- ERC721, ERC1155, Metaplex
+    balance VARCHAR(100) NOT NULL DEFAULT '1',
+    name VARCHAR(255) NOT NULL,
+    collection_name VARCHAR(255),
+    description TEXT,
+    image_url TEXT,
+    metadata_url TEXT,
+    attributes TEXT, -- 元数据中的attributes数组（JSON）
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, chain_id, contract_address, token_id)
+);
+
+-- 每个钱包的NFT日志扫描进度
+CREATE TABLE IF NOT EXISTS nft_scan_checkpoints (
+    wallet_address VARCHAR(100) NOT NULL,
+    chain_id INTEGER NOT NULL,
+    last_scanned_block BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, chain_id)
+);
@@ -0,0 +1,3 @@
+-- NFT所属集合（Metaplex集合mint）及验证状态
+ALTER TABLE nft_assets ADD COLUMN IF NOT EXISTS collection_address VARCHAR(100);
+ALTER TABLE nft_assets ADD COLUMN IF NOT EXISTS collection_verified BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,18 @@
+-- 钱包资产缓存（每次刷新按链整体替换）
+CREATE TABLE IF NOT EXISTS assets (
+    wallet_address VARCHAR(100) NOT NULL,
+    chain_id INTEGER NOT NULL,
+    asset_type VARCHAR(20) NOT NULL, -- native, token
+    symbol VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    contract_address VARCHAR(100),
+    balance DOUBLE PRECISION,
+    decimals INTEGER,
+    price_usd DOUBLE PRECISION,
+    value_usd DOUBLE PRECISION,
+    logo_url TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_assets_wallet ON assets(wallet_address, chain_id);
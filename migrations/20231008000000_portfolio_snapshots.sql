@@ -0,0 +1,15 @@
+-- 钱包资产快照（每次快照每个资产一行，用于资产走势和盈亏统计）
+CREATE TABLE IF NOT EXISTS portfolio_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    wallet_address VARCHAR(100) NOT NULL,
+    snapshot_at BIGINT NOT NULL, -- 快照时间（Unix秒），同一次快照的所有资产相同
+    chain_id INTEGER NOT NULL,
+    asset_type VARCHAR(20) NOT NULL, -- native, token, nft
+    symbol VARCHAR(255) NOT NULL,
+    contract_address VARCHAR(100),
+    balance DOUBLE PRECISION,
+    price_usd DOUBLE PRECISION,
+    value_usd DOUBLE PRECISION
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_snapshots_wallet ON portfolio_snapshots(wallet_address, snapshot_at);
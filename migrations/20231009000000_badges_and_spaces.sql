@@ -0,0 +1,34 @@
+-- 用户徽章（资产等级和指定资产持有者，定期按链上资产重新验证）
+CREATE TABLE IF NOT EXISTS user_badges (
+    user_id VARCHAR(100) NOT NULL,
+    badge VARCHAR(50) NOT NULL, -- 如 tier_100k、bayc_holder
+    badge_type VARCHAR(20) NOT NULL, -- tier, holder
+    label VARCHAR(100) NOT NULL,
+    chain_id INTEGER,
+    contract_address VARCHAR(100),
+    verified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, badge)
+);
+
+-- 资产门槛空间：只有满足持仓要求的钱包才能在对应标签下发帖和评论
+CREATE TABLE IF NOT EXISTS gated_spaces (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tag VARCHAR(50) UNIQUE NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    creator_id VARCHAR(100) NOT NULL,
+    requirement_type VARCHAR(20) NOT NULL, -- value, token, nft
+    chain_id INTEGER, -- value类型为空
+    contract_address VARCHAR(100), -- 为空表示原生币
+    min_amount DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- 空间成员资格，定期重新检查
+CREATE TABLE IF NOT EXISTS space_members (
+    space_id UUID NOT NULL REFERENCES gated_spaces(id) ON DELETE CASCADE,
+    user_id VARCHAR(100) NOT NULL,
+    eligible BOOLEAN NOT NULL,
+    checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (space_id, user_id)
+);
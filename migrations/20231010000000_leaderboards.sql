@@ -0,0 +1,5 @@
+-- 不参与排行榜的用户
+CREATE TABLE IF NOT EXISTS leaderboard_opt_outs (
+    user_id VARCHAR(100) PRIMARY KEY,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,33 @@
+-- 帖子和评论的永久存储状态：none（不需要上链）、pending、submitted、confirmed、failed
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS storage_status VARCHAR(20) NOT NULL DEFAULT 'pending';
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS storage_status VARCHAR(20) NOT NULL DEFAULT 'pending';
+
+-- 已有内容在上传时同步完成，有交易ID的视为已确认
+UPDATE posts SET storage_status = CASE WHEN arweave_tx_id IS NULL THEN 'none' ELSE 'confirmed' END;
+UPDATE comments SET storage_status = CASE WHEN arweave_tx_id IS NULL THEN 'none' ELSE 'confirmed' END;
+
+-- 待上传到Arweave/IPFS的内容，由后台任务上传并跟踪确认
+CREATE TABLE IF NOT EXISTS storage_outbox (
+    id UUID PRIMARY KEY,
+    content_table VARCHAR(20) NOT NULL,
+    content_id VARCHAR(100) NOT NULL,
+    backend VARCHAR(20) NOT NULL,
+    payload TEXT NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    tags TEXT NOT NULL DEFAULT '[]',
+    expected_ref VARCHAR(100),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    item_id VARCHAR(100),
+    tx_id VARCHAR(100),
+    confirmations INTEGER NOT NULL DEFAULT 0,
+    submitted_at BIGINT,
+    confirmed_at BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_outbox_status ON storage_outbox(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_storage_outbox_tx_id ON storage_outbox(tx_id);
@@ -0,0 +1,8 @@
+-- 上链内容的信封加密：主密钥ID和加密后的数据密钥，轮换主密钥时重新加密数据密钥
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_key_id VARCHAR(64);
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_wrapped_key TEXT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_key_id VARCHAR(64);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_wrapped_key TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_posts_content_key_id ON posts(content_key_id);
+CREATE INDEX IF NOT EXISTS idx_comments_content_key_id ON comments(content_key_id);
@@ -0,0 +1,8 @@
+-- 正文的SHA-256，用于核对数据库内容与永久存储是否一致
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_hash VARCHAR(64);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_hash VARCHAR(64);
+
+UPDATE posts SET content_hash = encode(sha256(convert_to(content, 'UTF8')), 'hex')
+WHERE content_hash IS NULL AND content <> '';
+UPDATE comments SET content_hash = encode(sha256(convert_to(content, 'UTF8')), 'hex')
+WHERE content_hash IS NULL;
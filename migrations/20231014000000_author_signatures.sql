@@ -0,0 +1,9 @@
+-- 作者钱包对内容规范消息的签名，签名同时写入Arweave标签
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS author_signature TEXT;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS signed_at BIGINT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS author_signature TEXT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS signed_at BIGINT;
+
+-- 同一签名只能使用一次，防止重放
+CREATE UNIQUE INDEX IF NOT EXISTS idx_posts_author_signature ON posts(author_signature) WHERE author_signature IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_comments_author_signature ON comments(author_signature) WHERE author_signature IS NOT NULL;
@@ -0,0 +1,9 @@
+-- 发件箱记录的内容类型：media（图片等媒体文件）、record（帖子和评论正文），各自可配置存储后端
+ALTER TABLE storage_outbox ADD COLUMN IF NOT EXISTS kind VARCHAR(20) NOT NULL DEFAULT 'record';
+
+-- 已有记录中IPFS上传的都是媒体文件
+UPDATE storage_outbox SET kind = 'media' WHERE backend = 'ipfs';
+
+-- 内容引用不再只是CID和Arweave ID，S3/本地存储使用SHA-256十六进制
+ALTER TABLE storage_outbox ALTER COLUMN expected_ref TYPE VARCHAR(200);
+ALTER TABLE storage_outbox ALTER COLUMN item_id TYPE VARCHAR(200);
@@ -0,0 +1,3 @@
+-- 资产价格的来源和来源给出的价格时间
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS price_source VARCHAR(50);
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS price_updated_at BIGINT;
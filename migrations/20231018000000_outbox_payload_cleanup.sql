@@ -0,0 +1,4 @@
+-- 确认后清除发件箱中的内容副本，只保留引用和状态
+ALTER TABLE storage_outbox ALTER COLUMN payload DROP NOT NULL;
+
+UPDATE storage_outbox SET payload = NULL WHERE status = 'confirmed';
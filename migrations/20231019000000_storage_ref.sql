@@ -0,0 +1,14 @@
+-- 正文在记录存储后端中的引用，与后端无关；arweave_tx_id只记录Arweave数据项ID
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS storage_ref VARCHAR(200);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS storage_ref VARCHAR(200);
+
+UPDATE posts SET storage_ref = arweave_tx_id WHERE storage_ref IS NULL AND arweave_tx_id IS NOT NULL;
+UPDATE comments SET storage_ref = arweave_tx_id WHERE storage_ref IS NULL AND arweave_tx_id IS NOT NULL;
+
+-- 之前写入arweave_tx_id的其他后端引用移除
+UPDATE posts p SET arweave_tx_id = NULL FROM storage_outbox o
+WHERE o.content_table = 'posts' AND o.content_id = p.id::text AND o.kind = 'record'
+  AND o.backend <> 'arweave' AND o.item_id = p.arweave_tx_id;
+UPDATE comments c SET arweave_tx_id = NULL FROM storage_outbox o
+WHERE o.content_table = 'comments' AND o.content_id = c.id::text AND o.kind = 'record'
+  AND o.backend <> 'arweave' AND o.item_id = c.arweave_tx_id;
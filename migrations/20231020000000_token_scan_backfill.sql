@@ -0,0 +1,7 @@
+-- 新钱包先扫描最近的区块，更早的区块由后台任务向前回溯
+-- first_scanned_block为已扫描区间的起点，已有检查点都是从起始区块开始扫描的，视为回溯完成
+ALTER TABLE token_scan_checkpoints ADD COLUMN IF NOT EXISTS first_scanned_block BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE token_scan_checkpoints ADD COLUMN IF NOT EXISTS backfill_complete BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE INDEX IF NOT EXISTS idx_token_scan_checkpoints_backfill
+    ON token_scan_checkpoints (updated_at) WHERE NOT backfill_complete;
@@ -0,0 +1,146 @@
+use crate::blockchain::provider_pool;
+use crate::middlewares::auth::AuthenticatedUser;
+use crate::services::asset_service::normalize_address;
+use crate::services::job_service::JobScheduler;
+use crate::services::storage_service::StorageService;
+use crate::utils::error::ServiceError;
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::env;
+use std::sync::Arc;
+
+// 批量校验的默认和最大数量
+const DEFAULT_VERIFY_LIMIT: u64 = 20;
+const MAX_VERIFY_LIMIT: u64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyQuery {
+    pub limit: Option<u64>,
+}
+
+// 管理员钱包地址，通过 ADMIN_WALLETS 配置，多个地址用逗号分隔
+pub(crate) fn is_admin(auth_user: &AuthenticatedUser) -> bool {
+    let wallet = normalize_address(&auth_user.wallet_address);
+
+    env::var("ADMIN_WALLETS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .any(|a| normalize_address(a) == wallet)
+}
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden().json(serde_json::json!({
+        "status": "error",
+        "message": "需要管理员权限"
+    }))
+}
+
+/// 获取所有后台任务的运行状态
+pub async fn get_jobs(
+    auth_user: AuthenticatedUser,
+    scheduler: web::Data<Arc<JobScheduler>>,
+) -> impl Responder {
+    if !is_admin(&auth_user) {
+        return forbidden();
+    }
+
+    HttpResponse::Ok().json(scheduler.list_status().await)
+}
+
+/// 立即运行指定后台任务
+pub async fn run_job(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    scheduler: web::Data<Arc<JobScheduler>>,
+) -> impl Responder {
+    if !is_admin(&auth_user) {
+        return forbidden();
+    }
+    let name = path.into_inner();
+
+    match JobScheduler::trigger(&scheduler, &name).await {
+        Ok(_) => HttpResponse::Accepted().json(serde_json::json!({
+            "status": "success",
+            "message": format!("任务{}已开始运行", name)
+        })),
+        Err(ServiceError::NotFound(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("运行任务失败: {}", err)
+        })),
+    }
+}
+
+/// 获取EVM RPC节点统计
+pub async fn get_rpc_metrics(auth_user: AuthenticatedUser) -> impl Responder {
+    if !is_admin(&auth_user) {
+        return forbidden();
+    }
+
+    HttpResponse::Ok().json(provider_pool::all_metrics())
+}
+
+/// 重新下载并解密最近上链的内容，与数据库比对
+pub async fn verify_recent_content(
+    query: web::Query<VerifyQuery>,
+    auth_user: AuthenticatedUser,
+    storage_service: web::Data<Arc<StorageService>>,
+) -> impl Responder {
+    if !is_admin(&auth_user) {
+        return forbidden();
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_VERIFY_LIMIT).clamp(1, MAX_VERIFY_LIMIT);
+
+    match storage_service.verify_recent(limit).await {
+        Ok(results) => HttpResponse::Ok().json(results),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("校验上链内容失败: {}", err)
+        })),
+    }
+}
+
+/// 重新下载并解密指定内容，与数据库比对
+pub async fn verify_content(
+    path: web::Path<(String, String)>,
+    auth_user: AuthenticatedUser,
+    storage_service: web::Data<Arc<StorageService>>,
+) -> impl Responder {
+    if !is_admin(&auth_user) {
+        return forbidden();
+    }
+    let (content_table, content_id) = path.into_inner();
+
+    match storage_service.verify_content(&content_table, &content_id).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(ServiceError::NotFound(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(ServiceError::BadRequest(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("校验上链内容失败: {}", err)
+        })),
+    }
+}
+
+/// 配置管理路由
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .route("/jobs", web::get().to(get_jobs))
+            .route("/jobs/{name}/run", web::post().to(run_job))
+            .route("/rpc", web::get().to(get_rpc_metrics))
+            .route("/storage/verify", web::get().to(verify_recent_content))
+            .route("/storage/verify/{table}/{id}", web::get().to(verify_content)),
+    );
+}
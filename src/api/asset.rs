@@ -1,157 +1,186 @@
-use crate::services::asset_service::AssetService;
+use crate::services::asset_service::{validate_wallet_address, wallet_scope, AssetService};
+use crate::services::portfolio_service::PortfolioService;
 use crate::utils::error::ServiceError;
 use crate::middlewares::auth::AuthenticatedUser;
 use actix_web::{web, HttpResponse, Responder};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::sync::Arc;
 
+/// 资产查询参数
+#[derive(Debug, Deserialize)]
+pub struct AssetQuery {
+    pub chain: Option<String>, // SOL或EVM链简称，如 ETH、ARB，不指定时按地址格式查询
+}
+
+/// 资产历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub range: Option<String>, // 24h、7d、30d、90d、1y、all，默认30d
+    pub start: Option<String>, // 盈亏起始日期，YYYY-MM-DD或Unix秒，默认为区间内第一次快照
+}
+
+// 资产查询失败时的响应
+fn error_response(message: &str, err: ServiceError) -> HttpResponse {
+    let body = serde_json::json!({
+        "status": "error",
+        "message": format!("{}: {}", message, err)
+    });
+
+    match err {
+        ServiceError::BadRequest(_) => HttpResponse::BadRequest().json(body),
+        _ => HttpResponse::InternalServerError().json(body),
+    }
+}
+
 /// 获取用户资产列表
 pub async fn get_assets(
     auth_user: AuthenticatedUser,
-    user_service: web::Data<Arc<crate::services::user_service::UserService>>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
-    // 获取用户钱包地址
-    let wallet_address: String = match user_service.get_wallet_address_by_user_id(auth_user.user_id).await {
-        Ok(address) => address,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": "获取钱包地址失败"
-            }));
-        }
-    };
-
-    // 获取用户资产
-    match asset_service.get_user_assets(&wallet_address).await {
+    match asset_service
+        .get_wallet_assets(&auth_user.wallet_address, wallet_scope(&auth_user.wallet_chain))
+        .await
+    {
+        Ok(assets) => HttpResponse::Ok().json(assets),
+        Err(err) => error_response("获取资产失败", err),
+    }
+}
+
+/// 刷新用户资产
+pub async fn refresh_assets(
+    auth_user: AuthenticatedUser,
+    asset_service: web::Data<Arc<AssetService>>,
+) -> impl Responder {
+    match asset_service
+        .request_refresh(&auth_user.wallet_address, wallet_scope(&auth_user.wallet_chain))
+        .await
+    {
         Ok(assets) => HttpResponse::Ok().json(assets),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取资产失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("刷新资产失败", err),
     }
 }
 
 /// 获取特定钱包地址的资产
 pub async fn get_wallet_assets(
+    _auth_user: AuthenticatedUser,
     path: web::Path<String>,
+    query: web::Query<AssetQuery>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
     let wallet_address = path.into_inner();
-    
+
     // 获取指定钱包的资产
-    match asset_service.get_user_assets(&wallet_address).await {
+    match asset_service
+        .get_wallet_assets(&wallet_address, query.chain.as_deref())
+        .await
+    {
         Ok(assets) => HttpResponse::Ok().json(assets),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取资产失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("获取资产失败", err),
     }
 }
 
 /// 获取用户NFT资产
 pub async fn get_nfts(
     auth_user: AuthenticatedUser,
-    user_service: web::Data<Arc<crate::services::user_service::UserService>>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
-    // 获取用户钱包地址
-    let wallet_address = match user_service.get_wallet_address_by_user_id(auth_user.user_id).await {
-        Ok(address) => address,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": "获取钱包地址失败"
-            }));
-        }
-    };
-
-    // 获取用户NFT
-    match asset_service.get_user_nfts(&wallet_address).await {
+    match asset_service
+        .get_wallet_nfts(&auth_user.wallet_address, wallet_scope(&auth_user.wallet_chain))
+        .await
+    {
         Ok(nfts) => HttpResponse::Ok().json(nfts),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取NFT资产失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("获取NFT资产失败", err),
     }
 }
 
 /// 获取指定钱包的NFT资产
 pub async fn get_wallet_nfts(
+    _auth_user: AuthenticatedUser,
     path: web::Path<String>,
+    query: web::Query<AssetQuery>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
     let wallet_address = path.into_inner();
-    
+
     // 获取指定钱包的NFT
-    match asset_service.get_user_nfts(&wallet_address).await {
+    match asset_service
+        .get_wallet_nfts(&wallet_address, query.chain.as_deref())
+        .await
+    {
         Ok(nfts) => HttpResponse::Ok().json(nfts),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取NFT资产失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("获取NFT资产失败", err),
     }
 }
 
 /// 获取资产总价值
 pub async fn get_total_value(
     auth_user: AuthenticatedUser,
-    user_service: web::Data<Arc<crate::services::user_service::UserService>>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
-    // 获取用户钱包地址
-    let wallet_address = match user_service.get_wallet_address_by_user_id(auth_user.user_id).await {
-        Ok(address) => address,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": "获取钱包地址失败"
-            }));
-        }
-    };
-
     // 获取总价值
-    match asset_service.get_total_value(&wallet_address).await {
+    match asset_service.get_total_value(&auth_user.wallet_address).await {
         Ok(value) => HttpResponse::Ok().json(serde_json::json!({
             "total_value": value,
             "currency": "USD"
         })),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取资产总价值失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("获取资产总价值失败", err),
     }
 }
 
 /// 获取指定钱包的资产总价值
 pub async fn get_wallet_total_value(
+    _auth_user: AuthenticatedUser,
     path: web::Path<String>,
     asset_service: web::Data<Arc<AssetService>>,
 ) -> impl Responder {
     let wallet_address = path.into_inner();
-    
+
     // 获取总价值
     match asset_service.get_total_value(&wallet_address).await {
         Ok(value) => HttpResponse::Ok().json(serde_json::json!({
             "total_value": value,
             "currency": "USD"
         })),
-        Err(err) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": format!("获取资产总价值失败: {}", err)
-            }))
-        }
+        Err(err) => error_response("获取资产总价值失败", err),
+    }
+}
+
+/// 获取用户资产历史
+pub async fn get_history(
+    auth_user: AuthenticatedUser,
+    query: web::Query<HistoryQuery>,
+    portfolio_service: web::Data<Arc<PortfolioService>>,
+) -> impl Responder {
+    let range = query.range.as_deref().unwrap_or("30d");
+
+    match portfolio_service
+        .get_history(&auth_user.wallet_address, range, query.start.as_deref())
+        .await
+    {
+        Ok(history) => HttpResponse::Ok().json(history),
+        Err(err) => error_response("获取资产历史失败", err),
+    }
+}
+
+/// 获取指定钱包的资产历史
+pub async fn get_wallet_history(
+    _auth_user: AuthenticatedUser,
+    path: web::Path<String>,
+    query: web::Query<HistoryQuery>,
+    portfolio_service: web::Data<Arc<PortfolioService>>,
+) -> impl Responder {
+    let wallet_address = path.into_inner();
+    if let Err(err) = validate_wallet_address(&wallet_address, None) {
+        return error_response("获取资产历史失败", err);
+    }
+    let range = query.range.as_deref().unwrap_or("30d");
+
+    match portfolio_service
+        .get_history(&wallet_address, range, query.start.as_deref())
+        .await
+    {
+        Ok(history) => HttpResponse::Ok().json(history),
+        Err(err) => error_response("获取资产历史失败", err),
     }
 }
 
@@ -161,11 +190,14 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         web::scope("/assets")
             // 当前用户资产相关
             .route("/me", web::get().to(get_assets))
+            .route("/me/refresh", web::post().to(refresh_assets))
             .route("/me/nfts", web::get().to(get_nfts))
             .route("/me/total", web::get().to(get_total_value))
-            // 查询指定钱包资产
+            .route("/me/history", web::get().to(get_history))
+            // 查询指定钱包资产，需登录；刷新只能通过 /me/refresh 刷新自己的钱包
             .route("/wallet/{address}", web::get().to(get_wallet_assets))
             .route("/wallet/{address}/nfts", web::get().to(get_wallet_nfts))
             .route("/wallet/{address}/total", web::get().to(get_wallet_total_value))
+            .route("/wallet/{address}/history", web::get().to(get_wallet_history))
     );
-} 
\ No newline at end of file
+}
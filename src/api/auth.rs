@@ -4,12 +4,19 @@ use actix_web::{web, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    wallet_address: String,
+    chain_type: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WalletLoginRequest {
     wallet_address: String,
     chain_type: String,
     signature: String,
-    message: String,
+    challenge_id: String,
+    message: String, // 钱包签名的登录消息，须与挑战返回的一致
 }
 
 #[derive(Debug, Serialize)]
@@ -18,6 +25,33 @@ pub struct LoginResponse {
     user_id: Option<i32>,
 }
 
+/// 获取登录挑战，客户端用钱包对返回的message签名后调用登录接口
+pub async fn login_challenge(
+    data: web::Json<ChallengeRequest>,
+    user_service: web::Data<Arc<UserService>>,
+) -> impl Responder {
+    let data = data.into_inner();
+    match user_service
+        .create_login_challenge(data.wallet_address, data.chain_type)
+        .await
+    {
+        Ok((challenge, message)) => HttpResponse::Ok().json(serde_json::json!({
+            "challenge_id": challenge.id,
+            "nonce": challenge.nonce,
+            "message": message,
+            "expires_at": challenge.expires_at
+        })),
+        Err(ServiceError::BadRequest(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("生成登录挑战失败: {}", err)
+        })),
+    }
+}
+
 /// 钱包登录接口
 pub async fn wallet_login(
     data: web::Json<WalletLoginRequest>,
@@ -28,6 +62,7 @@ pub async fn wallet_login(
         data.wallet_address.clone(),
         data.chain_type.clone(),
         data.signature.clone(),
+        data.challenge_id.clone(),
         data.message.clone(),
     ).await {
         Ok(token) => {
@@ -41,10 +76,16 @@ pub async fn wallet_login(
         Err(err) => {
             // 登录失败，返回错误信息
             match err {
-                ServiceError::AuthenticationError(_) => {
+                ServiceError::AuthenticationError(msg) => {
                     HttpResponse::Unauthorized().json(serde_json::json!({
                         "status": "error",
-                        "message": "签名验证失败"
+                        "message": msg
+                    }))
+                },
+                ServiceError::BadRequest(msg) => {
+                    HttpResponse::BadRequest().json(serde_json::json!({
+                        "status": "error",
+                        "message": msg
                     }))
                 },
                 _ => {
@@ -72,6 +113,7 @@ pub async fn verify_token() -> impl Responder {
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
+            .route("/challenge", web::post().to(login_challenge))
             .route("/wallet-login", web::post().to(wallet_login))
             .route("/verify", web::get().to(verify_token))
     );
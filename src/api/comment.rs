@@ -1,15 +1,17 @@
 use crate::services::content_service::ContentService;
 use crate::services::user_service::UserService;
+use crate::utils::authorship::AuthorSignature;
 use crate::utils::error::ServiceError;
 use crate::middlewares::auth::AuthenticatedUser;
 use actix_web::{web, HttpResponse, Responder};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use std::sync::Arc;
 
 #[derive(Debug, Deserialize)]
 pub struct CreateCommentRequest {
     content: String,
     parent_id: Option<String>, // 回复的父评论ID，如果是直接评论帖子则为None
+    signature: Option<AuthorSignature>, // 可选的作者签名，对规范消息签名
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,27 +26,16 @@ pub async fn create_comment(
     data: web::Json<CreateCommentRequest>,
     auth_user: AuthenticatedUser,
     content_service: web::Data<Arc<ContentService>>,
-    user_service: web::Data<Arc<UserService>>,
 ) -> impl Responder {
     let post_id: String = path.into_inner();
-    
-    // 获取用户钱包地址
-    let wallet_address = match user_service.get_wallet_address_by_user_id(auth_user.user_id).await {
-        Ok(address) => address,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "status": "error",
-                "message": "获取钱包地址失败"
-            }));
-        }
-    };
 
     // 创建评论
     match content_service.create_comment(
         auth_user.user_id,
         post_id,
         &data.content,
-        data.parent_id,
+        data.parent_id.clone(),
+        data.signature.clone(),
     ).await {
         Ok(comment) => HttpResponse::Created().json(comment),
         Err(err) => {
@@ -55,6 +46,19 @@ pub async fn create_comment(
                         "message": msg
                     }))
                 },
+                ServiceError::BadRequest(msg) => {
+                    HttpResponse::BadRequest().json(serde_json::json!({
+                        "status": "error",
+                        "message": msg
+                    }))
+                },
+                // 门槛空间内的帖子，不满足持仓要求
+                ServiceError::Unauthorized(msg) => {
+                    HttpResponse::Forbidden().json(serde_json::json!({
+                        "status": "error",
+                        "message": msg
+                    }))
+                },
                 _ => {
                     HttpResponse::InternalServerError().json(serde_json::json!({
                         "status": "error",
@@ -95,7 +99,7 @@ pub async fn get_post_comments(
     
     for comment in comments {
         // 获取评论作者信息
-        let author = match user_service.get_profile(comment.user_id).await {
+        let author = match user_service.get_profile(comment.user_id.clone()).await {
             Ok(profile) => profile,
             Err(_) => {
                 continue; // 如果获取作者信息失败，跳过该评论
@@ -141,7 +145,7 @@ pub async fn get_comment_replies(
     
     for reply in replies {
         // 获取回复作者信息
-        let author = match user_service.get_profile(reply.user_id).await {
+        let author = match user_service.get_profile(reply.user_id.clone()).await {
             Ok(profile) => profile,
             Err(_) => {
                 continue; // 如果获取作者信息失败，跳过该回复
@@ -188,7 +192,7 @@ pub async fn get_comment(
     };
 
     // 获取评论作者信息
-    let author = match user_service.get_profile(comment.user_id).await {
+    let author = match user_service.get_profile(comment.user_id.clone()).await {
         Ok(profile) => profile,
         Err(_) => {
             return HttpResponse::InternalServerError().json(serde_json::json!({
@@ -213,13 +217,5 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             // 获取评论的回复
             .route("/{comment_id}/replies", web::get().to(get_comment_replies))
     );
-    
-    // 帖子评论相关路由，放在posts命名空间下
-    cfg.service(
-        web::scope("/posts")
-            // 创建评论
-            .route("/{post_id}/comments", web::post().to(create_comment))
-            // 获取帖子的评论
-            .route("/{post_id}/comments", web::get().to(get_post_comments))
-    );
+    // 帖子评论路由注册在 api::post 的 /posts scope 中
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use crate::middlewares::auth::AuthenticatedUser;
+use crate::services::leaderboard_service::LeaderboardService;
+use crate::utils::error::ServiceError;
+use crate::utils::pagination::Pagination;
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+// 排行榜相关错误统一转换为HTTP响应
+fn leaderboard_error_response(err: ServiceError, action: &str) -> HttpResponse {
+    match err {
+        ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        _ => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("{}: {}", action, err)
+        })),
+    }
+}
+
+/// 分页获取排行榜，board为 value、pnl_7d、pnl_30d、trades 或 engagement
+pub async fn get_leaderboard(
+    path: web::Path<String>,
+    query: web::Query<LeaderboardQuery>,
+    leaderboard_service: web::Data<Arc<LeaderboardService>>,
+) -> impl Responder {
+    let board = path.into_inner();
+    let pagination = Pagination::new(query.page, query.per_page);
+
+    match leaderboard_service.get_board(&board, &pagination).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(err) => leaderboard_error_response(err, "获取排行榜失败"),
+    }
+}
+
+/// 获取当前用户在排行榜中的排名
+pub async fn get_my_rank(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    leaderboard_service: web::Data<Arc<LeaderboardService>>,
+) -> impl Responder {
+    let board = path.into_inner();
+
+    match leaderboard_service.get_rank(&board, &auth_user.user_id).await {
+        Ok(rank) => HttpResponse::Ok().json(rank),
+        Err(err) => leaderboard_error_response(err, "获取排名失败"),
+    }
+}
+
+/// 退出排行榜
+pub async fn opt_out(
+    auth_user: AuthenticatedUser,
+    leaderboard_service: web::Data<Arc<LeaderboardService>>,
+) -> impl Responder {
+    match leaderboard_service.opt_out(&auth_user.user_id).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "已退出排行榜"
+        })),
+        Err(err) => leaderboard_error_response(err, "退出排行榜失败"),
+    }
+}
+
+/// 重新加入排行榜
+pub async fn opt_in(
+    auth_user: AuthenticatedUser,
+    leaderboard_service: web::Data<Arc<LeaderboardService>>,
+) -> impl Responder {
+    match leaderboard_service.opt_in(&auth_user.user_id).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "已加入排行榜，将在下次更新后显示"
+        })),
+        Err(err) => leaderboard_error_response(err, "加入排行榜失败"),
+    }
+}
+
+/// 配置排行榜路由
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/leaderboards")
+            .route("/opt-out", web::post().to(opt_out))
+            .route("/opt-in", web::post().to(opt_in))
+            .route("/{board}", web::get().to(get_leaderboard))
+            .route("/{board}/me", web::get().to(get_my_rank)),
+    );
+}
@@ -0,0 +1,171 @@
+use crate::middlewares::auth::AuthenticatedUser;
+use crate::services::media_service::{MediaService, MAX_MEDIA_PER_POST};
+use crate::utils::error::ServiceError;
+use crate::utils::media::{self, MediaKind, MEDIA_SNIFF_LEN};
+use actix_multipart::Multipart;
+use actix_web::{web, HttpResponse, Responder};
+use futures::StreamExt;
+use std::sync::Arc;
+
+/// 上传媒体文件（multipart/form-data，可包含多个文件字段）
+pub async fn upload_media(
+    auth_user: AuthenticatedUser,
+    mut payload: Multipart,
+    media_service: web::Data<Arc<MediaService>>,
+) -> impl Responder {
+    let mut files: Vec<Vec<u8>> = Vec::new();
+
+    // 读取所有文件字段
+    while let Some(field) = payload.next().await {
+        let mut field = match field {
+            Ok(field) => field,
+            Err(_) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": "上传数据格式错误"
+                }));
+            }
+        };
+
+        if files.len() >= MAX_MEDIA_PER_POST {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("单次最多上传{}个文件", MAX_MEDIA_PER_POST)
+            }));
+        }
+
+        let mut data = Vec::new();
+        let mut kind: Option<MediaKind> = None;
+        while let Some(chunk) = field.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(_) => {
+                    return HttpResponse::BadRequest().json(serde_json::json!({
+                        "status": "error",
+                        "message": "读取上传文件失败"
+                    }));
+                }
+            };
+            data.extend_from_slice(&chunk);
+
+            // 收到足够的文件头后立即识别类型，不支持的文件不再继续读取
+            if kind.is_none() && data.len() >= MEDIA_SNIFF_LEN {
+                match media::detect_media_kind(&data) {
+                    Some(detected) => kind = Some(detected),
+                    None => return unsupported_media(),
+                }
+            }
+
+            // 按类型限制大小，超过时提前终止，避免占用过多内存
+            if let Some(kind) = kind {
+                if data.len() > kind.max_size() {
+                    return HttpResponse::PayloadTooLarge().json(serde_json::json!({
+                        "status": "error",
+                        "message": format!(
+                            "文件过大，{}最大支持{}MB",
+                            kind.category(),
+                            kind.max_size() / 1024 / 1024
+                        )
+                    }));
+                }
+            }
+        }
+
+        // 小于文件头长度的文件在读完后识别
+        if kind.is_none() && !data.is_empty() && media::detect_media_kind(&data).is_none() {
+            return unsupported_media();
+        }
+
+        if !data.is_empty() {
+            files.push(data);
+        }
+    }
+
+    if files.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "未找到上传文件"
+        }));
+    }
+
+    // 逐个处理并上传
+    let mut uploaded = Vec::with_capacity(files.len());
+    for data in files {
+        match media_service
+            .upload_media(auth_user.user_id.clone(), data)
+            .await
+        {
+            Ok(media) => {
+                let (url, thumbnail_url) = media_service.get_media_urls(&media);
+                uploaded.push(serde_json::json!({
+                    "media": media,
+                    "url": url,
+                    "thumbnail_url": thumbnail_url
+                }));
+            }
+            Err(ServiceError::BadRequest(msg)) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": msg
+                }));
+            }
+            Err(err) => {
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("上传媒体失败: {}", err)
+                }));
+            }
+        }
+    }
+
+    HttpResponse::Created().json(uploaded)
+}
+
+fn unsupported_media() -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "status": "error",
+        "message": "不支持的媒体类型"
+    }))
+}
+
+/// 获取媒体详情
+pub async fn get_media(
+    path: web::Path<String>,
+    media_service: web::Data<Arc<MediaService>>,
+) -> impl Responder {
+    let media_id = path.into_inner();
+
+    match media_service.get_media(&media_id).await {
+        Ok(media) => {
+            let (url, thumbnail_url) = media_service.get_media_urls(&media);
+            HttpResponse::Ok().json(serde_json::json!({
+                "media": media,
+                "url": url,
+                "thumbnail_url": thumbnail_url
+            }))
+        }
+        Err(err) => match err {
+            ServiceError::NotFound(_) => HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": "媒体不存在"
+            })),
+            ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": msg
+            })),
+            _ => HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": format!("获取媒体失败: {}", err)
+            })),
+        },
+    }
+}
+
+/// 配置Media路由
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/media")
+            .route("", web::post().to(upload_media))
+            .route("/{media_id}", web::get().to(get_media)),
+    );
+}
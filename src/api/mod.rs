@@ -1,8 +1,12 @@
 pub mod auth;
 pub mod user;
-// pub mod asset;
+pub mod asset;
 pub mod post;
 pub mod comment;
+pub mod media;
+pub mod space;
+pub mod leaderboard;
+pub mod admin;
 
 use actix_web::{HttpResponse, web};
 
@@ -1,19 +1,45 @@
+use crate::api::comment;
+use crate::blockchain::chains;
 use crate::middlewares::auth::AuthenticatedUser;
-use crate::services::content_service::ContentService;
-use crate::services::storage_service::StorageService;
+use crate::models::rbatis_entities::{PostEntity, UserBadgeEntity};
+use crate::services::badge_service::BadgeService;
+use crate::services::content_service::{ContentService, NewPost};
+use crate::services::media_service::MediaService;
+use crate::services::storage_service::{StorageService, CONTENT_TABLE_POSTS};
 use crate::services::user_service::UserService;
+use crate::utils::authorship::AuthorSignature;
 use crate::utils::error::ServiceError;
 use actix_web::{web, HttpResponse, Responder};
 use base64;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Deserialize)]
 pub struct CreatePostRequest {
     content: String,
     image_data: Option<String>, // Base64编码的图片数据
+    media_ids: Option<Vec<String>>, // 通过 /media 上传后返回的媒体ID
     tags: Vec<String>,
     tx_hash: Option<String>, // 可选的交易哈希，用于验证投资操作
+    tx_chain: Option<String>, // 交易所在链，SOL 或 EVM 链简称（ETH、ARB、OP、BASE、POLYGON、BSC），默认 ETH
+    signature: Option<AuthorSignature>, // 可选的作者签名，对规范消息签名
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SigningMessageRequest {
+    kind: String, // post、quote、comment
+    content: String,
+    tags: Option<Vec<String>>,
+    parent: Option<String>, // 引用的帖子ID，或评论的父评论/帖子ID
+    signed_at: Option<i64>, // 默认为当前时间
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuotePostRequest {
+    content: String,
+    tags: Option<Vec<String>>,
+    signature: Option<AuthorSignature>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,16 +50,56 @@ pub struct PostListQuery {
     tag: Option<String>,
 }
 
+/// 附带作者徽章的帖子，转发和引用帖子附带原帖
+#[derive(Debug, Serialize)]
+pub struct PostWithBadges {
+    #[serde(flatten)]
+    post: PostEntity,
+    author_badges: Vec<UserBadgeEntity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_post: Option<PostEntity>,
+}
+
+// 为帖子列表附加作者徽章和原帖，获取失败时不影响列表展示
+async fn attach_badges(
+    posts: Vec<PostEntity>,
+    content_service: &ContentService,
+    badge_service: &BadgeService,
+) -> Vec<PostWithBadges> {
+    let user_ids: Vec<String> = posts.iter().map(|p| p.user_id.clone()).collect();
+    let badges = badge_service
+        .get_badges_for_users(&user_ids)
+        .await
+        .unwrap_or_default();
+    let originals: HashMap<String, PostEntity> = content_service
+        .get_original_posts(&posts)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.id.to_string(), p))
+        .collect();
+
+    posts
+        .into_iter()
+        .map(|post| PostWithBadges {
+            author_badges: badges.get(&post.user_id).cloned().unwrap_or_default(),
+            original_post: post.repost_of_id.as_ref().and_then(|id| originals.get(id).cloned()),
+            post,
+        })
+        .collect()
+}
+
 /// 创建新帖子
 pub async fn create_post(
     auth_user: AuthenticatedUser,
     data: web::Json<CreatePostRequest>,
     content_service: web::Data<Arc<ContentService>>,
+    media_service: web::Data<Arc<MediaService>>,
     user_service: web::Data<Arc<UserService>>,
 ) -> impl Responder {
     // 获取用户钱包地址
     let wallet_address = match user_service
-        .get_wallet_address_by_user_id(auth_user.user_id)
+        .get_wallet_address_by_user_id(auth_user.user_id.clone())
         .await
     {
         Ok(address) => address,
@@ -48,12 +114,12 @@ pub async fn create_post(
     // 处理图片数据
     let image_data: Option<Vec<u8>> = match &data.image_data {
         Some(base64_data) => {
-            // 解码Base64图片数据
-            match base64::decode(
-                base64_data
-                    .replace("data:image/jpeg;base64,", "")
-                    .replace("data:image/png;base64,", ""),
-            ) {
+            // 去掉 data:<mime>;base64, 前缀后解码
+            let encoded = match base64_data.split_once(";base64,") {
+                Some((_, encoded)) => encoded,
+                None => base64_data.as_str(),
+            };
+            match base64::decode(encoded) {
                 Ok(decoded) => Some(decoded),
                 Err(_) => {
                     return HttpResponse::BadRequest().json(serde_json::json!({
@@ -66,19 +132,56 @@ pub async fn create_post(
         None => None,
     };
 
+    // 解析引用的媒体，直接附带的图片也计入数量上限
+    let media_cids: Vec<String> = match &data.media_ids {
+        Some(media_ids) => match media_service
+            .resolve_post_media(&auth_user.user_id, media_ids, usize::from(image_data.is_some()))
+            .await
+        {
+            Ok(media_list) => media_list.into_iter().map(|m| m.ipfs_cid).collect(),
+            Err(err) => {
+                return match err {
+                    ServiceError::BadRequest(msg) | ServiceError::NotFound(msg) => {
+                        HttpResponse::BadRequest().json(serde_json::json!({
+                            "status": "error",
+                            "message": msg
+                        }))
+                    }
+                    _ => HttpResponse::InternalServerError().json(serde_json::json!({
+                        "status": "error",
+                        "message": format!("获取媒体失败: {}", err)
+                    })),
+                };
+            }
+        },
+        None => Vec::new(),
+    };
+
     // 创建帖子
     match content_service
-        .create_post(
-            auth_user.user_id,
-            &wallet_address,
-            &data.content,
+        .create_post(NewPost {
+            user_id: auth_user.user_id,
+            wallet_address: &wallet_address,
+            content: &data.content,
             image_data,
-            data.tags.clone(),
-            data.tx_hash.clone(),
-        )
+            media_cids,
+            tags: data.tags.clone(),
+            tx_hash: data.tx_hash.clone(),
+            tx_chain: data.tx_chain.clone(),
+            author_signature: data.signature.clone(),
+        })
         .await
     {
         Ok(post) => HttpResponse::Created().json(post),
+        Err(ServiceError::BadRequest(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        // 使用了门槛空间标签，但不满足持仓要求
+        Err(ServiceError::Unauthorized(msg)) => HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
         Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
             "status": "error",
             "message": format!("创建帖子失败: {}", err)
@@ -86,10 +189,46 @@ pub async fn create_post(
     }
 }
 
+/// 获取作者签名用的规范消息
+pub async fn get_signing_message(
+    auth_user: AuthenticatedUser,
+    data: web::Json<SigningMessageRequest>,
+    content_service: web::Data<Arc<ContentService>>,
+) -> impl Responder {
+    let signed_at = data.signed_at.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let tags = data.tags.clone().unwrap_or_default();
+
+    match content_service
+        .signing_message(
+            &auth_user.user_id,
+            &data.kind,
+            &data.content,
+            &tags,
+            data.parent.as_deref(),
+            signed_at,
+        )
+        .await
+    {
+        Ok(message) => HttpResponse::Ok().json(serde_json::json!({
+            "message": message,
+            "signed_at": signed_at
+        })),
+        Err(ServiceError::BadRequest(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("生成签名消息失败: {}", err)
+        })),
+    }
+}
+
 /// 获取帖子列表
 pub async fn get_posts(
     query: web::Query<PostListQuery>,
     content_service: web::Data<Arc<ContentService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
 ) -> impl Responder {
     // 设置默认分页参数
     let page: i32 = query.page.unwrap_or(1);
@@ -116,7 +255,7 @@ pub async fn get_posts(
 
     // 返回帖子列表
     match posts {
-        Ok(posts) => HttpResponse::Ok().json(posts),
+        Ok(posts) => HttpResponse::Ok().json(attach_badges(posts, &content_service, &badge_service).await),
         Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
             "status": "error",
             "message": format!("获取帖子列表失败: {}", err)
@@ -124,11 +263,35 @@ pub async fn get_posts(
     }
 }
 
+/// 获取关注者时间线
+pub async fn get_timeline(
+    auth_user: AuthenticatedUser,
+    query: web::Query<PostListQuery>,
+    content_service: web::Data<Arc<ContentService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
+) -> impl Responder {
+    // 设置默认分页参数
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(20);
+
+    match content_service
+        .get_timeline(&auth_user.user_id, page, page_size)
+        .await
+    {
+        Ok(posts) => HttpResponse::Ok().json(attach_badges(posts, &content_service, &badge_service).await),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("获取时间线失败: {}", err)
+        })),
+    }
+}
+
 /// 根据标签获取帖子
 pub async fn get_posts_by_tag(
     path: web::Path<String>,
     query: web::Query<PostListQuery>,
     content_service: web::Data<Arc<ContentService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
 ) -> impl Responder {
     let tag = path.into_inner();
 
@@ -141,7 +304,7 @@ pub async fn get_posts_by_tag(
         .get_posts_by_tag(&tag, page, page_size)
         .await
     {
-        Ok(posts) => HttpResponse::Ok().json(posts),
+        Ok(posts) => HttpResponse::Ok().json(attach_badges(posts, &content_service, &badge_service).await),
         Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
             "status": "error",
             "message": format!("获取帖子列表失败: {}", err)
@@ -154,6 +317,7 @@ pub async fn get_user_posts(
     path: web::Path<String>,
     query: web::Query<PostListQuery>,
     content_service: web::Data<Arc<ContentService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
 ) -> impl Responder {
     let user_id = path.into_inner();
 
@@ -166,7 +330,7 @@ pub async fn get_user_posts(
         .get_user_posts(user_id, page, page_size)
         .await
     {
-        Ok(posts) => HttpResponse::Ok().json(posts),
+        Ok(posts) => HttpResponse::Ok().json(attach_badges(posts, &content_service, &badge_service).await),
         Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
             "status": "error",
             "message": format!("获取用户帖子失败: {}", err)
@@ -180,12 +344,20 @@ pub async fn get_post_detail(
     content_service: web::Data<Arc<ContentService>>,
     user_service: web::Data<Arc<UserService>>,
     storage_service: web::Data<Arc<StorageService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
     auth_user: Option<AuthenticatedUser>,
 ) -> impl Responder {
     let post_id = path.into_inner();
 
     // 获取帖子详情
-    let post = match content_service.get_post(post_id).await {
+    let post = match content_service.get_post(post_id.clone()).await {
+        // 已隐藏的帖子不对外展示
+        Ok(post) if post.is_hidden => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": "帖子不存在"
+            }));
+        }
         Ok(post) => post,
         Err(err) => {
             return match err {
@@ -201,8 +373,14 @@ pub async fn get_post_detail(
         }
     };
 
+    // 作者徽章（获取失败时不影响详情展示）
+    let author_badges = badge_service
+        .get_badges(&post.user_id)
+        .await
+        .unwrap_or_default();
+
     // 获取帖子作者信息
-    let author_profile = match user_service.get_profile(post.user_id).await {
+    let author_profile = match user_service.get_profile(post.user_id.clone()).await {
         Ok(profile) => profile,
         Err(_) => {
             return HttpResponse::InternalServerError().json(serde_json::json!({
@@ -213,41 +391,89 @@ pub async fn get_post_detail(
     };
 
     // 获取帖子点赞数
-    let likes_count = match content_service.get_post_likes_count(post_id).await {
-        Ok(count) => count,
-        Err(_) => 0, // 如果获取失败，默认为0
-    };
+    // 如果获取失败，默认为0
+    let likes_count = content_service
+        .get_post_likes_count(&post_id)
+        .await
+        .unwrap_or_default();
 
     // 检查当前用户是否已点赞（如果有登录用户）
     let has_liked = if let Some(auth_user) = &auth_user {
-        match content_service
-            .has_user_liked(auth_user.user_id, post_id)
+        content_service
+            .has_user_liked(&auth_user.user_id, &post_id)
             .await
-        {
-            Ok(liked) => liked,
-            Err(_) => false,
-        }
+            .unwrap_or_default()
     } else {
         false
     };
 
-    // 如果帖子有图片，生成URL
-    let image_url = if let Some(cid) = &post.image_ipfs_cid {
-        Some(storage_service.get_ipfs_url(cid))
-    } else {
-        None
+    // 转发或引用的帖子，附带原帖内容（原帖获取失败时不影响详情展示）
+    let original_post = match &post.repost_of_id {
+        Some(original_id) => content_service
+            .get_post(original_id.clone())
+            .await
+            .ok()
+            .filter(|original| !original.is_hidden),
+        None => None,
     };
 
+    // 交易凭证（获取失败时不影响详情展示）
+    let trade_proof = content_service
+        .get_post_trade_proof(&post)
+        .await
+        .unwrap_or(None);
+    // 交易和发起钱包在区块浏览器中的链接，目前仅EVM链提供
+    let trade_explorer = trade_proof.as_ref().and_then(|proof| {
+        let chain = chains::get_chain_by_code(&proof.chain)?;
+        Some(serde_json::json!({
+            "tx_url": chain.tx_url(&proof.tx_hash),
+            "address_url": chain.address_url(&proof.from_address)
+        }))
+    });
+
+    // 如果帖子有图片，生成URL
+    let image_urls: Vec<String> = post
+        .images_ipfs_cids
+        .iter()
+        .flatten()
+        .map(|cid| storage_service.media_url(cid))
+        .collect();
+
     // 构建响应
     HttpResponse::Ok().json(serde_json::json!({
         "post": post,
         "author": author_profile,
+        "author_badges": author_badges,
         "likes_count": likes_count,
         "has_liked": has_liked,
-        "image_url": image_url
+        "image_urls": image_urls,
+        "original_post": original_post,
+        "trade_proof": trade_proof,
+        "trade_explorer": trade_explorer,
+        "verified_trade": post.trade_verified
     }))
 }
 
+/// 获取帖子的完整性证明，重新从Arweave和IPFS取回内容并与记录的哈希比对，结果按帖子缓存
+pub async fn get_post_proof(
+    path: web::Path<String>,
+    storage_service: web::Data<Arc<StorageService>>,
+) -> impl Responder {
+    let post_id = path.into_inner();
+
+    match storage_service.cached_content_proof(CONTENT_TABLE_POSTS, &post_id).await {
+        Ok(proof) => HttpResponse::Ok().json(proof),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": "帖子不存在"
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("获取完整性证明失败: {}", err)
+        })),
+    }
+}
+
 /// 点赞帖子
 pub async fn like_post(
     path: web::Path<String>,
@@ -299,6 +525,66 @@ pub async fn unlike_post(
     }
 }
 
+/// 转发帖子
+pub async fn repost_post(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    content_service: web::Data<Arc<ContentService>>,
+) -> impl Responder {
+    let post_id = path.into_inner();
+
+    match content_service.repost_post(auth_user.user_id, post_id).await {
+        Ok(post) => HttpResponse::Created().json(post),
+        Err(err) => repost_error_response(err, "转发失败"),
+    }
+}
+
+/// 引用转发帖子
+pub async fn quote_post(
+    path: web::Path<String>,
+    data: web::Json<QuotePostRequest>,
+    auth_user: AuthenticatedUser,
+    content_service: web::Data<Arc<ContentService>>,
+) -> impl Responder {
+    let post_id = path.into_inner();
+
+    match content_service
+        .quote_post(
+            auth_user.user_id,
+            post_id,
+            &data.content,
+            data.tags.clone().unwrap_or_default(),
+            data.signature.clone(),
+        )
+        .await
+    {
+        Ok(post) => HttpResponse::Created().json(post),
+        Err(err) => repost_error_response(err, "引用转发失败"),
+    }
+}
+
+// 转发相关错误统一转换为HTTP响应
+fn repost_error_response(err: ServiceError, action: &str) -> HttpResponse {
+    match err {
+        ServiceError::NotFound(msg) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        ServiceError::Unauthorized(msg) => HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        _ => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("{}: {}", action, err)
+        })),
+    }
+}
+
 /// 获取热门标签
 pub async fn get_hot_tags(content_service: web::Data<Arc<ContentService>>) -> impl Responder {
     // 获取热门标签（默认返回前10个）
@@ -316,6 +602,7 @@ pub async fn search_posts(
     query: web::Query<PostListQuery>,
     search_query: web::Path<String>,
     content_service: web::Data<Arc<ContentService>>,
+    badge_service: web::Data<Arc<BadgeService>>,
 ) -> impl Responder {
     let search_term = search_query.into_inner();
 
@@ -328,7 +615,7 @@ pub async fn search_posts(
         .search_posts(&search_term, page, page_size)
         .await
     {
-        Ok(posts) => HttpResponse::Ok().json(posts),
+        Ok(posts) => HttpResponse::Ok().json(attach_badges(posts, &content_service, &badge_service).await),
         Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
             "status": "error",
             "message": format!("搜索帖子失败: {}", err)
@@ -343,16 +630,25 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             // 帖子列表和创建
             .route("", web::get().to(get_posts))
             .route("", web::post().to(create_post))
-            // 帖子详情、点赞和取消点赞
-            .route("/{post_id}", web::get().to(get_post_detail))
-            .route("/{post_id}/like", web::post().to(like_post))
-            .route("/{post_id}/unlike", web::post().to(unlike_post))
-            // 标签相关
+            .route("/signing-message", web::post().to(get_signing_message))
+            .route("/timeline", web::get().to(get_timeline))
+            // 标签相关，固定路径需在 /{post_id} 之前注册
             .route("/tags", web::get().to(get_hot_tags))
             .route("/tag/{tag}", web::get().to(get_posts_by_tag))
             // 用户帖子
             .route("/user/{user_id}", web::get().to(get_user_posts))
             // 搜索
-            .route("/search/{query}", web::get().to(search_posts)),
+            .route("/search/{query}", web::get().to(search_posts))
+            // 帖子详情、点赞和取消点赞
+            .route("/{post_id}", web::get().to(get_post_detail))
+            .route("/{post_id}/proof", web::get().to(get_post_proof))
+            .route("/{post_id}/like", web::post().to(like_post))
+            .route("/{post_id}/unlike", web::post().to(unlike_post))
+            // 转发和引用转发
+            .route("/{post_id}/repost", web::post().to(repost_post))
+            .route("/{post_id}/quote", web::post().to(quote_post))
+            // 帖子评论，与帖子路由同在一个scope，否则会被 /posts 吞掉
+            .route("/{post_id}/comments", web::post().to(comment::create_comment))
+            .route("/{post_id}/comments", web::get().to(comment::get_post_comments)),
     );
 }
@@ -0,0 +1,126 @@
+use crate::api::admin::is_admin;
+use crate::middlewares::auth::AuthenticatedUser;
+use crate::services::space_service::{NewSpace, SpaceService};
+use crate::utils::error::ServiceError;
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSpaceRequest {
+    tag: String,
+    name: String,
+    description: Option<String>,
+    requirement_type: String, // value（资产总价值，美元）、token（代币数量）、nft（NFT个数）
+    chain: Option<String>, // SOL或EVM链简称，token和nft类型必填
+    contract_address: Option<String>, // 代币或NFT合约地址，token类型为空表示原生币
+    min_amount: f64,
+}
+
+// 空间相关错误统一转换为HTTP响应
+fn space_error_response(err: ServiceError, action: &str) -> HttpResponse {
+    match err {
+        ServiceError::NotFound(msg) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        ServiceError::Unauthorized(msg) => HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        _ => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("{}: {}", action, err)
+        })),
+    }
+}
+
+/// 创建资产门槛空间，普通用户只能为还没有帖子的新标签创建
+pub async fn create_space(
+    auth_user: AuthenticatedUser,
+    data: web::Json<CreateSpaceRequest>,
+    space_service: web::Data<Arc<SpaceService>>,
+) -> impl Responder {
+    let data = data.into_inner();
+
+    match space_service
+        .create_space(
+            &auth_user.user_id,
+            is_admin(&auth_user),
+            NewSpace {
+                tag: &data.tag,
+                name: &data.name,
+                description: data.description,
+                requirement_type: &data.requirement_type,
+                chain: data.chain.as_deref(),
+                contract_address: data.contract_address,
+                min_amount: data.min_amount,
+            },
+        )
+        .await
+    {
+        Ok(space) => HttpResponse::Created().json(space),
+        Err(err) => space_error_response(err, "创建空间失败"),
+    }
+}
+
+/// 获取空间列表
+pub async fn get_spaces(space_service: web::Data<Arc<SpaceService>>) -> impl Responder {
+    match space_service.list_spaces().await {
+        Ok(spaces) => HttpResponse::Ok().json(spaces),
+        Err(err) => space_error_response(err, "获取空间列表失败"),
+    }
+}
+
+/// 获取空间详情
+pub async fn get_space(
+    path: web::Path<String>,
+    space_service: web::Data<Arc<SpaceService>>,
+) -> impl Responder {
+    let tag = path.into_inner();
+
+    match space_service.get_space_by_tag(&tag).await {
+        Ok(Some(space)) => HttpResponse::Ok().json(space),
+        Ok(None) => space_error_response(ServiceError::NotFound("空间不存在".into()), "获取空间失败"),
+        Err(err) => space_error_response(err, "获取空间失败"),
+    }
+}
+
+/// 检查当前用户是否满足空间的持仓要求
+pub async fn check_eligibility(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    space_service: web::Data<Arc<SpaceService>>,
+) -> impl Responder {
+    let tag = path.into_inner();
+
+    let space = match space_service.get_space_by_tag(&tag).await {
+        Ok(Some(space)) => space,
+        Ok(None) => return space_error_response(ServiceError::NotFound("空间不存在".into()), "检查资格失败"),
+        Err(err) => return space_error_response(err, "检查资格失败"),
+    };
+
+    match space_service.check_eligibility(&auth_user.user_id, &space).await {
+        Ok(member) => HttpResponse::Ok().json(serde_json::json!({
+            "space": space,
+            "eligible": member.eligible,
+            "checked_at": member.checked_at
+        })),
+        Err(err) => space_error_response(err, "检查资格失败"),
+    }
+}
+
+/// 配置Space路由
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/spaces")
+            .route("", web::get().to(get_spaces))
+            .route("", web::post().to(create_space))
+            .route("/{tag}", web::get().to(get_space))
+            .route("/{tag}/eligibility", web::get().to(check_eligibility)),
+    );
+}
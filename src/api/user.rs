@@ -1,8 +1,10 @@
 use crate::middlewares::auth::AuthenticatedUser;
+use crate::models::rbatis_entities::{UserBadgeEntity, UserProfileEntity};
 use crate::utils::error::ServiceError;
+use crate::services::badge_service::BadgeService;
 use crate::services::user_service::UserService;
 use actix_web::{web, HttpResponse, Responder};
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 #[derive(Debug, Deserialize)]
@@ -12,14 +14,28 @@ pub struct UpdateProfileRequest {
     avatar_data: Option<String>, // Base64编码的图像数据
 }
 
+/// 附带徽章的用户资料
+#[derive(Debug, Serialize)]
+pub struct ProfileWithBadges {
+    #[serde(flatten)]
+    profile: UserProfileEntity,
+    badges: Vec<UserBadgeEntity>,
+}
+
 /// 获取当前用户资料
 pub async fn get_current_profile(
     auth_user: AuthenticatedUser,
     user_service: web::Data<Arc<UserService>>,
-
+    badge_service: web::Data<Arc<BadgeService>>,
 ) -> impl Responder {
+    // 徽章获取失败时不影响资料展示
+    let badges = badge_service
+        .get_badges(&auth_user.user_id)
+        .await
+        .unwrap_or_default();
+
     match user_service.get_profile(auth_user.user_id).await {
-        Ok(profile) => HttpResponse::Ok().json(profile),
+        Ok(profile) => HttpResponse::Ok().json(ProfileWithBadges { profile, badges }),
         Err(err) => match err {
             ServiceError::NotFound(_) => HttpResponse::NotFound().json(serde_json::json!({
                 "status": "error",
@@ -63,6 +79,98 @@ pub async fn update_profile(
     }
 }
 
+/// 获取当前用户的徽章
+pub async fn get_my_badges(
+    auth_user: AuthenticatedUser,
+    badge_service: web::Data<Arc<BadgeService>>,
+) -> impl Responder {
+    match badge_service.get_badges(&auth_user.user_id).await {
+        Ok(badges) => HttpResponse::Ok().json(badges),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("获取徽章失败: {}", err)
+        })),
+    }
+}
+
+/// 按当前链上资产重新验证徽章
+pub async fn refresh_my_badges(
+    auth_user: AuthenticatedUser,
+    badge_service: web::Data<Arc<BadgeService>>,
+) -> impl Responder {
+    match badge_service.refresh_user_badges(&auth_user.user_id).await {
+        Ok(badges) => HttpResponse::Ok().json(badges),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("验证徽章失败: {}", err)
+        })),
+    }
+}
+
+/// 获取指定用户的徽章
+pub async fn get_user_badges(
+    path: web::Path<String>,
+    badge_service: web::Data<Arc<BadgeService>>,
+) -> impl Responder {
+    let user_id = path.into_inner();
+
+    match badge_service.get_badges(&user_id).await {
+        Ok(badges) => HttpResponse::Ok().json(badges),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("获取徽章失败: {}", err)
+        })),
+    }
+}
+
+/// 关注用户
+pub async fn follow_user(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    user_service: web::Data<Arc<UserService>>,
+) -> impl Responder {
+    let user_id = path.into_inner();
+
+    match user_service.follow_user(&auth_user.user_id, &user_id).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "关注成功"
+        })),
+        Err(ServiceError::BadRequest(msg)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(ServiceError::NotFound(msg)) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": msg
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("关注失败: {}", err)
+        })),
+    }
+}
+
+/// 取消关注
+pub async fn unfollow_user(
+    path: web::Path<String>,
+    auth_user: AuthenticatedUser,
+    user_service: web::Data<Arc<UserService>>,
+) -> impl Responder {
+    let user_id = path.into_inner();
+
+    match user_service.unfollow_user(&auth_user.user_id, &user_id).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "message": "取消关注成功"
+        })),
+        Err(err) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("取消关注失败: {}", err)
+        })),
+    }
+}
+
 /// 通过钱包地址获取用户资料
 // pub async fn get_profile_by_wallet(
 //     path: web::Path<String>,
@@ -108,6 +216,13 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         web::scope("/users")
             .route("/me", web::get().to(get_current_profile))
             .route("/update_profile", web::post().to(update_profile))
+            // 资产徽章
+            .route("/me/badges", web::get().to(get_my_badges))
+            .route("/me/badges/refresh", web::post().to(refresh_my_badges))
+            .route("/{user_id}/badges", web::get().to(get_user_badges))
+            // 关注关系
+            .route("/{user_id}/follow", web::post().to(follow_user))
+            .route("/{user_id}/unfollow", web::post().to(unfollow_user))
             // .route("/wallet/{address}", web::get().to(get_profile_by_wallet)),
     );
 }
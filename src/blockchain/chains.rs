@@ -0,0 +1,212 @@
+use std::env;
+
+/// EVM链配置
+#[derive(Debug)]
+pub struct EvmChain {
+    pub chain_id: i32,
+    pub code: &'static str, // 链简称，用于交易凭证等场景，如 ETH、ARB
+    pub name: &'static str,
+    pub rpc_url_env: &'static str,
+    pub native_symbol: &'static str,
+    pub native_name: &'static str,
+    pub native_decimals: u8,
+    pub wrapped_native: &'static str, // 包装原生币合约（WETH、WBNB等）
+    pub explorer_url: &'static str,
+    pub native_usd_feed: &'static str, // Chainlink 原生币/USD 喂价合约
+    pub uniswap_v3_factory: &'static str, // Uniswap V3 工厂合约，用于按池子价格估算代币价格
+    pub coingecko_platform: &'static str, // CoinGecko 平台ID
+    pub coingecko_native_id: &'static str, // CoinGecko 原生币ID
+    pub curated_tokens: &'static [&'static str], // 常见代币，无需扫描即可直接查询余额
+}
+
+impl EvmChain {
+    // 读取RPC地址，未配置时返回None
+    pub fn rpc_url(&self) -> Option<String> {
+        env::var(self.rpc_url_env).ok().filter(|url| !url.is_empty())
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.rpc_url().is_some()
+    }
+
+    // 区块浏览器中的交易链接
+    pub fn tx_url(&self, tx_hash: &str) -> String {
+        format!("{}/tx/{}", self.explorer_url, tx_hash)
+    }
+
+    // 区块浏览器中的地址链接
+    pub fn address_url(&self, address: &str) -> String {
+        format!("{}/address/{}", self.explorer_url, address)
+    }
+}
+
+pub const ETHEREUM_CHAIN_ID: i32 = 1;
+pub const OPTIMISM_CHAIN_ID: i32 = 10;
+pub const BSC_CHAIN_ID: i32 = 56;
+pub const POLYGON_CHAIN_ID: i32 = 137;
+pub const BASE_CHAIN_ID: i32 = 8453;
+pub const ARBITRUM_CHAIN_ID: i32 = 42161;
+
+// 支持的EVM链
+pub static EVM_CHAINS: &[EvmChain] = &[
+    EvmChain {
+        chain_id: ETHEREUM_CHAIN_ID,
+        code: "ETH",
+        name: "Ethereum",
+        rpc_url_env: "ETHEREUM_RPC_URL",
+        native_symbol: "ETH",
+        native_name: "Ether",
+        native_decimals: 18,
+        wrapped_native: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        explorer_url: "https://etherscan.io",
+        native_usd_feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419",
+        uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+        coingecko_platform: "ethereum",
+        coingecko_native_id: "ethereum",
+        curated_tokens: &[
+            "0xdAC17F958D2ee523a2206206994597C13D831ec7", // USDT
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", // USDC
+            "0x6B175474E89094C44Da98b954EedeAC495271d0F", // DAI
+            "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", // WETH
+            "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", // WBTC
+            "0x514910771AF9Ca656af840dff83E8264EcF986CA", // LINK
+            "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984", // UNI
+            "0x7Fc66500c84A76Ad7e9c93437bFc5Ac33E2DDaE9", // AAVE
+            "0x95aD61b0a150d79219dCF64E1E6Cc01f0B64C4cE", // SHIB
+            "0x6982508145454Ce325dDbE47a25d4ec3d2311933", // PEPE
+            "0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84", // stETH
+            "0x5A98FcBEA516Cf06857215779Fd812CA3beF1B32", // LDO
+        ],
+    },
+    EvmChain {
+        chain_id: ARBITRUM_CHAIN_ID,
+        code: "ARB",
+        name: "Arbitrum One",
+        rpc_url_env: "ARBITRUM_RPC_URL",
+        native_symbol: "ETH",
+        native_name: "Ether",
+        native_decimals: 18,
+        wrapped_native: "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1",
+        explorer_url: "https://arbiscan.io",
+        native_usd_feed: "0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612",
+        uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+        coingecko_platform: "arbitrum-one",
+        coingecko_native_id: "ethereum",
+        curated_tokens: &[
+            "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", // USDC
+            "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9", // USDT
+            "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", // DAI
+            "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1", // WETH
+            "0x2f2a2543B76A4166549F7aaB2e75Bef0aefC5B0f", // WBTC
+            "0x912CE59144191C1204E64559FE8253a0e49E6548", // ARB
+        ],
+    },
+    EvmChain {
+        chain_id: OPTIMISM_CHAIN_ID,
+        code: "OP",
+        name: "Optimism",
+        rpc_url_env: "OPTIMISM_RPC_URL",
+        native_symbol: "ETH",
+        native_name: "Ether",
+        native_decimals: 18,
+        wrapped_native: "0x4200000000000000000000000000000000000006",
+        explorer_url: "https://optimistic.etherscan.io",
+        native_usd_feed: "0x13e3Ee699D1909E989722E753853AE30b17e08c5",
+        uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+        coingecko_platform: "optimistic-ethereum",
+        coingecko_native_id: "ethereum",
+        curated_tokens: &[
+            "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85", // USDC
+            "0x94b008aA00579c1307B0EF2c499aD98a8ce58e58", // USDT
+            "0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1", // DAI
+            "0x4200000000000000000000000000000000000006", // WETH
+            "0x4200000000000000000000000000000000000042", // OP
+        ],
+    },
+    EvmChain {
+        chain_id: BASE_CHAIN_ID,
+        code: "BASE",
+        name: "Base",
+        rpc_url_env: "BASE_RPC_URL",
+        native_symbol: "ETH",
+        native_name: "Ether",
+        native_decimals: 18,
+        wrapped_native: "0x4200000000000000000000000000000000000006",
+        explorer_url: "https://basescan.org",
+        native_usd_feed: "0x71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70",
+        uniswap_v3_factory: "0x33128a8fC17869897dcE68Ed026d694621f6FDfD",
+        coingecko_platform: "base",
+        coingecko_native_id: "ethereum",
+        curated_tokens: &[
+            "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913", // USDC
+            "0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb", // DAI
+            "0x4200000000000000000000000000000000000006", // WETH
+        ],
+    },
+    EvmChain {
+        chain_id: POLYGON_CHAIN_ID,
+        code: "POLYGON",
+        name: "Polygon",
+        rpc_url_env: "POLYGON_RPC_URL",
+        native_symbol: "POL",
+        native_name: "Polygon Ecosystem Token",
+        native_decimals: 18,
+        wrapped_native: "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270",
+        explorer_url: "https://polygonscan.com",
+        native_usd_feed: "0xAB594600376Ec9fD91F8e885dADF0CE036862dE0",
+        uniswap_v3_factory: "0x1F98431c8aD98523631AE4a59f267346ea31F984",
+        coingecko_platform: "polygon-pos",
+        coingecko_native_id: "polygon-ecosystem-token",
+        curated_tokens: &[
+            "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", // USDC
+            "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174", // USDC.e
+            "0xc2132D05D31c914a87C6611C10748AEb04B58e8F", // USDT
+            "0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063", // DAI
+            "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619", // WETH
+            "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270", // WPOL
+        ],
+    },
+    EvmChain {
+        chain_id: BSC_CHAIN_ID,
+        code: "BSC",
+        name: "BNB Smart Chain",
+        rpc_url_env: "BSC_RPC_URL",
+        native_symbol: "BNB",
+        native_name: "BNB",
+        native_decimals: 18,
+        wrapped_native: "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c",
+        explorer_url: "https://bscscan.com",
+        native_usd_feed: "0x0567F2323251f0Aab15c8dFb1967E4e8A7D42aeE",
+        uniswap_v3_factory: "0xdB1d10011AD0Ff90774D0C6Bb92e5C5c8b4461F7",
+        coingecko_platform: "binance-smart-chain",
+        coingecko_native_id: "binancecoin",
+        curated_tokens: &[
+            "0x55d398326f99059fF775485246999027B3197955", // USDT
+            "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d", // USDC
+            "0xe9e7CEA3DedcA5984780Bafc599bD69ADd087D56", // BUSD
+            "0x2170Ed0880ac9A755fd29B2688956BD959F933F8", // ETH
+            "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c", // WBNB
+            "0x0E09FaBB73Bd3Ade0a17ECC321fD13a19e81cE82", // CAKE
+        ],
+    },
+];
+
+// 按链ID查找
+pub fn get_chain(chain_id: i32) -> Option<&'static EvmChain> {
+    EVM_CHAINS.iter().find(|c| c.chain_id == chain_id)
+}
+
+// 按链简称查找，不区分大小写
+pub fn get_chain_by_code(code: &str) -> Option<&'static EvmChain> {
+    EVM_CHAINS.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+// 按链ID查找，不支持时返回错误
+pub fn require_chain(chain_id: i32) -> Result<&'static EvmChain, String> {
+    get_chain(chain_id).ok_or_else(|| format!("Unsupported EVM chain: {}", chain_id))
+}
+
+// 已配置RPC地址的链
+pub fn configured_chains() -> Vec<&'static EvmChain> {
+    EVM_CHAINS.iter().filter(|c| c.is_configured()).collect()
+}
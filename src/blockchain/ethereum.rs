@@ -1,11 +1,15 @@
-use crate::models::asset::{Asset, NFT, TokenBalance, TokenPrice, TransactionVerification};
+use crate::blockchain::chains::{self, EvmChain};
+use crate::blockchain::provider_pool;
+use crate::blockchain::receipt_decoder::{self, DecodedReceipt};
+use crate::models::asset::{Asset, AssetMovement, NFT, SwapLeg, TokenBalance, TokenPrice, TransactionVerification};
+use std::collections::HashMap;
 use ethers::prelude::*;
+use ethers::abi::Token;
 use ethers::types::{Address, BlockNumber, U256};
 use ethers::providers::{Http, Provider};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::env;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -80,129 +84,141 @@ const ERC721_ABI: &str = r#"[
     }
 ]"#;
 
-// 创建以太坊客户端
-pub async fn get_eth_client() -> Result<Arc<Provider<Http>>, String> {
-    let rpc_url = env::var("ETHEREUM_RPC_URL")
-        .map_err(|_| "ETHEREUM_RPC_URL environment variable not set".to_string())?;
-    
-    let provider = Provider::<Http>::try_from(rpc_url)
-        .map_err(|e| format!("Failed to create Ethereum provider: {}", e))?;
-    
-    Ok(Arc::new(provider))
+// ERC1155合约ABI
+const ERC1155_ABI: &str = r#"[
+    {
+        "constant": true,
+        "inputs": [{"name": "_owner", "type": "address"}, {"name": "_id", "type": "uint256"}],
+        "name": "balanceOf",
+        "outputs": [{"name": "", "type": "uint256"}],
+        "type": "function"
+    },
+    {
+        "constant": true,
+        "inputs": [{"name": "_id", "type": "uint256"}],
+        "name": "uri",
+        "outputs": [{"name": "", "type": "string"}],
+        "type": "function"
+    }
+]"#;
+
+// NFT标准
+pub const NFT_STANDARD_ERC721: &str = "ERC721";
+pub const NFT_STANDARD_ERC1155: &str = "ERC1155";
+
+// 单次eth_getLogs查询的区块范围，多数RPC服务商对范围有限制
+const LOG_SCAN_BLOCK_RANGE: u64 = 10_000;
+// 单次Multicall打包的调用数量
+const MULTICALL_BATCH_SIZE: usize = 100;
+
+// 创建ERC20合约实例
+pub(crate) fn erc20_contract(token_address: Address, client: Arc<Provider<Http>>) -> Contract<Provider<Http>> {
+    Contract::new(
+        token_address,
+        serde_json::from_str::<ethers::abi::Abi>(ERC20_ABI).unwrap(),
+        client,
+    )
 }
 
-// 获取ETH余额
-pub async fn get_eth_balance(address: &str) -> Result<TokenBalance, String> {
-    let client = get_eth_client().await?;
+// 获取原生币余额
+pub async fn get_eth_balance(chain_id: i32, address: &str) -> Result<TokenBalance, String> {
+    let chain = chains::require_chain(chain_id)?;
+    let pool = provider_pool::get_pool(chain_id)?;
     
     let address = Address::from_str(address.trim_start_matches("0x"))
         .map_err(|e| format!("Invalid Ethereum address: {}", e))?;
     
-    let balance = client.get_balance(address, None)
-        .await
-        .map_err(|e| format!("Failed to get ETH balance: {}", e))?;
+    let balance = pool.call("get balance", |client| async move {
+        client.get_balance(address, None)
+            .await
+            .map_err(|e| format!("Failed to get {} balance: {}", chain.native_symbol, e))
+    }).await?;
     
     Ok(TokenBalance {
-        chain: "ETH".to_string(),
-        address: "".to_string(), // ETH没有合约地址
+        chain: chain.code.to_string(),
+        address: "".to_string(), // 原生币没有合约地址
         raw_balance: balance.to_string(),
-        decimals: 18,
+        decimals: chain.native_decimals,
     })
 }
 
-// 获取ERC20代币余额
-pub async fn get_erc20_balance(token_address: &str, wallet_address: &str) -> Result<TokenBalance, String> {
-    let client = get_eth_client().await?;
+// 获取代币信息
+pub async fn get_token_info(chain_id: i32, token_address: &str) -> Result<(String, String), String> {
+    let pool = provider_pool::get_pool(chain_id)?;
     
     let token_address = Address::from_str(token_address.trim_start_matches("0x"))
         .map_err(|e| format!("Invalid token address: {}", e))?;
     
-    let wallet_address = Address::from_str(wallet_address.trim_start_matches("0x"))
-        .map_err(|e| format!("Invalid wallet address: {}", e))?;
-    
-    // 创建合约实例
-    let contract = Contract::new(
-        token_address,
-        serde_json::from_str::<ethers::abi::Abi>(ERC20_ABI).unwrap(),
-        client.clone(),
-    );
-    
-    // 调用balanceOf
-    let balance: U256 = contract.method("balanceOf", wallet_address)
-        .map_err(|e| format!("Failed to create balanceOf method call: {}", e))?
-        .call()
-        .await
-        .map_err(|e| format!("Failed to call balanceOf: {}", e))?;
-    
-    // 获取代币精度
-    let decimals: u8 = contract.method("decimals", ())
-        .map_err(|e| format!("Failed to create decimals method call: {}", e))?
-        .call()
-        .await
-        .map_err(|e| format!("Failed to call decimals: {}", e))?;
-    
-    Ok(TokenBalance {
-        chain: "ETH".to_string(),
-        address: format!("0x{}", hex::encode(token_address.as_bytes())),
-        raw_balance: balance.to_string(),
-        decimals,
-    })
+    pool.call("get token info", |client| async move {
+        let contract = erc20_contract(token_address, client);
+        
+        // 获取代币符号
+        let symbol: String = contract.method("symbol", ())
+            .map_err(|e| format!("Failed to create symbol method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call symbol: {}", e))?;
+        
+        // 获取代币名称
+        let name: String = contract.method("name", ())
+            .map_err(|e| format!("Failed to create name method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call name: {}", e))?;
+        
+        Ok((symbol, name))
+    }).await
 }
 
-// 获取代币信息
-pub async fn get_token_info(token_address: &str) -> Result<(String, String), String> {
-    let client = get_eth_client().await?;
+// 获取代币精度
+pub async fn get_token_decimals(chain_id: i32, token_address: &str) -> Result<u8, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
     
     let token_address = Address::from_str(token_address.trim_start_matches("0x"))
         .map_err(|e| format!("Invalid token address: {}", e))?;
     
-    // 创建合约实例
-    let contract = Contract::new(
-        token_address,
-        serde_json::from_str::<ethers::abi::Abi>(ERC20_ABI).unwrap(),
-        client.clone(),
-    );
-    
-    // 获取代币符号
-    let symbol: String = contract.method("symbol", ())
-        .map_err(|e| format!("Failed to create symbol method call: {}", e))?
-        .call()
-        .await
-        .map_err(|e| format!("Failed to call symbol: {}", e))?;
-    
-    // 获取代币名称
-    let name: String = contract.method("name", ())
-        .map_err(|e| format!("Failed to create name method call: {}", e))?
-        .call()
-        .await
-        .map_err(|e| format!("Failed to call name: {}", e))?;
-    
-    Ok((symbol, name))
+    pool.call("get token decimals", |client| async move {
+        erc20_contract(token_address, client)
+            .method("decimals", ())
+            .map_err(|e| format!("Failed to create decimals method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call decimals: {}", e))
+    }).await
 }
 
 // 验证交易
-pub async fn verify_transaction(tx_hash: &str) -> Result<TransactionVerification, String> {
-    let client = get_eth_client().await?;
+pub async fn verify_transaction(chain_id: i32, tx_hash: &str) -> Result<TransactionVerification, String> {
+    let chain = chains::require_chain(chain_id)?;
+    let pool = provider_pool::get_pool(chain_id)?;
     
     let tx_hash = H256::from_str(tx_hash.trim_start_matches("0x"))
         .map_err(|e| format!("Invalid transaction hash: {}", e))?;
     
     // 获取交易信息
-    let tx = client.get_transaction(tx_hash)
-        .await
-        .map_err(|e| format!("Failed to get transaction: {}", e))?
+    let tx = pool.call("get transaction", |client| async move {
+        client.get_transaction(tx_hash)
+            .await
+            .map_err(|e| format!("Failed to get transaction: {}", e))
+    }).await?
         .ok_or_else(|| "Transaction not found".to_string())?;
     
-    // 获取交易收据
-    let receipt = client.get_transaction_receipt(tx_hash)
-        .await
-        .map_err(|e| format!("Failed to get transaction receipt: {}", e))?
+    // 获取交易收据，配置了 EVM_TX_QUORUM 时需多个节点返回一致的收据
+    let receipt = pool.quorum_call("get transaction receipt", provider_pool::tx_quorum(), |client| async move {
+        client.get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| format!("Failed to get transaction receipt: {}", e))
+    }).await?
         .ok_or_else(|| "Transaction receipt not found".to_string())?;
     
     // 获取区块时间戳
-    let block = client.get_block(receipt.block_number.unwrap())
-        .await
-        .map_err(|e| format!("Failed to get block: {}", e))?
+    let block_number = receipt.block_number
+        .ok_or_else(|| "Transaction is pending".to_string())?;
+    let block = pool.call("get block", |client| async move {
+        client.get_block(block_number)
+            .await
+            .map_err(|e| format!("Failed to get block: {}", e))
+    }).await?
         .ok_or_else(|| "Block not found".to_string())?;
     
     // 构建交易验证结果
@@ -213,14 +229,14 @@ pub async fn verify_transaction(tx_hash: &str) -> Result<TransactionVerification
     };
     
     // 判断是否为ERC20交易
-    let (token_address, token_symbol) = if tx.input.len() > 4 && &tx.input.to_vec()[0..4] == &[0xa9, 0x05, 0x9c, 0xbb] {
+    let (token_address, token_symbol) = if tx.input.len() > 4 && tx.input.to_vec()[0..4] == [0xa9, 0x05, 0x9c, 0xbb] {
         // 这是ERC20 transfer方法的签名
         // transfer(address,uint256)
         
         let token_address = tx.to.map(|addr| format!("0x{}", hex::encode(addr.as_bytes())));
         
         let token_symbol = if let Some(addr) = token_address.clone() {
-            match get_token_info(&addr).await {
+            match get_token_info(chain_id, &addr).await {
                 Ok((symbol, _)) => Some(symbol),
                 Err(_) => None,
             }
@@ -233,6 +249,12 @@ pub async fn verify_transaction(tx_hash: &str) -> Result<TransactionVerification
         (None, None)
     };
     
+    // 解析收据日志中的资产变动
+    let wrapped_native = Address::from_str(chain.wrapped_native.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid wrapped native address: {}", e))?;
+    let decoded = receipt_decoder::decode_receipt(&tx, &receipt, wrapped_native);
+    let (movements, swaps) = resolve_decoded(chain, &decoded).await;
+    
     Ok(TransactionVerification {
         is_valid: receipt.status.unwrap_or_default() == U64::from(1),
         transaction_hash: format!("0x{}", hex::encode(tx_hash.as_bytes())),
@@ -242,8 +264,513 @@ pub async fn verify_transaction(tx_hash: &str) -> Result<TransactionVerification
         token_address,
         token_symbol,
         timestamp: block.timestamp.as_u64() as i64,
-        block_number: receipt.block_number.unwrap().as_u64(),
+        block_number: block_number.as_u64(),
         status: status.to_string(),
-        chain: "ETH".to_string(),
+        chain: chain.code.to_string(),
+        movements,
+        balance_changes: Vec::new(),
+        swaps,
+        dex: decoded.dex,
     })
-} 
\ No newline at end of file
+}
+
+// 补充代币符号和精度，将原始资产变动和兑换转换为可展示的格式
+async fn resolve_decoded(chain: &EvmChain, decoded: &DecodedReceipt) -> (Vec<AssetMovement>, Vec<SwapLeg>) {
+    // 同一代币只查询一次
+    let mut token_info: HashMap<Address, (Option<String>, Option<u8>)> = HashMap::new();
+    let tokens = decoded
+        .movements
+        .iter()
+        .filter_map(|m| m.token_address)
+        .chain(decoded.swaps.iter().flat_map(|s| [s.token_in, s.token_out]).flatten());
+    for token in tokens {
+        if token_info.contains_key(&token) {
+            continue;
+        }
+        let address = format_address(&token);
+        let symbol = get_token_info(chain.chain_id, &address).await.ok().map(|(symbol, _)| symbol);
+        let decimals = get_token_decimals(chain.chain_id, &address).await.ok();
+        token_info.insert(token, (symbol, decimals));
+    }
+    let info = |token: Option<Address>| match token {
+        Some(token) => token_info.get(&token).cloned().unwrap_or((None, None)),
+        None => (Some(chain.native_symbol.to_string()), Some(chain.native_decimals)),
+    };
+
+    let movements = decoded
+        .movements
+        .iter()
+        .map(|movement| {
+            let (token_symbol, decimals) = info(movement.token_address);
+
+            AssetMovement {
+                token_address: movement.token_address.as_ref().map(format_address),
+                token_symbol,
+                decimals,
+                from_address: format_address(&movement.from),
+                to_address: format_address(&movement.to),
+                raw_amount: movement.amount.to_string(),
+                amount: format_amount(movement.amount, decimals),
+                source: movement.source.to_string(),
+            }
+        })
+        .collect();
+
+    // 兑换事件的资产未识别时数量没有意义
+    let swaps = decoded
+        .swaps
+        .iter()
+        .map(|swap| {
+            let (token_in_symbol, in_decimals) = swap.token_in.map(|t| info(Some(t))).unwrap_or((None, None));
+            let (token_out_symbol, out_decimals) = swap.token_out.map(|t| info(Some(t))).unwrap_or((None, None));
+
+            SwapLeg {
+                protocol: swap.protocol.to_string(),
+                pool: format_address(&swap.pool),
+                token_in: swap.token_in.as_ref().map(format_address),
+                token_in_symbol,
+                amount_in: format_amount(swap.amount_in, in_decimals),
+                token_out: swap.token_out.as_ref().map(format_address),
+                token_out_symbol,
+                amount_out: format_amount(swap.amount_out, out_decimals),
+            }
+        })
+        .collect();
+
+    (movements, swaps)
+}
+
+fn format_address(address: &Address) -> String {
+    format!("0x{}", hex::encode(address.as_bytes()))
+}
+
+// 按精度换算数量，精度未知时为None
+fn format_amount(amount: U256, decimals: Option<u8>) -> Option<f64> {
+    decimals.and_then(|d| {
+        ethers::utils::format_units(amount, d as u32)
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+    })
+}
+
+// 获取最新区块高度
+pub async fn get_block_number(chain_id: i32) -> Result<u64, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    pool.call("get block number", |client| async move {
+        client.get_block_number()
+            .await
+            .map(|n| n.as_u64())
+            .map_err(|e| format!("Failed to get block number: {}", e))
+    }).await
+}
+
+// 获取链上常见代币列表
+pub fn curated_tokens(chain_id: i32) -> Vec<String> {
+    chains::get_chain(chain_id)
+        .map(|chain| chain.curated_tokens.iter().map(|t| t.to_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+// 扫描区块范围内钱包转入或转出的ERC20 Transfer日志，返回涉及的代币合约
+pub async fn scan_transfer_tokens(chain_id: i32, wallet_address: &str, from_block: u64, to_block: u64) -> Result<Vec<String>, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    let wallet = Address::from_str(wallet_address.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid wallet address: {}", e))?;
+    let wallet_topic = H256::from(wallet);
+    let transfer_topic = H256::from(ethers::utils::keccak256("Transfer(address,address,uint256)"));
+    
+    let mut tokens: Vec<String> = Vec::new();
+    let mut start = from_block;
+    while start <= to_block {
+        let end = (start + LOG_SCAN_BLOCK_RANGE - 1).min(to_block);
+        
+        // 分别查询转出（topic1）和转入（topic2）
+        let outgoing = Filter::new()
+            .from_block(start)
+            .to_block(end)
+            .topic0(transfer_topic)
+            .topic1(wallet_topic);
+        let incoming = Filter::new()
+            .from_block(start)
+            .to_block(end)
+            .topic0(transfer_topic)
+            .topic2(wallet_topic);
+        
+        for filter in [outgoing, incoming] {
+            let filter = &filter;
+            let logs = pool.call("get logs", |client| async move {
+                client.get_logs(filter)
+                    .await
+                    .map_err(|e| format!("Failed to get logs: {}", e))
+            }).await?;
+            
+            // ERC721的Transfer有4个topic，这里只保留ERC20
+            for log in logs.iter().filter(|log| log.topics.len() == 3) {
+                let token = format!("0x{}", hex::encode(log.address.as_bytes()));
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+        }
+        
+        start = end + 1;
+    }
+    
+    Ok(tokens)
+}
+
+// 通过Multicall3批量查询代币余额和信息，只返回余额大于0的代币
+pub async fn get_erc20_assets(chain_id: i32, wallet_address: &str, token_addresses: &[String]) -> Result<Vec<Asset>, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    let wallet = Address::from_str(wallet_address.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid wallet address: {}", e))?;
+    
+    let tokens: Vec<Address> = token_addresses
+        .iter()
+        .filter_map(|t| Address::from_str(t.trim_start_matches("0x")).ok())
+        .collect();
+    
+    // 第一轮：批量查询余额
+    let mut balances: Vec<(Address, U256)> = Vec::new();
+    for chunk in tokens.chunks(MULTICALL_BATCH_SIZE) {
+        let calls: Vec<(Address, &str, Option<Address>)> = chunk
+            .iter()
+            .map(|token| (*token, "balanceOf", Some(wallet)))
+            .collect();
+        let results = pool.call("multicall balanceOf", |client| erc20_multicall(client, &calls)).await?;
+        
+        for (token, result) in chunk.iter().zip(results) {
+            if let Ok(Token::Uint(balance)) = result {
+                if !balance.is_zero() {
+                    balances.push((*token, balance));
+                }
+            }
+        }
+    }
+    
+    // 第二轮：只为有余额的代币查询精度、符号和名称
+    let mut assets = Vec::with_capacity(balances.len());
+    for chunk in balances.chunks(MULTICALL_BATCH_SIZE / 3) {
+        let calls: Vec<(Address, &str, Option<Address>)> = chunk
+            .iter()
+            .flat_map(|(token, _)| {
+                ["decimals", "symbol", "name"].map(|method| (*token, method, None))
+            })
+            .collect();
+        let results = pool.call("multicall token info", |client| erc20_multicall(client, &calls)).await?;
+        
+        for ((token, balance), info) in chunk.iter().zip(results.chunks(3)) {
+            // 部分老代币的symbol/name返回bytes32，解析失败时留空
+            // 精度超出u8范围的代币视为异常合约，跳过
+            let decimals = match &info[0] {
+                Ok(Token::Uint(d)) if d.bits() <= 64 => u8::try_from(d.low_u64()).ok(),
+                _ => None,
+            };
+            let decimals = match decimals {
+                Some(decimals) => decimals,
+                None => continue,
+            };
+            let symbol = match &info[1] {
+                Ok(Token::String(s)) => s.clone(),
+                _ => String::new(),
+            };
+            let name = match &info[2] {
+                Ok(Token::String(s)) => s.clone(),
+                _ => String::new(),
+            };
+            
+            let balance = ethers::utils::format_units(*balance, decimals as u32)
+                .ok()
+                .and_then(|s| s.parse::<f64>().ok());
+            
+            assets.push(Asset {
+                chain_id,
+                asset_type: "token".to_string(),
+                symbol,
+                name,
+                contract_address: Some(format!("0x{}", hex::encode(token.as_bytes()))),
+                balance,
+                decimals: Some(decimals),
+                price_usd: None,
+                value_usd: None,
+                price_source: None,
+                price_updated_at: None,
+                logo_url: None,
+                created_at: None,
+                updated_at: None,
+            });
+        }
+    }
+    
+    Ok(assets)
+}
+
+// 通过Multicall3批量调用ERC20只读方法，单个调用失败不影响其他调用
+// calls为（合约地址，方法名，可选的地址参数）
+async fn erc20_multicall(
+    client: Arc<Provider<Http>>,
+    calls: &[(Address, &str, Option<Address>)],
+) -> Result<Vec<Result<Token, Bytes>>, String> {
+    let mut multicall = Multicall::new(client.clone(), None)
+        .await
+        .map_err(|e| format!("Failed to create multicall: {}", e))?
+        .version(MulticallVersion::Multicall3);
+    
+    for (token, method, arg) in calls {
+        let contract = erc20_contract(*token, client.clone());
+        let call = match arg {
+            Some(address) => contract.method::<_, Token>(method, *address),
+            None => contract.method::<_, Token>(method, ()),
+        }
+        .map_err(|e| format!("Failed to create {} method call: {}", method, e))?;
+        multicall.add_call(call, true);
+    }
+    
+    multicall.call_raw()
+        .await
+        .map_err(|e| format!("Failed to call multicall: {}", e))
+}
+
+// 获取原生币持仓，余额为0时返回None
+pub async fn get_native_asset(chain_id: i32, wallet_address: &str) -> Result<Option<Asset>, String> {
+    let chain = chains::require_chain(chain_id)?;
+    let balance = get_eth_balance(chain_id, wallet_address).await?;
+    
+    let raw_balance = U256::from_dec_str(&balance.raw_balance)
+        .map_err(|e| format!("Invalid balance: {}", e))?;
+    if raw_balance.is_zero() {
+        return Ok(None);
+    }
+    
+    let amount = ethers::utils::format_units(raw_balance, chain.native_decimals as u32)
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok());
+    
+    Ok(Some(Asset {
+        chain_id,
+        asset_type: "native".to_string(),
+        symbol: chain.native_symbol.to_string(),
+        name: chain.native_name.to_string(),
+        contract_address: None,
+        balance: amount,
+        decimals: Some(chain.native_decimals),
+        price_usd: None,
+        value_usd: None,
+        price_source: None,
+        price_updated_at: None,
+        logo_url: None,
+        created_at: None,
+        updated_at: None,
+    }))
+}
+
+/// Transfer日志中发现的NFT
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftCandidate {
+    pub contract_address: Address,
+    pub token_id: U256,
+    pub standard: &'static str,
+}
+
+// 扫描区块范围内钱包转入或转出的ERC721 Transfer和ERC1155 TransferSingle/TransferBatch日志
+pub async fn scan_nft_transfers(chain_id: i32, wallet_address: &str, from_block: u64, to_block: u64) -> Result<Vec<NftCandidate>, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    let wallet = Address::from_str(wallet_address.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid wallet address: {}", e))?;
+    let wallet_topic = H256::from(wallet);
+    let transfer_topic = H256::from(ethers::utils::keccak256("Transfer(address,address,uint256)"));
+    let single_topic = H256::from(ethers::utils::keccak256("TransferSingle(address,address,address,uint256,uint256)"));
+    let batch_topic = H256::from(ethers::utils::keccak256("TransferBatch(address,address,address,uint256[],uint256[])"));
+    
+    let mut candidates: Vec<NftCandidate> = Vec::new();
+    let mut start = from_block;
+    while start <= to_block {
+        let end = (start + LOG_SCAN_BLOCK_RANGE - 1).min(to_block);
+        let range = Filter::new().from_block(start).to_block(end);
+        
+        // ERC721的from/to在topic1/topic2，ERC1155的在topic2/topic3（topic1为operator）
+        let filters = [
+            range.clone().topic0(transfer_topic).topic1(wallet_topic),
+            range.clone().topic0(transfer_topic).topic2(wallet_topic),
+            range.clone().topic0(vec![single_topic, batch_topic]).topic2(wallet_topic),
+            range.clone().topic0(vec![single_topic, batch_topic]).topic3(wallet_topic),
+        ];
+        
+        for filter in &filters {
+            let logs = pool.call("get NFT logs", |client| async move {
+                client.get_logs(filter)
+                    .await
+                    .map_err(|e| format!("Failed to get logs: {}", e))
+            }).await?;
+            
+            for log in &logs {
+                for candidate in decode_nft_log(log, transfer_topic, single_topic, batch_topic) {
+                    if !candidates.contains(&candidate) {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+        }
+        
+        start = end + 1;
+    }
+    
+    Ok(candidates)
+}
+
+// 从NFT转账日志中解析合约和tokenId
+fn decode_nft_log(log: &Log, transfer_topic: H256, single_topic: H256, batch_topic: H256) -> Vec<NftCandidate> {
+    let topic0 = match log.topics.first() {
+        Some(topic) => *topic,
+        None => return Vec::new(),
+    };
+    let candidate = |token_id: U256, standard: &'static str| NftCandidate {
+        contract_address: log.address,
+        token_id,
+        standard,
+    };
+    
+    if topic0 == transfer_topic {
+        // ERC20的Transfer只有3个topic
+        if log.topics.len() != 4 {
+            return Vec::new();
+        }
+        vec![candidate(U256::from_big_endian(log.topics[3].as_bytes()), NFT_STANDARD_ERC721)]
+    } else if topic0 == single_topic {
+        if log.data.len() < 64 {
+            return Vec::new();
+        }
+        vec![candidate(U256::from_big_endian(&log.data[0..32]), NFT_STANDARD_ERC1155)]
+    } else if topic0 == batch_topic {
+        let params = [
+            ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::Uint(256))),
+            ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::Uint(256))),
+        ];
+        match ethers::abi::decode(&params, &log.data) {
+            Ok(tokens) => match tokens.into_iter().next() {
+                Some(Token::Array(ids)) => ids
+                    .into_iter()
+                    .filter_map(|id| id.into_uint())
+                    .map(|id| candidate(id, NFT_STANDARD_ERC1155))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            Err(_) => Vec::new(),
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+// 确认钱包当前是否仍持有NFT，返回持有数量大于0的NFT（ERC721数量为1）
+pub async fn get_nft_balances(chain_id: i32, wallet_address: &str, candidates: &[NftCandidate]) -> Result<Vec<(NftCandidate, U256)>, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    let wallet = Address::from_str(wallet_address.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid wallet address: {}", e))?;
+    
+    let mut owned = Vec::new();
+    for chunk in candidates.chunks(MULTICALL_BATCH_SIZE) {
+        let results = pool.call("multicall NFT ownership", |client| nft_multicall(client, chunk, Some(wallet))).await?;
+        
+        for (candidate, result) in chunk.iter().zip(results) {
+            let balance = match result {
+                Ok(Token::Address(owner)) if owner == wallet => U256::one(),
+                Ok(Token::Uint(balance)) => balance,
+                _ => continue,
+            };
+            if !balance.is_zero() {
+                owned.push((candidate.clone(), balance));
+            }
+        }
+    }
+    
+    Ok(owned)
+}
+
+// 批量获取NFT的元数据URI，ERC1155的{id}占位符按规范替换为64位十六进制
+pub async fn get_nft_token_uris(chain_id: i32, candidates: &[NftCandidate]) -> Result<Vec<Option<String>>, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    let mut uris = Vec::with_capacity(candidates.len());
+    for chunk in candidates.chunks(MULTICALL_BATCH_SIZE) {
+        let results = pool.call("multicall NFT token URI", |client| nft_multicall(client, chunk, None)).await?;
+        
+        for (candidate, result) in chunk.iter().zip(results) {
+            let uri = match result {
+                Ok(Token::String(uri)) if !uri.is_empty() => uri,
+                _ => {
+                    uris.push(None);
+                    continue;
+                }
+            };
+            let uri = if candidate.standard == NFT_STANDARD_ERC1155 {
+                uri.replace("{id}", &format!("{:064x}", candidate.token_id))
+            } else {
+                uri
+            };
+            uris.push(Some(uri));
+        }
+    }
+    
+    Ok(uris)
+}
+
+// 获取NFT合约名称
+pub async fn get_nft_collection_name(chain_id: i32, contract_address: Address) -> Result<String, String> {
+    let pool = provider_pool::get_pool(chain_id)?;
+    
+    pool.call("get NFT collection name", |client| async move {
+        Contract::new(
+            contract_address,
+            serde_json::from_str::<ethers::abi::Abi>(ERC721_ABI).unwrap(),
+            client,
+        )
+        .method::<_, String>("name", ())
+        .map_err(|e| format!("Failed to create name method call: {}", e))?
+        .call()
+        .await
+        .map_err(|e| format!("Failed to call name: {}", e))
+    }).await
+}
+
+// 通过Multicall3批量查询NFT，传入钱包地址时查询持有情况（ownerOf / balanceOf），否则查询元数据URI（tokenURI / uri）
+async fn nft_multicall(
+    client: Arc<Provider<Http>>,
+    candidates: &[NftCandidate],
+    wallet: Option<Address>,
+) -> Result<Vec<Result<Token, Bytes>>, String> {
+    let erc721_abi = serde_json::from_str::<ethers::abi::Abi>(ERC721_ABI).unwrap();
+    let erc1155_abi = serde_json::from_str::<ethers::abi::Abi>(ERC1155_ABI).unwrap();
+    
+    let mut multicall = Multicall::new(client.clone(), None)
+        .await
+        .map_err(|e| format!("Failed to create multicall: {}", e))?
+        .version(MulticallVersion::Multicall3);
+    
+    for candidate in candidates {
+        let call = if candidate.standard == NFT_STANDARD_ERC1155 {
+            let contract = Contract::new(candidate.contract_address, erc1155_abi.clone(), client.clone());
+            match wallet {
+                Some(wallet) => contract.method::<_, Token>("balanceOf", (wallet, candidate.token_id)),
+                None => contract.method::<_, Token>("uri", candidate.token_id),
+            }
+        } else {
+            let contract = Contract::new(candidate.contract_address, erc721_abi.clone(), client.clone());
+            match wallet {
+                Some(_) => contract.method::<_, Token>("ownerOf", candidate.token_id),
+                None => contract.method::<_, Token>("tokenURI", candidate.token_id),
+            }
+        }
+        .map_err(|e| format!("Failed to create NFT method call: {}", e))?;
+        multicall.add_call(call, true);
+    }
+    
+    multicall.call_raw()
+        .await
+        .map_err(|e| format!("Failed to call multicall: {}", e))
+}
@@ -1,6 +1,10 @@
+pub mod chains;
 pub mod ethereum;
+pub mod price_feeds;
+pub mod provider_pool;
 pub mod solana;
+pub mod receipt_decoder;
 
 // 重新导出常用函数
-pub use ethereum::{get_eth_balance, get_erc20_balance, verify_transaction as verify_eth_transaction};
-pub use solana::{get_sol_balance, get_spl_balance }; 
\ No newline at end of file
+pub use ethereum::{get_eth_balance, verify_transaction as verify_eth_transaction};
+pub use solana::{get_sol_balance, get_spl_assets, verify_transaction as verify_sol_transaction}; 
\ No newline at end of file
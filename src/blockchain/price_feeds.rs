@@ -0,0 +1,228 @@
+use crate::blockchain::chains;
+use crate::blockchain::ethereum::erc20_contract;
+use crate::blockchain::provider_pool;
+use ethers::prelude::*;
+use ethers::types::{Address, I256, U256};
+use std::str::FromStr;
+
+// Chainlink喂价合约ABI
+const CHAINLINK_FEED_ABI: &str = r#"[
+    {
+        "inputs": [],
+        "name": "decimals",
+        "outputs": [{"name": "", "type": "uint8"}],
+        "stateMutability": "view",
+        "type": "function"
+    },
+    {
+        "inputs": [],
+        "name": "latestRoundData",
+        "outputs": [
+            {"name": "roundId", "type": "uint80"},
+            {"name": "answer", "type": "int256"},
+            {"name": "startedAt", "type": "uint256"},
+            {"name": "updatedAt", "type": "uint256"},
+            {"name": "answeredInRound", "type": "uint80"}
+        ],
+        "stateMutability": "view",
+        "type": "function"
+    }
+]"#;
+
+// Uniswap V3工厂合约ABI
+const UNISWAP_V3_FACTORY_ABI: &str = r#"[
+    {
+        "inputs": [
+            {"name": "tokenA", "type": "address"},
+            {"name": "tokenB", "type": "address"},
+            {"name": "fee", "type": "uint24"}
+        ],
+        "name": "getPool",
+        "outputs": [{"name": "pool", "type": "address"}],
+        "stateMutability": "view",
+        "type": "function"
+    }
+]"#;
+
+// Uniswap V3池子ABI，slot0只解码第一个返回值，兼容各分叉不同的slot0结构
+const UNISWAP_V3_POOL_ABI: &str = r#"[
+    {
+        "inputs": [],
+        "name": "slot0",
+        "outputs": [{"name": "sqrtPriceX96", "type": "uint160"}],
+        "stateMutability": "view",
+        "type": "function"
+    },
+    {
+        "inputs": [],
+        "name": "token0",
+        "outputs": [{"name": "", "type": "address"}],
+        "stateMutability": "view",
+        "type": "function"
+    }
+]"#;
+
+// 依次尝试的Uniswap V3费率档位
+const UNISWAP_V3_FEES: [u32; 4] = [500, 3000, 10000, 100];
+// Chainlink价格超过该时间（秒）未更新视为失效
+const MAX_FEED_AGE_SECS: i64 = 24 * 3600;
+
+/// 喂价结果
+#[derive(Debug, Clone)]
+pub struct FeedPrice {
+    pub price_usd: f64,
+    pub updated_at: i64,
+}
+
+// 读取Chainlink原生币/USD喂价
+pub async fn get_native_usd_price(chain_id: i32) -> Result<FeedPrice, String> {
+    let chain = chains::require_chain(chain_id)?;
+    let pool = provider_pool::get_pool(chain_id)?;
+
+    let feed_address = Address::from_str(chain.native_usd_feed)
+        .map_err(|e| format!("Invalid Chainlink feed address: {}", e))?;
+
+    let (answer, updated_at, decimals) = pool.call("get Chainlink price", |client| async move {
+        let contract = Contract::new(
+            feed_address,
+            serde_json::from_str::<ethers::abi::Abi>(CHAINLINK_FEED_ABI).unwrap(),
+            client,
+        );
+
+        let (_, answer, _, updated_at, _): (U256, I256, U256, U256, U256) = contract
+            .method("latestRoundData", ())
+            .map_err(|e| format!("Failed to create latestRoundData method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call latestRoundData: {}", e))?;
+
+        let decimals: u8 = contract
+            .method("decimals", ())
+            .map_err(|e| format!("Failed to create decimals method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call decimals: {}", e))?;
+
+        Ok((answer, updated_at, decimals))
+    }).await?;
+
+    if answer <= I256::zero() {
+        return Err(format!("Invalid Chainlink answer on {}: {}", chain.name, answer));
+    }
+    let updated_at = updated_at.as_u64() as i64;
+    if chrono::Utc::now().timestamp() - updated_at > MAX_FEED_AGE_SECS {
+        return Err(format!("Chainlink price on {} is stale", chain.name));
+    }
+
+    let price_usd = answer
+        .to_string()
+        .parse::<f64>()
+        .map_err(|e| format!("Invalid Chainlink answer: {}", e))?
+        / 10f64.powi(decimals as i32);
+
+    Ok(FeedPrice { price_usd, updated_at })
+}
+
+// 根据代币与包装原生币的Uniswap V3池子估算代币价格（以原生币计价）
+// 取原生币储备最多的池子，储备不足的池子价格容易被操纵，直接忽略
+pub async fn get_uniswap_native_price(chain_id: i32, token_address: &str) -> Result<Option<f64>, String> {
+    let chain = chains::require_chain(chain_id)?;
+    let pool = provider_pool::get_pool(chain_id)?;
+
+    let token = Address::from_str(token_address.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid token address: {}", e))?;
+    let wrapped_native = Address::from_str(chain.wrapped_native)
+        .map_err(|e| format!("Invalid wrapped native address: {}", e))?;
+    let factory_address = Address::from_str(chain.uniswap_v3_factory)
+        .map_err(|e| format!("Invalid Uniswap factory address: {}", e))?;
+    let native_decimals = chain.native_decimals;
+    let min_reserve = U256::exp10(native_decimals as usize);
+
+    if token == wrapped_native {
+        return Ok(Some(1.0));
+    }
+
+    pool.call("get Uniswap price", |client| async move {
+        let factory = Contract::new(
+            factory_address,
+            serde_json::from_str::<ethers::abi::Abi>(UNISWAP_V3_FACTORY_ABI).unwrap(),
+            client.clone(),
+        );
+        let native = erc20_contract(wrapped_native, client.clone());
+
+        let token_decimals: u8 = erc20_contract(token, client.clone())
+            .method("decimals", ())
+            .map_err(|e| format!("Failed to create decimals method call: {}", e))?
+            .call()
+            .await
+            .map_err(|e| format!("Failed to call decimals: {}", e))?;
+
+        // (原生币储备, sqrtPriceX96, 代币是否为token0)
+        let mut best: Option<(U256, U256, bool)> = None;
+
+        for fee in UNISWAP_V3_FEES {
+            let pool_address: Address = factory
+                .method("getPool", (token, wrapped_native, fee))
+                .map_err(|e| format!("Failed to create getPool method call: {}", e))?
+                .call()
+                .await
+                .map_err(|e| format!("Failed to call getPool: {}", e))?;
+            if pool_address == Address::zero() {
+                continue;
+            }
+
+            let reserve: U256 = native
+                .method("balanceOf", pool_address)
+                .map_err(|e| format!("Failed to create balanceOf method call: {}", e))?
+                .call()
+                .await
+                .map_err(|e| format!("Failed to call balanceOf: {}", e))?;
+            if reserve < min_reserve || best.is_some_and(|(r, _, _)| r >= reserve) {
+                continue;
+            }
+
+            let pool_contract = Contract::new(
+                pool_address,
+                serde_json::from_str::<ethers::abi::Abi>(UNISWAP_V3_POOL_ABI).unwrap(),
+                client.clone(),
+            );
+            let sqrt_price_x96: U256 = pool_contract
+                .method("slot0", ())
+                .map_err(|e| format!("Failed to create slot0 method call: {}", e))?
+                .call()
+                .await
+                .map_err(|e| format!("Failed to call slot0: {}", e))?;
+            let token0: Address = pool_contract
+                .method("token0", ())
+                .map_err(|e| format!("Failed to create token0 method call: {}", e))?
+                .call()
+                .await
+                .map_err(|e| format!("Failed to call token0: {}", e))?;
+
+            best = Some((reserve, sqrt_price_x96, token0 == token));
+        }
+
+        Ok(best.and_then(|(_, sqrt_price_x96, token_is_token0)| {
+            pool_price(sqrt_price_x96, token_is_token0, token_decimals, native_decimals)
+        }))
+    }).await
+}
+
+// 将sqrtPriceX96换算为代币以原生币计价的价格
+// sqrtPriceX96^2 / 2^192 为 token1/token0 的原始单位比例，再按精度差调整
+fn pool_price(sqrt_price_x96: U256, token_is_token0: bool, token_decimals: u8, native_decimals: u8) -> Option<f64> {
+    let sqrt_price = sqrt_price_x96.to_string().parse::<f64>().ok()? / 2f64.powi(96);
+    let raw_price = sqrt_price * sqrt_price;
+    if !raw_price.is_finite() || raw_price <= 0.0 {
+        return None;
+    }
+
+    let scale = 10f64.powi(token_decimals as i32 - native_decimals as i32);
+    let price = if token_is_token0 {
+        raw_price * scale
+    } else {
+        scale / raw_price
+    };
+
+    Some(price).filter(|p| p.is_finite() && *p > 0.0)
+}
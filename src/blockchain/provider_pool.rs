@@ -0,0 +1,396 @@
+use crate::blockchain::chains;
+use ethers::providers::{Http, Middleware, Provider};
+use lazy_static::lazy_static;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+// 连续失败多少次后将节点标记为不健康
+const FAILURE_THRESHOLD: u32 = 3;
+// 不健康节点的冷却时间，冷却期内只在没有其他可用节点时使用
+const FAILURE_COOLDOWN: Duration = Duration::from_secs(30);
+// 触发限流后的冷却时间
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+// 落后最高区块超过该值的节点视为不健康
+const MAX_BLOCK_LAG: u64 = 10;
+// 延迟的指数移动平均权重
+const LATENCY_EWMA_WEIGHT: f64 = 0.2;
+
+lazy_static! {
+    // 进程内共享的各链节点池
+    static ref POOLS: RwLock<HashMap<i32, Arc<ProviderPool>>> = RwLock::new(HashMap::new());
+}
+
+// 节点状态
+#[derive(Debug, Default)]
+struct EndpointState {
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    latency_ms: Option<f64>,
+    last_block: Option<u64>,
+}
+
+// 单个RPC节点
+struct Endpoint {
+    label: String, // 不含路径和参数的节点地址，避免在日志中泄露API Key
+    provider: Arc<Provider<Http>>,
+    state: Mutex<EndpointState>,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    rate_limited: AtomicU64,
+}
+
+impl Endpoint {
+    fn is_available(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap();
+        state.cooldown_until.is_none_or(|until| until <= now)
+    }
+
+    fn latency(&self) -> f64 {
+        self.state.lock().unwrap().latency_ms.unwrap_or(f64::MAX)
+    }
+
+    fn record_success(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        state.latency_ms = Some(match state.latency_ms {
+            Some(prev) => prev * (1.0 - LATENCY_EWMA_WEIGHT) + latency_ms * LATENCY_EWMA_WEIGHT,
+            None => latency_ms,
+        });
+        state.consecutive_failures = 0;
+        state.cooldown_until = None;
+    }
+
+    fn record_failure(&self, error: &str) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+
+        if is_rate_limit_error(error) {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+            state.cooldown_until = Some(Instant::now() + RATE_LIMIT_COOLDOWN);
+        } else if state.consecutive_failures >= FAILURE_THRESHOLD {
+            state.cooldown_until = Some(Instant::now() + FAILURE_COOLDOWN);
+        }
+    }
+}
+
+/// 节点调用统计
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointMetrics {
+    pub chain_id: i32,
+    pub endpoint: String,
+    pub healthy: bool,
+    pub latency_ms: Option<f64>,
+    pub last_block: Option<u64>,
+    pub requests: u64,
+    pub failures: u64,
+    pub rate_limited: u64,
+}
+
+/// 单条链的RPC节点池，按健康状态和延迟选择节点，出错时自动切换
+pub struct ProviderPool {
+    chain_id: i32,
+    endpoints: Vec<Endpoint>,
+}
+
+impl ProviderPool {
+    // 根据链配置创建节点池，RPC地址环境变量支持逗号分隔的多个节点
+    fn from_env(chain_id: i32) -> Result<Self, String> {
+        let chain = chains::require_chain(chain_id)?;
+        let rpc_urls = chain
+            .rpc_url()
+            .ok_or_else(|| format!("{} environment variable not set", chain.rpc_url_env))?;
+
+        let mut endpoints = Vec::new();
+        for url in rpc_urls.split(',').map(str::trim).filter(|u| !u.is_empty()) {
+            let provider = Provider::<Http>::try_from(url)
+                .map_err(|e| format!("Failed to create {} provider: {}", chain.name, e))?;
+            endpoints.push(Endpoint {
+                label: endpoint_label(url),
+                provider: Arc::new(provider),
+                state: Mutex::new(EndpointState::default()),
+                requests: AtomicU64::new(0),
+                failures: AtomicU64::new(0),
+                rate_limited: AtomicU64::new(0),
+            });
+        }
+
+        if endpoints.is_empty() {
+            return Err(format!("No RPC endpoint configured for {}", chain.name));
+        }
+
+        Ok(Self { chain_id, endpoints })
+    }
+
+    // 可用节点按延迟排序，冷却中的节点排在最后作为兜底
+    fn ordered_endpoints(&self) -> Vec<&Endpoint> {
+        let now = Instant::now();
+        let mut endpoints: Vec<&Endpoint> = self.endpoints.iter().collect();
+        endpoints.sort_by(|a, b| {
+            b.is_available(now)
+                .cmp(&a.is_available(now))
+                .then(a.latency().total_cmp(&b.latency()))
+        });
+        endpoints
+    }
+
+    // 在单个节点上执行调用并记录统计
+    async fn call_endpoint<T, F, Fut>(&self, endpoint: &Endpoint, operation: &str, call: &F) -> Result<T, String>
+    where
+        F: Fn(Arc<Provider<Http>>) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        endpoint.requests.fetch_add(1, Ordering::Relaxed);
+        let started = Instant::now();
+
+        let result = match tokio::time::timeout(rpc_timeout(), call(endpoint.provider.clone())).await {
+            Ok(result) => result,
+            Err(_) => Err("request timed out".to_string()),
+        };
+
+        match &result {
+            Ok(_) => {
+                endpoint.record_success(started.elapsed());
+                log::debug!(
+                    "EVM RPC {} on chain {} served by {} in {:?}",
+                    operation, self.chain_id, endpoint.label, started.elapsed()
+                );
+            }
+            Err(e) if is_revert_error(e) => {
+                // 节点已正常响应，执行失败与节点无关，不计入失败
+                endpoint.record_success(started.elapsed());
+                log::debug!(
+                    "EVM RPC {} on chain {} reverted on {}: {}",
+                    operation, self.chain_id, endpoint.label, e
+                );
+            }
+            Err(e) => {
+                endpoint.record_failure(e);
+                log::warn!(
+                    "EVM RPC {} on chain {} failed on {}: {}",
+                    operation, self.chain_id, endpoint.label, e
+                );
+            }
+        }
+
+        result
+    }
+
+    /// 按节点优先级依次调用，网络错误、超时或限流时切换到下一个节点，合约回滚直接返回
+    pub async fn call<T, F, Fut>(&self, operation: &str, call: F) -> Result<T, String>
+    where
+        F: Fn(Arc<Provider<Http>>) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let mut last_error = String::new();
+
+        for endpoint in self.ordered_endpoints() {
+            match self.call_endpoint(endpoint, operation, &call).await {
+                Ok(value) => return Ok(value),
+                Err(e) if is_revert_error(&e) => return Err(format!("Failed to {}: {}", operation, e)),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(format!("Failed to {}: {}", operation, last_error))
+    }
+
+    /// 向多个节点查询，至少quorum个节点返回一致结果时才采用，配置的节点数不足quorum时返回错误
+    pub async fn quorum_call<T, F, Fut>(&self, operation: &str, quorum: usize, call: F) -> Result<T, String>
+    where
+        T: PartialEq,
+        F: Fn(Arc<Provider<Http>>) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let quorum = quorum.max(1);
+        if quorum > self.endpoints.len() {
+            log::error!(
+                "EVM RPC quorum of {} on chain {} requires more endpoints than the {} configured",
+                quorum, self.chain_id, self.endpoints.len()
+            );
+            return Err(format!(
+                "Failed to {}: quorum of {} requires at least {} RPC endpoints, only {} configured",
+                operation, quorum, quorum, self.endpoints.len()
+            ));
+        }
+        if quorum == 1 {
+            return self.call(operation, call).await;
+        }
+
+        let mut results: Vec<(T, usize)> = Vec::new();
+        let mut last_error = String::new();
+
+        for endpoint in self.ordered_endpoints() {
+            match self.call_endpoint(endpoint, operation, &call).await {
+                Ok(value) => {
+                    let index = match results.iter().position(|(v, _)| *v == value) {
+                        Some(index) => {
+                            results[index].1 += 1;
+                            index
+                        }
+                        None => {
+                            results.push((value, 1));
+                            results.len() - 1
+                        }
+                    };
+                    if results[index].1 >= quorum {
+                        return Ok(results.swap_remove(index).0);
+                    }
+                }
+                Err(e) if is_revert_error(&e) => return Err(format!("Failed to {}: {}", operation, e)),
+                Err(e) => last_error = e,
+            }
+        }
+
+        if results.len() > 1 {
+            Err(format!("Failed to {}: RPC endpoints returned inconsistent results", operation))
+        } else {
+            Err(format!("Failed to {}: quorum of {} not reached {}", operation, quorum, last_error))
+        }
+    }
+
+    /// 健康检查：查询各节点最新区块，超时、出错或落后过多的节点进入冷却
+    pub async fn check_health(&self) {
+        let mut heights = Vec::with_capacity(self.endpoints.len());
+
+        for endpoint in &self.endpoints {
+            let started = Instant::now();
+            let result = tokio::time::timeout(rpc_timeout(), endpoint.provider.get_block_number()).await;
+            match result {
+                Ok(Ok(block)) => {
+                    endpoint.record_success(started.elapsed());
+                    endpoint.state.lock().unwrap().last_block = Some(block.as_u64());
+                    heights.push(Some(block.as_u64()));
+                }
+                Ok(Err(e)) => {
+                    endpoint.record_failure(&e.to_string());
+                    heights.push(None);
+                }
+                Err(_) => {
+                    endpoint.record_failure("request timed out");
+                    heights.push(None);
+                }
+            }
+        }
+
+        let max_height = heights.iter().flatten().copied().max().unwrap_or(0);
+        for (endpoint, height) in self.endpoints.iter().zip(heights) {
+            if let Some(height) = height {
+                if height + MAX_BLOCK_LAG < max_height {
+                    log::warn!(
+                        "EVM RPC {} on chain {} is {} blocks behind",
+                        endpoint.label, self.chain_id, max_height - height
+                    );
+                    endpoint.state.lock().unwrap().cooldown_until = Some(Instant::now() + FAILURE_COOLDOWN);
+                }
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> Vec<EndpointMetrics> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let healthy = endpoint.is_available(now);
+                let state = endpoint.state.lock().unwrap();
+                EndpointMetrics {
+                    chain_id: self.chain_id,
+                    endpoint: endpoint.label.clone(),
+                    healthy,
+                    latency_ms: state.latency_ms,
+                    last_block: state.last_block,
+                    requests: endpoint.requests.load(Ordering::Relaxed),
+                    failures: endpoint.failures.load(Ordering::Relaxed),
+                    rate_limited: endpoint.rate_limited.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+// 获取指定链的节点池，首次使用时创建
+pub fn get_pool(chain_id: i32) -> Result<Arc<ProviderPool>, String> {
+    if let Some(pool) = POOLS.read().unwrap().get(&chain_id) {
+        return Ok(pool.clone());
+    }
+
+    let mut pools = POOLS.write().unwrap();
+    if let Some(pool) = pools.get(&chain_id) {
+        return Ok(pool.clone());
+    }
+    let pool = Arc::new(ProviderPool::from_env(chain_id)?);
+    pools.insert(chain_id, pool.clone());
+    Ok(pool)
+}
+
+// 交易验证所需的一致节点数，通过 EVM_TX_QUORUM 配置（默认1，即不做多节点校验）
+pub fn tx_quorum() -> usize {
+    env::var("EVM_TX_QUORUM")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1)
+}
+
+// 所有已创建节点池的统计
+pub fn all_metrics() -> Vec<EndpointMetrics> {
+    let pools: Vec<Arc<ProviderPool>> = POOLS.read().unwrap().values().cloned().collect();
+    pools.iter().flat_map(|pool| pool.metrics()).collect()
+}
+
+// 启动后台健康检查，间隔通过 EVM_RPC_HEALTH_INTERVAL_SECS 配置（默认60秒）
+pub fn spawn_health_checks() {
+    let interval = env::var("EVM_RPC_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            for chain in chains::configured_chains() {
+                match get_pool(chain.chain_id) {
+                    Ok(pool) => pool.check_health().await,
+                    Err(e) => log::warn!("Failed to create RPC pool for {}: {}", chain.name, e),
+                }
+            }
+        }
+    });
+}
+
+// 单次请求超时，通过 EVM_RPC_TIMEOUT_SECS 配置（默认15秒）
+fn rpc_timeout() -> Duration {
+    let secs = env::var("EVM_RPC_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(15);
+    Duration::from_secs(secs)
+}
+
+fn is_rate_limit_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("429") || error.contains("rate limit") || error.contains("too many requests")
+}
+
+// 合约回滚等执行错误由请求本身决定，换节点结果相同；其余错误（网络、超时、5xx等）视为节点故障
+fn is_revert_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("revert")
+        || error.contains("invalid opcode")
+        || error.contains("code: 3,") // JSON-RPC执行错误
+        || error.contains("invalid output type") // 返回数据无法按ABI解析，如调用的地址不是合约
+}
+
+fn endpoint_label(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) => format!("{}://{}", parsed.scheme(), parsed.host_str().unwrap_or_default()),
+        Err(_) => "invalid-url".to_string(),
+    }
+}
@@ -0,0 +1,381 @@
+use ethers::types::{Address, Log, Transaction, TransactionReceipt, H256, I256, U256};
+use ethers::utils::keccak256;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+// 资产变动来源
+pub const MOVEMENT_NATIVE: &str = "native";
+pub const MOVEMENT_TRANSFER: &str = "transfer";
+pub const MOVEMENT_WRAP: &str = "wrap";
+pub const MOVEMENT_UNWRAP: &str = "unwrap";
+
+lazy_static! {
+    // 事件签名哈希
+    static ref TRANSFER_TOPIC: H256 = event_topic("Transfer(address,address,uint256)");
+    static ref WETH_DEPOSIT_TOPIC: H256 = event_topic("Deposit(address,uint256)");
+    static ref WETH_WITHDRAWAL_TOPIC: H256 = event_topic("Withdrawal(address,uint256)");
+    static ref UNISWAP_V2_SWAP_TOPIC: H256 =
+        event_topic("Swap(address,uint256,uint256,uint256,uint256,address)");
+    static ref UNISWAP_V3_SWAP_TOPIC: H256 =
+        event_topic("Swap(address,address,int256,int256,uint160,uint128,int24)");
+
+    // 常见DEX路由及聚合器合约
+    static ref KNOWN_ROUTERS: HashMap<Address, &'static str> = {
+        let mut routers = HashMap::new();
+        routers.insert(parse_address("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"), "Uniswap V2");
+        routers.insert(parse_address("0xE592427A0AEce92De3Edee1F18E0157C05861564"), "Uniswap V3");
+        routers.insert(parse_address("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"), "Uniswap V3");
+        routers.insert(parse_address("0xEf1c6E67703c7BD7107eed8303Fbe6EC2554BF6B"), "Uniswap Universal Router");
+        routers.insert(parse_address("0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"), "Uniswap Universal Router");
+        routers.insert(parse_address("0xd9e1cE17f2641f24aE83637ab66a2cca9C378B9F"), "SushiSwap");
+        routers.insert(parse_address("0x1111111254EEB25477B68fb85Ed929f73A960582"), "1inch");
+        routers.insert(parse_address("0x111111125421cA6dc452d289314280a0f8842A65"), "1inch");
+        routers.insert(parse_address("0xDef1C0ded9bec7F1a1670819833240f027b25EfF"), "0x");
+        routers.insert(parse_address("0xDEF171Fe48CF0115B1d80b88dc8eAB59176FEe57"), "ParaSwap");
+        routers.insert(parse_address("0x9008D19f58AAbD9eD0D60971565AA8510560ab41"), "CoW Protocol");
+        routers
+    };
+}
+
+/// 单笔资产变动（未做精度换算）
+#[derive(Debug, Clone)]
+pub struct RawMovement {
+    pub token_address: Option<Address>, // None表示原生币
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    pub source: &'static str,
+}
+
+/// DEX池子中的兑换事件（未做精度换算），资产按同一交易中与池子之间的转账确定
+#[derive(Debug, Clone)]
+pub struct SwapEvent {
+    pub protocol: &'static str,
+    pub pool: Address,
+    pub token_in: Option<Address>, // 转入池子的资产，未找到对应转账时为None
+    pub amount_in: U256,
+    pub token_out: Option<Address>, // 池子转出的资产
+    pub amount_out: U256,
+}
+
+/// 交易收据解析结果
+#[derive(Debug, Clone, Default)]
+pub struct DecodedReceipt {
+    pub movements: Vec<RawMovement>,
+    pub swaps: Vec<SwapEvent>,
+    pub dex: Option<String>,
+}
+
+// 解析交易收据中的事件日志，归一化为资产变动列表，wrapped_native为所在链的包装原生币合约
+pub fn decode_receipt(
+    tx: &Transaction,
+    receipt: &TransactionReceipt,
+    wrapped_native: Address,
+) -> DecodedReceipt {
+    let mut decoded = DecodedReceipt::default();
+
+    // 交易本身携带的原生币
+    if !tx.value.is_zero() {
+        if let Some(to) = tx.to {
+            decoded.movements.push(RawMovement {
+                token_address: None,
+                from: tx.from,
+                to,
+                amount: tx.value,
+                source: MOVEMENT_NATIVE,
+            });
+        }
+    }
+
+    for log in &receipt.logs {
+        decode_log(log, tx, wrapped_native, &mut decoded);
+    }
+
+    // 兑换事件只记录数量，按数量一致的池子转入、转出转账确定资产
+    for swap in &mut decoded.swaps {
+        swap.token_in = decoded
+            .movements
+            .iter()
+            .find(|m| m.source == MOVEMENT_TRANSFER && m.to == swap.pool && m.amount == swap.amount_in)
+            .and_then(|m| m.token_address);
+        swap.token_out = decoded
+            .movements
+            .iter()
+            .find(|m| m.source == MOVEMENT_TRANSFER && m.from == swap.pool && m.amount == swap.amount_out)
+            .and_then(|m| m.token_address);
+    }
+
+    // 优先使用路由合约识别DEX，其次使用兑换事件的协议
+    decoded.dex = tx
+        .to
+        .and_then(|to| KNOWN_ROUTERS.get(&to))
+        .map(|name| name.to_string())
+        .or_else(|| decoded.swaps.first().map(|s| s.protocol.to_string()));
+
+    decoded
+}
+
+fn decode_log(log: &Log, tx: &Transaction, wrapped_native: Address, decoded: &mut DecodedReceipt) {
+    let topic0 = match log.topics.first() {
+        Some(topic) => *topic,
+        None => return,
+    };
+
+    if topic0 == *TRANSFER_TOPIC {
+        // ERC721的Transfer有4个topic（tokenId也被索引），这里只处理ERC20
+        if log.topics.len() != 3 || log.data.len() < 32 {
+            return;
+        }
+        decoded.movements.push(RawMovement {
+            token_address: Some(log.address),
+            from: topic_to_address(&log.topics[1]),
+            to: topic_to_address(&log.topics[2]),
+            amount: U256::from_big_endian(&log.data[0..32]),
+            source: MOVEMENT_TRANSFER,
+        });
+    } else if topic0 == *WETH_DEPOSIT_TOPIC && log.address == wrapped_native {
+        // 包装：原生币转入包装合约，同时铸造等量包装币
+        if log.topics.len() != 2 || log.data.len() < 32 {
+            return;
+        }
+        let owner = topic_to_address(&log.topics[1]);
+        let amount = U256::from_big_endian(&log.data[0..32]);
+        decoded.movements.push(RawMovement {
+            token_address: Some(wrapped_native),
+            from: Address::zero(),
+            to: owner,
+            amount,
+            source: MOVEMENT_WRAP,
+        });
+    } else if topic0 == *WETH_WITHDRAWAL_TOPIC && log.address == wrapped_native {
+        // 解包装：销毁包装币并返还原生币
+        if log.topics.len() != 2 || log.data.len() < 32 {
+            return;
+        }
+        let owner = topic_to_address(&log.topics[1]);
+        let amount = U256::from_big_endian(&log.data[0..32]);
+        decoded.movements.push(RawMovement {
+            token_address: Some(wrapped_native),
+            from: owner,
+            to: Address::zero(),
+            amount,
+            source: MOVEMENT_UNWRAP,
+        });
+
+        // 路由合约解包装后会通过内部交易将原生币转给发起人，日志中不可见，这里按惯例补全
+        let recipient = if Some(owner) == tx.to && KNOWN_ROUTERS.contains_key(&owner) {
+            tx.from
+        } else {
+            owner
+        };
+        decoded.movements.push(RawMovement {
+            token_address: None,
+            from: wrapped_native,
+            to: recipient,
+            amount,
+            source: MOVEMENT_UNWRAP,
+        });
+    } else if topic0 == *UNISWAP_V2_SWAP_TOPIC {
+        // data: amount0In, amount1In, amount0Out, amount1Out
+        if log.data.len() < 128 {
+            return;
+        }
+        let word = |i: usize| U256::from_big_endian(&log.data[i * 32..(i + 1) * 32]);
+        let (amount0_in, amount1_in, amount0_out, amount1_out) = (word(0), word(1), word(2), word(3));
+        decoded.swaps.push(SwapEvent {
+            protocol: "Uniswap V2",
+            pool: log.address,
+            token_in: None,
+            amount_in: if amount0_in.is_zero() { amount1_in } else { amount0_in },
+            token_out: None,
+            amount_out: if amount0_out.is_zero() { amount1_out } else { amount0_out },
+        });
+    } else if topic0 == *UNISWAP_V3_SWAP_TOPIC {
+        // data: amount0, amount1 (int256，正数为转入池子，负数为池子转出), sqrtPriceX96, liquidity, tick
+        if log.data.len() < 64 {
+            return;
+        }
+        let amount0 = I256::from_raw(U256::from_big_endian(&log.data[0..32]));
+        let amount1 = I256::from_raw(U256::from_big_endian(&log.data[32..64]));
+        let (amount_in, amount_out) = if amount0.is_positive() {
+            (amount0, amount1)
+        } else {
+            (amount1, amount0)
+        };
+        decoded.swaps.push(SwapEvent {
+            protocol: "Uniswap V3",
+            pool: log.address,
+            token_in: None,
+            amount_in: amount_in.unsigned_abs(),
+            token_out: None,
+            amount_out: amount_out.unsigned_abs(),
+        });
+    }
+}
+
+fn event_topic(signature: &str) -> H256 {
+    H256::from(keccak256(signature.as_bytes()))
+}
+
+fn topic_to_address(topic: &H256) -> Address {
+    Address::from_slice(&topic.as_bytes()[12..])
+}
+
+fn parse_address(address: &str) -> Address {
+    address.parse().expect("invalid built-in address")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    const WALLET: &str = "0x8ba1f109551bd432803012645ac136ddd64dba72";
+    const RECIPIENT: &str = "0x28c6c06298d514db089934071355e5743bf21d60";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+    const V2_ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const V2_PAIR: &str = "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc";
+    const V3_ROUTER: &str = "0xe592427a0aece92de3edee1f18e0157c05861564";
+    const V3_POOL: &str = "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640";
+
+    // 收据样本为 eth_getTransactionByHash 与 eth_getTransactionReceipt 的返回格式
+    #[derive(Deserialize)]
+    struct Fixture {
+        transaction: Transaction,
+        receipt: TransactionReceipt,
+    }
+
+    fn decode_fixture(json: &str) -> DecodedReceipt {
+        let fixture: Fixture = serde_json::from_str(json).expect("invalid receipt fixture");
+        decode_receipt(&fixture.transaction, &fixture.receipt, parse_address(WETH))
+    }
+
+    fn ether(amount: u64) -> U256 {
+        U256::exp10(18) * amount
+    }
+
+    fn usdc(amount: u64) -> U256 {
+        U256::exp10(6) * amount
+    }
+
+    // (资产, 转出方, 转入方, 数量, 来源)
+    fn movements(decoded: &DecodedReceipt) -> Vec<(Option<Address>, Address, Address, U256, &'static str)> {
+        decoded
+            .movements
+            .iter()
+            .map(|m| (m.token_address, m.from, m.to, m.amount, m.source))
+            .collect()
+    }
+
+    #[test]
+    fn decodes_erc20_transfer() {
+        let decoded = decode_fixture(include_str!("../../tests/fixtures/receipts/erc20_transfer.json"));
+
+        assert_eq!(
+            movements(&decoded),
+            vec![(
+                Some(parse_address(USDC)),
+                parse_address(WALLET),
+                parse_address(RECIPIENT),
+                usdc(1000),
+                MOVEMENT_TRANSFER
+            )]
+        );
+        assert!(decoded.swaps.is_empty());
+        assert_eq!(decoded.dex, None);
+    }
+
+    #[test]
+    fn decodes_uniswap_v2_swap_via_router() {
+        let decoded = decode_fixture(include_str!("../../tests/fixtures/receipts/uniswap_v2_swap_eth_for_usdc.json"));
+        let (router, pair, wallet) = (parse_address(V2_ROUTER), parse_address(V2_PAIR), parse_address(WALLET));
+        let (weth, usdc_token) = (parse_address(WETH), parse_address(USDC));
+
+        assert_eq!(
+            movements(&decoded),
+            vec![
+                (None, wallet, router, ether(1), MOVEMENT_NATIVE),
+                (Some(weth), Address::zero(), router, ether(1), MOVEMENT_WRAP),
+                (Some(weth), router, pair, ether(1), MOVEMENT_TRANSFER),
+                (Some(usdc_token), pair, wallet, usdc(3000), MOVEMENT_TRANSFER),
+            ]
+        );
+
+        assert_eq!(decoded.swaps.len(), 1);
+        let swap = &decoded.swaps[0];
+        assert_eq!(swap.protocol, "Uniswap V2");
+        assert_eq!(swap.pool, pair);
+        assert_eq!((swap.token_in, swap.amount_in), (Some(weth), ether(1)));
+        assert_eq!((swap.token_out, swap.amount_out), (Some(usdc_token), usdc(3000)));
+        assert_eq!(decoded.dex.as_deref(), Some("Uniswap V2"));
+    }
+
+    #[test]
+    fn decodes_uniswap_v3_swap_via_router() {
+        let decoded = decode_fixture(include_str!("../../tests/fixtures/receipts/uniswap_v3_swap_usdc_for_eth.json"));
+        let (router, pool, wallet) = (parse_address(V3_ROUTER), parse_address(V3_POOL), parse_address(WALLET));
+        let (weth, usdc_token) = (parse_address(WETH), parse_address(USDC));
+        let half_ether = ether(1) / 2;
+
+        // 路由合约解包装后转给发起人的原生币由内部交易完成，按惯例补全
+        assert_eq!(
+            movements(&decoded),
+            vec![
+                (Some(weth), pool, router, half_ether, MOVEMENT_TRANSFER),
+                (Some(usdc_token), wallet, pool, usdc(1500), MOVEMENT_TRANSFER),
+                (Some(weth), router, Address::zero(), half_ether, MOVEMENT_UNWRAP),
+                (None, weth, wallet, half_ether, MOVEMENT_UNWRAP),
+            ]
+        );
+
+        assert_eq!(decoded.swaps.len(), 1);
+        let swap = &decoded.swaps[0];
+        assert_eq!(swap.protocol, "Uniswap V3");
+        assert_eq!(swap.pool, pool);
+        assert_eq!((swap.token_in, swap.amount_in), (Some(usdc_token), usdc(1500)));
+        assert_eq!((swap.token_out, swap.amount_out), (Some(weth), half_ether));
+        assert_eq!(decoded.dex.as_deref(), Some("Uniswap V3"));
+    }
+
+    #[test]
+    fn decodes_weth_wrap() {
+        let decoded = decode_fixture(include_str!("../../tests/fixtures/receipts/weth_wrap.json"));
+        let (weth, wallet) = (parse_address(WETH), parse_address(WALLET));
+
+        assert_eq!(
+            movements(&decoded),
+            vec![
+                (None, wallet, weth, ether(2), MOVEMENT_NATIVE),
+                (Some(weth), Address::zero(), wallet, ether(2), MOVEMENT_WRAP),
+            ]
+        );
+        assert!(decoded.swaps.is_empty());
+        assert_eq!(decoded.dex, None);
+    }
+
+    #[test]
+    fn decodes_weth_unwrap() {
+        let decoded = decode_fixture(include_str!("../../tests/fixtures/receipts/weth_unwrap.json"));
+        let (weth, wallet) = (parse_address(WETH), parse_address(WALLET));
+
+        assert_eq!(
+            movements(&decoded),
+            vec![
+                (Some(weth), wallet, Address::zero(), ether(1), MOVEMENT_UNWRAP),
+                (None, weth, wallet, ether(1), MOVEMENT_UNWRAP),
+            ]
+        );
+        assert!(decoded.swaps.is_empty());
+    }
+
+    #[test]
+    fn ignores_wrap_events_from_other_contracts() {
+        let json = include_str!("../../tests/fixtures/receipts/weth_wrap.json");
+        let fixture: Fixture = serde_json::from_str(json).unwrap();
+        let decoded = decode_receipt(&fixture.transaction, &fixture.receipt, parse_address(USDC));
+
+        assert_eq!(movements(&decoded).len(), 1);
+        assert_eq!(decoded.movements[0].source, MOVEMENT_NATIVE);
+    }
+}
@@ -1,18 +1,106 @@
-use crate::models::asset::{Asset, TokenBalance, TokenPrice, TransactionVerification, NFT};
-use solana_client::rpc_client::RpcClient;
+use crate::models::asset::{Asset, BalanceChange, TokenBalance, TokenPrice, TransactionVerification, NFT};
+use crate::utils::nft_metadata;
+use lazy_static::lazy_static;
+use solana_account_decoder::UiAccountData;
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
 use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use solana_transaction_status::{
+    EncodedTransaction, UiMessage, UiTransactionEncoding, UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+use std::collections::HashSet;
 use std::env;
+use std::future::Future;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
 // 原生SOL精度
 const SOL_DECIMALS: u8 = 9;
 
-// 获取Solana RPC客户端
-pub fn get_solana_client() -> Result<RpcClient, String> {
+// 资产表中Solana使用的链ID
+pub const SOLANA_CHAIN_ID: i32 = 2;
+
+// SPL Token程序
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+// Metaplex Token Metadata程序
+const METAPLEX_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+// get_multiple_accounts单次最多查询的账户数
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+// RPC调用失败后的最大重试次数
+const MAX_RPC_RETRIES: u32 = 3;
+// 重试的初始等待时间，之后按指数退避
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+lazy_static! {
+    // 进程内共享的非阻塞RPC客户端，避免在async函数中阻塞actix工作线程
+    static ref SOLANA_CLIENT: Result<Arc<RpcClient>, String> = build_solana_client();
+}
+
+// 根据环境变量创建RPC客户端
+// SOLANA_RPC_TIMEOUT_SECS: 单次请求超时（默认10秒）
+// SOLANA_COMMITMENT: processed / confirmed / finalized（默认confirmed）
+fn build_solana_client() -> Result<Arc<RpcClient>, String> {
     let rpc_url = env::var("SOLANA_RPC_URL")
         .map_err(|_| "SOLANA_RPC_URL environment variable not set".to_string())?;
 
-    Ok(RpcClient::new(rpc_url))
+    let commitment = match env::var("SOLANA_COMMITMENT").as_deref() {
+        Ok("processed") => CommitmentConfig::processed(),
+        Ok("finalized") => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    };
+
+    Ok(Arc::new(RpcClient::new_with_timeout_and_commitment(
+        rpc_url,
+        rpc_timeout(),
+        commitment,
+    )))
+}
+
+fn rpc_timeout() -> Duration {
+    let secs = env::var("SOLANA_RPC_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    Duration::from_secs(secs)
+}
+
+// 获取Solana RPC客户端
+pub fn get_solana_client() -> Result<Arc<RpcClient>, String> {
+    SOLANA_CLIENT.clone()
+}
+
+// 带超时和指数退避重试的RPC调用
+async fn with_retry<T, F, Fut>(operation: &str, mut call: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    let mut delay = RETRY_BASE_DELAY;
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_RPC_RETRIES {
+        match tokio::time::timeout(rpc_timeout(), call()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = "request timed out".to_string(),
+        }
+
+        if attempt < MAX_RPC_RETRIES {
+            log::warn!("Solana RPC {} failed (attempt {}): {}", operation, attempt, last_error);
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    Err(format!("Failed to {}: {}", operation, last_error))
 }
 
 // 获取SOL余额
@@ -21,9 +109,7 @@ pub async fn get_sol_balance(address: &str) -> Result<TokenBalance, String> {
 
     let pubkey = Pubkey::from_str(address).map_err(|e| format!("Invalid Solana address: {}", e))?;
 
-    let balance = client
-        .get_balance(&pubkey)
-        .map_err(|e| format!("Failed to get SOL balance: {}", e))?;
+    let balance = with_retry("get SOL balance", || client.get_balance(&pubkey)).await?;
 
     Ok(TokenBalance {
         chain: "SOL".to_string(),
@@ -33,64 +119,542 @@ pub async fn get_sol_balance(address: &str) -> Result<TokenBalance, String> {
     })
 }
 
-// 获取SPL代币余额（简化版，实际应使用SPL token库）
-pub async fn get_spl_balance(
-    token_address: &str,
-    wallet_address: &str,
-) -> Result<TokenBalance, String> {
-    // 在实际实现中，应该使用spl_token库查询代币余额
-    // 这里作为简化示例，返回一个空结果
+// 获取SOL持仓，余额为0时返回None
+pub async fn get_native_asset(address: &str) -> Result<Option<Asset>, String> {
+    let balance = get_sol_balance(address).await?;
+
+    let lamports: u64 = balance
+        .raw_balance
+        .parse()
+        .map_err(|e| format!("Invalid balance: {}", e))?;
+    if lamports == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Asset {
+        chain_id: SOLANA_CHAIN_ID,
+        asset_type: "native".to_string(),
+        symbol: "SOL".to_string(),
+        name: "Solana".to_string(),
+        contract_address: None,
+        balance: Some(lamports as f64 / LAMPORTS_PER_SOL as f64),
+        decimals: Some(SOL_DECIMALS),
+        price_usd: None,
+        value_usd: None,
+        price_source: None,
+        price_updated_at: None,
+        logo_url: None,
+        created_at: None,
+        updated_at: None,
+    }))
+}
+
+// 列出钱包持有的全部SPL代币（Token和Token-2022程序），并补充Metaplex元数据，NFT不包含在内
+pub async fn get_spl_assets(wallet_address: &str) -> Result<Vec<Asset>, String> {
+    let client = get_solana_client()?;
+
+    let owner = Pubkey::from_str(wallet_address).map_err(|e| format!("Invalid Solana address: {}", e))?;
+
+    let mut holdings = get_token_holdings(&client, &owner).await?;
+    let nft_mints = get_nft_mints(&client, &holdings).await?;
+    holdings.retain(|h| !nft_mints.contains(&h.mint));
+
+    // 批量读取元数据账户
+    let mints: Vec<String> = holdings.iter().map(|h| h.mint.clone()).collect();
+    let metadata_list = get_token_metadata_batch(&client, &mints).await?;
+
+    let mut assets = Vec::with_capacity(holdings.len());
+    for (holding, metadata) in holdings.into_iter().zip(metadata_list) {
+        let (name, symbol, logo_url) = match metadata {
+            Some(metadata) => (metadata.name, metadata.symbol, metadata.logo_url),
+            None => (String::new(), String::new(), None),
+        };
+
+        assets.push(Asset {
+            chain_id: SOLANA_CHAIN_ID,
+            asset_type: "token".to_string(),
+            symbol,
+            name,
+            contract_address: Some(holding.mint),
+            balance: Some(holding.raw_amount as f64 / 10f64.powi(holding.decimals as i32)),
+            decimals: Some(holding.decimals),
+            price_usd: None,
+            value_usd: None,
+            price_source: None,
+            price_updated_at: None,
+            logo_url,
+            created_at: None,
+            updated_at: None,
+        });
+    }
+
+    Ok(assets)
+}
+
+// 列出钱包持有的Metaplex NFT（供应量为1且精度为0的代币），补充链下元数据和集合信息
+pub async fn get_solana_nfts(wallet_address: &str) -> Result<Vec<NFT>, String> {
+    let client = get_solana_client()?;
+
+    let owner = Pubkey::from_str(wallet_address).map_err(|e| format!("Invalid Solana address: {}", e))?;
+
+    let holdings = get_token_holdings(&client, &owner).await?;
+    let mut mints: Vec<String> = get_nft_mints(&client, &holdings).await?.into_iter().collect();
+    mints.sort();
+
+    let metadata_list = read_metadata_accounts(&client, &mints).await?;
+
+    // 只读取已验证集合的名称，未验证的集合可能是仿冒
+    let mut collection_mints: Vec<String> = metadata_list
+        .iter()
+        .flatten()
+        .filter_map(|m| m.collection.as_ref())
+        .filter(|c| c.verified)
+        .map(|c| c.key.clone())
+        .collect();
+    collection_mints.sort();
+    collection_mints.dedup();
+    let collection_names: Vec<(String, String)> = read_metadata_accounts(&client, &collection_mints)
+        .await?
+        .into_iter()
+        .zip(collection_mints)
+        .filter_map(|(metadata, mint)| metadata.map(|m| (mint, m.name)))
+        .collect();
+
+    let mut nfts = Vec::with_capacity(mints.len());
+    for (mint, metadata) in mints.into_iter().zip(metadata_list) {
+        // 没有Metaplex元数据的不视为NFT
+        let metadata = match metadata {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+        let offchain = match nft_metadata::fetch_metadata(&metadata.uri).await {
+            Ok(offchain) => offchain,
+            Err(e) => {
+                log::warn!("获取Solana NFT元数据失败 {}: {}", metadata.uri, e);
+                nft_metadata::NftMetadata::default()
+            }
+        };
+
+        let collection_verified = metadata.collection.as_ref().is_some_and(|c| c.verified);
+        let collection_name = metadata
+            .collection
+            .as_ref()
+            .filter(|c| c.verified)
+            .and_then(|c| collection_names.iter().find(|(mint, _)| *mint == c.key))
+            .map(|(_, name)| name.clone())
+            .or_else(|| Some(metadata.symbol.clone()).filter(|s| !s.is_empty()));
+
+        nfts.push(NFT {
+            chain: "SOL".to_string(),
+            // Solana NFT没有合约和tokenId之分，两者均使用mint地址
+            contract_address: mint.clone(),
+            token_id: mint,
+            name: offchain.name.unwrap_or(metadata.name),
+            collection_name,
+            description: offchain.description,
+            image_url: offchain.image_url,
+            metadata_url: nft_metadata::resolve_uri(&metadata.uri),
+            floor_price_usd: None,
+            token_type: "Metaplex".to_string(),
+            collection_address: metadata.collection.map(|c| c.key),
+            collection_verified,
+            attributes: offchain.attributes,
+        });
+    }
+
+    Ok(nfts)
+}
+
+// 获取钱包的代币持仓，按mint汇总余额，同一代币可能有多个代币账户
+async fn get_token_holdings(client: &RpcClient, owner: &Pubkey) -> Result<Vec<ParsedTokenAccount>, String> {
+    let mut holdings: Vec<ParsedTokenAccount> = Vec::new();
+    for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+        let program = Pubkey::from_str(program_id).expect("invalid built-in program id");
+        let accounts = with_retry("get token accounts", || {
+            client.get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(program))
+        })
+        .await?;
+
+        for account in &accounts {
+            let parsed = match parse_token_account(&account.account.data) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            match holdings.iter_mut().find(|h| h.mint == parsed.mint) {
+                Some(holding) => holding.raw_amount += parsed.raw_amount,
+                None => holdings.push(parsed),
+            }
+        }
+    }
+
+    holdings.retain(|h| h.raw_amount > 0);
+
+    Ok(holdings)
+}
+
+// 找出持仓中的NFT：精度为0、持有1个，且mint的总供应量为1
+async fn get_nft_mints(client: &RpcClient, holdings: &[ParsedTokenAccount]) -> Result<HashSet<String>, String> {
+    let candidates: Vec<Pubkey> = holdings
+        .iter()
+        .filter(|h| h.decimals == 0 && h.raw_amount == 1)
+        .filter_map(|h| Pubkey::from_str(&h.mint).ok())
+        .collect();
+
+    let mut nft_mints = HashSet::new();
+    for chunk in candidates.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = with_retry("get mint accounts", || client.get_multiple_accounts(chunk)).await?;
+
+        for (mint, account) in chunk.iter().zip(accounts) {
+            // Mint布局：mint_authority(4+32) + supply(u64) + decimals(1) ...
+            let supply = account
+                .and_then(|account| account.data.get(36..44).map(|b| b.to_vec()))
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes);
+            if supply == Some(1) {
+                nft_mints.insert(mint.to_string());
+            }
+        }
+    }
+
+    Ok(nft_mints)
+}
+
+// jsonParsed编码的代币账户
+struct ParsedTokenAccount {
+    mint: String,
+    raw_amount: u128,
+    decimals: u8,
+}
+
+// 解析jsonParsed编码的代币账户数据
+fn parse_token_account(data: &UiAccountData) -> Option<ParsedTokenAccount> {
+    let parsed = match data {
+        UiAccountData::Json(parsed) => &parsed.parsed,
+        _ => return None,
+    };
+    let info = parsed.get("info")?;
+    let token_amount = info.get("tokenAmount")?;
+
+    Some(ParsedTokenAccount {
+        mint: info.get("mint")?.as_str()?.to_string(),
+        raw_amount: token_amount.get("amount")?.as_str()?.parse().ok()?,
+        decimals: u8::try_from(token_amount.get("decimals")?.as_u64()?).ok()?,
+    })
+}
+
+/// Metaplex代币元数据
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub logo_url: Option<String>,
+    pub collection: Option<MetadataCollection>,
+}
+
+/// Metaplex元数据中的集合信息，verified表示已由集合的更新权限签名确认
+pub struct MetadataCollection {
+    pub verified: bool,
+    pub key: String,
+}
+
+// 批量读取Metaplex Token Metadata账户，并从链下JSON中获取logo
+// 返回结果与mints一一对应，没有元数据的代币为None
+async fn get_token_metadata_batch(
+    client: &RpcClient,
+    mints: &[String],
+) -> Result<Vec<Option<TokenMetadata>>, String> {
+    let mut metadata_list = read_metadata_accounts(client, mints).await?;
+
+    for metadata in metadata_list.iter_mut().flatten() {
+        metadata.logo_url = fetch_offchain_image(&metadata.uri).await;
+    }
+
+    Ok(metadata_list)
+}
+
+// 批量读取Metaplex Token Metadata账户，返回结果与mints一一对应
+async fn read_metadata_accounts(
+    client: &RpcClient,
+    mints: &[String],
+) -> Result<Vec<Option<TokenMetadata>>, String> {
+    let metadata_program =
+        Pubkey::from_str(METAPLEX_METADATA_PROGRAM_ID).expect("invalid built-in program id");
+
+    let metadata_addresses: Vec<Pubkey> = mints
+        .iter()
+        .map(|mint| {
+            let mint = Pubkey::from_str(mint).unwrap_or_default();
+            Pubkey::find_program_address(
+                &[b"metadata", metadata_program.as_ref(), mint.as_ref()],
+                &metadata_program,
+            )
+            .0
+        })
+        .collect();
+
+    let mut metadata_list = Vec::with_capacity(mints.len());
+    for chunk in metadata_addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = with_retry("get metadata accounts", || client.get_multiple_accounts(chunk)).await?;
+
+        for account in accounts {
+            metadata_list.push(account.and_then(|account| parse_metadata_account(&account.data)));
+        }
+    }
+
+    Ok(metadata_list)
+}
+
+// 解析Metaplex元数据账户（borsh编码）
+// 布局：key(1) + update_authority(32) + mint(32) + name + symbol + uri，字符串为u32长度前缀
+// 之后依次为 seller_fee_basis_points(2) + creators + primary_sale_happened(1) + is_mutable(1)
+// + edition_nonce + token_standard + collection，Option字段带1字节标记
+pub fn parse_metadata_account(data: &[u8]) -> Option<TokenMetadata> {
+    let mut offset = 1 + 32 + 32;
+    let name = read_borsh_string(data, &mut offset)?;
+    let symbol = read_borsh_string(data, &mut offset)?;
+    let uri = read_borsh_string(data, &mut offset)?;
+
+    Some(TokenMetadata {
+        name,
+        symbol,
+        uri,
+        logo_url: None,
+        collection: parse_metadata_collection(data, offset),
+    })
+}
+
+// 跳过uri之后的字段读取集合信息，老版本账户没有该字段时返回None
+fn parse_metadata_collection(data: &[u8], mut offset: usize) -> Option<MetadataCollection> {
+    offset += 2; // seller_fee_basis_points
+
+    // creators: Option<Vec<Creator>>，每个Creator为 address(32) + verified(1) + share(1)
+    if *data.get(offset)? == 1 {
+        let len_bytes: [u8; 4] = data.get(offset + 1..offset + 5)?.try_into().ok()?;
+        offset += 5 + u32::from_le_bytes(len_bytes) as usize * 34;
+    } else {
+        offset += 1;
+    }
+
+    offset += 2; // primary_sale_happened + is_mutable
+
+    // edition_nonce和token_standard: Option<u8>
+    for _ in 0..2 {
+        offset += if *data.get(offset)? == 1 { 2 } else { 1 };
+    }
+
+    // collection: Option<Collection>，Collection为 verified(1) + key(32)
+    if *data.get(offset)? != 1 {
+        return None;
+    }
+    let verified = *data.get(offset + 1)? == 1;
+    let key = Pubkey::try_from(data.get(offset + 2..offset + 34)?).ok()?;
+
+    Some(MetadataCollection {
+        verified,
+        key: key.to_string(),
+    })
+}
+
+// 读取borsh字符串并去掉Metaplex的补齐字符
+fn read_borsh_string(data: &[u8], offset: &mut usize) -> Option<String> {
+    let len_bytes: [u8; 4] = data.get(*offset..*offset + 4)?.try_into().ok()?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    *offset += 4;
+
+    let bytes = data.get(*offset..*offset + len)?;
+    *offset += len;
+
+    Some(
+        String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string(),
+    )
+}
 
-    Err("Not implemented".to_string())
+// 获取链下元数据JSON中的图片地址
+async fn fetch_offchain_image(uri: &str) -> Option<String> {
+    nft_metadata::fetch_metadata(uri).await.ok()?.image_url
 }
 
+// Jupiter和Raydium等DEX程序ID
+const KNOWN_DEX_PROGRAMS: &[(&str, &str)] = &[
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter"),
+    ("JUP4Fb2cqiRUcaTHdrPC8h2gNsA8fvKXYbXUJJqRUrP", "Jupiter"),
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium"),
+    ("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", "Raydium"),
+    ("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", "Raydium"),
+];
+
 // 验证Solana交易
-// pub async fn verify_transaction(tx_signature: &str) -> Result<TransactionVerification, String> {
-//     let client = get_solana_client()?;
-
-//     let signature = Signature::from_str(tx_signature)
-//         .map_err(|e| format!("Invalid transaction signature: {}", e))?;
-
-//     // 获取交易信息
-//     let tx_info = client
-//         .get_transaction(
-//             &signature,
-//             solana_client::rpc_config::RpcTransactionConfig {
-//                 encoding: solana_client::rpc_config::RpcTransactionConfig::Base64,
-//                 commitment: None,
-//                 max_supported_transaction_version: None,
-//             },
-//         )
-//         .map_err(|e| format!("Failed to get transaction: {}", e))?;
-
-//     // 获取交易状态和时间戳
-//     let status = if tx_info.meta.as_ref().unwrap().status.is_ok() {
-//         "success"
-//     } else {
-//         "failed"
-//     };
-
-//     // 获取交易发送方和接收方（简化，实际上需要解析交易指令）
-//     let tx = tx_info.transaction;
-//     let from_address = tx.account_keys[0].to_string();
-//     let to_address = if tx.account_keys.len() > 1 {
-//         tx.account_keys[1].to_string()
-//     } else {
-//         "".to_string()
-//     };
-
-//     // 构建交易验证结果
-//     Ok(TransactionVerification {
-//         is_valid: tx_info.meta.as_ref().unwrap().status.is_ok(),
-//         transaction_hash: tx_signature.to_string(),
-//         from_address,
-//         to_address,
-//         value: tx_info.meta.as_ref().unwrap().fee.to_string(), // 实际值需要从交易指令中提取
-//         token_address: None,                                   // 简化，实际需要解析交易指令
-//         token_symbol: None,                                    // 简化，实际需要查询token信息
-//         timestamp: tx_info.block_time.unwrap_or(0) as i64,
-//         block_number: tx_info.slot,
-//         status: status.to_string(),
-//         chain: "SOL".to_string(),
-//     })
-// }
+pub async fn verify_transaction(tx_signature: &str) -> Result<TransactionVerification, String> {
+    let client = get_solana_client()?;
+
+    let signature = Signature::from_str(tx_signature)
+        .map_err(|e| format!("Invalid transaction signature: {}", e))?;
+
+    // 使用jsonParsed编码获取交易，账户列表中包含地址查找表加载的账户
+    let tx_info = with_retry("get transaction", || {
+        client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+    })
+    .await?;
+
+    let meta = tx_info
+        .transaction
+        .meta
+        .ok_or_else(|| "Transaction meta not found".to_string())?;
+
+    let account_keys: Vec<String> = match tx_info.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => match ui_tx.message {
+            UiMessage::Parsed(message) => message
+                .account_keys
+                .into_iter()
+                .map(|account| account.pubkey)
+                .collect(),
+            UiMessage::Raw(message) => message.account_keys,
+        },
+        _ => return Err("Unexpected transaction encoding".to_string()),
+    };
+
+    let is_success = meta.err.is_none();
+    let status = if is_success { "success" } else { "failed" };
+
+    // 手续费支付者即交易发起人
+    let from_address = account_keys.first().cloned().unwrap_or_default();
+
+    // 识别DEX程序
+    let dex = account_keys.iter().find_map(|key| {
+        KNOWN_DEX_PROGRAMS
+            .iter()
+            .find(|(program_id, _)| program_id == key)
+            .map(|(_, name)| name.to_string())
+    });
+
+    let balance_changes = parse_balance_changes(&account_keys, &meta);
+
+    // 交易发起人花费的SOL（不含手续费）
+    let sol_spent = balance_changes
+        .iter()
+        .find(|c| c.mint.is_none() && c.account == from_address)
+        .map(|c| (-c.delta).max(0.0))
+        .unwrap_or(0.0);
+
+    // 只涉及一种SPL代币时，将其作为交易代币
+    let mut mints: Vec<&String> = balance_changes
+        .iter()
+        .filter_map(|c| c.mint.as_ref())
+        .collect();
+    mints.sort();
+    mints.dedup();
+    let token_address = if mints.len() == 1 {
+        Some(mints[0].clone())
+    } else {
+        None
+    };
+
+    Ok(TransactionVerification {
+        is_valid: is_success,
+        transaction_hash: tx_signature.to_string(),
+        from_address,
+        to_address: account_keys.get(1).cloned().unwrap_or_default(),
+        value: ((sol_spent * LAMPORTS_PER_SOL as f64).round() as u64).to_string(),
+        token_address,
+        token_symbol: None,
+        timestamp: tx_info.block_time.unwrap_or(0),
+        block_number: tx_info.slot,
+        status: status.to_string(),
+        chain: "SOL".to_string(),
+        movements: Vec::new(),
+        balance_changes,
+        swaps: Vec::new(),
+        dex,
+    })
+}
+
+// 根据交易前后余额计算每个账户的SOL和SPL代币变动
+fn parse_balance_changes(account_keys: &[String], meta: &UiTransactionStatusMeta) -> Vec<BalanceChange> {
+    let mut changes = Vec::new();
+
+    // SOL余额变动
+    for (index, account) in account_keys.iter().enumerate() {
+        let pre = meta.pre_balances.get(index).copied().unwrap_or(0);
+        let post = meta.post_balances.get(index).copied().unwrap_or(0);
+        // 手续费支付者的变动中扣除手续费，只保留实际转移的金额
+        let fee = if index == 0 { meta.fee } else { 0 };
+        let delta = post as i128 - pre as i128 + fee as i128;
+        if delta == 0 {
+            continue;
+        }
+
+        changes.push(BalanceChange {
+            account: account.clone(),
+            owner: account.clone(),
+            mint: None,
+            token_symbol: Some("SOL".to_string()),
+            decimals: SOL_DECIMALS,
+            pre_amount: pre as f64 / LAMPORTS_PER_SOL as f64,
+            post_amount: post as f64 / LAMPORTS_PER_SOL as f64,
+            delta: delta as f64 / LAMPORTS_PER_SOL as f64,
+        });
+    }
+
+    // SPL代币余额变动，交易前不存在的代币账户视为0
+    let pre_token_balances: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.pre_token_balances.clone()).unwrap_or_default();
+    let post_token_balances: Vec<UiTransactionTokenBalance> =
+        Option::from(meta.post_token_balances.clone()).unwrap_or_default();
+
+    let mut token_keys: Vec<(u8, String)> = pre_token_balances
+        .iter()
+        .chain(post_token_balances.iter())
+        .map(|b| (b.account_index, b.mint.clone()))
+        .collect();
+    token_keys.sort();
+    token_keys.dedup();
+
+    for (account_index, mint) in token_keys {
+        let find = |balances: &[UiTransactionTokenBalance]| {
+            balances
+                .iter()
+                .find(|b| b.account_index == account_index && b.mint == mint)
+                .cloned()
+        };
+        let pre = find(&pre_token_balances);
+        let post = find(&post_token_balances);
+
+        let pre_amount = pre.as_ref().and_then(|b| b.ui_token_amount.ui_amount).unwrap_or(0.0);
+        let post_amount = post.as_ref().and_then(|b| b.ui_token_amount.ui_amount).unwrap_or(0.0);
+        if pre_amount == post_amount {
+            continue;
+        }
+
+        let balance = match post.or(pre) {
+            Some(balance) => balance,
+            None => continue,
+        };
+        let owner: Option<String> = Option::from(balance.owner.clone());
+
+        changes.push(BalanceChange {
+            account: account_keys
+                .get(account_index as usize)
+                .cloned()
+                .unwrap_or_default(),
+            owner: owner.unwrap_or_default(),
+            mint: Some(mint),
+            token_symbol: None,
+            decimals: balance.ui_token_amount.decimals,
+            pre_amount,
+            post_amount,
+            delta: post_amount - pre_amount,
+        });
+    }
+
+    changes
+}
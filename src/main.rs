@@ -3,6 +3,7 @@ use actix_web::{middleware, web, App, HttpServer};
 use dotenv::dotenv;
 use log::info;
 use std::env;
+use std::sync::Arc;
 
 // 导入rbatis配置
 mod config;
@@ -37,7 +38,129 @@ async fn main() -> std::io::Result<()> {
     // 初始化Redis连接
     let redis_url = env::var("REDIS_URL").expect("REDIS_URL must be set");
     let redis_client = redis::Client::open(redis_url).expect("Failed to connect to Redis");
-    let redis_pool = web::Data::new(redis_client);
+    let redis_pool = web::Data::new(redis_client.clone());
+    
+    // 初始化服务
+    let redis_client = Arc::new(redis_client);
+    let asset_service = Arc::new(services::asset_service::AssetService::new(
+        rb.clone(),
+        redis_client.clone(),
+    ));
+    let portfolio_service = Arc::new(services::portfolio_service::PortfolioService::new(
+        rb.clone(),
+        asset_service.clone(),
+    ));
+    
+    let badge_service = Arc::new(services::badge_service::BadgeService::new(
+        rb.clone(),
+        asset_service.clone(),
+    ));
+    let space_service = Arc::new(services::space_service::SpaceService::new(
+        rb.clone(),
+        asset_service.clone(),
+    ));
+    let leaderboard_service = Arc::new(services::leaderboard_service::LeaderboardService::new(
+        rb.clone(),
+        redis_client.clone(),
+    ));
+    let storage_service = Arc::new(services::storage_service::StorageService::new(
+        rb.clone(),
+        redis_client.clone(),
+    ));
+    let user_service = Arc::new(services::user_service::UserService::new(rb.clone()));
+    let trade_service = Arc::new(services::trade_service::TradeService::new(
+        rb.clone(),
+        Arc::new(services::price_service::PriceService::new(redis_client.clone())),
+    ));
+    let content_service = Arc::new(services::content_service::ContentService::new(
+        rb.clone(),
+        storage_service.clone(),
+        trade_service,
+        space_service.clone(),
+    ));
+    let media_service = Arc::new(services::media_service::MediaService::new(
+        rb.clone(),
+        storage_service.clone(),
+    ));
+    
+    // 注册并启动后台任务
+    let mut scheduler = services::job_service::JobScheduler::new(Some(redis_client.clone()));
+    scheduler.register(
+        "asset_refresh",
+        services::asset_service::refresh_interval_secs(),
+        asset_service.clone(),
+        |s| async move { s.refresh_registered_wallets().await },
+    );
+    scheduler.register(
+        "price_update",
+        services::price_service::refresh_interval_secs(),
+        asset_service.clone(),
+        |s| async move { s.refresh_prices().await },
+    );
+    scheduler.register(
+        "token_backfill",
+        services::token_discovery_service::backfill_interval_secs(),
+        asset_service.clone(),
+        |s| async move { s.backfill_token_discovery().await },
+    );
+    scheduler.register(
+        "portfolio_snapshot",
+        services::portfolio_service::snapshot_interval_secs(),
+        portfolio_service.clone(),
+        |s| async move { s.snapshot_all().await },
+    );
+    scheduler.register(
+        "badge_recheck",
+        services::badge_service::recheck_interval_secs(),
+        badge_service.clone(),
+        |s| async move { s.refresh_all().await },
+    );
+    scheduler.register(
+        "space_recheck",
+        services::space_service::recheck_interval_secs(),
+        space_service.clone(),
+        |s| async move { s.recheck_all().await },
+    );
+    scheduler.register(
+        "leaderboard_update",
+        services::leaderboard_service::update_interval_secs(),
+        leaderboard_service.clone(),
+        |s| async move { s.update_all().await },
+    );
+    scheduler.register(
+        "storage_upload",
+        services::storage_service::outbox_interval_secs(),
+        storage_service.clone(),
+        |s| async move { s.process_outbox().await },
+    );
+    scheduler.register(
+        "storage_confirm",
+        services::storage_service::confirm_interval_secs(),
+        storage_service.clone(),
+        |s| async move { s.check_confirmations().await },
+    );
+    scheduler.register(
+        "content_key_rotation",
+        services::storage_service::key_rotation_interval_secs(),
+        storage_service.clone(),
+        |s| async move { s.rotate_content_keys().await },
+    );
+    let scheduler = Arc::new(scheduler);
+    services::job_service::JobScheduler::start(scheduler.clone());
+    
+    let asset_service = web::Data::new(asset_service);
+    let portfolio_service = web::Data::new(portfolio_service);
+    let badge_service = web::Data::new(badge_service);
+    let space_service = web::Data::new(space_service);
+    let leaderboard_service = web::Data::new(leaderboard_service);
+    let storage_service = web::Data::new(storage_service);
+    let user_service = web::Data::new(user_service);
+    let content_service = web::Data::new(content_service);
+    let media_service = web::Data::new(media_service);
+    let scheduler = web::Data::new(scheduler);
+    
+    // 启动EVM RPC节点健康检查
+    blockchain::provider_pool::spawn_health_checks();
     
     // 启动HTTP服务器
     HttpServer::new(move || {
@@ -53,11 +176,25 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             .app_data(redis_pool.clone())
             .app_data(web::Data::new(rb.clone()))
+            .app_data(asset_service.clone())
+            .app_data(portfolio_service.clone())
+            .app_data(badge_service.clone())
+            .app_data(space_service.clone())
+            .app_data(leaderboard_service.clone())
+            .app_data(storage_service.clone())
+            .app_data(user_service.clone())
+            .app_data(content_service.clone())
+            .app_data(media_service.clone())
+            .app_data(scheduler.clone())
             // 注册API路由
             .configure(api::user::config)
-            // .configure(api::asset::config)
-            // .configure(api::post::config)
-            // .configure(api::comment::config)
+            .configure(api::asset::config)
+            .configure(api::space::config)
+            .configure(api::leaderboard::config)
+            .configure(api::admin::config)
+            .configure(api::post::config)
+            .configure(api::comment::config)
+            .configure(api::media::config)
             .configure(api::auth::config)
             // 默认404处理
             .default_service(web::route().to(api::not_found))
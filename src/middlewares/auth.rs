@@ -1,6 +1,6 @@
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
-use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::header::HeaderMap;
 use actix_web::{Error, HttpMessage};
 use crate::utils::jwt;
 use futures::future::{ok, Ready};
@@ -26,7 +26,7 @@ pub struct AuthenticatedUser {
     pub wallet_chain: String,
 }
 
-// 新增 FromRequest 实现
+// 新增 FromRequest 实现：优先使用中间件写入的用户信息，未经过中间件时直接校验令牌
 impl actix_web::FromRequest for AuthenticatedUser {
     type Error = actix_web::Error;
     type Future = futures::future::Ready<Result<Self, Self::Error>>;
@@ -35,12 +35,34 @@ impl actix_web::FromRequest for AuthenticatedUser {
         let value = req.extensions().get::<AuthenticatedUser>().cloned();
         let result = match value {
             Some(user) => Ok(user),
-            None => Err(ErrorUnauthorized("Unauthorized")),
+            None => authenticate(req.headers()),
         };
         futures::future::ready(result)
     }
 }
 
+// 从Authorization请求头解析并验证JWT令牌
+fn authenticate(headers: &HeaderMap) -> Result<AuthenticatedUser, Error> {
+    let token = headers
+        .get("Authorization")
+        .map(|h| h.to_str().unwrap_or_default())
+        .unwrap_or_default()
+        .trim_start_matches("Bearer ");
+
+    if token.is_empty() {
+        return Err(ErrorUnauthorized("Missing authorization token"));
+    }
+
+    let claims = jwt::validate_token(token).map_err(|_| ErrorUnauthorized("Invalid authorization token"))?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| ErrorForbidden("Invalid user ID"))?;
+
+    Ok(AuthenticatedUser {
+        user_id: user_id.to_string(),
+        wallet_address: claims.wallet_address,
+        wallet_chain: claims.wallet_chain,
+    })
+}
+
 // 实现Transform特性
 impl<S, B> Transform<S, ServiceRequest> for Auth
 where
@@ -78,44 +100,10 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // 从请求头中获取认证令牌
-        let token = req
-            .headers()
-            .get("Authorization")
-            .map(|h| h.to_str().unwrap_or_default())
-            .unwrap_or_default()
-            .trim_start_matches("Bearer ")
-            .to_string();
-
-        if token.is_empty() {
-            return Box::pin(async move {
-                Err(ErrorUnauthorized("Missing authorization token"))
-            });
-        }
-
         // 验证JWT令牌
-        let user_info: AuthenticatedUser = match jwt::validate_token(&token) {
-            Ok(claims) => {
-                let user_id = match Uuid::parse_str(&claims.sub) {
-                    Ok(i) => i.to_string(),
-                    Err(_) => {
-                        return Box::pin(async move {
-                            Err(ErrorForbidden("Invalid user ID"))
-                        });
-                    }
-                };
-
-                AuthenticatedUser {
-                    user_id,
-                    wallet_address: claims.wallet_address,
-                    wallet_chain: claims.wallet_chain,
-                }
-            }
-            Err(_) => {
-                return Box::pin(async move {
-                    Err(ErrorUnauthorized("Invalid authorization token"))
-                });
-            }
+        let user_info = match authenticate(req.headers()) {
+            Ok(user) => user,
+            Err(err) => return Box::pin(async move { Err(err) }),
         };
 
         // 将用户信息添加到请求扩展中，供后续处理使用
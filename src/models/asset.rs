@@ -11,6 +11,12 @@ pub struct Asset {
     pub decimals: Option<u8>,
     pub price_usd: Option<f64>,
     pub value_usd: Option<f64>,
+    #[serde(default)]
+    pub price_source: Option<String>, // 价格来源，如 coingecko、chainlink、uniswap_v3
+    #[serde(default)]
+    pub price_updated_at: Option<i64>, // 价格来源给出的更新时间（Unix秒）
+    #[serde(default)]
+    pub logo_url: Option<String>,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
 }
@@ -27,6 +33,12 @@ pub struct NFT {
     pub metadata_url: Option<String>, // 元数据URL
     pub floor_price_usd: Option<f64>, // 地板价（USD）
     pub token_type: String,      // ERC721, ERC1155, Metaplex, etc.
+    #[serde(default)]
+    pub collection_address: Option<String>, // 集合地址（Metaplex集合mint）
+    #[serde(default)]
+    pub collection_verified: bool, // 集合是否已验证
+    #[serde(default)]
+    pub attributes: Option<serde_json::Value>, // 元数据中的属性列表
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,6 +54,47 @@ pub struct AssetsResponse {
     pub tokens: Vec<Asset>,
     pub nfts: Vec<NFT>,
     pub total_value_usd: f64,
+    #[serde(default)]
+    pub refreshed_at: i64, // 最近一次从链上刷新的时间（Unix秒）
+}
+
+/// 资产走势中的一个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioPoint {
+    pub timestamp: i64,
+    pub value_usd: f64,
+}
+
+/// 资产分布（按链或按资产）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AllocationEntry {
+    pub label: String,           // 链简称或资产符号
+    pub chain_id: i32,
+    pub contract_address: Option<String>, // 按链分布时为None
+    pub value_usd: f64,
+    pub percentage: f64,
+}
+
+/// 区间盈亏（按资产总价值变化计算，包含转入转出）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioPnl {
+    pub start_at: i64,
+    pub start_value_usd: f64,
+    pub end_at: i64,
+    pub end_value_usd: f64,
+    pub pnl_usd: f64,
+    pub pnl_percent: Option<f64>, // 起始价值为0时为None
+}
+
+/// 资产历史
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioHistory {
+    pub wallet_address: String,
+    pub range: String,
+    pub series: Vec<PortfolioPoint>,
+    pub allocation_by_chain: Vec<AllocationEntry>,
+    pub allocation_by_asset: Vec<AllocationEntry>,
+    pub pnl: Option<PortfolioPnl>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,14 +103,58 @@ pub struct AssetsQueryParams {
     pub wallet_chain: Option<String>, // 不指定时，查询所有支持的链
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPrice {
+    #[serde(default)]
+    pub chain_id: i32,
+    #[serde(default)]
+    pub contract_address: Option<String>, // None表示原生币
+    #[serde(default)]
     pub symbol: String,
+    #[serde(default)]
     pub name: String,
     pub price_usd: f64,
     pub change_24h: Option<f64>,
     pub market_cap_usd: Option<f64>,
-    pub updated_at: i64,
+    #[serde(default)]
+    pub source: String, // 价格来源，如 coingecko、chainlink、uniswap_v3
+    pub updated_at: i64, // 价格来源给出的更新时间
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetMovement {
+    pub token_address: Option<String>, // None表示原生币
+    pub token_symbol: Option<String>,
+    pub decimals: Option<u8>,
+    pub from_address: String,
+    pub to_address: String,
+    pub raw_amount: String,
+    pub amount: Option<f64>, // 按精度换算后的数量，精度未知时为None
+    pub source: String,      // native, transfer, wrap, unwrap
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapLeg {
+    pub protocol: String,
+    pub pool: String,
+    pub token_in: Option<String>, // 转入池子的资产，无法识别时为None
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Option<f64>, // 按精度换算后的数量，资产或精度未知时为None
+    pub token_out: Option<String>,
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub account: String,
+    pub owner: String,          // SOL账户为自身地址，SPL代币账户为所有者钱包
+    pub mint: Option<String>,   // None表示原生SOL
+    pub token_symbol: Option<String>,
+    pub decimals: u8,
+    pub pre_amount: f64,
+    pub post_amount: f64,
+    pub delta: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -73,4 +170,8 @@ pub struct TransactionVerification {
     pub block_number: u64,
     pub status: String, // "success", "pending", "failed"
     pub chain: String,
+    pub movements: Vec<AssetMovement>,
+    pub balance_changes: Vec<BalanceChange>, // 按账户的余额变动，目前仅Solana提供
+    pub swaps: Vec<SwapLeg>,                 // 按执行顺序的DEX兑换，目前仅EVM提供
+    pub dex: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// 后台任务的运行状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_secs: u64,
+    pub running: bool,
+    pub instance_id: Option<String>, // 最近一次运行任务的实例
+    pub run_count: u64,
+    pub consecutive_failures: u32,
+    pub last_attempts: u32, // 最近一次运行的尝试次数（含重试）
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    pub last_success_at: Option<i64>,
+    pub last_processed: Option<usize>, // 最近一次成功处理的数量，含义取决于任务
+    pub last_error: Option<String>,
+}
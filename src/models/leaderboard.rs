@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// 排行榜条目
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: i64, // 从1开始
+    pub user_id: String,
+    pub username: Option<String>,
+    pub nickname: Option<String>,
+    pub avatar_ipfs_cid: Option<String>,
+    pub score: f64, // 含义取决于榜单：美元价值、涨跌百分比、交易数或互动数
+}
+
+/// 用户在排行榜中的位置
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardRank {
+    pub board: String,
+    pub rank: Option<i64>, // 未上榜或已退出排行榜时为None
+    pub score: Option<f64>,
+    pub total: i64,
+    pub updated_at: Option<i64>,
+}
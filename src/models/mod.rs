@@ -1,6 +1,9 @@
 pub mod auth;
 pub mod asset;
+pub mod job;
+pub mod leaderboard;
 pub mod rbatis_entities;
+pub mod storage;
 
 // 公共响应结构
 use serde::{Deserialize, Serialize};
@@ -3,9 +3,32 @@ use rbatis::rbdc::datetime::DateTime;
 use uuid::Uuid;
 use rbatis::crud;
 
+// Postgres的UUID列：rbatis默认把字符串按varchar绑定，写入uuid列会报类型错误，
+// 这里按rbs的Uuid扩展类型序列化，JSON中仍输出字符串
+mod pg_uuid {
+    use serde::Serializer;
+    use std::fmt::Display;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("Uuid", &value.to_string())
+    }
+
+    pub mod option {
+        use serde::Serializer;
+        use std::fmt::Display;
+
+        pub fn serialize<T: Display, S: Serializer>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+}
+
 // 为User结构体自动生成CRUD方法
 // 如果指定了表名，则使用指定的表名；否则，使用结构体名称的蛇形命名法作为表名
-crud!(AssetEntity {}); // 表名为user
+crud!(AssetEntity {}, "assets");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetEntity {
     pub wallet_address: String,
@@ -18,25 +41,36 @@ pub struct AssetEntity {
     pub decimals: Option<i32>,
     pub price_usd: Option<f64>,
     pub value_usd: Option<f64>,
+    pub price_source: Option<String>,
+    pub price_updated_at: Option<i64>,
+    pub logo_url: Option<String>,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
 }
-crud!(NftAssetEntity {});
+crud!(NftAssetEntity {}, "nft_assets");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NftAssetEntity {
     pub wallet_address: String,
     pub chain_id: i32,
     pub contract_address: String,
     pub token_id: String,
+    pub token_type: String,
+    pub balance: String,
     pub name: String,
+    pub collection_name: Option<String>,
+    pub collection_address: Option<String>,
+    pub collection_verified: bool,
+    pub description: Option<String>,
     pub image_url: Option<String>,
     pub metadata_url: Option<String>,
+    pub attributes: Option<String>,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
 }
-crud!(UserEntity {}); 
+crud!(UserEntity {}, "users");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub id: Uuid,
     pub username: String,
     pub nickname: Option<String>,
@@ -46,10 +80,9 @@ pub struct UserEntity {
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
-crud!(UserProfileEntity {}); 
+// 用户资料，从users表查询，没有单独的表
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfileEntity {
-    pub id: i32,
     pub user_id: Uuid,
     pub username: Option<String>,
     pub nickname: Option<String>,
@@ -58,50 +91,81 @@ pub struct UserProfileEntity {
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
-crud!(PostEntity {});
+crud!(PostEntity {}, "posts");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostEntity {
-    pub id: i32,
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub user_id: String,
     pub content: String,
     pub images_ipfs_cids: Option<Vec<String>>,
-    pub arweave_tx_id: Option<String>,
+    pub arweave_tx_id: Option<String>, // 正文存储在Arweave时的数据项ID
+    pub storage_ref: Option<String>,   // 正文在记录存储后端中的引用
     pub transaction_hash: Option<String>,
     pub transaction_chain: Option<String>,
     pub like_count: i32,
     pub comment_count: i32,
     pub tags: Option<Vec<String>>,
+    pub post_type: String,            // original, repost, quote
+    #[serde(serialize_with = "pg_uuid::option::serialize")]
+    pub repost_of_id: Option<String>, // 转发或引用的原帖ID
+    pub repost_count: i32,
+    pub is_hidden: bool,
+    pub trade_verified: bool, // 引用的交易已通过链上验证
+    pub storage_status: String, // 永久存储状态：none、pending、submitted、confirmed、failed
+    pub content_key_id: Option<String>,      // 加密上链内容的主密钥ID，为空表示明文
+    pub content_wrapped_key: Option<String>, // 主密钥加密后的数据密钥
+    pub content_hash: Option<String>,        // 正文的SHA-256，十六进制
+    pub author_signature: Option<String>,    // 作者钱包对规范消息的签名
+    pub signed_at: Option<i64>,              // 签名时间，规范消息的一部分
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
-crud!(CommentEntity {});
+crud!(CommentEntity {}, "comments");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommentEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub id: Uuid,
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub post_id: String,
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub user_id: String,
+    #[serde(serialize_with = "pg_uuid::option::serialize")]
     pub parent_id: Option<String>,
     pub content: String,
-    pub arweave_tx_id: Option<String>,
+    pub arweave_tx_id: Option<String>, // 正文存储在Arweave时的数据项ID
+    pub storage_ref: Option<String>,   // 正文在记录存储后端中的引用
     pub like_count: i32,
+    pub storage_status: String, // 永久存储状态：pending、submitted、confirmed、failed
+    pub content_key_id: Option<String>,      // 加密上链内容的主密钥ID，为空表示明文
+    pub content_wrapped_key: Option<String>, // 主密钥加密后的数据密钥
+    pub content_hash: Option<String>,        // 正文的SHA-256，十六进制
+    pub author_signature: Option<String>,    // 作者钱包对规范消息的签名
+    pub signed_at: Option<i64>,              // 签名时间，规范消息的一部分
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
-crud!(UserLikeEntity {});
+crud!(UserLikeEntity {}, "likes");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserLikeEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub id: Uuid,
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub user_id: Uuid,
+    #[serde(serialize_with = "pg_uuid::option::serialize")]
     pub post_id: Option<Uuid>,
+    #[serde(serialize_with = "pg_uuid::option::serialize")]
     pub comment_id: Option<Uuid>,
     pub created_at: DateTime,
 }
 
-crud!(AuthChallengeEntity {});
+crud!(AuthChallengeEntity {}, "login_challenges");
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthChallengeEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
     pub id: Uuid,
     pub wallet_address: String,
     pub wallet_chain: String,
@@ -110,9 +174,160 @@ pub struct AuthChallengeEntity {
     pub expires_at: DateTime,
 }
 
-crud!(TagEntity {});
+// 标签统计，由posts.tags聚合得到
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagEntity {
-    pub id: i32,
     pub name: String,
-}
\ No newline at end of file
+    pub post_count: i64,
+}
+
+crud!(MediaEntity {}, "media");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub user_id: String,
+    pub media_type: String, // image, gif, video
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub ipfs_cid: String,
+    pub thumbnail_ipfs_cid: Option<String>,
+    pub created_at: DateTime,
+}
+
+crud!(TradeProofEntity {}, "trade_proofs");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeProofEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub user_id: String,
+    pub chain: String,
+    pub tx_hash: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub block_number: i64,
+    pub block_time: i64,
+    pub token_in: Option<String>,        // 卖出的代币，原生币为None
+    pub token_in_symbol: Option<String>,
+    pub amount_in: Option<f64>,
+    pub token_out: Option<String>,       // 买入的代币
+    pub token_out_symbol: Option<String>,
+    pub amount_out: Option<f64>,
+    pub dex: Option<String>,
+    pub value_usd: Option<f64>,          // 交易发生时的美元价值
+    pub status: String,                  // verified, flagged
+    pub created_at: DateTime,
+}
+
+crud!(WalletTokenEntity {}, "wallet_tokens");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTokenEntity {
+    pub wallet_address: String,
+    pub chain_id: i32,
+    pub contract_address: String,
+    pub created_at: DateTime,
+}
+
+crud!(TokenScanCheckpointEntity {}, "token_scan_checkpoints");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenScanCheckpointEntity {
+    pub wallet_address: String,
+    pub chain_id: i32,
+    pub first_scanned_block: i64,
+    pub last_scanned_block: i64,
+    pub backfill_complete: bool,
+    pub updated_at: DateTime,
+}
+
+crud!(NftScanCheckpointEntity {}, "nft_scan_checkpoints");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftScanCheckpointEntity {
+    pub wallet_address: String,
+    pub chain_id: i32,
+    pub last_scanned_block: i64,
+    pub updated_at: DateTime,
+}
+
+crud!(PortfolioSnapshotEntity {}, "portfolio_snapshots");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSnapshotEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub snapshot_at: i64,                // 快照时间（Unix秒）
+    pub chain_id: i32,
+    pub asset_type: String,              // native, token, nft
+    pub symbol: String,
+    pub contract_address: Option<String>,
+    pub balance: Option<f64>,
+    pub price_usd: Option<f64>,
+    pub value_usd: Option<f64>,
+}
+
+crud!(UserBadgeEntity {}, "user_badges");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserBadgeEntity {
+    pub user_id: String,
+    pub badge: String,                   // 如 tier_100k、bayc_holder
+    pub badge_type: String,              // tier, holder
+    pub label: String,
+    pub chain_id: Option<i32>,
+    pub contract_address: Option<String>,
+    pub verified_at: DateTime,
+}
+
+crud!(GatedSpaceEntity {}, "gated_spaces");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatedSpaceEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    pub tag: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub creator_id: String,
+    pub requirement_type: String,        // value, token, nft
+    pub chain_id: Option<i32>,           // value类型为None
+    pub contract_address: Option<String>, // None表示原生币
+    pub min_amount: f64,
+    pub created_at: DateTime,
+}
+
+crud!(SpaceMemberEntity {}, "space_members");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceMemberEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub space_id: Uuid,
+    pub user_id: String,
+    pub eligible: bool,
+    pub checked_at: DateTime,
+}
+
+crud!(StorageOutboxEntity {}, "storage_outbox");
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageOutboxEntity {
+    #[serde(serialize_with = "pg_uuid::serialize")]
+    pub id: Uuid,
+    pub content_table: String, // posts、comments
+    pub content_id: String,
+    pub kind: String,    // media、record
+    pub backend: String, // ipfs、arweave、local、s3、memory
+    pub payload: Option<String>, // base64编码的内容，确认后清除
+    pub content_type: String,
+    pub tags: String,             // JSON数组：[[name, value], ...]
+    pub expected_ref: Option<String>, // 按内容寻址的后端为本地计算的引用（CID或SHA-256）
+    pub status: String,           // pending、submitted、confirmed、failed
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub item_id: Option<String>, // 后端返回的内容引用：Arweave数据项ID、IPFS CID或对象键
+    pub tx_id: Option<String>,   // Arweave bundle交易ID
+    pub confirmations: i32,
+    pub submitted_at: Option<i64>,
+    pub confirmed_at: Option<i64>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// 上链内容的校验结果：重新下载并解密后与数据库中的内容比对
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentVerification {
+    pub content_table: String,
+    pub content_id: String,
+    pub storage_ref: Option<String>, // 正文在记录存储后端中的引用
+    pub arweave_tx_id: Option<String>,
+    pub key_id: Option<String>, // 为空表示明文上链
+    pub matches: bool,
+    pub error: Option<String>,
+}
+
+/// 内容的完整性证明，任何人都可以据此核对内容是否与永久存储一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentProof {
+    pub content_table: String,
+    pub content_id: String,
+    pub content_hash: Option<String>, // 发帖时记录的正文SHA-256，明文上链时同时写入Content-SHA256标签
+    pub storage_status: String,
+    pub storage_backend: Option<String>, // 正文所在的记录存储后端
+    pub storage_ref: Option<String>,     // 正文在记录存储后端中的引用
+    pub storage_url: Option<String>,
+    pub arweave_tx_id: Option<String>, // 正文存储在Arweave时的数据项ID
+    pub arweave_url: Option<String>,
+    pub bundle_tx_id: Option<String>, // 数据项所在的bundle交易
+    pub confirmations: i32,
+    pub encrypted: bool,
+    pub archived_hash: Option<String>, // 从存储后端取回并解密后的正文哈希
+    pub ipfs: Vec<IpfsProof>,
+    pub status: String, // verified、pending、mismatch、unavailable、not_archived
+    pub error: Option<String>,
+    pub checked_at: i64,
+}
+
+/// 媒体文件的校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpfsProof {
+    pub cid: String,
+    pub url: String,
+    pub verified: bool, // 已取回且内容与引用一致
+    pub error: Option<String>,
+}
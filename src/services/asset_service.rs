@@ -1,302 +1,528 @@
-// use crate::models::asset::*;
-// use crate::models::rbatis_entities::{AssetEntity, NftAssetEntity};
-// use crate::utils::error::ServiceError;
-// use ethers::prelude::*;
-// use std::sync::Arc;
-// use redis::Client as RedisClient;
-// use rbatis::RBatis;
-// /// 资产服务，处理多链资产聚合和展示
-// pub struct AssetService {
-//     db: Arc<RBatis>,
-//     redis: Arc<RedisClient>,
-//     eth_client: Arc<EthClient>,
-//     solana_client: Arc<SolanaClient>,
-// }
-
-// impl AssetService {
-//     pub fn new(
-//         db: Arc<RBatis>,
-//         redis: Arc<RedisClient>,
-//         eth_client: Arc<EthClient>,
-//         solana_client: Arc<SolanaClient>,
-//     ) -> Self {
-//         Self {
-//             db,
-//             redis,
-//             eth_client,
-//             solana_client,
-//         }
-//     }
-
-//     /// 获取用户资产总览
-//     pub async fn get_user_assets(&self, wallet_address: &str) -> Result<Vec<Asset>, ServiceError> {
-//         // 获取用户在数据库中已有的资产记录
-//         let cached_assets = self.get_cached_assets(wallet_address).await?;
-
-//         // 如果缓存不存在或已过期，则从链上重新获取
-//         if cached_assets.is_empty() {
-//             // 创建或更新资产记录
-//             self.update_assets(wallet_address).await?;
-//             // 再次获取资产
-//             self.get_cached_assets(wallet_address).await
-//         } else {
-//             Ok(cached_assets)
-//         }
-//     }
-
-//     /// 从数据库获取已缓存的资产
-//     async fn get_cached_assets(&self, wallet_address: &str) -> Result<Vec<Asset>, ServiceError> {
-//         let entities = self.db.query_list_by_column::<AssetEntity>("wallet_address", &wallet_address).await
-//             .map_err(|_| ServiceError::InternalServerError)?;
-            
-//         // 将AssetEntity转换为Asset
-//         let assets: Vec<Asset> = entities.into_iter().map(|e| Asset {
-//             symbol: e.symbol,
-//             name: e.name,
-//             balance: e.balance,
-//             decimals: e.decimals.map(|d| d as u8),
-//             price_usd: e.price_usd,
-//             value_usd: e.value_usd,
-//             chain_id: e.chain_id,
-//             asset_type: e.asset_type.clone(),
-//             contract_address: e.contract_address.clone(),
-//             created_at: e.created_at.clone(),
-//             updated_at: e.updated_at.clone(),
-//         }).collect();
-
-//         Ok(assets)
-//     }
-
-//     /// 更新用户资产数据
-//     async fn update_assets(&self, wallet_address: &str) -> Result<(), ServiceError> {
-//         // 获取ETH链上资产
-//         let eth_assets = self.eth_client.get_assets(wallet_address).await?;
-        
-//         // 获取Solana链上资产
-//         let solana_assets = self.solana_client.get_assets(wallet_address).await?;
-        
-//         // 合并所有资产
-//         let mut all_assets = Vec::with_capacity(eth_assets.len() + solana_assets.len());
-//         all_assets.extend(eth_assets);
-//         all_assets.extend(solana_assets);
-
-//         // 获取最新资产价格并计算价值
-//         self.update_asset_prices(&mut all_assets).await?;
-        
-//         // 保存到数据库
-//         for asset in all_assets {
-//             self.save_asset(wallet_address, &asset).await?;
-//         }
-
-//         Ok(())
-//     }
-
-//     /// 更新资产价格
-//     async fn update_asset_prices(&self, assets: &mut Vec<Asset>) -> Result<(), ServiceError> {
-//         // 获取所有代币地址
-//         let token_addresses: Vec<String> = assets
-//             .iter()
-//             .filter(|a| a.asset_type == AssetType::Token)
-//             .map(|a| a.contract_address.clone().unwrap_or_default())
-//             .collect();
-
-//         // 从外部API获取价格（模拟实现）
-//         let prices = self.fetch_token_prices(&token_addresses).await?;
-        
-//         // 更新资产价格
-//         for asset in assets.iter_mut() {
-//             if asset.asset_type == AssetType::Token {
-//                 if let Some(addr) = &asset.contract_address {
-//                     if let Some(price) = prices.get(addr) {
-//                         asset.price_usd = Some(*price);
-//                         // 计算总价值
-//                         if let Some(balance) = asset.balance {
-//                             asset.value_usd = Some(balance * *price);
-//                         }
-//                     }
-//                 }
-//             }
-//         }
-
-//         Ok(())
-//     }
-
-//     /// 从外部API获取代币价格
-//     async fn fetch_token_prices(&self, token_addresses: &[String]) -> Result<std::collections::HashMap<String, f64>, ServiceError> {
-//         let mut prices = std::collections::HashMap::new();
-        
-//         for addr in token_addresses {
-//             let cache_key = format!("token_price:{}", addr);
-//             let mut con = self.redis.get_async_connection().await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-            
-//             let cached_price: Option<f64> = redis::cmd("GET")
-//                 .arg(&cache_key)
-//                 .query_async(&mut con)
-//                 .await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-                
-//             if let Some(price) = cached_price {
-//                 prices.insert(addr.clone(), price);
-//             } else {
-//                 let price = 1.0; // 模拟价格
-//                 prices.insert(addr.clone(), price);
-                
-//                 redis::cmd("SETEX")
-//                     .arg(&cache_key)
-//                     .arg(300)
-//                     .arg(price)
-//                     .query_async(&mut con)
-//                     .await
-//                     .map_err(|_| ServiceError::InternalServerError)?;
-//             }
-//         }
-        
-//         Ok(prices)
-//     }
-
-//     /// 保存资产到数据库
-//     async fn save_asset(&self, wallet_address: &str, asset: &Asset) -> Result<(), ServiceError> {
-//         // 检查资产是否已存在
-//         let existing = self.db.query_by_column::<Option<AssetEntity>>("wallet_address", &wallet_address)
-//             .await
-//             .map_err(|_| ServiceError::InternalServerError)?;
-            
-//         // 创建AssetEntity
-//         let entity = AssetEntity {
-//             wallet_address: wallet_address.to_string(),
-//             chain_id: asset.chain_id,
-//             asset_type: asset.asset_type.clone(),
-//             symbol: asset.symbol.clone(),
-//             name: asset.name.clone(),
-//             contract_address: asset.contract_address.clone(),
-//             balance: asset.balance,
-//             decimals: asset.decimals.map(|d| d as i32),
-//             price_usd: asset.price_usd,
-//             value_usd: asset.value_usd,
-//             created_at: None, // 数据库会自动设置
-//             updated_at: None, // 数据库会自动设置
-//         };
-        
-//         if existing.is_some() {
-//             // 更新现有记录
-//             self.db.update_by_column::<AssetEntity>(&entity, "wallet_address")
-//                 .await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-//         } else {
-//             // 插入新记录
-//             self.db.save(&entity, &[])
-//                 .await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-//         }
-
-//         Ok(())
-//     }
-
-//     /// 获取特定代币余额
-//     pub async fn get_token_balance(&self, wallet_address: &str, chain_id: i32, contract_address: &str) -> Result<TokenBalance, ServiceError> {
-//         // 根据链类型选择不同的客户端
-//         let balance = match chain_id {
-//             1 => self.eth_client.get_token_balance(wallet_address, contract_address).await?,
-//             2 => self.solana_client.get_token_balance(wallet_address, contract_address).await?,
-//             _ => return Err(ServiceError::BadRequest("不支持的链ID".into())),
-//         };
-
-//         Ok(balance)
-//     }
-
-//     /// 获取用户NFT资产
-//     pub async fn get_user_nfts(&self, wallet_address: &str) -> Result<Vec<NftAsset>, ServiceError> {
-//         let entities = self.db.query_list_by_column::<NftAssetEntity>("wallet_address", &wallet_address)
-//             .await
-//             .map_err(|_| ServiceError::InternalServerError)?;
-            
-//         // 将NftAssetEntity转换为NftAsset
-//         let nfts: Vec<NftAsset> = entities.into_iter().map(|e| NftAsset {
-//             chain_id: e.chain_id,
-//             contract_address: e.contract_address,
-//             token_id: e.token_id,
-//             name: e.name,
-//             image_url: e.image_url,
-//             metadata_url: e.metadata_url,
-//             created_at: e.created_at.map(Into::into),
-//             updated_at: e.updated_at.map(Into::into),
-//         }).collect();
-
-//         if nfts.is_empty() {
-//             // 获取以太坊NFT
-//             let eth_nfts = self.eth_client.get_nfts(wallet_address).await?;
-            
-//             // 获取Solana NFT
-//             let solana_nfts = self.solana_client.get_nfts(wallet_address).await?;
-            
-//             // 合并所有NFT
-//             let mut all_nfts = Vec::with_capacity(eth_nfts.len() + solana_nfts.len());
-//             all_nfts.extend(eth_nfts);
-//             all_nfts.extend(solana_nfts);
-            
-//             // 保存到数据库
-//             for nft in &all_nfts {
-//                 self.save_nft(wallet_address, nft).await?;
-//             }
-            
-//             Ok(all_nfts)
-//         } else {
-//             Ok(nfts)
-//         }
-//     }
-
-//     /// 保存NFT到数据库
-//     async fn save_nft(&self, wallet_address: &str, nft: &NftAsset) -> Result<(), ServiceError> {
-//         // 检查NFT是否已存在
-//         let existing = self.db.query_by_column::<Option<NftAssetEntity>>("wallet_address", &wallet_address)
-//             .await
-//             .map_err(|_| ServiceError::InternalServerError)?;
-            
-//         // 创建NftAssetEntity
-//         let entity = NftAssetEntity {
-//             wallet_address: wallet_address.to_string(),
-//             chain_id: nft.chain_id,
-//             contract_address: nft.contract_address.clone(),
-//             token_id: nft.token_id.clone(),
-//             name: nft.name.clone(),
-//             image_url: nft.image_url.clone(),
-//             metadata_url: nft.metadata_url.clone(),
-//             created_at: None, // 数据库会自动设置
-//             updated_at: None, // 数据库会自动设置
-//         };
-        
-//         if existing.is_some() {
-//             // 更新现有记录
-//             self.db.update_by_column::<NftAssetEntity>(&entity, "wallet_address")
-//                 .await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-//         } else {
-//             // 插入新记录
-//             self.db.save(&entity, &[])
-//                 .await
-//                 .map_err(|_| ServiceError::InternalServerError)?;
-//         }
-
-//         Ok(())
-//     }
-
-//     /// 获取用户资产总价值（美元）
-//     pub async fn get_total_value(&self, wallet_address: &str) -> Result<f64, ServiceError> {
-//         // 使用rbatis执行原生SQL查询获取总价值
-//         let sql = "SELECT COALESCE(SUM(value_usd), 0) FROM assets WHERE wallet_address = ?".to_string();
-        
-//         let total: Option<f64> = self.db.query_decode(&sql, vec![rbs::to_value(wallet_address)])
-//             .await
-//             .map_err(|e| {
-//                 log::error!("获取资产总价值失败: {}", e);
-//                 ServiceError::InternalServerError
-//             })?;
-        
-//         // 处理空值并保留两位小数
-//         let total = total.unwrap_or(0.0);
-//         let total = (total * 100.0).round() / 100.0;
-//         Ok(total)
-//     }
-    
-// }
\ No newline at end of file
+use crate::blockchain::chains;
+use crate::blockchain::solana::{self, SOLANA_CHAIN_ID};
+use crate::models::asset::*;
+use crate::models::rbatis_entities::AssetEntity;
+use crate::services::nft_service::NftService;
+use crate::services::price_service::{PriceService, TokenKey};
+use crate::services::token_discovery_service::TokenDiscoveryService;
+use crate::utils::error::ServiceError;
+use futures::future::join_all;
+use rbatis::rbdc::datetime::DateTime;
+use rbatis::executor::Executor;
+use rbatis::RBatis;
+use redis::Client as RedisClient;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// Redis中资产缓存的过期时间（秒），通过 ASSET_CACHE_TTL_SECS 配置
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+// 数据库中的资产超过该时间（秒）视为过期，需要从链上刷新，通过 ASSET_STALE_SECS 配置
+const DEFAULT_STALE_SECS: i64 = 900;
+// 两次主动刷新的最小间隔（秒），避免频繁扫描链上数据
+const MIN_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// 资产服务，处理多链资产聚合和展示
+/// 读取顺序：Redis缓存 -> 数据库（未过期时） -> 链上刷新
+pub struct AssetService {
+    db: Arc<RBatis>,
+    redis: Arc<RedisClient>,
+    token_discovery: TokenDiscoveryService,
+    nft_service: NftService,
+    price_service: PriceService,
+}
+
+// 单条链的刷新结果
+struct ChainAssets {
+    chain_id: i32,
+    result: Result<Vec<Asset>, ServiceError>,
+}
+
+impl AssetService {
+    pub fn new(db: Arc<RBatis>, redis: Arc<RedisClient>) -> Self {
+        Self {
+            token_discovery: TokenDiscoveryService::new(db.clone()),
+            nft_service: NftService::new(db.clone()),
+            price_service: PriceService::new(redis.clone()),
+            db,
+            redis,
+        }
+    }
+
+    /// 获取钱包资产，wallet_chain为SOL或EVM链简称，不指定时按地址格式查询所有支持的链
+    pub async fn get_wallet_assets(
+        &self,
+        wallet_address: &str,
+        wallet_chain: Option<&str>,
+    ) -> Result<AssetsResponse, ServiceError> {
+        let wallet_address = normalize_address(wallet_address);
+        let chain_ids = resolve_chain_ids(&wallet_address, wallet_chain)?;
+        let cache_key = cache_key(&wallet_address, wallet_chain);
+
+        if let Some(response) = self.get_cached_response(&cache_key).await {
+            return Ok(response);
+        }
+
+        // 数据库中有未过期的记录时直接使用
+        let entities = self.get_stored_assets(&wallet_address, &chain_ids).await?;
+        let oldest = entities
+            .iter()
+            .filter_map(|e| e.updated_at.as_ref().map(|d| d.unix_timestamp()))
+            .min();
+        if let Some(oldest) = oldest {
+            if now() - oldest < stale_secs() {
+                let nfts = self.get_stored_nfts(&wallet_address, &chain_ids).await?;
+                let response = build_response(
+                    entities.into_iter().map(entity_to_asset).collect(),
+                    nfts,
+                    oldest,
+                );
+                self.set_cached_response(&cache_key, &response).await;
+                return Ok(response);
+            }
+        }
+
+        self.refresh_assets(&wallet_address, wallet_chain).await
+    }
+
+    /// 从链上重新获取资产，各链并发查询，失败的链沿用数据库中的旧数据
+    pub async fn refresh_assets(
+        &self,
+        wallet_address: &str,
+        wallet_chain: Option<&str>,
+    ) -> Result<AssetsResponse, ServiceError> {
+        let wallet_address = normalize_address(wallet_address);
+        let chain_ids = resolve_chain_ids(&wallet_address, wallet_chain)?;
+
+        let token_futures = chain_ids
+            .iter()
+            .map(|chain_id| self.fetch_chain_assets(*chain_id, &wallet_address));
+        let (chain_results, nfts) = futures::join!(
+            join_all(token_futures),
+            self.nft_service.get_nfts(&wallet_address, wallet_chain)
+        );
+
+        let mut tokens = Vec::new();
+        for chain in chain_results {
+            match chain.result {
+                Ok(mut assets) => {
+                    self.price_service.apply_prices(&mut assets).await;
+                    self.save_assets(&wallet_address, chain.chain_id, &assets).await?;
+                    tokens.extend(assets);
+                }
+                Err(e) => {
+                    log::warn!("刷新链{}资产失败，使用已保存的数据: {}", chain.chain_id, e);
+                    let stored = self.get_stored_assets(&wallet_address, &[chain.chain_id]).await?;
+                    tokens.extend(stored.into_iter().map(entity_to_asset));
+                }
+            }
+        }
+
+        let nfts = match nfts {
+            Ok(nfts) => nfts,
+            Err(e) => {
+                log::warn!("刷新NFT失败，使用已保存的数据: {}", e);
+                self.get_stored_nfts(&wallet_address, &chain_ids).await?
+            }
+        };
+
+        let response = build_response(tokens, nfts, now());
+        self.set_cached_response(&cache_key(&wallet_address, wallet_chain), &response)
+            .await;
+
+        Ok(response)
+    }
+
+    /// 主动刷新资产，同一钱包在最小间隔内只刷新一次，其余请求返回当前数据
+    pub async fn request_refresh(
+        &self,
+        wallet_address: &str,
+        wallet_chain: Option<&str>,
+    ) -> Result<AssetsResponse, ServiceError> {
+        let lock_key = format!("assets:refresh:{}", normalize_address(wallet_address));
+
+        if self.try_lock(&lock_key, MIN_REFRESH_INTERVAL_SECS).await {
+            self.refresh_assets(wallet_address, wallet_chain).await
+        } else {
+            self.get_wallet_assets(wallet_address, wallet_chain).await
+        }
+    }
+
+    /// 获取钱包NFT
+    pub async fn get_wallet_nfts(
+        &self,
+        wallet_address: &str,
+        wallet_chain: Option<&str>,
+    ) -> Result<Vec<NFT>, ServiceError> {
+        Ok(self.get_wallet_assets(wallet_address, wallet_chain).await?.nfts)
+    }
+
+    /// 获取钱包资产总价值（美元），包含代币价值和NFT地板价
+    pub async fn get_total_value(&self, wallet_address: &str) -> Result<f64, ServiceError> {
+        Ok(self.get_wallet_assets(wallet_address, None).await?.total_value_usd)
+    }
+
+    /// 回溯钱包更早区块中的代币，返回处理的钱包数
+    pub async fn backfill_token_discovery(&self) -> Result<usize, ServiceError> {
+        self.token_discovery.backfill().await
+    }
+
+    /// 刷新所有注册用户的资产，只有过期的钱包才会重新扫描链上数据，返回成功的钱包数
+    pub async fn refresh_registered_wallets(&self) -> Result<usize, ServiceError> {
+        #[derive(Debug, Deserialize)]
+        struct UserWallet {
+            wallet_address: String,
+            wallet_chain: String,
+        }
+
+        let wallets: Vec<UserWallet> = self
+            .db
+            .query_decode("SELECT DISTINCT wallet_address, wallet_chain FROM users", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut count = 0;
+        for wallet in &wallets {
+            match self
+                .get_wallet_assets(&wallet.wallet_address, wallet_scope(&wallet.wallet_chain))
+                .await
+            {
+                Ok(_) => count += 1,
+                Err(e) => log::warn!("刷新钱包{}资产失败: {}", wallet.wallet_address, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// 预热已保存资产涉及的所有代币价格，返回查到价格的代币数
+    pub async fn refresh_prices(&self) -> Result<usize, ServiceError> {
+        #[derive(Debug, Deserialize)]
+        struct HeldToken {
+            chain_id: i32,
+            contract_address: Option<String>,
+        }
+
+        let held: Vec<HeldToken> = self
+            .db
+            .query_decode("SELECT DISTINCT chain_id, contract_address FROM assets", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut chain_tokens: HashMap<i32, Vec<TokenKey>> = HashMap::new();
+        for token in held {
+            chain_tokens.entry(token.chain_id).or_default().push(token.contract_address);
+        }
+
+        let mut count = 0;
+        for (chain_id, tokens) in chain_tokens {
+            match self.price_service.refresh_prices(chain_id, &tokens).await {
+                Ok(found) => count += found,
+                Err(e) => log::warn!("刷新链{}代币价格失败: {}", chain_id, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    // 获取单条链的原生币和代币持仓
+    async fn fetch_chain_assets(&self, chain_id: i32, wallet_address: &str) -> ChainAssets {
+        let result = if chain_id == SOLANA_CHAIN_ID {
+            fetch_solana_assets(wallet_address).await
+        } else {
+            self.token_discovery
+                .get_chain_holdings(chain_id, wallet_address)
+                .await
+        };
+
+        ChainAssets { chain_id, result }
+    }
+
+    async fn get_stored_assets(
+        &self,
+        wallet_address: &str,
+        chain_ids: &[i32],
+    ) -> Result<Vec<AssetEntity>, ServiceError> {
+        let mut entities = Vec::new();
+        for chain_id in chain_ids {
+            let chain_entities: Vec<AssetEntity> = self
+                .db
+                .query_decode(
+                    "SELECT * FROM assets WHERE wallet_address = ? AND chain_id = ?",
+                    vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+                )
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+            entities.extend(chain_entities);
+        }
+
+        Ok(entities)
+    }
+
+    async fn get_stored_nfts(
+        &self,
+        wallet_address: &str,
+        chain_ids: &[i32],
+    ) -> Result<Vec<NFT>, ServiceError> {
+        let mut nfts = Vec::new();
+        for chain_id in chain_ids {
+            nfts.extend(self.nft_service.get_saved_nfts(*chain_id, wallet_address).await?);
+        }
+
+        Ok(nfts)
+    }
+
+    // 按链整体替换资产记录
+    async fn save_assets(
+        &self,
+        wallet_address: &str,
+        chain_id: i32,
+        assets: &[Asset],
+    ) -> Result<(), ServiceError> {
+        // 删除和写入在同一事务中，写入失败时保留原有数据
+        let tx = self
+            .db
+            .acquire_begin()
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+            .defer_async(|tx| async move {
+                if !tx.done() {
+                    if let Err(e) = tx.rollback().await {
+                        log::error!("回滚事务失败: {}", e);
+                    }
+                }
+            });
+
+        tx.exec(
+            "DELETE FROM assets WHERE wallet_address = ? AND chain_id = ?",
+            vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+        )
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let entities: Vec<AssetEntity> = assets
+            .iter()
+            .map(|asset| AssetEntity {
+                wallet_address: wallet_address.to_string(),
+                chain_id: asset.chain_id,
+                asset_type: asset.asset_type.clone(),
+                symbol: asset.symbol.clone(),
+                name: asset.name.clone(),
+                contract_address: asset.contract_address.clone(),
+                balance: asset.balance,
+                decimals: asset.decimals.map(|d| d as i32),
+                price_usd: asset.price_usd,
+                value_usd: asset.value_usd,
+                price_source: asset.price_source.clone(),
+                price_updated_at: asset.price_updated_at,
+                logo_url: asset.logo_url.clone(),
+                created_at: Some(DateTime::now()),
+                updated_at: Some(DateTime::now()),
+            })
+            .collect();
+
+        if !entities.is_empty() {
+            AssetEntity::insert_batch(&tx, &entities, 100)
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+
+    // 读取Redis缓存，Redis不可用时视为未命中
+    async fn get_cached_response(&self, key: &str) -> Option<AssetsResponse> {
+        let mut con = self.redis.get_async_connection().await.ok()?;
+        let cached: Option<String> = redis::cmd("GET").arg(key).query_async(&mut con).await.ok()?;
+
+        cached.and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    async fn set_cached_response(&self, key: &str, response: &AssetsResponse) {
+        let json = match serde_json::to_string(response) {
+            Ok(json) => json,
+            Err(_) => return,
+        };
+        let mut con = match self.redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(e) => {
+                log::warn!("写入资产缓存失败: {}", e);
+                return;
+            }
+        };
+
+        let result: redis::RedisResult<()> = redis::cmd("SETEX")
+            .arg(key)
+            .arg(cache_ttl_secs())
+            .arg(json)
+            .query_async(&mut con)
+            .await;
+        if let Err(e) = result {
+            log::warn!("写入资产缓存失败: {}", e);
+        }
+    }
+
+    // 获取短期锁，Redis不可用时直接放行
+    async fn try_lock(&self, key: &str, ttl_secs: u64) -> bool {
+        let mut con = match self.redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(_) => return true,
+        };
+
+        let result: redis::RedisResult<Option<String>> = redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_secs)
+            .query_async(&mut con)
+            .await;
+
+        !matches!(result, Ok(None))
+    }
+}
+
+// 获取Solana钱包的SOL和SPL代币持仓
+async fn fetch_solana_assets(wallet_address: &str) -> Result<Vec<Asset>, ServiceError> {
+    let (native, tokens) = futures::join!(
+        solana::get_native_asset(wallet_address),
+        solana::get_spl_assets(wallet_address)
+    );
+
+    let mut assets = Vec::new();
+    if let Some(native) = native.map_err(ServiceError::ExternalService)? {
+        assets.push(native);
+    }
+    assets.extend(tokens.map_err(ServiceError::ExternalService)?);
+
+    Ok(assets)
+}
+
+// 确定需要查询的链：Solana地址只查Solana，EVM地址查询指定链或所有已配置的链
+fn resolve_chain_ids(wallet_address: &str, wallet_chain: Option<&str>) -> Result<Vec<i32>, ServiceError> {
+    validate_wallet_address(wallet_address, wallet_chain)?;
+
+    match wallet_chain {
+        Some(chain) if chain.eq_ignore_ascii_case("SOL") => Ok(vec![SOLANA_CHAIN_ID]),
+        Some(chain) => chains::get_chain_by_code(chain)
+            .map(|chain| vec![chain.chain_id])
+            .ok_or_else(|| ServiceError::BadRequest("不支持的链类型".into())),
+        None if wallet_address.starts_with("0x") => Ok(chains::configured_chains()
+            .iter()
+            .map(|chain| chain.chain_id)
+            .collect()),
+        None => Ok(vec![SOLANA_CHAIN_ID]),
+    }
+}
+
+fn build_response(tokens: Vec<Asset>, nfts: Vec<NFT>, refreshed_at: i64) -> AssetsResponse {
+    let total = tokens.iter().filter_map(|a| a.value_usd).sum::<f64>()
+        + nfts.iter().filter_map(|n| n.floor_price_usd).sum::<f64>();
+
+    AssetsResponse {
+        tokens,
+        nfts,
+        // 保留两位小数
+        total_value_usd: (total * 100.0).round() / 100.0,
+        refreshed_at,
+    }
+}
+
+fn entity_to_asset(e: AssetEntity) -> Asset {
+    Asset {
+        chain_id: e.chain_id,
+        asset_type: e.asset_type,
+        symbol: e.symbol,
+        name: e.name,
+        contract_address: e.contract_address,
+        balance: e.balance,
+        decimals: e.decimals.map(|d| d as u8),
+        price_usd: e.price_usd,
+        value_usd: e.value_usd,
+        price_source: e.price_source,
+        price_updated_at: e.price_updated_at,
+        logo_url: e.logo_url,
+        created_at: e.created_at,
+        updated_at: e.updated_at,
+    }
+}
+
+// 按链简称查找链ID，支持SOL和EVM链简称
+pub(crate) fn chain_id_by_code(code: &str) -> Option<i32> {
+    if code.eq_ignore_ascii_case("SOL") {
+        return Some(SOLANA_CHAIN_ID);
+    }
+
+    chains::get_chain_by_code(code).map(|chain| chain.chain_id)
+}
+
+// 登录钱包的查询范围：Solana钱包只查Solana，EVM钱包（wallet_chain为ethereum）查询所有已配置的EVM链
+pub(crate) fn wallet_scope(wallet_chain: &str) -> Option<&'static str> {
+    if wallet_chain.eq_ignore_ascii_case("solana") || wallet_chain.eq_ignore_ascii_case("SOL") {
+        Some("SOL")
+    } else {
+        None
+    }
+}
+
+// 校验钱包地址格式：EVM为0x开头的40位十六进制，Solana为base58编码的公钥；未指定链时按地址格式判断
+pub(crate) fn validate_wallet_address(wallet_address: &str, wallet_chain: Option<&str>) -> Result<(), ServiceError> {
+    let is_solana = match wallet_chain {
+        Some(chain) => chain.eq_ignore_ascii_case("SOL"),
+        None => !wallet_address.starts_with("0x"),
+    };
+    let valid = if is_solana {
+        Pubkey::from_str(wallet_address).is_ok()
+    } else {
+        wallet_address.len() == 42
+            && wallet_address.starts_with("0x")
+            && wallet_address[2..].chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    if !valid {
+        return Err(ServiceError::BadRequest("无效的钱包地址".into()));
+    }
+    Ok(())
+}
+
+// EVM地址不区分大小写，统一转为小写；Solana地址区分大小写
+pub(crate) fn normalize_address(wallet_address: &str) -> String {
+    if wallet_address.starts_with("0x") {
+        wallet_address.to_lowercase()
+    } else {
+        wallet_address.to_string()
+    }
+}
+
+fn cache_key(wallet_address: &str, wallet_chain: Option<&str>) -> String {
+    let scope = wallet_chain.map(|c| c.to_uppercase()).unwrap_or_else(|| "ALL".to_string());
+    format!("assets:{}:{}", scope, wallet_address)
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn cache_ttl_secs() -> u64 {
+    env::var("ASSET_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CACHE_TTL_SECS)
+}
+
+/// 定时刷新注册用户资产的间隔（秒），与资产过期时间一致
+pub fn refresh_interval_secs() -> u64 {
+    stale_secs().max(60) as u64
+}
+
+fn stale_secs() -> i64 {
+    env::var("ASSET_STALE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_STALE_SECS)
+}
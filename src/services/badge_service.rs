@@ -0,0 +1,266 @@
+use crate::blockchain::chains::ETHEREUM_CHAIN_ID;
+use crate::blockchain::solana::SOLANA_CHAIN_ID;
+use crate::models::asset::AssetsResponse;
+use crate::models::rbatis_entities::UserBadgeEntity;
+use crate::services::asset_service::{chain_id_by_code, wallet_scope, AssetService};
+use crate::utils::error::ServiceError;
+use rbatis::rbdc::datetime::DateTime;
+use rbatis::RBatis;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+// 徽章类型
+pub const BADGE_TYPE_TIER: &str = "tier";
+pub const BADGE_TYPE_HOLDER: &str = "holder";
+
+// 重新验证徽章的间隔（秒），通过 BADGE_RECHECK_INTERVAL_SECS 配置
+const DEFAULT_RECHECK_INTERVAL_SECS: u64 = 6 * 3600;
+
+// 资产等级，从高到低，只授予满足的最高等级
+const WEALTH_TIERS: [(f64, &str, &str); 3] = [
+    (1_000_000.0, "tier_1m", "$1M+"),
+    (100_000.0, "tier_100k", "$100K+"),
+    (10_000.0, "tier_10k", "$10K+"),
+];
+
+// 指定资产持有者徽章
+struct HolderBadge {
+    badge: &'static str,
+    label: &'static str,
+    chain_id: i32,
+    contract_address: Option<&'static str>, // None表示原生币
+    min_amount: f64,                        // 代币数量或NFT个数
+}
+
+static HOLDER_BADGES: &[HolderBadge] = &[
+    HolderBadge {
+        badge: "eth_32",
+        label: "32+ ETH",
+        chain_id: ETHEREUM_CHAIN_ID,
+        contract_address: None,
+        min_amount: 32.0,
+    },
+    HolderBadge {
+        badge: "wbtc_1",
+        label: "1+ WBTC",
+        chain_id: ETHEREUM_CHAIN_ID,
+        contract_address: Some("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
+        min_amount: 1.0,
+    },
+    HolderBadge {
+        badge: "sol_1000",
+        label: "1000+ SOL",
+        chain_id: SOLANA_CHAIN_ID,
+        contract_address: None,
+        min_amount: 1000.0,
+    },
+    HolderBadge {
+        badge: "bayc_holder",
+        label: "BAYC Holder",
+        chain_id: ETHEREUM_CHAIN_ID,
+        contract_address: Some("0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D"),
+        min_amount: 1.0,
+    },
+    HolderBadge {
+        badge: "pudgy_holder",
+        label: "Pudgy Penguins Holder",
+        chain_id: ETHEREUM_CHAIN_ID,
+        contract_address: Some("0xBd3531dA5CF5857e7CfAA92426877b022e612cf8"),
+        min_amount: 1.0,
+    },
+];
+
+// 用户的登录钱包
+#[derive(Debug, Deserialize)]
+pub(crate) struct UserWallet {
+    pub user_id: String,
+    pub wallet_address: String,
+    pub wallet_chain: String,
+}
+
+/// 徽章服务，根据链上资产为用户授予资产等级和持有者徽章
+pub struct BadgeService {
+    db: Arc<RBatis>,
+    asset_service: Arc<AssetService>,
+}
+
+impl BadgeService {
+    pub fn new(db: Arc<RBatis>, asset_service: Arc<AssetService>) -> Self {
+        Self { db, asset_service }
+    }
+
+    /// 重新计算所有用户的徽章，单个用户失败不影响其他用户
+    pub async fn refresh_all(&self) -> Result<usize, ServiceError> {
+        let wallets: Vec<UserWallet> = self
+            .db
+            .query_decode(
+                "SELECT id::text AS user_id, wallet_address, wallet_chain FROM users",
+                vec![],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut count = 0;
+        for wallet in &wallets {
+            match self.refresh_badges(wallet).await {
+                Ok(_) => count += 1,
+                Err(e) => log::warn!("用户{}徽章验证失败: {}", wallet.user_id, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// 重新计算指定用户的徽章
+    pub async fn refresh_user_badges(&self, user_id: &str) -> Result<Vec<UserBadgeEntity>, ServiceError> {
+        let wallet = get_user_wallet(&self.db, user_id).await?;
+
+        self.refresh_badges(&wallet).await
+    }
+
+    /// 获取用户的徽章
+    pub async fn get_badges(&self, user_id: &str) -> Result<Vec<UserBadgeEntity>, ServiceError> {
+        self.db
+            .query_decode(
+                "SELECT * FROM user_badges WHERE user_id = ? ORDER BY badge_type DESC, badge",
+                vec![rbs::to_value!(user_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// 批量获取多个用户的徽章，用于帖子列表
+    pub async fn get_badges_for_users(
+        &self,
+        user_ids: &[String],
+    ) -> Result<HashMap<String, Vec<UserBadgeEntity>>, ServiceError> {
+        let mut badges = HashMap::new();
+        for user_id in user_ids {
+            if !badges.contains_key(user_id) {
+                badges.insert(user_id.clone(), self.get_badges(user_id).await?);
+            }
+        }
+
+        Ok(badges)
+    }
+
+    // 按当前资产整体替换用户的徽章
+    async fn refresh_badges(&self, wallet: &UserWallet) -> Result<Vec<UserBadgeEntity>, ServiceError> {
+        let assets = self
+            .asset_service
+            .get_wallet_assets(&wallet.wallet_address, wallet_scope(&wallet.wallet_chain))
+            .await?;
+        let badges = compute_badges(&wallet.user_id, &assets);
+
+        self.db
+            .exec(
+                "DELETE FROM user_badges WHERE user_id = ?",
+                vec![rbs::to_value!(&wallet.user_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        if !badges.is_empty() {
+            UserBadgeEntity::insert_batch(self.db.as_ref(), &badges, 20)
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(badges)
+    }
+}
+
+// 查询用户的登录钱包
+pub(crate) async fn get_user_wallet(db: &RBatis, user_id: &str) -> Result<UserWallet, ServiceError> {
+    let wallets: Vec<UserWallet> = db
+        .query_decode(
+            "SELECT id::text AS user_id, wallet_address, wallet_chain FROM users WHERE id::text = ?",
+            vec![rbs::to_value!(user_id)],
+        )
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    wallets
+        .into_iter()
+        .next()
+        .ok_or_else(|| ServiceError::NotFound("用户不存在".into()))
+}
+
+// 根据资产计算徽章
+fn compute_badges(user_id: &str, assets: &AssetsResponse) -> Vec<UserBadgeEntity> {
+    let mut badges = Vec::new();
+
+    if let Some((_, badge, label)) = WEALTH_TIERS
+        .iter()
+        .find(|(threshold, _, _)| assets.total_value_usd >= *threshold)
+    {
+        badges.push(UserBadgeEntity {
+            user_id: user_id.to_string(),
+            badge: badge.to_string(),
+            badge_type: BADGE_TYPE_TIER.to_string(),
+            label: label.to_string(),
+            chain_id: None,
+            contract_address: None,
+            verified_at: DateTime::now(),
+        });
+    }
+
+    for holder in HOLDER_BADGES {
+        if holding_amount(assets, holder.chain_id, holder.contract_address) >= holder.min_amount {
+            badges.push(UserBadgeEntity {
+                user_id: user_id.to_string(),
+                badge: holder.badge.to_string(),
+                badge_type: BADGE_TYPE_HOLDER.to_string(),
+                label: holder.label.to_string(),
+                chain_id: Some(holder.chain_id),
+                contract_address: holder.contract_address.map(|a| a.to_string()),
+                verified_at: DateTime::now(),
+            });
+        }
+    }
+
+    badges
+}
+
+// 钱包持有指定资产的数量：代币为余额，NFT为持有个数，contract_address为None表示原生币
+pub(crate) fn holding_amount(assets: &AssetsResponse, chain_id: i32, contract_address: Option<&str>) -> f64 {
+    let same_address = |address: Option<&str>| match (address, contract_address) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (None, None) => true,
+        _ => false,
+    };
+
+    let token_amount: f64 = assets
+        .tokens
+        .iter()
+        .filter(|a| a.chain_id == chain_id && same_address(a.contract_address.as_deref()))
+        .filter_map(|a| a.balance)
+        .sum();
+
+    let nft_count = match contract_address {
+        Some(_) => assets
+            .nfts
+            .iter()
+            .filter(|n| chain_id_by_code(&n.chain) == Some(chain_id))
+            // Metaplex NFT按集合地址匹配
+            .filter(|n| {
+                same_address(Some(&n.contract_address))
+                    || (n.collection_verified && same_address(n.collection_address.as_deref()))
+            })
+            .count(),
+        None => 0,
+    };
+
+    token_amount + nft_count as f64
+}
+
+/// 重新验证徽章的间隔（秒）
+pub fn recheck_interval_secs() -> u64 {
+    env::var("BADGE_RECHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_RECHECK_INTERVAL_SECS)
+}
@@ -1,107 +1,256 @@
-use crate::models::rbatis_entities::{CommentEntity, PostEntity, TagEntity, UserLikeEntity};
-use crate::services::storage_service::StorageService;
-use crate::utils::error::ServiceError;
+use crate::models::rbatis_entities::{
+    CommentEntity, PostEntity, TagEntity, TradeProofEntity,
+};
+use crate::services::asset_service::normalize_address;
+use crate::services::badge_service::get_user_wallet;
+use crate::services::space_service::SpaceService;
+use crate::services::storage_service::{
+    content_hash, StorageService, CONTENT_TABLE_COMMENTS, CONTENT_TABLE_POSTS, STORAGE_STATUS_NONE,
+    STORAGE_STATUS_PENDING,
+};
+use crate::services::trade_service::{TradeService, TRADE_STATUS_VERIFIED};
+use crate::utils::arweave::Tag;
+use crate::utils::authorship::{AuthorSignature, SignedContent, MAX_SIGNED_MESSAGE_LEN, SIGNATURE_VERSION};
+use crate::utils::crypto;
+use crate::utils::error::{is_unique_violation, ServiceError};
 use rbatis::rbdc::datetime::DateTime;
+use rbatis::executor::{Executor, RBatisTxExecutorGuard};
 use rbatis::RBatis;
+use std::collections::HashSet;
+use std::env;
 use std::sync::Arc;
+use uuid::Uuid;
+
+// 帖子类型
+pub const POST_TYPE_ORIGINAL: &str = "original";
+pub const POST_TYPE_REPOST: &str = "repost";
+pub const POST_TYPE_QUOTE: &str = "quote";
+
+// 引用链最大深度，防止引用链过长或成环
+const MAX_QUOTE_DEPTH: usize = 16;
+
+// Arweave内容类型标签
+const CONTENT_TYPE_TEXT: &str = "text/plain";
+const CONTENT_TYPE_IMAGE: &str = "application/octet-stream";
+const CONTENT_KIND_POST: &str = "post";
+const CONTENT_KIND_QUOTE: &str = "quote";
+const CONTENT_KIND_COMMENT: &str = "comment";
+
+// 列表中可见的帖子：未隐藏，且纯转发的原帖也未隐藏
+const VISIBLE_POST: &str = "NOT p.is_hidden AND NOT (p.post_type = 'repost' AND EXISTS \
+     (SELECT 1 FROM posts o WHERE o.id = p.repost_of_id AND o.is_hidden))";
+
+// 列表接口每页最多返回的条数
+const MAX_PAGE_SIZE: i32 = 100;
+
+// 作者签名时间与服务器时间的最大偏差（秒），通过 CONTENT_SIGNATURE_MAX_SKEW_SECS 配置
+const DEFAULT_SIGNATURE_MAX_SKEW_SECS: i64 = 600;
+
+/// 发帖参数
+pub struct NewPost<'a> {
+    pub user_id: String,
+    pub wallet_address: &'a str,
+    pub content: &'a str,
+    pub image_data: Option<Vec<u8>>,
+    // 已通过媒体接口上传的文件的内容引用
+    pub media_cids: Vec<String>,
+    pub tags: Vec<String>,
+    pub tx_hash: Option<String>,
+    pub tx_chain: Option<String>,
+    pub author_signature: Option<AuthorSignature>,
+}
 
 /// 内容服务，处理发帖、评论、点赞等社交功能
 pub struct ContentService {
     db: Arc<RBatis>,
     storage_service: Arc<StorageService>,
+    trade_service: Arc<TradeService>,
+    space_service: Arc<SpaceService>,
 }
 
 impl ContentService {
-    pub fn new(db: Arc<RBatis>, storage_service: Arc<StorageService>) -> Self {
+    pub fn new(
+        db: Arc<RBatis>,
+        storage_service: Arc<StorageService>,
+        trade_service: Arc<TradeService>,
+        space_service: Arc<SpaceService>,
+    ) -> Self {
         Self {
             db,
             storage_service,
+            trade_service,
+            space_service,
         }
     }
 
     /// 创建新帖子
-    pub async fn create_post(
-        &self,
-        user_id: String,
-        wallet_address: &str,
-        content: &str,
-        image_data: Option<Vec<u8>>,
-        tags: Vec<String>,
-        tx_hash: Option<String>,
-    ) -> Result<PostEntity, ServiceError> {
-        // 如果有图片，先上传到IPFS
-        let image_cid = match image_data {
-            Some(data) => Some(self.storage_service.upload_to_ipfs(&data).await?),
+    pub async fn create_post(&self, post: NewPost<'_>) -> Result<PostEntity, ServiceError> {
+        let NewPost {
+            user_id,
+            wallet_address,
+            content,
+            image_data,
+            media_cids,
+            tags,
+            tx_hash,
+            tx_chain,
+            author_signature,
+        } = post;
+
+        // 使用门槛空间标签时需满足持仓要求
+        self.space_service.require_tag_access(&user_id, &tags).await?;
+
+        // 客户端提供了作者签名时，验证签名属于作者钱包
+        let signed_message = match &author_signature {
+            Some(signature) => Some(
+                self.verify_author_signature(&user_id, CONTENT_KIND_POST, content, &tags, None, signature)
+                    .await?,
+            ),
             None => None,
         };
 
-        // 将内容存储到Arweave
-        let content_id = self
-            .storage_service
-            .upload_to_arweave(content.as_bytes())
-            .await?;
+        // 引用了链上交易时，先验证交易属于作者钱包
+        let trade_proof = match &tx_hash {
+            Some(hash) => {
+                let chain = tx_chain.unwrap_or_else(|| "ETH".to_string()).to_uppercase();
+                Some(
+                    self.trade_service
+                        .verify_trade(&user_id, wallet_address, &chain, hash)
+                        .await?,
+                )
+            }
+            None => None,
+        };
 
-        // 创建帖子实体
+        // 帖子ID由应用生成，以便写入Arweave标签
+        let post_id = Uuid::new_v4();
+        let post_id_str = post_id.to_string();
+
+        // 帖子和发件箱记录在同一事务中写入
+        let tx = self.begin().await?;
+
+        // 图片加入媒体发件箱，内容引用在本地计算后立即可用
+        let mut image_cids = Vec::with_capacity(media_cids.len() + 1);
+        if let Some(data) = image_data {
+            image_cids.push(
+                self.storage_service
+                    .enqueue_media(&tx, CONTENT_TABLE_POSTS, &post_id_str, &data, CONTENT_TYPE_IMAGE)
+                    .await?,
+            );
+        }
+        // 已通过媒体接口上传的文件直接引用其内容引用
+        image_cids.extend(media_cids);
+
+        // 创建帖子实体，内容上链前状态为pending
         let post_entity = PostEntity {
-            id: 0, // 数据库会自动生成ID
+            id: post_id,
             user_id,
             content: content.to_string(),
-            images_ipfs_cids: if let Some(cid) = &image_cid {
-                Some(vec![cid.clone()])
-            } else {
+            images_ipfs_cids: if image_cids.is_empty() {
                 None
+            } else {
+                Some(image_cids)
             },
-            arweave_tx_id: Some(content_id),
-            transaction_hash: tx_hash,
-            transaction_chain: None,
+            arweave_tx_id: None,
+            storage_ref: None,
+            transaction_hash: trade_proof.as_ref().map(|p| p.tx_hash.clone()),
+            transaction_chain: trade_proof.as_ref().map(|p| p.chain.clone()),
             like_count: 0,
             comment_count: 0,
             tags: Some(tags),
+            post_type: POST_TYPE_ORIGINAL.to_string(),
+            repost_of_id: None,
+            repost_count: 0,
+            is_hidden: false,
+            trade_verified: trade_proof
+                .as_ref()
+                .is_some_and(|p| p.status == TRADE_STATUS_VERIFIED),
+            storage_status: STORAGE_STATUS_PENDING.to_string(),
+            content_key_id: None,
+            content_wrapped_key: None,
+            content_hash: Some(content_hash(content.as_bytes())),
+            author_signature: author_signature.as_ref().map(|s| s.signature.clone()),
+            signed_at: author_signature.as_ref().map(|s| s.signed_at),
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
         };
-        let rb: RBatis = RBatis::new();
         // 保存帖子
-        PostEntity::insert(&rb, &post_entity)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+        insert_post(&tx, &post_entity).await?;
+
+        // 内容加入发件箱，由后台任务写入永久存储
+        self.storage_service
+            .enqueue_record(
+                &tx,
+                CONTENT_TABLE_POSTS,
+                &post_id_str,
+                content.as_bytes(),
+                CONTENT_TYPE_TEXT,
+                content_tags(
+                    CONTENT_KIND_POST,
+                    wallet_address,
+                    &with_signature_tags(vec![("Post-Id", post_id_str.as_str())], &author_signature, &signed_message),
+                ),
+            )
+            .await?;
+        commit(&tx).await?;
 
         Ok(post_entity)
     }
 
-    /// 获取或创建标签
-    async fn get_or_create_tag(&self, tag_name: &str) -> Result<i32, ServiceError> {
-        // 使用select_by_column方法查询标签
-        let tag = TagEntity::select_by_column(&rb, "name", tag_name)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            .first()
-            .cloned();
-        let rb: RBatis = RBatis::new();
-        if let Some(tag) = tag {
-            Ok(tag.id)
-        } else {
-            // 创建新标签
-            let new_tag: TagEntity = TagEntity {
-                id: 0, // 数据库会自动生成ID
-                name: tag_name.to_string(),
-            };
+    // 验证作者签名，返回签名的规范消息
+    async fn verify_author_signature(
+        &self,
+        user_id: &str,
+        kind: &str,
+        content: &str,
+        tags: &[String],
+        parent: Option<&str>,
+        signature: &AuthorSignature,
+    ) -> Result<String, ServiceError> {
+        if (chrono::Utc::now().timestamp() - signature.signed_at).abs() > signature_max_skew_secs() {
+            return Err(ServiceError::BadRequest("签名时间已过期，请重新签名".into()));
+        }
+        if tags.iter().any(|tag| tag.contains(',') || tag.contains('\n')) {
+            return Err(ServiceError::BadRequest("签名内容的标签不能包含逗号或换行".into()));
+        }
 
-            // 使用insert方法保存标签
-            TagEntity::insert(&rb, &new_tag)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?;
+        let wallet = get_user_wallet(&self.db, user_id).await?;
+        let author = normalize_address(&wallet.wallet_address);
+        let message = canonical_message(&author, kind, content, tags, parent, signature.signed_at);
+        if message.len() > MAX_SIGNED_MESSAGE_LEN {
+            return Err(ServiceError::BadRequest("签名内容过长".into()));
+        }
 
-            // 获取新创建的标签ID
-            let tag = TagEntity::select_by_column(&rb, "name", tag_name)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?
-                .first()
-                .cloned()
-                .ok_or(ServiceError::InternalServerError)?;
+        if !crypto::verify_wallet_signature(&wallet.wallet_chain, &message, &signature.signature, &author) {
+            return Err(ServiceError::BadRequest("作者签名验证失败".into()));
+        }
 
-            Ok(tag.id)
+        Ok(message)
+    }
+
+    /// 生成作者需要签名的规范消息，客户端也可按相同规则自行生成
+    pub async fn signing_message(
+        &self,
+        user_id: &str,
+        kind: &str,
+        content: &str,
+        tags: &[String],
+        parent: Option<&str>,
+        signed_at: i64,
+    ) -> Result<String, ServiceError> {
+        if ![CONTENT_KIND_POST, CONTENT_KIND_QUOTE, CONTENT_KIND_COMMENT].contains(&kind) {
+            return Err(ServiceError::BadRequest(format!("不支持的内容类型: {}", kind)));
         }
+        let wallet = get_user_wallet(&self.db, user_id).await?;
+
+        Ok(canonical_message(
+            &normalize_address(&wallet.wallet_address),
+            kind,
+            content,
+            tags,
+            parent,
+            signed_at,
+        ))
     }
 
     /// 按热度获取帖子列表
@@ -110,46 +259,20 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<PostEntity>, ServiceError> {
-        let offset = (page - 1) * page_size;
-
-        // 使用rbatis执行原生SQL查询
-        let sql = format!(
-            r#"
-            SELECT * FROM posts
-            ORDER BY (COALESCE(like_count, 0) + COALESCE(comment_count, 0) * 2) DESC, created_at DESC
-            LIMIT {} OFFSET {}
-        "#,
-            page_size, offset
-        );
+        let (limit, offset) = page_bounds(page, page_size);
 
-        let post_entities: Vec<PostEntity> = self
-            .db
-            .fetch_by_sql(&sql)
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p WHERE {} \
+                     ORDER BY (p.like_count + p.comment_count * 2 + p.repost_count * 3) DESC, p.created_at DESC \
+                     LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
-
-        // 将实体转换为Post模型
-        let posts: Vec<PostEntity> = post_entities
-            .into_iter()
-            .map(|entity: PostEntity| {
-                PostEntity {
-                    id: uuid::Uuid::new_v4(), // 这里需要根据实际情况调整
-                    user_id: entity.user_id,
-                    content: entity.content,
-                    images_ipfs_cids: entity.images_ipfs_cids,
-                    arweave_tx_id: entity.arweave_tx_id,
-                    transaction_hash: entity.transaction_hash,
-                    transaction_chain: entity.transaction_chain,
-                    like_count: entity.like_count,
-                    comment_count: entity.comment_count,
-                    tags: entity.tags,
-                    created_at: entity.created_at.into(),
-                    updated_at: entity.updated_at.into(),
-                }
-            })
-            .collect();
-
-        Ok(posts)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 按时间获取帖子列表
@@ -158,40 +281,18 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<PostEntity>, ServiceError> {
-        let offset = (page - 1) * page_size;
+        let (limit, offset) = page_bounds(page, page_size);
 
-        // 使用rbatis分页查询
-        let wrapper = self.db.new_wrapper().order_by(true, &["created_at"]);
-
-        let page_result = self
-            .db
-            .fetch_page_by_wrapper::<PostEntity>(wrapper, page as u64, page_size as u64)
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p WHERE {} ORDER BY p.created_at DESC LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
-
-        // 将实体转换为Post模型
-        let posts = page_result
-            .records
-            .into_iter()
-            .map(|entity| {
-                PostEntity {
-                    id: uuid::Uuid::new_v4(), // 这里需要根据实际情况调整
-                    user_id: entity.user_id,
-                    content: entity.content,
-                    images_ipfs_cids: entity.images_ipfs_cids,
-                    arweave_tx_id: entity.arweave_tx_id,
-                    transaction_hash: entity.transaction_hash,
-                    transaction_chain: entity.transaction_chain,
-                    like_count: entity.like_count,
-                    comment_count: entity.comment_count,
-                    tags: entity.tags,
-                    created_at: entity.created_at.into(),
-                    updated_at: entity.updated_at.into(),
-                }
-            })
-            .collect();
-
-        Ok(posts)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 按标签获取帖子列表
@@ -201,48 +302,18 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<PostEntity>, ServiceError> {
-        let offset = (page - 1) * page_size;
-
-        // 使用rbatis执行原生SQL查询
-        let sql = format!(
-            r#"
-            SELECT p.* FROM posts p
-            WHERE p.tags @> ARRAY[$1]::text[]
-            ORDER BY p.created_at DESC
-            LIMIT {} OFFSET {}
-        "#,
-            page_size, offset
-        );
+        let (limit, offset) = page_bounds(page, page_size);
 
-        let params = vec![rbs::to_value(tag)];
-        let post_entities: Vec<PostEntity> = self
-            .db
-            .fetch_by_sql_with(&sql, params)
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p WHERE ? = ANY(p.tags) AND {} ORDER BY p.created_at DESC LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![rbs::to_value!(tag), rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
-
-        // 将实体转换为Post模型
-        let posts: Vec<PostEntity> = post_entities
-            .into_iter()
-            .map(|entity: PostEntity| {
-                PostEntity {
-                    id: uuid::Uuid::new_v4(), // 这里需要根据实际情况调整
-                    user_id: entity.user_id,
-                    content: entity.content,
-                    images_ipfs_cids: entity.images_ipfs_cids,
-                    arweave_tx_id: entity.arweave_tx_id,
-                    transaction_hash: entity.transaction_hash,
-                    transaction_chain: entity.transaction_chain,
-                    like_count: entity.like_count,
-                    comment_count: entity.comment_count,
-                    tags: entity.tags,
-                    created_at: entity.created_at.into(),
-                    updated_at: entity.updated_at.into(),
-                }
-            })
-            .collect();
-
-        Ok(posts)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 获取用户帖子列表
@@ -252,136 +323,377 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<PostEntity>, ServiceError> {
-        let offset = (page - 1) * page_size;
+        let (limit, offset) = page_bounds(page, page_size);
 
-        // 使用rbatis查询用户帖子
-        let wrapper = self
-            .db
-            .new_wrapper()
-            .eq("user_id", user_id)
-            .order_by(false, &["created_at"]);
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p WHERE p.user_id::text = ? AND {} ORDER BY p.created_at DESC LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![rbs::to_value!(user_id), rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
 
-        let page_result = self
-            .db
-            .fetch_page_by_wrapper::<PostEntity>(wrapper, page as u64, page_size as u64)
+    /// 获取关注者时间线：自己和关注用户的原创、转发和引用帖子，按时间倒序
+    pub async fn get_timeline(
+        &self,
+        user_id: &str,
+        page: i32,
+        page_size: i32,
+    ) -> Result<Vec<PostEntity>, ServiceError> {
+        let (limit, offset) = page_bounds(page, page_size);
+
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p \
+                     WHERE (p.user_id = ?::uuid OR p.user_id IN (SELECT followee_id FROM follows WHERE follower_id = ?::uuid)) \
+                     AND {} ORDER BY p.created_at DESC LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![
+                    rbs::to_value!(user_id),
+                    rbs::to_value!(user_id),
+                    rbs::to_value!(limit),
+                    rbs::to_value!(offset),
+                ],
+            )
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
 
-        // 将实体转换为Post模型
-        let posts = page_result
-            .records
-            .into_iter()
-            .map(|entity| PostEntity {
-                id: entity.id,
-                user_id: entity.user_id,
-                content: entity.content,
-                images_ipfs_cids: entity.images_ipfs_cids,
-                arweave_tx_id: entity.arweave_tx_id,
-                transaction_hash: entity.transaction_hash,
-                transaction_chain: entity.transaction_chain,
-                like_count: entity.like_count,
-                comment_count: entity.comment_count,
-                tags: entity.tags,
-                created_at: entity.created_at.into(),
-                updated_at: entity.updated_at.into(),
-            })
-            .collect();
-
-        Ok(posts)
+    /// 批量获取转发和引用帖子的原帖，已隐藏的原帖不返回
+    pub async fn get_original_posts(&self, posts: &[PostEntity]) -> Result<Vec<PostEntity>, ServiceError> {
+        let ids: HashSet<&str> = posts.iter().filter_map(|p| p.repost_of_id.as_deref()).collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<&str> = ids.into_iter().collect();
+
+        self.db
+            .query_decode(
+                "SELECT * FROM posts WHERE id = ANY(string_to_array(?, ',')::uuid[]) AND NOT is_hidden",
+                vec![rbs::to_value!(ids.join(","))],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 获取帖子详情
     pub async fn get_post(&self, post_id: String) -> Result<PostEntity, ServiceError> {
-        // 使用select_by_column方法查询帖子
-        let entity = PostEntity::select_by_column(&self.db, "id", post_id)
+        let posts: Vec<PostEntity> = self
+            .db
+            .query_decode("SELECT * FROM posts WHERE id::text = ?", vec![rbs::to_value!(post_id)])
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?
-            .first()
-            .cloned()
-            .ok_or(ServiceError::NotFound("帖子不存在".into()))?;
-
-        // 将实体转换为Post模型
-        let post = PostEntity {
-            id: entity.id,
-            user_id: entity.user_id,
-            content: entity.content,
-            images_ipfs_cids: entity.images_ipfs_cids,
-            arweave_tx_id: entity.arweave_tx_id,
-            transaction_hash: entity.transaction_hash,
-            transaction_chain: entity.transaction_chain,
-            like_count: entity.like_count,
-            comment_count: entity.comment_count,
-            tags: entity.tags,
-            created_at: entity.created_at.into(),
-            updated_at: entity.updated_at.into(),
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        posts
+            .into_iter()
+            .next()
+            .ok_or(ServiceError::NotFound("帖子不存在".into()))
+    }
+
+    /// 获取帖子引用的交易凭证
+    pub async fn get_post_trade_proof(
+        &self,
+        post: &PostEntity,
+    ) -> Result<Option<TradeProofEntity>, ServiceError> {
+        match (&post.transaction_chain, &post.transaction_hash) {
+            (Some(chain), Some(tx_hash)) => self.trade_service.get_trade_proof(chain, tx_hash).await,
+            _ => Ok(None),
+        }
+    }
+
+    /// 转发帖子（不附带内容）
+    pub async fn repost_post(
+        &self,
+        user_id: String,
+        post_id: String,
+    ) -> Result<PostEntity, ServiceError> {
+        // 转发一个转发帖时，直接指向原帖
+        let target = self.resolve_repost_target(post_id).await?;
+        let target_id = target.id.to_string();
+
+        let post_entity = PostEntity {
+            id: Uuid::new_v4(),
+            user_id,
+            content: String::new(),
+            images_ipfs_cids: None,
+            arweave_tx_id: None,
+            storage_ref: None,
+            transaction_hash: None,
+            transaction_chain: None,
+            like_count: 0,
+            comment_count: 0,
+            tags: None,
+            post_type: POST_TYPE_REPOST.to_string(),
+            repost_of_id: Some(target_id.clone()),
+            repost_count: 0,
+            is_hidden: false,
+            trade_verified: false,
+            storage_status: STORAGE_STATUS_NONE.to_string(),
+            content_key_id: None,
+            content_wrapped_key: None,
+            content_hash: None,
+            author_signature: None,
+            signed_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
         };
 
-        Ok(post)
+        // 同一用户对同一帖子只能转发一次，由唯一索引保证
+        let tx = self.begin().await?;
+        insert_post(&tx, &post_entity)
+            .await
+            .map_err(|e| match e {
+                ServiceError::DatabaseError(msg) if is_unique_violation(&msg) => {
+                    ServiceError::BadRequest("已经转发过该帖子".into())
+                }
+                e => e,
+            })?;
+        increment_repost_count(&tx, &target_id).await?;
+        commit(&tx).await?;
+
+        Ok(post_entity)
     }
 
-    /// 创建评论
-    pub async fn create_comment(
+    /// 引用转发帖子，引用内容与原创帖子一样永久存储
+    pub async fn quote_post(
         &self,
         user_id: String,
         post_id: String,
         content: &str,
-        parent_id: Option<String>,
-    ) -> Result<CommentEntity, ServiceError> {
-        // 验证帖子是否存在
-        let post_wrapper = self.db.new_wrapper().eq("id", post_id);
+        tags: Vec<String>,
+        author_signature: Option<AuthorSignature>,
+    ) -> Result<PostEntity, ServiceError> {
+        if content.trim().is_empty() {
+            return Err(ServiceError::BadRequest("引用内容不能为空".into()));
+        }
+        self.space_service.require_tag_access(&user_id, &tags).await?;
+
+        // 签名中的parent为客户端引用的帖子ID
+        let signed_message = match &author_signature {
+            Some(signature) => Some(
+                self.verify_author_signature(&user_id, CONTENT_KIND_QUOTE, content, &tags, Some(&post_id), signature)
+                    .await?,
+            ),
+            None => None,
+        };
 
-        let post_exists = self
-            .db
-            .fetch_count_by_wrapper::<PostEntity>(post_wrapper)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            > 0;
+        let target = self.resolve_repost_target(post_id).await?;
+        let target_id = target.id.to_string();
+        self.check_quote_chain(&target).await?;
 
-        if !post_exists {
-            return Err(ServiceError::NotFound("帖子不存在".into()));
+        let post_entity = PostEntity {
+            id: Uuid::new_v4(),
+            user_id,
+            content: content.to_string(),
+            images_ipfs_cids: None,
+            arweave_tx_id: None,
+            storage_ref: None,
+            transaction_hash: None,
+            transaction_chain: None,
+            like_count: 0,
+            comment_count: 0,
+            tags: Some(tags),
+            post_type: POST_TYPE_QUOTE.to_string(),
+            repost_of_id: Some(target_id.clone()),
+            repost_count: 0,
+            is_hidden: false,
+            trade_verified: false,
+            storage_status: STORAGE_STATUS_PENDING.to_string(),
+            content_key_id: None,
+            content_wrapped_key: None,
+            content_hash: Some(content_hash(content.as_bytes())),
+            author_signature: author_signature.as_ref().map(|s| s.signature.clone()),
+            signed_at: author_signature.as_ref().map(|s| s.signed_at),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+
+        let wallet = get_user_wallet(&self.db, &post_entity.user_id).await?;
+        let tx = self.begin().await?;
+        insert_post(&tx, &post_entity).await?;
+        increment_repost_count(&tx, &target_id).await?;
+
+        // 引用内容加入发件箱
+        let post_id = post_entity.id.to_string();
+        self.storage_service
+            .enqueue_record(
+                &tx,
+                CONTENT_TABLE_POSTS,
+                &post_id,
+                content.as_bytes(),
+                CONTENT_TYPE_TEXT,
+                content_tags(
+                    CONTENT_KIND_QUOTE,
+                    &wallet.wallet_address,
+                    &with_signature_tags(
+                        vec![("Post-Id", post_id.as_str()), ("Quote-Of", target_id.as_str())],
+                        &author_signature,
+                        &signed_message,
+                    ),
+                ),
+            )
+            .await?;
+        commit(&tx).await?;
+
+        Ok(post_entity)
+    }
+
+    /// 确定转发目标：纯转发帖指向其原帖，且目标不能是已隐藏的帖子
+    async fn resolve_repost_target(&self, post_id: String) -> Result<PostEntity, ServiceError> {
+        let mut target = self.get_post(post_id).await?;
+
+        if target.post_type == POST_TYPE_REPOST {
+            let original_id = target
+                .repost_of_id
+                .clone()
+                .ok_or(ServiceError::NotFound("原帖不存在".into()))?;
+            target = self.get_post(original_id).await?;
         }
 
-        // 如果是回复评论，验证父评论是否存在
-        if let Some(parent_id_val) = parent_id {
-            let comment_wrapper = self.db.new_wrapper().eq("id", parent_id_val);
+        if target.is_hidden {
+            return Err(ServiceError::BadRequest("该帖子已被隐藏，无法转发".into()));
+        }
 
-            let comment_exists = self
-                .db
-                .fetch_count_by_wrapper::<CommentEntity>(comment_wrapper)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?
-                > 0;
+        Ok(target)
+    }
+
+    /// 检查引用链，防止成环和过深的嵌套
+    async fn check_quote_chain(&self, target: &PostEntity) -> Result<(), ServiceError> {
+        let mut visited = HashSet::new();
+        visited.insert(target.id.to_string());
 
-            if !comment_exists {
-                return Err(ServiceError::NotFound("父评论不存在".into()));
+        let mut next_id = target.repost_of_id.clone();
+        while let Some(id) = next_id {
+            if !visited.insert(id.clone()) {
+                return Err(ServiceError::BadRequest("检测到循环引用".into()));
             }
+            if visited.len() > MAX_QUOTE_DEPTH {
+                return Err(ServiceError::BadRequest("引用层级过深".into()));
+            }
+
+            next_id = match self.get_post(id).await {
+                Ok(post) => post.repost_of_id,
+                // 链上的帖子不存在时视为链的终点
+                Err(ServiceError::NotFound(_)) => None,
+                Err(err) => return Err(err),
+            };
         }
 
-        // 将内容存储到Arweave
-        let content_id = self
-            .storage_service
-            .upload_to_arweave(content.as_bytes())
-            .await?;
+        Ok(())
+    }
 
-        // 创建评论实体
-        let comment_id = uuid::Uuid::new_v4();
+    // 开启事务，未提交时释放后回滚
+    async fn begin(&self) -> Result<RBatisTxExecutorGuard, ServiceError> {
+        let tx = self
+            .db
+            .acquire_begin()
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(tx.defer_async(|tx| async move {
+            if !tx.done() {
+                if let Err(e) = tx.rollback().await {
+                    log::error!("回滚事务失败: {}", e);
+                }
+            }
+        }))
+    }
+
+    /// 创建评论
+    pub async fn create_comment(
+        &self,
+        user_id: String,
+        post_id: String,
+        content: &str,
+        parent_id: Option<String>,
+        author_signature: Option<AuthorSignature>,
+    ) -> Result<CommentEntity, ServiceError> {
+        // 验证帖子是否存在，门槛空间内的帖子只有满足持仓要求的用户才能评论
+        let post = self.get_post(post_id.clone()).await?;
+        if let Some(tags) = &post.tags {
+            self.space_service.require_tag_access(&user_id, tags).await?;
+        }
+
+        // 如果是回复评论，验证父评论存在且属于同一帖子
+        if let Some(parent_id_val) = &parent_id {
+            let parent = self.get_comment(parent_id_val.clone()).await.map_err(|err| match err {
+                ServiceError::NotFound(_) => ServiceError::NotFound("父评论不存在".into()),
+                err => err,
+            })?;
+            if parent.post_id != post_id {
+                return Err(ServiceError::BadRequest("父评论不属于该帖子".into()));
+            }
+        }
+
+        // 签名中的parent为回复的父评论ID，直接评论帖子时为帖子ID
+        let signed_message = match &author_signature {
+            Some(signature) => {
+                let parent = parent_id.as_deref().unwrap_or(&post_id);
+                Some(
+                    self.verify_author_signature(&user_id, CONTENT_KIND_COMMENT, content, &[], Some(parent), signature)
+                        .await?,
+                )
+            }
+            None => None,
+        };
+
+        // 创建评论实体，内容上链前状态为pending
         let comment_entity = CommentEntity {
-            id: comment_id,
+            id: Uuid::new_v4(),
             post_id,
             user_id,
             parent_id,
             content: content.to_string(),
-            arweave_tx_id: Some(content_id),
+            arweave_tx_id: None,
+            storage_ref: None,
             like_count: 0,
+            storage_status: STORAGE_STATUS_PENDING.to_string(),
+            content_key_id: None,
+            content_wrapped_key: None,
+            content_hash: Some(content_hash(content.as_bytes())),
+            author_signature: author_signature.as_ref().map(|s| s.signature.clone()),
+            signed_at: author_signature.as_ref().map(|s| s.signed_at),
             created_at: DateTime::now(),
             updated_at: DateTime::now(),
         };
 
-        // 保存评论
-        self.db
-            .save(&comment_entity, &[])
+        // 评论和发件箱记录在同一事务中写入
+        let wallet = get_user_wallet(&self.db, &comment_entity.user_id).await?;
+        let tx = self.begin().await?;
+        CommentEntity::insert(&tx, &comment_entity)
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        // 内容加入发件箱，由后台任务写入永久存储
+        let comment_id = comment_entity.id.to_string();
+        let mut extra_tags = vec![
+            ("Post-Id", comment_entity.post_id.as_str()),
+            ("Comment-Id", comment_id.as_str()),
+        ];
+        if let Some(parent_id) = &comment_entity.parent_id {
+            extra_tags.push(("Parent-Comment-Id", parent_id.as_str()));
+        }
+        self.storage_service
+            .enqueue_record(
+                &tx,
+                CONTENT_TABLE_COMMENTS,
+                &comment_id,
+                content.as_bytes(),
+                CONTENT_TYPE_TEXT,
+                content_tags(
+                    CONTENT_KIND_COMMENT,
+                    &wallet.wallet_address,
+                    &with_signature_tags(extra_tags, &author_signature, &signed_message),
+                ),
+            )
+            .await?;
+        commit(&tx).await?;
 
         Ok(comment_entity)
     }
@@ -393,19 +705,16 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<CommentEntity>, ServiceError> {
-        // 使用wrapper构建查询条件并按时间排序
-        let wrapper = self.db
-            .new_wrapper()
-            .eq("post_id", post_id)
-            .is_null("parent_id")
-            .order_by(true, &["created_at"])
-            .page(page as u64, page_size as u64);
-
-        let page_result = CommentEntity::fetch_page_by_wrapper(&self.db, wrapper)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+        let (limit, offset) = page_bounds(page, page_size);
 
-        Ok(page_result.records)
+        self.db
+            .query_decode(
+                "SELECT * FROM comments WHERE post_id::text = ? AND parent_id IS NULL \
+                 ORDER BY created_at LIMIT ? OFFSET ?",
+                vec![rbs::to_value!(post_id), rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 获取评论回复
@@ -415,35 +724,29 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<CommentEntity>, ServiceError> {
-        // 使用rbatis查询评论回复
-        let wrapper = self
-            .db
-            .new_wrapper()
-            .eq("parent_id", comment_id)
-            .order_by(true, &["created_at"]);
+        let (limit, offset) = page_bounds(page, page_size);
 
-        let page_result = self
-            .db
-            .fetch_page_by_wrapper::<CommentEntity>(wrapper, page as u64, page_size as u64)
+        self.db
+            .query_decode(
+                "SELECT * FROM comments WHERE parent_id::text = ? ORDER BY created_at LIMIT ? OFFSET ?",
+                vec![rbs::to_value!(comment_id), rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
-
-        Ok(page_result.records)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 获取评论详情
     pub async fn get_comment(&self, comment_id: String) -> Result<CommentEntity, ServiceError> {
-        // 使用rbatis查询评论
-        let wrapper = self.db.new_wrapper().eq("id", comment_id);
-
-        let entity = self
+        let comments: Vec<CommentEntity> = self
             .db
-            .fetch_by_wrapper::<CommentEntity>(wrapper)
+            .query_decode("SELECT * FROM comments WHERE id::text = ?", vec![rbs::to_value!(comment_id)])
             .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            .ok_or(ServiceError::NotFound("评论不存在".into()))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        Ok(entity)
+        comments
+            .into_iter()
+            .next()
+            .ok_or(ServiceError::NotFound("评论不存在".into()))
     }
 
     /// 点赞帖子
@@ -453,120 +756,100 @@ impl ContentService {
         post_id: String,
     ) -> Result<(), ServiceError> {
         // 验证帖子是否存在
-        let post_wrapper: bool = self.db.new_wrapper().eq("id", post_id);
-
-        let post_exists: bool = self
-            .db
-            .fetch_count_by_wrapper::<PostEntity>(post_wrapper)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            > 0;
-
-        if !post_exists {
-            return Err(ServiceError::NotFound("帖子不存在".into()));
-        }
-
-        // 检查是否已点赞
-        let like_wrapper = self.db.new_wrapper().eq("user_id", user_id);
+        self.get_post(post_id.clone()).await?;
 
-        let exists = self
+        // 唯一约束保证同一用户只能点赞一次
+        let result = self
             .db
-            .fetch_count_by_wrapper::<UserLikeEntity>(like_wrapper)
+            .exec(
+                "INSERT INTO likes (user_id, post_id) VALUES (?::uuid, ?::uuid) \
+                 ON CONFLICT (user_id, post_id) DO NOTHING",
+                vec![rbs::to_value!(&user_id), rbs::to_value!(&post_id)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            > 0;
-
-        if exists {
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        if result.rows_affected == 0 {
             return Err(ServiceError::BadRequest("已经点赞过该帖子".into()));
         }
 
-        // 创建点赞记录
-        let like_entity = UserLikeEntity {
-            id: uuid::Uuid::new_v4(),
-            user_id,
-            post_id: Some(post_id),
-            comment_id: None,
-            created_at: DateTime::now(),
-        };
-
         self.db
-            .save(&like_entity, &[])
+            .exec(
+                "UPDATE posts SET like_count = like_count + 1 WHERE id::text = ?",
+                vec![rbs::to_value!(&post_id)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
         Ok(())
     }
 
     /// 取消点赞
-    pub async fn unlike_post(        &self,
+    pub async fn unlike_post(
+        &self,
         user_id: String,
         post_id: String,
     ) -> Result<(), ServiceError> {
-        // 使用rbatis删除点赞记录
-        let wrapper = self.db.new_wrapper().eq("user_id", user_id);
-
-        self.db
-            .remove_by_wrapper::<UserLikeEntity>(wrapper)
+        let result = self
+            .db
+            .exec(
+                "DELETE FROM likes WHERE user_id::text = ? AND post_id::text = ?",
+                vec![rbs::to_value!(&user_id), rbs::to_value!(&post_id)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected > 0 {
+            self.db
+                .exec(
+                    "UPDATE posts SET like_count = GREATEST(like_count - 1, 0) WHERE id::text = ?",
+                    vec![rbs::to_value!(&post_id)],
+                )
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        }
 
         Ok(())
     }
 
     /// 获取帖子点赞数
-    pub async fn get_post_likes_count(&self, post_id: String) -> Result<i64, ServiceError> {
-        // 使用rbatis查询点赞数
-        let wrapper = self.db.new_wrapper().eq("post_id", post_id);
-
-        let count = self
-            .db
-            .fetch_count_by_wrapper::<UserLikeEntity>(wrapper)
+    pub async fn get_post_likes_count(&self, post_id: &str) -> Result<i64, ServiceError> {
+        self.db
+            .query_decode(
+                "SELECT COUNT(*) AS count FROM likes WHERE post_id::text = ?",
+                vec![rbs::to_value!(post_id)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
-
-        Ok(count)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 检查用户是否已点赞
     pub async fn has_user_liked(
         &self,
-        user_id: String,
-        post_id: String,
+        user_id: &str,
+        post_id: &str,
     ) -> Result<bool, ServiceError> {
-        // 使用rbatis查询是否已点赞
-        let wrapper = self.db.new_wrapper().eq("user_id", user_id);
-
-        let count = self
+        let count: i64 = self
             .db
-            .fetch_count_by_wrapper::<UserLikeEntity>(wrapper)
+            .query_decode(
+                "SELECT COUNT(*) AS count FROM likes WHERE user_id::text = ? AND post_id::text = ?",
+                vec![rbs::to_value!(user_id), rbs::to_value!(post_id)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
         Ok(count > 0)
     }
 
     /// 获取热门标签
     pub async fn get_hot_tags(&self, limit: i32) -> Result<Vec<TagEntity>, ServiceError> {
-        // 使用rbatis执行原生SQL查询获取热门标签
-        let sql = format!(
-            r#"
-            SELECT t.id, t.name, COUNT(pt.post_id) as post_count
-            FROM tags t
-            JOIN post_tags pt ON t.id = pt.tag_id
-            GROUP BY t.id, t.name
-            ORDER BY COUNT(pt.post_id) DESC
-            LIMIT {}
-        "#,
-            limit
-        );
-
-        let tags: Vec<TagEntity> = self
-            .db
-            .fetch_by_sql(&sql)
+        self.db
+            .query_decode(
+                "SELECT tag AS name, COUNT(*) AS post_count FROM posts p, unnest(p.tags) AS tag \
+                 WHERE NOT p.is_hidden GROUP BY tag ORDER BY post_count DESC, tag LIMIT ?",
+                vec![rbs::to_value!(limit)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?;
-
-        Ok(tags)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
     }
 
     /// 搜索帖子
@@ -576,45 +859,140 @@ impl ContentService {
         page: i32,
         page_size: i32,
     ) -> Result<Vec<PostEntity>, ServiceError> {
-        let offset = (page - 1) * page_size;
-        let search_term = format!("%{}%", query);
-
-        // 使用rbatis执行原生SQL查询搜索帖子
-        let sql = format!(
-            r#"
-            SELECT * FROM posts
-            WHERE content ILIKE '{}'
-            ORDER BY created_at DESC
-            LIMIT {} OFFSET {}
-        "#,
-            search_term, page_size, offset
+        let (limit, offset) = page_bounds(page, page_size);
+        // 转义LIKE通配符，按字面匹配
+        let pattern = format!(
+            "%{}%",
+            query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
         );
 
-        let post_entities: Vec<PostEntity> = self
-            .db
-            .fetch_by_sql(&sql)
+        self.db
+            .query_decode(
+                &format!(
+                    "SELECT p.* FROM posts p WHERE p.content ILIKE ? AND {} ORDER BY p.created_at DESC LIMIT ? OFFSET ?",
+                    VISIBLE_POST
+                ),
+                vec![rbs::to_value!(pattern), rbs::to_value!(limit), rbs::to_value!(offset)],
+            )
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+}
 
-        // 将实体转换为Post模型
-        let posts = post_entities
-            .into_iter()
-            .map(|entity| PostEntity {
-                id: entity.id,
-                user_id: entity.user_id,
-                content: entity.content,
-                images_ipfs_cids: entity.images_ipfs_cids,
-                arweave_tx_id: entity.arweave_tx_id,
-                transaction_hash: entity.transaction_hash,
-                transaction_chain: entity.transaction_chain,
-                like_count: entity.like_count,
-                comment_count: entity.comment_count,
-                tags: entity.tags,
-                created_at: entity.created_at.into(),
-                updated_at: entity.updated_at.into(),
-            })
-            .collect();
-
-        Ok(posts)
+// 分页参数转换为LIMIT和OFFSET，页码从1开始，每页最多100条
+fn page_bounds(page: i32, page_size: i32) -> (i64, i64) {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE) as i64;
+    let page = page.max(1) as i64;
+    (page_size, (page - 1) * page_size)
+}
+
+// 原帖转发数加一
+async fn increment_repost_count(executor: &dyn Executor, post_id: &str) -> Result<(), ServiceError> {
+    executor
+        .exec(
+            "UPDATE posts SET repost_count = repost_count + 1 WHERE id::text = ?",
+            vec![rbs::to_value!(post_id)],
+        )
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+async fn commit(tx: &RBatisTxExecutorGuard) -> Result<(), ServiceError> {
+    tx.commit().await.map_err(|e| ServiceError::DatabaseError(e.to_string()))
+}
+
+// 保存帖子。tags和images_ipfs_cids是text[]列，而rbatis把数组按json绑定，需要在SQL中转换
+// repost_of_id可能为空，先按text绑定再转为uuid，避免连接缓存的预编译语句按uuid解析字符串参数
+async fn insert_post(executor: &dyn Executor, post: &PostEntity) -> Result<(), ServiceError> {
+    executor
+        .exec(
+            "INSERT INTO posts (id, user_id, content, images_ipfs_cids, arweave_tx_id, storage_ref, transaction_hash, \
+             transaction_chain, like_count, comment_count, tags, post_type, repost_of_id, repost_count, \
+             is_hidden, trade_verified, storage_status, content_key_id, content_wrapped_key, content_hash, \
+             author_signature, signed_at, created_at, updated_at) \
+             VALUES (?::uuid, ?::uuid, ?, (SELECT array_agg(v) FROM json_array_elements_text(?) v), ?, ?, ?, ?, ?, ?, \
+             (SELECT array_agg(v) FROM json_array_elements_text(?) v), ?, ?::text::uuid, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            vec![
+                rbs::to_value!(post.id.to_string()),
+                rbs::to_value!(&post.user_id),
+                rbs::to_value!(&post.content),
+                rbs::to_value!(&post.images_ipfs_cids),
+                rbs::to_value!(&post.arweave_tx_id),
+                rbs::to_value!(&post.storage_ref),
+                rbs::to_value!(&post.transaction_hash),
+                rbs::to_value!(&post.transaction_chain),
+                rbs::to_value!(post.like_count),
+                rbs::to_value!(post.comment_count),
+                rbs::to_value!(&post.tags),
+                rbs::to_value!(&post.post_type),
+                rbs::to_value!(&post.repost_of_id),
+                rbs::to_value!(post.repost_count),
+                rbs::to_value!(post.is_hidden),
+                rbs::to_value!(post.trade_verified),
+                rbs::to_value!(&post.storage_status),
+                rbs::to_value!(&post.content_key_id),
+                rbs::to_value!(&post.content_wrapped_key),
+                rbs::to_value!(&post.content_hash),
+                rbs::to_value!(&post.author_signature),
+                rbs::to_value!(post.signed_at),
+                rbs::to_value!(&post.created_at),
+                rbs::to_value!(&post.updated_at),
+            ],
+        )
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+// Arweave内容标签：内容类型、作者钱包及附加的ID标签
+fn content_tags(kind: &str, wallet_address: &str, extra: &[(&str, &str)]) -> Vec<Tag> {
+    let mut tags = vec![Tag::new("Content-Kind", kind), Tag::new("Author-Wallet", wallet_address)];
+    tags.extend(extra.iter().map(|(name, value)| Tag::new(name, value)));
+    tags
+}
+
+fn canonical_message(
+    author: &str,
+    kind: &str,
+    content: &str,
+    tags: &[String],
+    parent: Option<&str>,
+    signed_at: i64,
+) -> String {
+    let hash = content_hash(content.as_bytes());
+    SignedContent {
+        kind,
+        author,
+        content_hash: &hash,
+        tags,
+        parent,
+        signed_at,
+    }
+    .canonical_message()
+}
+
+// 附加作者签名标签，签名、规范消息和版本一起上链，可脱离数据库独立验证
+// 内容加密时存储服务不发布这些标签，避免据明文哈希比对内容
+fn with_signature_tags<'a>(
+    mut tags: Vec<(&'a str, &'a str)>,
+    author_signature: &'a Option<AuthorSignature>,
+    signed_message: &'a Option<String>,
+) -> Vec<(&'a str, &'a str)> {
+    if let (Some(signature), Some(message)) = (author_signature, signed_message) {
+        tags.push(("Author-Signature", &signature.signature));
+        tags.push(("Signature-Version", SIGNATURE_VERSION));
+        tags.push(("Signed-Message", message));
     }
+    tags
+}
+
+fn signature_max_skew_secs() -> i64 {
+    env::var("CONTENT_SIGNATURE_MAX_SKEW_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_SIGNATURE_MAX_SKEW_SECS)
 }
@@ -0,0 +1,489 @@
+use crate::models::job::JobStatus;
+use crate::utils::error::ServiceError;
+use futures::future::BoxFuture;
+use redis::Client as RedisClient;
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Redis任务队列，设置 JOB_QUEUE=redis 时启用，多个实例共同消费
+const QUEUE_KEY: &str = "jobs:queue";
+// 失败重试次数，通过 JOB_MAX_RETRIES 配置
+const DEFAULT_MAX_RETRIES: u32 = 3;
+// 重试的初始等待时间（秒），之后每次翻倍，通过 JOB_RETRY_BASE_SECS 配置
+const DEFAULT_RETRY_BASE_SECS: u64 = 5;
+const MAX_RETRY_BACKOFF_SECS: u64 = 300;
+// 分布式锁的有效期（秒），任务运行期间每隔三分之一有效期续期一次，实例崩溃后锁很快过期
+const LOCK_TTL_SECS: u64 = 60;
+// 队列消费者阻塞等待的时间（秒）
+const QUEUE_POLL_SECS: u64 = 5;
+
+// 释放锁时确认锁仍属于当前实例，避免误删其他实例的锁
+const RELEASE_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end
+"#;
+
+// 续期时确认锁仍属于当前实例
+const RENEW_LOCK_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("EXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end
+"#;
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<usize, ServiceError>> + Send + Sync>;
+
+// 已注册的任务
+struct JobDefinition {
+    name: &'static str,
+    interval_secs: u64,
+    run: JobFn,
+}
+
+/// 后台任务调度器
+/// 每个任务按固定间隔运行，失败时按指数退避重试；配置Redis时通过分布式锁保证同一时间只有一个实例运行同一任务
+pub struct JobScheduler {
+    redis: Option<Arc<RedisClient>>,
+    instance_id: String,
+    use_queue: bool,
+    jobs: Vec<JobDefinition>,
+    status: Mutex<HashMap<&'static str, JobStatus>>,
+}
+
+impl JobScheduler {
+    pub fn new(redis: Option<Arc<RedisClient>>) -> Self {
+        let use_queue = redis.is_some()
+            && env::var("JOB_QUEUE").map(|v| v.eq_ignore_ascii_case("redis")).unwrap_or(false);
+        let instance_id = env::var("INSTANCE_ID").unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+
+        Self {
+            redis,
+            instance_id,
+            use_queue,
+            jobs: Vec::new(),
+            status: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 注册任务，job返回本次处理的数量
+    pub fn register<T, F, Fut>(&mut self, name: &'static str, interval_secs: u64, service: Arc<T>, job: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn(Arc<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<usize, ServiceError>> + Send + 'static,
+    {
+        let interval_secs = interval_secs.max(1);
+        let run: JobFn = Arc::new(move || -> BoxFuture<'static, Result<usize, ServiceError>> {
+            Box::pin(job(service.clone()))
+        });
+
+        self.status.lock().unwrap().insert(
+            name,
+            JobStatus {
+                name: name.to_string(),
+                interval_secs,
+                ..Default::default()
+            },
+        );
+        self.jobs.push(JobDefinition { name, interval_secs, run });
+    }
+
+    /// 启动所有任务的定时器，启用Redis队列时同时启动队列消费者
+    pub fn start(scheduler: Arc<JobScheduler>) {
+        for job in &scheduler.jobs {
+            let scheduler = scheduler.clone();
+            let name = job.name;
+            let interval = job.interval_secs;
+
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+                loop {
+                    ticker.tick().await;
+                    if scheduler.use_queue {
+                        scheduler.schedule(name, interval).await;
+                    } else {
+                        scheduler.execute(name).await;
+                    }
+                }
+            });
+        }
+
+        log::info!(
+            "后台任务调度器已启动，实例: {}，Redis队列: {}",
+            scheduler.instance_id,
+            scheduler.use_queue
+        );
+
+        if scheduler.use_queue {
+            tokio::spawn(async move {
+                scheduler.consume_queue().await;
+            });
+        }
+    }
+
+    /// 立即运行指定任务，启用Redis队列时放入队列由任意实例执行
+    pub async fn trigger(scheduler: &Arc<JobScheduler>, name: &str) -> Result<(), ServiceError> {
+        let name = scheduler
+            .find(name)
+            .map(|job| job.name)
+            .ok_or_else(|| ServiceError::NotFound(format!("任务不存在: {}", name)))?;
+
+        if scheduler.use_queue {
+            return scheduler.enqueue(name).await;
+        }
+
+        let scheduler = scheduler.clone();
+        tokio::spawn(async move {
+            scheduler.execute(name).await;
+        });
+
+        Ok(())
+    }
+
+    /// 获取所有任务的状态，配置Redis时返回所有实例中最近一次运行的状态
+    pub async fn list_status(&self) -> Vec<JobStatus> {
+        let mut statuses = Vec::with_capacity(self.jobs.len());
+        for job in &self.jobs {
+            let local = self.status.lock().unwrap().get(job.name).cloned().unwrap_or_default();
+            let shared = self.get_shared_status(job.name).await;
+            statuses.push(match shared {
+                Some(shared) if shared.last_started_at >= local.last_started_at => shared,
+                _ => local,
+            });
+        }
+
+        statuses
+    }
+
+    // 获取锁并运行任务，失败时按指数退避重试
+    async fn execute(&self, name: &'static str) {
+        let job = match self.find(name) {
+            Some(job) => job,
+            None => return,
+        };
+        // 先在本地标记为运行中，避免同一实例重复运行
+        {
+            let mut statuses = self.status.lock().unwrap();
+            let status = statuses.entry(name).or_default();
+            if status.running {
+                log::debug!("任务{}仍在运行，跳过本次执行", name);
+                return;
+            }
+            status.running = true;
+        }
+
+        let lock_key = format!("jobs:lock:{}", name);
+        let locked = match self.acquire_lock(&lock_key, LOCK_TTL_SECS).await {
+            Ok(true) => true,
+            Ok(false) => {
+                log::debug!("任务{}正在其他实例运行，跳过本次执行", name);
+                false
+            }
+            Err(e) => {
+                log::warn!("获取任务{}的锁失败，跳过本次执行: {}", name, e);
+                false
+            }
+        };
+        if !locked {
+            if let Some(status) = self.status.lock().unwrap().get_mut(name) {
+                status.running = false;
+            }
+            return;
+        }
+
+        let started_at = chrono::Utc::now().timestamp();
+        self.update_status(name, |status| {
+            status.last_started_at = Some(started_at);
+            status.instance_id = Some(self.instance_id.clone());
+        })
+        .await;
+
+        let heartbeat = self.spawn_lock_heartbeat(&lock_key);
+        let max_retries = max_retries();
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            // 在单独的任务中运行，任务panic时按失败处理，不会让状态停留在运行中
+            let outcome = match tokio::spawn((job.run)()).await {
+                Ok(outcome) => outcome,
+                Err(e) => Err(ServiceError::ExternalService(format!("任务异常退出: {}", panic_message(e)))),
+            };
+            match outcome {
+                Ok(processed) => break Ok(processed),
+                Err(e) if attempts > max_retries => break Err(e),
+                Err(e) => {
+                    let backoff = retry_backoff_secs(attempts);
+                    log::warn!("任务{}第{}次运行失败，{}秒后重试: {}", name, attempts, backoff, e);
+                    tokio::time::sleep(Duration::from_secs(backoff)).await;
+                }
+            }
+        };
+
+        let finished_at = chrono::Utc::now().timestamp();
+        match &result {
+            Ok(processed) => log::info!("任务{}完成，处理{}项，耗时{}秒", name, processed, finished_at - started_at),
+            Err(e) => log::error!("任务{}在{}次尝试后失败: {}", name, attempts, e),
+        }
+        self.update_status(name, |status| {
+            status.running = false;
+            status.run_count += 1;
+            status.last_attempts = attempts;
+            status.last_finished_at = Some(finished_at);
+            match &result {
+                Ok(processed) => {
+                    status.consecutive_failures = 0;
+                    status.last_success_at = Some(finished_at);
+                    status.last_processed = Some(*processed);
+                    status.last_error = None;
+                }
+                Err(e) => {
+                    status.consecutive_failures += 1;
+                    status.last_error = Some(e.to_string());
+                }
+            }
+        })
+        .await;
+
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.abort();
+        }
+        self.release_lock(&lock_key).await;
+    }
+
+    // 任务运行期间定期续期锁，未配置Redis时不需要
+    fn spawn_lock_heartbeat(&self, key: &str) -> Option<tokio::task::JoinHandle<()>> {
+        let redis = self.redis.clone()?;
+        let key = key.to_string();
+        let instance_id = self.instance_id.clone();
+
+        Some(tokio::spawn(async move {
+            let period = Duration::from_secs(LOCK_TTL_SECS / 3);
+            loop {
+                tokio::time::sleep(period).await;
+                let renewed: Result<i64, String> = match redis.get_async_connection().await {
+                    Ok(mut con) => redis::Script::new(RENEW_LOCK_SCRIPT)
+                        .key(&key)
+                        .arg(&instance_id)
+                        .arg(LOCK_TTL_SECS)
+                        .invoke_async(&mut con)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match renewed {
+                    Ok(1) => {}
+                    Ok(_) => log::warn!("任务锁{}已失效，可能已被其他实例获取", key),
+                    Err(e) => log::warn!("续期任务锁{}失败: {}", key, e),
+                }
+            }
+        }))
+    }
+
+    // 每个间隔只由一个实例把任务放入队列
+    async fn schedule(&self, name: &'static str, interval_secs: u64) {
+        let schedule_key = format!("jobs:schedule:{}", name);
+        match self.acquire_lock(&schedule_key, interval_secs.saturating_sub(1).max(1)).await {
+            Ok(true) => {
+                if let Err(e) = self.enqueue(name).await {
+                    log::warn!("任务{}放入队列失败: {}", name, e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("调度任务{}失败: {}", name, e),
+        }
+    }
+
+    async fn enqueue(&self, name: &str) -> Result<(), ServiceError> {
+        let redis = self
+            .redis
+            .as_ref()
+            .ok_or_else(|| ServiceError::ExternalService("未配置Redis任务队列".into()))?;
+        let mut con = redis
+            .get_async_connection()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Redis连接失败: {}", e)))?;
+
+        redis::cmd("LPUSH")
+            .arg(QUEUE_KEY)
+            .arg(name)
+            .query_async::<_, ()>(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))
+    }
+
+    // 从Redis队列取出任务执行，每个任务单独运行以免长任务阻塞队列
+    async fn consume_queue(self: Arc<Self>) {
+        let redis = match &self.redis {
+            Some(redis) => redis.clone(),
+            None => return,
+        };
+
+        loop {
+            let mut con = match redis.get_async_connection().await {
+                Ok(con) => con,
+                Err(e) => {
+                    log::warn!("任务队列连接Redis失败: {}", e);
+                    tokio::time::sleep(Duration::from_secs(QUEUE_POLL_SECS)).await;
+                    continue;
+                }
+            };
+
+            loop {
+                let popped: redis::RedisResult<Option<(String, String)>> = redis::cmd("BRPOP")
+                    .arg(QUEUE_KEY)
+                    .arg(QUEUE_POLL_SECS)
+                    .query_async(&mut con)
+                    .await;
+
+                match popped {
+                    Ok(Some((_, name))) => match self.find(&name) {
+                        Some(job) => {
+                            let scheduler = self.clone();
+                            let name = job.name;
+                            tokio::spawn(async move {
+                                scheduler.execute(name).await;
+                            });
+                        }
+                        None => log::warn!("队列中的任务未注册: {}", name),
+                    },
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::warn!("读取任务队列失败: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // 未配置Redis时视为单实例运行，直接获得锁
+    async fn acquire_lock(&self, key: &str, ttl_secs: u64) -> Result<bool, String> {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return Ok(true),
+        };
+        let mut con = redis.get_async_connection().await.map_err(|e| e.to_string())?;
+
+        let result: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(&self.instance_id)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_secs)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.is_some())
+    }
+
+    async fn release_lock(&self, key: &str) {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return,
+        };
+        let mut con = match redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(e) => {
+                log::warn!("释放任务锁失败: {}", e);
+                return;
+            }
+        };
+
+        let result: redis::RedisResult<i64> = redis::Script::new(RELEASE_LOCK_SCRIPT)
+            .key(key)
+            .arg(&self.instance_id)
+            .invoke_async(&mut con)
+            .await;
+        if let Err(e) = result {
+            log::warn!("释放任务锁失败: {}", e);
+        }
+    }
+
+    // 更新本地状态，并同步到Redis供其他实例查询
+    async fn update_status<F>(&self, name: &'static str, update: F)
+    where
+        F: FnOnce(&mut JobStatus),
+    {
+        let status = {
+            let mut statuses = self.status.lock().unwrap();
+            let status = statuses.entry(name).or_default();
+            update(status);
+            status.clone()
+        };
+
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return,
+        };
+        let json = match serde_json::to_string(&status) {
+            Ok(json) => json,
+            Err(_) => return,
+        };
+        let mut con = match redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(_) => return,
+        };
+
+        let result: redis::RedisResult<()> = redis::cmd("SET")
+            .arg(format!("jobs:status:{}", name))
+            .arg(json)
+            .query_async(&mut con)
+            .await;
+        if let Err(e) = result {
+            log::warn!("保存任务{}状态失败: {}", name, e);
+        }
+    }
+
+    async fn get_shared_status(&self, name: &str) -> Option<JobStatus> {
+        let mut con = self.redis.as_ref()?.get_async_connection().await.ok()?;
+        let json: Option<String> = redis::cmd("GET")
+            .arg(format!("jobs:status:{}", name))
+            .query_async(&mut con)
+            .await
+            .ok()?;
+
+        serde_json::from_str(&json?).ok()
+    }
+
+    fn find(&self, name: &str) -> Option<&JobDefinition> {
+        self.jobs.iter().find(|job| job.name == name)
+    }
+}
+
+// 取出任务panic时的消息
+fn panic_message(error: tokio::task::JoinError) -> String {
+    match error.try_into_panic() {
+        Ok(panic) => panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+        Err(error) => error.to_string(),
+    }
+}
+
+// 第n次失败后的等待时间：base * 2^(n-1)，不超过上限
+fn retry_backoff_secs(attempt: u32) -> u64 {
+    let base = env::var("JOB_RETRY_BASE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_RETRY_BASE_SECS);
+
+    base.saturating_mul(1u64 << attempt.saturating_sub(1).min(16))
+        .min(MAX_RETRY_BACKOFF_SECS)
+}
+
+fn max_retries() -> u32 {
+    env::var("JOB_MAX_RETRIES")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_MAX_RETRIES)
+}
@@ -0,0 +1,411 @@
+use crate::models::leaderboard::{LeaderboardEntry, LeaderboardRank};
+use crate::models::PaginatedResponse;
+use crate::services::asset_service::normalize_address;
+use crate::utils::error::ServiceError;
+use crate::utils::pagination::Pagination;
+use rbatis::RBatis;
+use redis::Client as RedisClient;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::Arc;
+
+// 榜单
+pub const BOARD_VALUE: &str = "value"; // 资产总价值（美元）
+pub const BOARD_PNL_7D: &str = "pnl_7d"; // 7天资产涨跌（百分比）
+pub const BOARD_PNL_30D: &str = "pnl_30d"; // 30天资产涨跌（百分比）
+pub const BOARD_TRADES: &str = "trades"; // 已验证交易数
+pub const BOARD_ENGAGEMENT: &str = "engagement"; // 帖子互动数（点赞、评论、转发）
+pub const BOARDS: [&str; 5] = [BOARD_VALUE, BOARD_PNL_7D, BOARD_PNL_30D, BOARD_TRADES, BOARD_ENGAGEMENT];
+
+// 排行榜计算间隔（秒），通过 LEADERBOARD_INTERVAL_SECS 配置
+const DEFAULT_INTERVAL_SECS: u64 = 900;
+// 计算涨跌时起始资产需达到的最低价值，避免小额账户的百分比失真
+const MIN_PNL_BASE_USD: f64 = 100.0;
+const DAY_SECS: i64 = 24 * 3600;
+
+// 用户得分
+#[derive(Debug, Deserialize)]
+struct UserScore {
+    user_id: String,
+    score: f64,
+}
+
+// 用户的登录钱包
+#[derive(Debug, Deserialize)]
+struct UserWalletRow {
+    user_id: String,
+    wallet_address: String,
+}
+
+// 钱包在某次快照时的总价值
+#[derive(Debug, Deserialize)]
+struct WalletValue {
+    wallet_address: String,
+    snapshot_at: i64,
+    value_usd: f64,
+}
+
+// 排行榜展示的用户信息
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    user_id: String,
+    username: Option<String>,
+    nickname: Option<String>,
+    avatar_ipfs_cid: Option<String>,
+}
+
+/// 排行榜服务，定期计算各榜单并写入Redis有序集合
+pub struct LeaderboardService {
+    db: Arc<RBatis>,
+    redis: Arc<RedisClient>,
+}
+
+impl LeaderboardService {
+    pub fn new(db: Arc<RBatis>, redis: Arc<RedisClient>) -> Self {
+        Self { db, redis }
+    }
+
+    /// 重新计算所有榜单，单个榜单失败不影响其他榜单，返回成功的榜单数
+    pub async fn update_all(&self) -> Result<usize, ServiceError> {
+        let mut count = 0;
+        for board in BOARDS {
+            match self.update_board(board).await {
+                Ok(_) => count += 1,
+                Err(e) => log::error!("计算排行榜{}失败: {}", board, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// 重新计算指定榜单
+    pub async fn update_board(&self, board: &str) -> Result<usize, ServiceError> {
+        let scores = match board {
+            BOARD_VALUE => self.value_scores().await?,
+            BOARD_PNL_7D => self.pnl_scores(7 * DAY_SECS).await?,
+            BOARD_PNL_30D => self.pnl_scores(30 * DAY_SECS).await?,
+            BOARD_TRADES => {
+                self.query_scores(
+                    "SELECT user_id::text AS user_id, COUNT(*)::float8 AS score FROM trade_proofs \
+                     WHERE status = 'verified' GROUP BY user_id",
+                )
+                .await?
+            }
+            BOARD_ENGAGEMENT => {
+                self.query_scores(
+                    "SELECT user_id::text AS user_id, SUM(like_count + comment_count + repost_count)::float8 AS score \
+                     FROM posts WHERE is_hidden = FALSE GROUP BY user_id",
+                )
+                .await?
+            }
+            _ => return Err(ServiceError::BadRequest("不支持的排行榜".into())),
+        };
+
+        let opted_out = self.get_opted_out().await?;
+        let scores: Vec<UserScore> = scores
+            .into_iter()
+            .filter(|s| s.score.is_finite() && !opted_out.contains(&s.user_id))
+            .collect();
+
+        self.write_board(board, &scores).await?;
+
+        Ok(scores.len())
+    }
+
+    /// 分页获取榜单
+    pub async fn get_board(
+        &self,
+        board: &str,
+        pagination: &Pagination,
+    ) -> Result<PaginatedResponse<LeaderboardEntry>, ServiceError> {
+        check_board(board)?;
+        let mut con = self.connection().await?;
+        let key = board_key(board);
+
+        let start = pagination.offset();
+        let stop = start + pagination.limit() - 1;
+        let members: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
+            .arg(&key)
+            .arg(start)
+            .arg(stop)
+            .arg("WITHSCORES")
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+        let total: i64 = redis::cmd("ZCARD")
+            .arg(&key)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+
+        let user_ids: Vec<&str> = members.iter().map(|(user_id, _)| user_id.as_str()).collect();
+        let mut users = self.get_users_info(&user_ids).await?;
+
+        let mut entries = Vec::with_capacity(members.len());
+        for (index, (user_id, score)) in members.into_iter().enumerate() {
+            let info = users.remove(&user_id);
+            entries.push(LeaderboardEntry {
+                rank: start + index as i64 + 1,
+                username: info.as_ref().and_then(|i| i.username.clone()),
+                nickname: info.as_ref().and_then(|i| i.nickname.clone()),
+                avatar_ipfs_cid: info.and_then(|i| i.avatar_ipfs_cid),
+                user_id,
+                score,
+            });
+        }
+
+        Ok(pagination.paginate(entries, total))
+    }
+
+    /// 获取用户在榜单中的排名
+    pub async fn get_rank(&self, board: &str, user_id: &str) -> Result<LeaderboardRank, ServiceError> {
+        check_board(board)?;
+        let mut con = self.connection().await?;
+        let key = board_key(board);
+
+        let rank: Option<i64> = redis::cmd("ZREVRANK")
+            .arg(&key)
+            .arg(user_id)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+        let score: Option<f64> = redis::cmd("ZSCORE")
+            .arg(&key)
+            .arg(user_id)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+        let total: i64 = redis::cmd("ZCARD")
+            .arg(&key)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+        let updated_at: Option<i64> = redis::cmd("GET")
+            .arg(updated_at_key(board))
+            .query_async(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+
+        Ok(LeaderboardRank {
+            board: board.to_string(),
+            rank: rank.map(|r| r + 1),
+            score,
+            total,
+            updated_at,
+        })
+    }
+
+    /// 退出排行榜，立即从所有榜单中移除
+    pub async fn opt_out(&self, user_id: &str) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "INSERT INTO leaderboard_opt_outs (user_id) VALUES (?) ON CONFLICT (user_id) DO NOTHING",
+                vec![rbs::to_value!(user_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut con = self.connection().await?;
+        let mut pipe = redis::pipe();
+        for board in BOARDS {
+            pipe.cmd("ZREM").arg(board_key(board)).arg(user_id).ignore();
+        }
+        pipe.query_async::<_, ()>(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 重新加入排行榜，下次计算时生效
+    pub async fn opt_in(&self, user_id: &str) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "DELETE FROM leaderboard_opt_outs WHERE user_id = ?",
+                vec![rbs::to_value!(user_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // 各用户登录钱包最近一次快照的总价值
+    async fn value_scores(&self) -> Result<Vec<UserScore>, ServiceError> {
+        let latest: Vec<WalletValue> = self
+            .db
+            .query_decode(
+                "SELECT s.wallet_address, s.snapshot_at, SUM(COALESCE(s.value_usd, 0)) AS value_usd \
+                 FROM portfolio_snapshots s \
+                 WHERE s.snapshot_at = (SELECT MAX(snapshot_at) FROM portfolio_snapshots WHERE wallet_address = s.wallet_address) \
+                 GROUP BY s.wallet_address, s.snapshot_at",
+                vec![],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let values: HashMap<String, f64> = latest
+            .into_iter()
+            .map(|v| (v.wallet_address, v.value_usd))
+            .collect();
+
+        self.scores_by_wallet(&values).await
+    }
+
+    // 各用户登录钱包在窗口期内的资产涨跌百分比
+    async fn pnl_scores(&self, window_secs: i64) -> Result<Vec<UserScore>, ServiceError> {
+        let from = chrono::Utc::now().timestamp() - window_secs;
+        let series: Vec<WalletValue> = self
+            .db
+            .query_decode(
+                "SELECT wallet_address, snapshot_at, SUM(COALESCE(value_usd, 0)) AS value_usd \
+                 FROM portfolio_snapshots WHERE snapshot_at >= ? \
+                 GROUP BY wallet_address, snapshot_at ORDER BY snapshot_at",
+                vec![rbs::to_value!(from)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        // 按时间顺序，记录每个钱包的第一个和最后一个值
+        let mut ranges: HashMap<String, (f64, f64)> = HashMap::new();
+        for point in series {
+            ranges
+                .entry(point.wallet_address)
+                .and_modify(|(_, end)| *end = point.value_usd)
+                .or_insert((point.value_usd, point.value_usd));
+        }
+
+        let changes: HashMap<String, f64> = ranges
+            .into_iter()
+            .filter(|(_, (start, _))| *start >= MIN_PNL_BASE_USD)
+            .map(|(wallet, (start, end))| (wallet, ((end - start) / start * 10000.0).round() / 100.0))
+            .collect();
+
+        self.scores_by_wallet(&changes).await
+    }
+
+    // 将按钱包统计的得分映射到用户
+    async fn scores_by_wallet(&self, values: &HashMap<String, f64>) -> Result<Vec<UserScore>, ServiceError> {
+        let users: Vec<UserWalletRow> = self
+            .db
+            .query_decode("SELECT id::text AS user_id, wallet_address FROM users", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(users
+            .into_iter()
+            .filter_map(|user| {
+                let score = *values.get(&normalize_address(&user.wallet_address))?;
+                Some(UserScore { user_id: user.user_id, score })
+            })
+            .collect())
+    }
+
+    async fn query_scores(&self, sql: &str) -> Result<Vec<UserScore>, ServiceError> {
+        self.db
+            .query_decode(sql, vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+
+    async fn get_opted_out(&self) -> Result<HashSet<String>, ServiceError> {
+        #[derive(Debug, Deserialize)]
+        struct OptOut {
+            user_id: String,
+        }
+
+        let rows: Vec<OptOut> = self
+            .db
+            .query_decode("SELECT user_id FROM leaderboard_opt_outs", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|r| r.user_id).collect())
+    }
+
+    // 一次查询当前页所有用户的展示信息
+    async fn get_users_info(&self, user_ids: &[&str]) -> Result<HashMap<String, UserInfo>, ServiceError> {
+        if user_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let users: Vec<UserInfo> = self
+            .db
+            .query_decode(
+                "SELECT id::text AS user_id, username, nickname, avatar_ipfs_cid FROM users \
+                 WHERE id = ANY(string_to_array(?, ',')::uuid[])",
+                vec![rbs::to_value!(user_ids.join(","))],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(users.into_iter().map(|u| (u.user_id.clone(), u)).collect())
+    }
+
+    // 先写入临时键再整体替换，避免读取到计算到一半的榜单
+    async fn write_board(&self, board: &str, scores: &[UserScore]) -> Result<(), ServiceError> {
+        let mut con = self.connection().await?;
+        let key = board_key(board);
+        let tmp_key = format!("{}:tmp", key);
+
+        let mut pipe = redis::pipe();
+        pipe.atomic().cmd("DEL").arg(&tmp_key).ignore();
+        for chunk in scores.chunks(500) {
+            let mut zadd = redis::cmd("ZADD");
+            zadd.arg(&tmp_key);
+            for score in chunk {
+                zadd.arg(score.score).arg(&score.user_id);
+            }
+            pipe.add_command(zadd).ignore();
+        }
+        if scores.is_empty() {
+            pipe.cmd("DEL").arg(&key).ignore();
+        } else {
+            pipe.cmd("RENAME").arg(&tmp_key).arg(&key).ignore();
+        }
+        pipe.cmd("SET")
+            .arg(updated_at_key(board))
+            .arg(chrono::Utc::now().timestamp())
+            .ignore();
+
+        pipe.query_async::<_, ()>(&mut con)
+            .await
+            .map_err(|e| ServiceError::ExternalService(e.to_string()))
+    }
+
+    async fn connection(&self) -> Result<redis::aio::Connection, ServiceError> {
+        self.redis
+            .get_async_connection()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Redis连接失败: {}", e)))
+    }
+}
+
+/// 排行榜计算间隔（秒）
+pub fn update_interval_secs() -> u64 {
+    env::var("LEADERBOARD_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_INTERVAL_SECS)
+}
+
+fn check_board(board: &str) -> Result<(), ServiceError> {
+    if BOARDS.contains(&board) {
+        Ok(())
+    } else {
+        Err(ServiceError::BadRequest(format!(
+            "不支持的排行榜，可选 {}",
+            BOARDS.join("、")
+        )))
+    }
+}
+
+fn board_key(board: &str) -> String {
+    format!("leaderboard:{}", board)
+}
+
+fn updated_at_key(board: &str) -> String {
+    format!("leaderboard:{}:updated_at", board)
+}
@@ -0,0 +1,129 @@
+use crate::models::rbatis_entities::MediaEntity;
+use crate::services::storage_service::StorageService;
+use crate::utils::error::ServiceError;
+use crate::utils::media;
+use rbatis::rbdc::datetime::DateTime;
+use rbatis::RBatis;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// 每个帖子最多引用的媒体数量
+pub const MAX_MEDIA_PER_POST: usize = 9;
+// 缩略图统一编码为JPEG
+const THUMBNAIL_MIME_TYPE: &str = "image/jpeg";
+
+/// 媒体服务，处理图片、GIF和短视频的上传
+pub struct MediaService {
+    db: Arc<RBatis>,
+    storage_service: Arc<StorageService>,
+}
+
+impl MediaService {
+    pub fn new(db: Arc<RBatis>, storage_service: Arc<StorageService>) -> Self {
+        Self {
+            db,
+            storage_service,
+        }
+    }
+
+    /// 上传单个媒体文件：校验类型和大小，去除元数据，生成缩略图并上传到媒体存储
+    pub async fn upload_media(
+        &self,
+        user_id: String,
+        data: Vec<u8>,
+    ) -> Result<MediaEntity, ServiceError> {
+        let kind = media::validate_media(&data).map_err(ServiceError::BadRequest)?;
+
+        let cleaned = media::strip_metadata(kind, &data).map_err(ServiceError::BadRequest)?;
+        let dimensions = media::image_dimensions(kind, &cleaned);
+
+        // 缩略图生成失败不影响原图上传
+        let thumbnail = media::generate_thumbnail(kind, &cleaned).unwrap_or_else(|e| {
+            log::warn!("生成缩略图失败: {}", e);
+            None
+        });
+
+        let ipfs_cid = self.storage_service.upload_media(&cleaned, kind.mime_type()).await?;
+        let thumbnail_ipfs_cid = match thumbnail {
+            Some(thumb) => Some(self.storage_service.upload_media(&thumb, THUMBNAIL_MIME_TYPE).await?),
+            None => None,
+        };
+
+        let media_entity = MediaEntity {
+            id: Uuid::new_v4(),
+            user_id,
+            media_type: kind.category().to_string(),
+            mime_type: kind.mime_type().to_string(),
+            size_bytes: cleaned.len() as i64,
+            width: dimensions.map(|(w, _)| w as i32),
+            height: dimensions.map(|(_, h)| h as i32),
+            ipfs_cid,
+            thumbnail_ipfs_cid,
+            created_at: DateTime::now(),
+        };
+
+        MediaEntity::insert(self.db.as_ref(), &media_entity)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(media_entity)
+    }
+
+    /// 获取媒体详情
+    pub async fn get_media(&self, media_id: &str) -> Result<MediaEntity, ServiceError> {
+        let media_uuid = Uuid::parse_str(media_id)
+            .map_err(|_| ServiceError::BadRequest("无效的媒体ID".into()))?;
+
+        let media: Vec<MediaEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM media WHERE id = ?::uuid",
+                vec![rbs::to_value!(media_uuid.to_string())],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        media
+            .into_iter()
+            .next()
+            .ok_or(ServiceError::NotFound("媒体不存在".into()))
+    }
+
+    /// 解析帖子引用的媒体，只允许引用自己上传的媒体，返回顺序与请求一致
+    /// inline_count为请求中直接附带的图片数量，与引用的媒体一起计入上限
+    pub async fn resolve_post_media(
+        &self,
+        user_id: &str,
+        media_ids: &[String],
+        inline_count: usize,
+    ) -> Result<Vec<MediaEntity>, ServiceError> {
+        if media_ids.len() + inline_count > MAX_MEDIA_PER_POST {
+            return Err(ServiceError::BadRequest(format!(
+                "每个帖子最多包含{}个媒体文件",
+                MAX_MEDIA_PER_POST
+            )));
+        }
+
+        let mut media_list = Vec::with_capacity(media_ids.len());
+        for media_id in media_ids {
+            let media = self.get_media(media_id).await?;
+            if media.user_id != user_id {
+                return Err(ServiceError::BadRequest("不能引用他人上传的媒体".into()));
+            }
+            media_list.push(media);
+        }
+
+        Ok(media_list)
+    }
+
+    /// 生成媒体访问URL
+    pub fn get_media_urls(&self, media: &MediaEntity) -> (String, Option<String>) {
+        (
+            self.storage_service.media_url(&media.ipfs_cid),
+            media
+                .thumbnail_ipfs_cid
+                .as_ref()
+                .map(|cid| self.storage_service.media_url(cid)),
+        )
+    }
+}
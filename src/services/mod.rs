@@ -2,4 +2,14 @@
 pub mod user_service;
 pub mod asset_service;
 pub mod content_service;
-pub mod storage_service; 
\ No newline at end of file
+pub mod storage_service;
+pub mod media_service;
+pub mod trade_service;
+pub mod token_discovery_service;
+pub mod nft_service;
+pub mod price_service;
+pub mod portfolio_service;
+pub mod badge_service;
+pub mod space_service;
+pub mod leaderboard_service;
+pub mod job_service;
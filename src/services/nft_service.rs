@@ -0,0 +1,442 @@
+use crate::blockchain::chains::{self, EvmChain};
+use crate::blockchain::ethereum::{self, NftCandidate, NFT_STANDARD_ERC1155, NFT_STANDARD_ERC721};
+use crate::blockchain::solana::{self, SOLANA_CHAIN_ID};
+use crate::models::asset::NFT;
+use crate::models::rbatis_entities::{NftAssetEntity, NftScanCheckpointEntity};
+use crate::utils::error::ServiceError;
+use crate::utils::nft_metadata;
+use ethers::types::{Address, U256};
+use rbatis::RBatis;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// 单次发现任务最多扫描的区块数，未扫描完的部分在下次刷新时继续
+const MAX_BLOCKS_PER_RUN: u64 = 500_000;
+// 单次发现任务最多拉取的元数据数量，其余的在下次刷新时补全
+const MAX_METADATA_FETCHES_PER_RUN: usize = 50;
+
+/// NFT服务，发现钱包持有的ERC721/ERC1155和Metaplex NFT并保存元数据
+pub struct NftService {
+    db: Arc<RBatis>,
+}
+
+impl NftService {
+    pub fn new(db: Arc<RBatis>) -> Self {
+        Self { db }
+    }
+
+    /// 获取钱包持有的NFT，wallet_chain为SOL或EVM链简称，不指定时按地址格式判断
+    pub async fn get_nfts(&self, wallet_address: &str, wallet_chain: Option<&str>) -> Result<Vec<NFT>, ServiceError> {
+        match wallet_chain {
+            Some(chain) if chain.eq_ignore_ascii_case("SOL") => self.discover_solana_nfts(wallet_address).await,
+            Some(chain) => {
+                let chain = chains::get_chain_by_code(chain)
+                    .ok_or_else(|| ServiceError::BadRequest("不支持的链类型".into()))?;
+                self.discover_nfts(chain.chain_id, wallet_address).await
+            }
+            None if wallet_address.starts_with("0x") => self.get_evm_nfts(wallet_address).await,
+            None => self.discover_solana_nfts(wallet_address).await,
+        }
+    }
+
+    /// 获取Solana钱包持有的Metaplex NFT，并同步保存的记录
+    pub async fn discover_solana_nfts(&self, wallet_address: &str) -> Result<Vec<NFT>, ServiceError> {
+        let nfts = solana::get_solana_nfts(wallet_address)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+        let stored = self.get_stored_nfts(SOLANA_CHAIN_ID, wallet_address).await?;
+
+        for nft in &nfts {
+            self.save_nft(&nft_to_entity(wallet_address, SOLANA_CHAIN_ID, nft)).await?;
+        }
+        for entity in &stored {
+            if !nfts.iter().any(|nft| nft.token_id == entity.token_id) {
+                self.delete_nft(entity).await?;
+            }
+        }
+
+        Ok(nfts)
+    }
+
+    /// 汇总钱包在所有已配置EVM链上持有的NFT，单条链失败不影响其他链
+    pub async fn get_evm_nfts(&self, wallet_address: &str) -> Result<Vec<NFT>, ServiceError> {
+        let mut nfts = Vec::new();
+
+        for chain in chains::configured_chains() {
+            match self.discover_nfts(chain.chain_id, wallet_address).await {
+                Ok(chain_nfts) => nfts.extend(chain_nfts),
+                Err(e) => log::warn!("获取{} NFT失败: {}", chain.name, e),
+            }
+        }
+
+        Ok(nfts)
+    }
+
+    /// 扫描新的转账日志，重新确认持有情况并补全元数据，返回当前持有的NFT
+    pub async fn discover_nfts(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<NFT>, ServiceError> {
+        let chain = chains::require_chain(chain_id).map_err(ServiceError::BadRequest)?;
+        let wallet_address = wallet_address.to_lowercase();
+
+        // 已保存的NFT与新扫描到的NFT一起重新确认持有情况
+        let stored = self.get_stored_nfts(chain_id, &wallet_address).await?;
+        let mut candidates: Vec<NftCandidate> = stored.iter().filter_map(entity_to_candidate).collect();
+        for candidate in self.scan_new_transfers(chain, &wallet_address).await? {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        let owned = ethereum::get_nft_balances(chain_id, &wallet_address, &candidates)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+
+        // 已有图片的NFT沿用保存的元数据，其余的重新拉取
+        let stored_by_key: HashMap<(String, String), &NftAssetEntity> = stored
+            .iter()
+            .map(|e| ((e.contract_address.clone(), e.token_id.clone()), e))
+            .collect();
+        let missing: Vec<NftCandidate> = owned
+            .iter()
+            .map(|(candidate, _)| candidate)
+            .filter(|c| {
+                stored_by_key
+                    .get(&candidate_key(c))
+                    .is_none_or(|e| e.image_url.is_none())
+            })
+            .take(MAX_METADATA_FETCHES_PER_RUN)
+            .cloned()
+            .collect();
+        let fetched = self.fetch_metadata(chain_id, &missing).await;
+
+        let mut collection_names: HashMap<Address, Option<String>> = HashMap::new();
+        let mut entities = Vec::with_capacity(owned.len());
+        for (candidate, balance) in &owned {
+            let key = candidate_key(candidate);
+            let mut entity = match fetched.get(&key) {
+                Some(entity) => entity.clone(),
+                None => match stored_by_key.get(&key) {
+                    Some(entity) => (*entity).clone(),
+                    None => new_entity(chain_id, candidate, None, None),
+                },
+            };
+            entity.wallet_address = wallet_address.clone();
+            entity.balance = balance.to_string();
+
+            if entity.collection_name.is_none() {
+                if let Entry::Vacant(entry) = collection_names.entry(candidate.contract_address) {
+                    let name = ethereum::get_nft_collection_name(chain_id, candidate.contract_address)
+                        .await
+                        .ok()
+                        .filter(|name| !name.is_empty());
+                    entry.insert(name);
+                }
+                entity.collection_name = collection_names[&candidate.contract_address].clone();
+            }
+            if entity.name.is_empty() {
+                entity.name = format!(
+                    "{} #{}",
+                    entity.collection_name.as_deref().unwrap_or("NFT"),
+                    entity.token_id
+                );
+            }
+
+            self.save_nft(&entity).await?;
+            entities.push(entity);
+        }
+
+        // 删除已转出的NFT
+        for entity in &stored {
+            let still_owned = entities.iter().any(|e| {
+                e.contract_address == entity.contract_address && e.token_id == entity.token_id
+            });
+            if !still_owned {
+                self.delete_nft(entity).await?;
+            }
+        }
+
+        Ok(entities.iter().map(|e| entity_to_nft(chain.code, e)).collect())
+    }
+
+    /// 获取已保存的NFT
+    pub async fn get_stored_nfts(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<NftAssetEntity>, ServiceError> {
+        self.db
+            .query_decode(
+                "SELECT * FROM nft_assets WHERE wallet_address = ? AND chain_id = ?",
+                vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// 获取已保存的NFT，不访问链上数据
+    pub async fn get_saved_nfts(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<NFT>, ServiceError> {
+        let chain_code = if chain_id == SOLANA_CHAIN_ID {
+            "SOL"
+        } else {
+            chains::require_chain(chain_id).map_err(ServiceError::BadRequest)?.code
+        };
+        let entities = self.get_stored_nfts(chain_id, wallet_address).await?;
+
+        Ok(entities.iter().map(|e| entity_to_nft(chain_code, e)).collect())
+    }
+
+    // 从上次的检查点继续扫描NFT转账日志
+    async fn scan_new_transfers(&self, chain: &EvmChain, wallet_address: &str) -> Result<Vec<NftCandidate>, ServiceError> {
+        let latest_block = ethereum::get_block_number(chain.chain_id)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+
+        let checkpoint = self.get_checkpoint(chain.chain_id, wallet_address).await?;
+        let from_block = match &checkpoint {
+            Some(checkpoint) => checkpoint.last_scanned_block as u64 + 1,
+            None => discovery_start_block(chain),
+        };
+        if from_block > latest_block {
+            return Ok(Vec::new());
+        }
+
+        let to_block = latest_block.min(from_block + MAX_BLOCKS_PER_RUN - 1);
+        let candidates = ethereum::scan_nft_transfers(chain.chain_id, wallet_address, from_block, to_block)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+        self.save_checkpoint(chain.chain_id, wallet_address, to_block, checkpoint.is_some())
+            .await?;
+
+        Ok(candidates)
+    }
+
+    // 拉取tokenURI和元数据，失败的NFT只保留基础信息
+    async fn fetch_metadata(
+        &self,
+        chain_id: i32,
+        candidates: &[NftCandidate],
+    ) -> HashMap<(String, String), NftAssetEntity> {
+        let uris = match ethereum::get_nft_token_uris(chain_id, candidates).await {
+            Ok(uris) => uris,
+            Err(e) => {
+                log::warn!("获取NFT元数据URI失败: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        let mut entities = HashMap::new();
+        for (candidate, uri) in candidates.iter().zip(uris) {
+            let metadata = match &uri {
+                Some(uri) => match nft_metadata::fetch_metadata(uri).await {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        log::warn!("获取NFT元数据失败 {}: {}", uri, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            entities.insert(candidate_key(candidate), new_entity(chain_id, candidate, uri, metadata));
+        }
+
+        entities
+    }
+
+    async fn save_nft(&self, entity: &NftAssetEntity) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "INSERT INTO nft_assets (wallet_address, chain_id, contract_address, token_id, token_type, balance, \
+                 name, collection_name, collection_address, collection_verified, description, image_url, \
+                 metadata_url, attributes, created_at, updated_at) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW(), NOW()) \
+                 ON CONFLICT (wallet_address, chain_id, contract_address, token_id) DO UPDATE SET \
+                 balance = EXCLUDED.balance, name = EXCLUDED.name, collection_name = EXCLUDED.collection_name, \
+                 collection_address = EXCLUDED.collection_address, collection_verified = EXCLUDED.collection_verified, \
+                 description = EXCLUDED.description, image_url = EXCLUDED.image_url, \
+                 metadata_url = EXCLUDED.metadata_url, attributes = EXCLUDED.attributes, updated_at = NOW()",
+                vec![
+                    rbs::to_value!(&entity.wallet_address),
+                    rbs::to_value!(entity.chain_id),
+                    rbs::to_value!(&entity.contract_address),
+                    rbs::to_value!(&entity.token_id),
+                    rbs::to_value!(&entity.token_type),
+                    rbs::to_value!(&entity.balance),
+                    rbs::to_value!(&entity.name),
+                    rbs::to_value!(&entity.collection_name),
+                    rbs::to_value!(&entity.collection_address),
+                    rbs::to_value!(entity.collection_verified),
+                    rbs::to_value!(&entity.description),
+                    rbs::to_value!(&entity.image_url),
+                    rbs::to_value!(&entity.metadata_url),
+                    rbs::to_value!(&entity.attributes),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_nft(&self, entity: &NftAssetEntity) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "DELETE FROM nft_assets WHERE wallet_address = ? AND chain_id = ? AND contract_address = ? AND token_id = ?",
+                vec![
+                    rbs::to_value!(&entity.wallet_address),
+                    rbs::to_value!(entity.chain_id),
+                    rbs::to_value!(&entity.contract_address),
+                    rbs::to_value!(&entity.token_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_checkpoint(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+    ) -> Result<Option<NftScanCheckpointEntity>, ServiceError> {
+        let checkpoints: Vec<NftScanCheckpointEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM nft_scan_checkpoints WHERE wallet_address = ? AND chain_id = ?",
+                vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(checkpoints.into_iter().next())
+    }
+
+    async fn save_checkpoint(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+        last_scanned_block: u64,
+        exists: bool,
+    ) -> Result<(), ServiceError> {
+        let sql = if exists {
+            "UPDATE nft_scan_checkpoints SET last_scanned_block = ?, updated_at = NOW() \
+             WHERE wallet_address = ? AND chain_id = ?"
+        } else {
+            "INSERT INTO nft_scan_checkpoints (last_scanned_block, wallet_address, chain_id, updated_at) \
+             VALUES (?, ?, ?, NOW())"
+        };
+
+        self.db
+            .exec(
+                sql,
+                vec![
+                    rbs::to_value!(last_scanned_block as i64),
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+fn candidate_key(candidate: &NftCandidate) -> (String, String) {
+    (
+        format!("0x{}", hex::encode(candidate.contract_address.as_bytes())),
+        candidate.token_id.to_string(),
+    )
+}
+
+fn entity_to_candidate(entity: &NftAssetEntity) -> Option<NftCandidate> {
+    let standard = match entity.token_type.as_str() {
+        NFT_STANDARD_ERC721 => NFT_STANDARD_ERC721,
+        NFT_STANDARD_ERC1155 => NFT_STANDARD_ERC1155,
+        _ => return None,
+    };
+
+    Some(NftCandidate {
+        contract_address: Address::from_str(entity.contract_address.trim_start_matches("0x")).ok()?,
+        token_id: U256::from_dec_str(&entity.token_id).ok()?,
+        standard,
+    })
+}
+
+fn new_entity(
+    chain_id: i32,
+    candidate: &NftCandidate,
+    metadata_url: Option<String>,
+    metadata: Option<nft_metadata::NftMetadata>,
+) -> NftAssetEntity {
+    let (contract_address, token_id) = candidate_key(candidate);
+    let metadata = metadata.unwrap_or_default();
+
+    NftAssetEntity {
+        wallet_address: String::new(),
+        chain_id,
+        contract_address,
+        token_id,
+        token_type: candidate.standard.to_string(),
+        balance: "1".to_string(),
+        name: metadata.name.unwrap_or_default(),
+        collection_name: None,
+        collection_address: None,
+        collection_verified: false,
+        description: metadata.description,
+        image_url: metadata.image_url,
+        // data: URI可能很长，只保存可访问的地址
+        metadata_url: metadata_url.filter(|uri| !uri.starts_with("data:")),
+        attributes: metadata.attributes.map(|a| a.to_string()),
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn nft_to_entity(wallet_address: &str, chain_id: i32, nft: &NFT) -> NftAssetEntity {
+    NftAssetEntity {
+        wallet_address: wallet_address.to_string(),
+        chain_id,
+        contract_address: nft.contract_address.clone(),
+        token_id: nft.token_id.clone(),
+        token_type: nft.token_type.clone(),
+        balance: "1".to_string(),
+        name: nft.name.clone(),
+        collection_name: nft.collection_name.clone(),
+        collection_address: nft.collection_address.clone(),
+        collection_verified: nft.collection_verified,
+        description: nft.description.clone(),
+        image_url: nft.image_url.clone(),
+        metadata_url: nft.metadata_url.clone(),
+        attributes: nft.attributes.as_ref().map(|a| a.to_string()),
+        created_at: None,
+        updated_at: None,
+    }
+}
+
+fn entity_to_nft(chain_code: &str, entity: &NftAssetEntity) -> NFT {
+    NFT {
+        chain: chain_code.to_string(),
+        contract_address: entity.contract_address.clone(),
+        token_id: entity.token_id.clone(),
+        name: entity.name.clone(),
+        collection_name: entity.collection_name.clone(),
+        description: entity.description.clone(),
+        image_url: entity.image_url.clone(),
+        metadata_url: entity.metadata_url.clone(),
+        floor_price_usd: None,
+        token_type: entity.token_type.clone(),
+        collection_address: entity.collection_address.clone(),
+        collection_verified: entity.collection_verified,
+        attributes: entity
+            .attributes
+            .as_deref()
+            .and_then(|a| serde_json::from_str(a).ok()),
+    }
+}
+
+// 新钱包开始扫描的区块，与代币发现共用 {链简称}_DISCOVERY_START_BLOCK 配置
+fn discovery_start_block(chain: &EvmChain) -> u64 {
+    env::var(format!("{}_DISCOVERY_START_BLOCK", chain.code))
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,383 @@
+use crate::blockchain::chains;
+use crate::blockchain::solana::SOLANA_CHAIN_ID;
+use crate::models::asset::{AllocationEntry, AssetsResponse, PortfolioHistory, PortfolioPnl, PortfolioPoint};
+use crate::models::rbatis_entities::PortfolioSnapshotEntity;
+use crate::services::asset_service::{chain_id_by_code, normalize_address, wallet_scope, AssetService};
+use crate::utils::error::ServiceError;
+use chrono::NaiveDate;
+use rbatis::RBatis;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// 快照间隔（秒），通过 PORTFOLIO_SNAPSHOT_INTERVAL_SECS 配置
+const DEFAULT_SNAPSHOT_INTERVAL_SECS: u64 = 3600;
+// 钱包没有任何资产时写入的零价值快照的资产类型，保证走势中出现清仓后的零值点
+const EMPTY_SNAPSHOT_ASSET_TYPE: &str = "empty";
+// 走势图最多返回的点数，超出时按时间分桶取每个桶的最后一个点
+const MAX_SERIES_POINTS: i64 = 200;
+
+// 已注册用户的钱包
+#[derive(Debug, Deserialize)]
+struct UserWallet {
+    wallet_address: String,
+    wallet_chain: String,
+}
+
+/// 资产快照服务，定期记录注册用户的资产，提供资产走势、分布和盈亏统计
+pub struct PortfolioService {
+    db: Arc<RBatis>,
+    asset_service: Arc<AssetService>,
+}
+
+impl PortfolioService {
+    pub fn new(db: Arc<RBatis>, asset_service: Arc<AssetService>) -> Self {
+        Self { db, asset_service }
+    }
+
+    /// 为所有注册用户的钱包生成快照，单个钱包失败不影响其他钱包
+    pub async fn snapshot_all(&self) -> Result<usize, ServiceError> {
+        let wallets: Vec<UserWallet> = self
+            .db
+            .query_decode("SELECT DISTINCT wallet_address, wallet_chain FROM users", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        // 同一轮快照使用相同的时间，按快照间隔对齐
+        let interval = snapshot_interval_secs() as i64;
+        let now = chrono::Utc::now().timestamp();
+        let snapshot_at = now - now % interval;
+
+        let mut count = 0;
+        for wallet in &wallets {
+            match self
+                .snapshot_wallet(&wallet.wallet_address, &wallet.wallet_chain, snapshot_at)
+                .await
+            {
+                Ok(()) => count += 1,
+                Err(e) => log::warn!("钱包{}资产快照失败: {}", wallet.wallet_address, e),
+            }
+        }
+
+        log::info!("完成资产快照: {}/{}", count, wallets.len());
+        Ok(count)
+    }
+
+    /// 刷新钱包资产并记录快照
+    pub async fn snapshot_wallet(
+        &self,
+        wallet_address: &str,
+        wallet_chain: &str,
+        snapshot_at: i64,
+    ) -> Result<(), ServiceError> {
+        let wallet_address = normalize_address(wallet_address);
+        let assets = self
+            .asset_service
+            .refresh_assets(&wallet_address, wallet_scope(wallet_chain))
+            .await?;
+
+        // 重复执行同一轮快照时覆盖之前的记录
+        self.db
+            .exec(
+                "DELETE FROM portfolio_snapshots WHERE wallet_address = ? AND snapshot_at = ?",
+                vec![rbs::to_value!(&wallet_address), rbs::to_value!(snapshot_at)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut entities = snapshot_entities(&wallet_address, snapshot_at, &assets);
+        if entities.is_empty() {
+            entities.push(empty_snapshot(&wallet_address, wallet_chain, snapshot_at));
+        }
+
+        PortfolioSnapshotEntity::insert_batch(self.db.as_ref(), &entities, 100)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 获取资产历史，range为 24h、7d、30d、90d、1y 或 all，start为盈亏起始日期（YYYY-MM-DD或Unix秒）
+    pub async fn get_history(
+        &self,
+        wallet_address: &str,
+        range: &str,
+        start: Option<&str>,
+    ) -> Result<PortfolioHistory, ServiceError> {
+        let wallet_address = normalize_address(wallet_address);
+        let now = chrono::Utc::now().timestamp();
+        let from = match parse_range(range)? {
+            Some(secs) => now - secs,
+            None => 0,
+        };
+
+        let series: Vec<PortfolioPoint> = self
+            .db
+            .query_decode(
+                "SELECT snapshot_at AS timestamp, SUM(COALESCE(value_usd, 0)) AS value_usd \
+                 FROM portfolio_snapshots WHERE wallet_address = ? AND snapshot_at >= ? \
+                 GROUP BY snapshot_at ORDER BY snapshot_at",
+                vec![rbs::to_value!(&wallet_address), rbs::to_value!(from)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        // 分布按最近一次快照计算
+        let latest: Vec<PortfolioSnapshotEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM portfolio_snapshots WHERE wallet_address = ? AND snapshot_at = \
+                 (SELECT MAX(snapshot_at) FROM portfolio_snapshots WHERE wallet_address = ?)",
+                vec![rbs::to_value!(&wallet_address), rbs::to_value!(&wallet_address)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let pnl = match start {
+            Some(start) => {
+                let start_at = parse_start(start)?;
+                self.get_pnl(&wallet_address, start_at).await?
+            }
+            None => pnl_between(series.first(), series.last()),
+        };
+
+        Ok(PortfolioHistory {
+            wallet_address,
+            range: range.to_string(),
+            series: downsample(series, now - from.max(0)),
+            allocation_by_chain: allocation_by_chain(&latest),
+            allocation_by_asset: allocation_by_asset(&latest),
+            pnl,
+        })
+    }
+
+    // 计算从起始时间之后的第一次快照到最近一次快照的盈亏
+    async fn get_pnl(&self, wallet_address: &str, start_at: i64) -> Result<Option<PortfolioPnl>, ServiceError> {
+        let sql = |order: &str, condition: &str| {
+            format!(
+                "SELECT snapshot_at AS timestamp, SUM(COALESCE(value_usd, 0)) AS value_usd \
+                 FROM portfolio_snapshots WHERE wallet_address = ? {} \
+                 GROUP BY snapshot_at ORDER BY snapshot_at {} LIMIT 1",
+                condition, order
+            )
+        };
+
+        let start: Vec<PortfolioPoint> = self
+            .db
+            .query_decode(
+                &sql("ASC", "AND snapshot_at >= ?"),
+                vec![rbs::to_value!(wallet_address), rbs::to_value!(start_at)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        let end: Vec<PortfolioPoint> = self
+            .db
+            .query_decode(&sql("DESC", ""), vec![rbs::to_value!(wallet_address)])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(pnl_between(start.first(), end.first()))
+    }
+}
+
+// 将资产和有地板价的NFT转换为快照记录
+fn snapshot_entities(wallet_address: &str, snapshot_at: i64, assets: &AssetsResponse) -> Vec<PortfolioSnapshotEntity> {
+    let tokens = assets.tokens.iter().map(|asset| PortfolioSnapshotEntity {
+        id: Uuid::new_v4(),
+        wallet_address: wallet_address.to_string(),
+        snapshot_at,
+        chain_id: asset.chain_id,
+        asset_type: asset.asset_type.clone(),
+        symbol: asset.symbol.clone(),
+        contract_address: asset.contract_address.clone(),
+        balance: asset.balance,
+        price_usd: asset.price_usd,
+        value_usd: asset.value_usd,
+    });
+
+    let nfts = assets.nfts.iter().filter_map(|nft| {
+        let floor_price = nft.floor_price_usd?;
+        Some(PortfolioSnapshotEntity {
+            id: Uuid::new_v4(),
+            wallet_address: wallet_address.to_string(),
+            snapshot_at,
+            chain_id: chain_id_by_code(&nft.chain)?,
+            asset_type: "nft".to_string(),
+            symbol: nft.collection_name.clone().unwrap_or_else(|| nft.name.clone()),
+            contract_address: Some(nft.contract_address.clone()),
+            balance: Some(1.0),
+            price_usd: Some(floor_price),
+            value_usd: Some(floor_price),
+        })
+    });
+
+    tokens.chain(nfts).collect()
+}
+
+// 零价值快照，只计入走势，不计入分布
+fn empty_snapshot(wallet_address: &str, wallet_chain: &str, snapshot_at: i64) -> PortfolioSnapshotEntity {
+    let chain_id = match wallet_scope(wallet_chain) {
+        Some(_) => SOLANA_CHAIN_ID,
+        None => chains::ETHEREUM_CHAIN_ID,
+    };
+
+    PortfolioSnapshotEntity {
+        id: Uuid::new_v4(),
+        wallet_address: wallet_address.to_string(),
+        snapshot_at,
+        chain_id,
+        asset_type: EMPTY_SNAPSHOT_ASSET_TYPE.to_string(),
+        symbol: String::new(),
+        contract_address: None,
+        balance: Some(0.0),
+        price_usd: None,
+        value_usd: Some(0.0),
+    }
+}
+
+fn allocation_by_chain(entities: &[PortfolioSnapshotEntity]) -> Vec<AllocationEntry> {
+    let mut values: HashMap<i32, f64> = HashMap::new();
+    for entity in entities.iter().filter(|e| e.asset_type != EMPTY_SNAPSHOT_ASSET_TYPE) {
+        *values.entry(entity.chain_id).or_default() += entity.value_usd.unwrap_or(0.0);
+    }
+
+    let entries = values
+        .into_iter()
+        .map(|(chain_id, value_usd)| AllocationEntry {
+            label: chain_code(chain_id),
+            chain_id,
+            contract_address: None,
+            value_usd,
+            percentage: 0.0,
+        })
+        .collect();
+
+    with_percentages(entries)
+}
+
+fn allocation_by_asset(entities: &[PortfolioSnapshotEntity]) -> Vec<AllocationEntry> {
+    let mut entries: Vec<AllocationEntry> = Vec::new();
+    for entity in entities.iter().filter(|e| e.asset_type != EMPTY_SNAPSHOT_ASSET_TYPE) {
+        let value_usd = entity.value_usd.unwrap_or(0.0);
+        // 同一集合的NFT合并统计
+        match entries
+            .iter_mut()
+            .find(|e| e.chain_id == entity.chain_id && e.contract_address == entity.contract_address)
+        {
+            Some(entry) => entry.value_usd += value_usd,
+            None => entries.push(AllocationEntry {
+                label: entity.symbol.clone(),
+                chain_id: entity.chain_id,
+                contract_address: entity.contract_address.clone(),
+                value_usd,
+                percentage: 0.0,
+            }),
+        }
+    }
+
+    with_percentages(entries)
+}
+
+// 计算占比并按价值从高到低排序
+fn with_percentages(mut entries: Vec<AllocationEntry>) -> Vec<AllocationEntry> {
+    let total: f64 = entries.iter().map(|e| e.value_usd).sum();
+    for entry in &mut entries {
+        entry.percentage = if total > 0.0 {
+            (entry.value_usd / total * 10000.0).round() / 100.0
+        } else {
+            0.0
+        };
+    }
+    entries.sort_by(|a, b| b.value_usd.total_cmp(&a.value_usd));
+
+    entries
+}
+
+fn pnl_between(start: Option<&PortfolioPoint>, end: Option<&PortfolioPoint>) -> Option<PortfolioPnl> {
+    let (start, end) = (start?, end?);
+    let pnl_usd = end.value_usd - start.value_usd;
+
+    Some(PortfolioPnl {
+        start_at: start.timestamp,
+        start_value_usd: start.value_usd,
+        end_at: end.timestamp,
+        end_value_usd: end.value_usd,
+        pnl_usd,
+        pnl_percent: if start.value_usd > 0.0 {
+            Some((pnl_usd / start.value_usd * 10000.0).round() / 100.0)
+        } else {
+            None
+        },
+    })
+}
+
+// 按时间分桶，每个桶保留最后一个点
+fn downsample(series: Vec<PortfolioPoint>, span_secs: i64) -> Vec<PortfolioPoint> {
+    if series.len() as i64 <= MAX_SERIES_POINTS {
+        return series;
+    }
+
+    let span_secs = match (series.first(), series.last()) {
+        (Some(first), Some(last)) => span_secs.min(last.timestamp - first.timestamp),
+        _ => span_secs,
+    };
+    let bucket_secs = (span_secs / MAX_SERIES_POINTS).max(1);
+    let mut points: Vec<PortfolioPoint> = Vec::new();
+    for point in series {
+        match points.last_mut() {
+            Some(last) if last.timestamp / bucket_secs == point.timestamp / bucket_secs => *last = point,
+            _ => points.push(point),
+        }
+    }
+
+    points
+}
+
+// 解析查询区间，返回区间长度（秒），all返回None
+fn parse_range(range: &str) -> Result<Option<i64>, ServiceError> {
+    const DAY: i64 = 24 * 3600;
+
+    match range {
+        "24h" => Ok(Some(DAY)),
+        "7d" => Ok(Some(7 * DAY)),
+        "30d" => Ok(Some(30 * DAY)),
+        "90d" => Ok(Some(90 * DAY)),
+        "1y" => Ok(Some(365 * DAY)),
+        "all" => Ok(None),
+        _ => Err(ServiceError::BadRequest("不支持的时间范围，可选 24h、7d、30d、90d、1y、all".into())),
+    }
+}
+
+// 解析盈亏起始时间，支持 YYYY-MM-DD 和 Unix秒
+fn parse_start(start: &str) -> Result<i64, ServiceError> {
+    if let Ok(timestamp) = start.parse::<i64>() {
+        return Ok(timestamp);
+    }
+
+    NaiveDate::parse_from_str(start, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc().timestamp())
+        .ok_or_else(|| ServiceError::BadRequest("起始日期格式应为 YYYY-MM-DD".into()))
+}
+
+fn chain_code(chain_id: i32) -> String {
+    if chain_id == SOLANA_CHAIN_ID {
+        return "SOL".to_string();
+    }
+
+    chains::get_chain(chain_id)
+        .map(|chain| chain.code.to_string())
+        .unwrap_or_else(|| chain_id.to_string())
+}
+
+/// 快照间隔（秒）
+pub fn snapshot_interval_secs() -> u64 {
+    env::var("PORTFOLIO_SNAPSHOT_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL_SECS)
+}
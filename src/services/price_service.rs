@@ -0,0 +1,719 @@
+use crate::blockchain::chains;
+use crate::blockchain::price_feeds;
+use crate::blockchain::solana::SOLANA_CHAIN_ID;
+use crate::models::asset::{Asset, TokenPrice};
+use crate::utils::error::ServiceError;
+use async_trait::async_trait;
+use redis::Client as RedisClient;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 价格来源名称
+pub const SOURCE_COINGECKO: &str = "coingecko";
+pub const SOURCE_CHAINLINK: &str = "chainlink";
+pub const SOURCE_UNISWAP_V3: &str = "uniswap_v3";
+pub const SOURCE_ONCHAIN: &str = "onchain";
+
+// 价格缓存时间（秒），通过 PRICE_CACHE_TTL_SECS 配置
+const DEFAULT_PRICE_CACHE_TTL_SECS: u64 = 60;
+// 查不到价格的代币的缓存时间（秒），避免反复查询
+const PRICE_MISS_TTL_SECS: u64 = 600;
+// 历史价格缓存时间（秒），历史价格不会变化
+const HISTORICAL_PRICE_TTL_SECS: u64 = 7 * 24 * 3600;
+// 查询历史价格时在目标时间前后取数据的范围（秒），CoinGecko对1天内的范围返回5分钟粒度数据
+const HISTORICAL_PRICE_WINDOW_SECS: i64 = 3600;
+// 历史价格缓存按该时间粒度（秒）复用
+const HISTORICAL_PRICE_BUCKET_SECS: i64 = 300;
+// CoinGecko单次查询的合约数量
+const COINGECKO_BATCH_SIZE: usize = 30;
+const COINGECKO_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_COINGECKO_API_URL: &str = "https://api.coingecko.com/api/v3";
+
+// 代币标识，None表示原生币；EVM地址统一小写
+pub type TokenKey = Option<String>;
+
+/// 价格来源
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    // 来源名称，用于配置和日志
+    fn name(&self) -> &'static str;
+
+    // 批量查询同一条链上的代币价格，查不到价格的代币不包含在结果中
+    async fn get_prices(&self, chain_id: i32, tokens: &[TokenKey]) -> Result<Vec<TokenPrice>, String>;
+
+    // 查询代币在指定时间（Unix秒）附近的美元价格，不支持历史价格的来源返回None
+    async fn get_historical_price(
+        &self,
+        _chain_id: i32,
+        _token: &TokenKey,
+        _timestamp: i64,
+    ) -> Result<Option<f64>, String> {
+        Ok(None)
+    }
+}
+
+/// CoinGecko兼容的HTTP价格来源
+pub struct CoinGeckoSource {
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl CoinGeckoSource {
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            client: reqwest::Client::builder()
+                .timeout(COINGECKO_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    // 通过 COINGECKO_API_URL、COINGECKO_API_KEY 配置
+    pub fn from_env() -> Self {
+        let base_url = env::var("COINGECKO_API_URL").unwrap_or_else(|_| DEFAULT_COINGECKO_API_URL.to_string());
+        let api_key = env::var("COINGECKO_API_KEY").ok().filter(|k| !k.is_empty());
+
+        Self::new(&base_url, api_key)
+    }
+
+    async fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<serde_json::Value, String> {
+        let mut request = self.client.get(format!("{}{}", self.base_url, path)).query(query);
+        if let Some(api_key) = &self.api_key {
+            // 付费版与免费版使用不同的请求头
+            let header = if self.base_url.contains("pro-api.coingecko.com") {
+                "x-cg-pro-api-key"
+            } else {
+                "x-cg-demo-api-key"
+            };
+            request = request.header(header, api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to request CoinGecko: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("CoinGecko returned HTTP {}", response.status()));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid CoinGecko response: {}", e))
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn name(&self) -> &'static str {
+        SOURCE_COINGECKO
+    }
+
+    async fn get_prices(&self, chain_id: i32, tokens: &[TokenKey]) -> Result<Vec<TokenPrice>, String> {
+        let (platform, native_id) = coingecko_ids(chain_id)?;
+        let mut prices = Vec::new();
+
+        let price_params = || {
+            vec![
+                ("vs_currencies", "usd".to_string()),
+                ("include_market_cap", "true".to_string()),
+                ("include_24hr_change", "true".to_string()),
+                ("include_last_updated_at", "true".to_string()),
+            ]
+        };
+
+        // 原生币按币种ID查询
+        if tokens.iter().any(|t| t.is_none()) {
+            let mut query = price_params();
+            query.push(("ids", native_id.to_string()));
+            let json = self.get_json("/simple/price", &query).await?;
+            if let Some(price) = json.get(native_id).and_then(|v| parse_coingecko_price(chain_id, None, v)) {
+                prices.push(price);
+            }
+        }
+
+        // 代币按平台和合约地址查询
+        let addresses: Vec<&String> = tokens.iter().flatten().collect();
+        for batch in addresses.chunks(COINGECKO_BATCH_SIZE) {
+            let mut query = price_params();
+            query.push((
+                "contract_addresses",
+                batch.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(","),
+            ));
+            let json = self
+                .get_json(&format!("/simple/token_price/{}", platform), &query)
+                .await?;
+            let entries = match json.as_object() {
+                Some(entries) => entries,
+                None => continue,
+            };
+
+            for (address, value) in entries {
+                // 返回的地址大小写可能与请求不一致
+                let key = batch
+                    .iter()
+                    .find(|a| a.eq_ignore_ascii_case(address))
+                    .map(|a| a.to_string());
+                if let Some(price) = key.and_then(|k| parse_coingecko_price(chain_id, Some(k), value)) {
+                    prices.push(price);
+                }
+            }
+        }
+
+        Ok(prices)
+    }
+
+    async fn get_historical_price(
+        &self,
+        chain_id: i32,
+        token: &TokenKey,
+        timestamp: i64,
+    ) -> Result<Option<f64>, String> {
+        let (platform, native_id) = coingecko_ids(chain_id)?;
+        let path = match token {
+            None => format!("/coins/{}/market_chart/range", native_id),
+            Some(address) => format!("/coins/{}/contract/{}/market_chart/range", platform, address),
+        };
+        let query = [
+            ("vs_currency", "usd".to_string()),
+            ("from", (timestamp - HISTORICAL_PRICE_WINDOW_SECS).to_string()),
+            ("to", (timestamp + HISTORICAL_PRICE_WINDOW_SECS).to_string()),
+        ];
+        let json = self.get_json(&path, &query).await?;
+
+        Ok(nearest_price(&json, timestamp))
+    }
+}
+
+/// 链上价格来源：原生币读取Chainlink喂价，代币按与包装原生币的Uniswap V3池子价格换算
+pub struct OnchainSource;
+
+#[async_trait]
+impl PriceSource for OnchainSource {
+    fn name(&self) -> &'static str {
+        SOURCE_ONCHAIN
+    }
+
+    async fn get_prices(&self, chain_id: i32, tokens: &[TokenKey]) -> Result<Vec<TokenPrice>, String> {
+        // 仅支持EVM链
+        let chain = match chains::get_chain(chain_id) {
+            Some(chain) => chain,
+            None => return Ok(Vec::new()),
+        };
+        let native = price_feeds::get_native_usd_price(chain_id).await?;
+        let mut prices = Vec::new();
+
+        for token in tokens {
+            match token {
+                None => prices.push(TokenPrice {
+                    chain_id,
+                    contract_address: None,
+                    symbol: chain.native_symbol.to_string(),
+                    name: chain.native_name.to_string(),
+                    price_usd: native.price_usd,
+                    change_24h: None,
+                    market_cap_usd: None,
+                    source: SOURCE_CHAINLINK.to_string(),
+                    updated_at: native.updated_at,
+                }),
+                Some(address) => match price_feeds::get_uniswap_native_price(chain_id, address).await {
+                    Ok(Some(native_price)) => prices.push(TokenPrice {
+                        chain_id,
+                        contract_address: Some(address.clone()),
+                        symbol: String::new(),
+                        name: String::new(),
+                        price_usd: native_price * native.price_usd,
+                        change_24h: None,
+                        market_cap_usd: None,
+                        source: SOURCE_UNISWAP_V3.to_string(),
+                        updated_at: chrono::Utc::now().timestamp(),
+                    }),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("获取{}链上价格失败: {}", address, e),
+                },
+            }
+        }
+
+        Ok(prices)
+    }
+}
+
+/// 价格服务，按来源顺序查询代币价格并缓存到Redis
+pub struct PriceService {
+    redis: Arc<RedisClient>,
+    sources: Vec<Box<dyn PriceSource>>,
+}
+
+impl PriceService {
+    // 通过 PRICE_SOURCES 配置来源及顺序，如 "coingecko,onchain"
+    pub fn new(redis: Arc<RedisClient>) -> Self {
+        let config = env::var("PRICE_SOURCES").unwrap_or_else(|_| "coingecko,onchain".to_string());
+        let sources = config
+            .split(',')
+            .filter_map(|name| -> Option<Box<dyn PriceSource>> {
+                match name.trim() {
+                    SOURCE_COINGECKO => Some(Box::new(CoinGeckoSource::from_env())),
+                    SOURCE_ONCHAIN => Some(Box::new(OnchainSource)),
+                    "" => None,
+                    other => {
+                        log::warn!("未知的价格来源: {}", other);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self::with_sources(redis, sources)
+    }
+
+    pub fn with_sources(redis: Arc<RedisClient>, sources: Vec<Box<dyn PriceSource>>) -> Self {
+        Self { redis, sources }
+    }
+
+    /// 批量查询同一条链上的代币价格，先读缓存，未命中的按来源顺序查询
+    pub async fn get_prices(
+        &self,
+        chain_id: i32,
+        tokens: &[TokenKey],
+    ) -> Result<HashMap<TokenKey, TokenPrice>, ServiceError> {
+        if chain_id != SOLANA_CHAIN_ID && chains::get_chain(chain_id).is_none() {
+            return Err(ServiceError::BadRequest(format!("不支持的链: {}", chain_id)));
+        }
+
+        let mut tokens: Vec<TokenKey> = tokens.iter().map(|t| token_key(t.as_deref())).collect();
+        tokens.sort();
+        tokens.dedup();
+
+        let mut prices = HashMap::new();
+        let mut pending = Vec::new();
+        for (token, cached) in tokens.iter().zip(self.get_cached(chain_id, &tokens).await) {
+            match cached {
+                // 已缓存的价格，或已确认查不到价格
+                Some(Some(price)) => {
+                    prices.insert(token.clone(), price);
+                }
+                Some(None) => {}
+                None => pending.push(token.clone()),
+            }
+        }
+
+        prices.extend(self.fetch_from_sources(chain_id, pending).await);
+
+        Ok(prices)
+    }
+
+    /// 跳过缓存直接从来源查询并更新缓存，用于定时预热价格，返回查到价格的代币数
+    pub async fn refresh_prices(&self, chain_id: i32, tokens: &[TokenKey]) -> Result<usize, ServiceError> {
+        if chain_id != SOLANA_CHAIN_ID && chains::get_chain(chain_id).is_none() {
+            return Err(ServiceError::BadRequest(format!("不支持的链: {}", chain_id)));
+        }
+
+        let mut tokens: Vec<TokenKey> = tokens.iter().map(|t| token_key(t.as_deref())).collect();
+        tokens.sort();
+        tokens.dedup();
+
+        Ok(self.fetch_from_sources(chain_id, tokens).await.len())
+    }
+
+    // 按来源顺序查询价格并写入缓存，所有来源都查不到的代币缓存为空
+    async fn fetch_from_sources(&self, chain_id: i32, mut pending: Vec<TokenKey>) -> HashMap<TokenKey, TokenPrice> {
+        let mut prices = HashMap::new();
+        for source in &self.sources {
+            if pending.is_empty() {
+                break;
+            }
+
+            match source.get_prices(chain_id, &pending).await {
+                Ok(found) => {
+                    for price in found {
+                        let key = token_key(price.contract_address.as_deref());
+                        if price.price_usd > 0.0 && pending.contains(&key) {
+                            self.set_cached(chain_id, &key, Some(&price)).await;
+                            pending.retain(|t| t != &key);
+                            prices.insert(key, price);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("价格来源{}查询链{}失败: {}", source.name(), chain_id, e),
+            }
+        }
+
+        for token in &pending {
+            self.set_cached(chain_id, token, None).await;
+        }
+
+        prices
+    }
+
+    /// 查询代币在指定时间（Unix秒）的美元价格，按来源顺序查询并长期缓存
+    pub async fn get_historical_price(
+        &self,
+        chain_id: i32,
+        contract_address: Option<&str>,
+        timestamp: i64,
+    ) -> Result<Option<f64>, ServiceError> {
+        if chain_id != SOLANA_CHAIN_ID && chains::get_chain(chain_id).is_none() {
+            return Err(ServiceError::BadRequest(format!("不支持的链: {}", chain_id)));
+        }
+
+        let token = token_key(contract_address);
+        let key = format!(
+            "{}:history:{}",
+            cache_key(chain_id, &token),
+            timestamp / HISTORICAL_PRICE_BUCKET_SECS
+        );
+        let mut con = self.redis.get_async_connection().await.ok();
+        if let Some(con) = con.as_mut() {
+            let cached: Option<String> = redis::cmd("GET").arg(&key).query_async(con).await.unwrap_or(None);
+            if let Some(json) = cached {
+                return Ok(serde_json::from_str::<Option<f64>>(&json).unwrap_or(None));
+            }
+        }
+
+        let mut price = None;
+        for source in &self.sources {
+            match source.get_historical_price(chain_id, &token, timestamp).await {
+                Ok(Some(found)) if found > 0.0 => {
+                    price = Some(found);
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("价格来源{}查询历史价格失败: {}", source.name(), e),
+            }
+        }
+
+        if let Some(con) = con.as_mut() {
+            let ttl = if price.is_some() { HISTORICAL_PRICE_TTL_SECS } else { PRICE_MISS_TTL_SECS };
+            let result: redis::RedisResult<()> = redis::cmd("SETEX")
+                .arg(&key)
+                .arg(ttl)
+                .arg(serde_json::to_string(&price).unwrap_or_else(|_| "null".to_string()))
+                .query_async(con)
+                .await;
+            if let Err(e) = result {
+                log::warn!("写入历史价格缓存失败: {}", e);
+            }
+        }
+
+        Ok(price)
+    }
+
+    /// 填充资产的价格和美元价值
+    pub async fn apply_prices(&self, assets: &mut [Asset]) {
+        let mut chain_tokens: HashMap<i32, Vec<TokenKey>> = HashMap::new();
+        for asset in assets.iter() {
+            chain_tokens
+                .entry(asset.chain_id)
+                .or_default()
+                .push(token_key(asset.contract_address.as_deref()));
+        }
+
+        for (chain_id, tokens) in chain_tokens {
+            let prices = match self.get_prices(chain_id, &tokens).await {
+                Ok(prices) => prices,
+                Err(e) => {
+                    log::warn!("获取链{}代币价格失败: {}", chain_id, e);
+                    continue;
+                }
+            };
+
+            for asset in assets.iter_mut().filter(|a| a.chain_id == chain_id) {
+                if let Some(price) = prices.get(&token_key(asset.contract_address.as_deref())) {
+                    asset.price_usd = Some(price.price_usd);
+                    asset.value_usd = asset.balance.map(|b| b * price.price_usd);
+                    asset.price_source = Some(price.source.clone());
+                    asset.price_updated_at = Some(price.updated_at);
+                }
+            }
+        }
+    }
+
+    // 批量读取缓存：None为未缓存，Some(None)为已缓存的查询失败记录；Redis不可用时视为全部未缓存
+    async fn get_cached(&self, chain_id: i32, tokens: &[TokenKey]) -> Vec<Option<Option<TokenPrice>>> {
+        let misses = || tokens.iter().map(|_| None).collect();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut con = match self.redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(_) => return misses(),
+        };
+        let keys: Vec<String> = tokens.iter().map(|t| cache_key(chain_id, t)).collect();
+        let values: Vec<Option<String>> = match redis::cmd("MGET").arg(&keys).query_async(&mut con).await {
+            Ok(values) => values,
+            Err(_) => return misses(),
+        };
+
+        values
+            .into_iter()
+            .map(|value| value.map(|json| serde_json::from_str::<TokenPrice>(&json).ok()))
+            .collect()
+    }
+
+    async fn set_cached(&self, chain_id: i32, token: &TokenKey, price: Option<&TokenPrice>) {
+        let (value, ttl) = match price {
+            Some(price) => match serde_json::to_string(price) {
+                Ok(json) => (json, price_cache_ttl_secs()),
+                Err(_) => return,
+            },
+            None => ("null".to_string(), PRICE_MISS_TTL_SECS),
+        };
+        let mut con = match self.redis.get_async_connection().await {
+            Ok(con) => con,
+            Err(_) => return,
+        };
+
+        let result: redis::RedisResult<()> = redis::cmd("SETEX")
+            .arg(cache_key(chain_id, token))
+            .arg(ttl)
+            .arg(value)
+            .query_async(&mut con)
+            .await;
+        if let Err(e) = result {
+            log::warn!("写入价格缓存失败: {}", e);
+        }
+    }
+}
+
+// 解析CoinGecko返回的单个价格
+fn parse_coingecko_price(chain_id: i32, contract_address: TokenKey, value: &serde_json::Value) -> Option<TokenPrice> {
+    let price_usd = value.get("usd")?.as_f64()?;
+
+    Some(TokenPrice {
+        chain_id,
+        contract_address,
+        symbol: String::new(),
+        name: String::new(),
+        price_usd,
+        change_24h: value.get("usd_24h_change").and_then(|v| v.as_f64()),
+        market_cap_usd: value.get("usd_market_cap").and_then(|v| v.as_f64()),
+        source: SOURCE_COINGECKO.to_string(),
+        updated_at: value
+            .get("last_updated_at")
+            .and_then(|v| v.as_i64())
+            .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+    })
+}
+
+// 从CoinGecko market_chart的 prices（[毫秒时间戳, 价格]）中取最接近目标时间的价格
+fn nearest_price(json: &serde_json::Value, timestamp: i64) -> Option<f64> {
+    json.get("prices")?
+        .as_array()?
+        .iter()
+        .filter_map(|point| {
+            let point = point.as_array()?;
+            let time = point.first()?.as_f64()? / 1000.0;
+            let price = point.get(1)?.as_f64()?;
+            Some(((time - timestamp as f64).abs(), price))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, price)| price)
+}
+
+// 链对应的CoinGecko平台ID和原生币ID
+fn coingecko_ids(chain_id: i32) -> Result<(&'static str, &'static str), String> {
+    if chain_id == SOLANA_CHAIN_ID {
+        return Ok(("solana", "solana"));
+    }
+    let chain = chains::require_chain(chain_id)?;
+
+    Ok((chain.coingecko_platform, chain.coingecko_native_id))
+}
+
+// EVM地址不区分大小写，统一转为小写；Solana地址区分大小写
+fn token_key(contract_address: Option<&str>) -> TokenKey {
+    contract_address.filter(|a| !a.is_empty()).map(|a| {
+        if a.starts_with("0x") {
+            a.to_lowercase()
+        } else {
+            a.to_string()
+        }
+    })
+}
+
+fn cache_key(chain_id: i32, token: &TokenKey) -> String {
+    format!("price:{}:{}", chain_id, token.as_deref().unwrap_or("native"))
+}
+
+/// 定时预热价格的间隔（秒），与价格缓存过期时间一致
+pub fn refresh_interval_secs() -> u64 {
+    price_cache_ttl_secs()
+}
+
+fn price_cache_ttl_secs() -> u64 {
+    env::var("PRICE_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_PRICE_CACHE_TTL_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    // 在本地随机端口启动CoinGecko兼容的模拟服务，返回服务地址
+    fn mock_coingecko() -> String {
+        let server = HttpServer::new(|| {
+            App::new()
+                .route(
+                    "/simple/price",
+                    web::get().to(|| async {
+                        HttpResponse::Ok().json(serde_json::json!({
+                            "ethereum": {
+                                "usd": 3000.5,
+                                "usd_market_cap": 360000000000.0,
+                                "usd_24h_change": -1.25,
+                                "last_updated_at": 1700000000
+                            }
+                        }))
+                    }),
+                )
+                .route(
+                    "/simple/token_price/ethereum",
+                    web::get().to(|| async {
+                        // 返回的地址为校验和格式，与请求的小写地址不一致
+                        HttpResponse::Ok().json(serde_json::json!({
+                            "0xA0b86991c6218b36c1d19d4a2e9Eb0cE3606eB48": {
+                                "usd": 0.9998,
+                                "last_updated_at": 1700000100
+                            }
+                        }))
+                    }),
+                )
+                .route(
+                    "/coins/ethereum/market_chart/range",
+                    web::get().to(|| async {
+                        HttpResponse::Ok().json(serde_json::json!({
+                            "prices": [
+                                [1699999500000u64, 2990.0],
+                                [1699999800000u64, 2995.0],
+                                [1700000100000u64, 3001.0]
+                            ]
+                        }))
+                    }),
+                )
+                .route(
+                    "/keyed/simple/price",
+                    web::get().to(|req: HttpRequest| async move {
+                        if req.headers().get("x-cg-demo-api-key").map(|v| v.as_bytes()) != Some(b"test-key") {
+                            return HttpResponse::Unauthorized().finish();
+                        }
+                        HttpResponse::Ok().json(serde_json::json!({ "ethereum": { "usd": 3000.5 } }))
+                    }),
+                )
+                .default_service(web::to(HttpResponse::InternalServerError))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("failed to bind mock server");
+        let address = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+
+        format!("http://{}", address)
+    }
+
+    // 不可用的Redis，缓存读写均失败，每次都从价格来源查询
+    fn offline_redis() -> Arc<RedisClient> {
+        Arc::new(RedisClient::open("redis://127.0.0.1:1").unwrap())
+    }
+
+    #[actix_web::test]
+    async fn coingecko_source_returns_native_and_token_prices() {
+        let source = CoinGeckoSource::new(&mock_coingecko(), None);
+        let mut prices = source
+            .get_prices(chains::ETHEREUM_CHAIN_ID, &[None, Some(USDC.to_string())])
+            .await
+            .unwrap();
+        prices.sort_by(|a, b| a.contract_address.cmp(&b.contract_address));
+
+        assert_eq!(prices.len(), 2);
+        let native = &prices[0];
+        assert_eq!(native.contract_address, None);
+        assert_eq!(native.price_usd, 3000.5);
+        assert_eq!(native.change_24h, Some(-1.25));
+        assert_eq!(native.market_cap_usd, Some(360000000000.0));
+        assert_eq!(native.source, SOURCE_COINGECKO);
+        assert_eq!(native.updated_at, 1700000000);
+
+        let token = &prices[1];
+        assert_eq!(token.contract_address.as_deref(), Some(USDC));
+        assert_eq!(token.price_usd, 0.9998);
+        assert_eq!(token.updated_at, 1700000100);
+    }
+
+    #[actix_web::test]
+    async fn coingecko_source_sends_api_key() {
+        let base_url = format!("{}/keyed", mock_coingecko());
+
+        let without_key = CoinGeckoSource::new(&base_url, None);
+        assert!(without_key.get_prices(chains::ETHEREUM_CHAIN_ID, &[None]).await.is_err());
+
+        let with_key = CoinGeckoSource::new(&base_url, Some("test-key".to_string()));
+        let prices = with_key.get_prices(chains::ETHEREUM_CHAIN_ID, &[None]).await.unwrap();
+        assert_eq!(prices[0].price_usd, 3000.5);
+    }
+
+    #[actix_web::test]
+    async fn coingecko_source_picks_nearest_historical_price() {
+        let source = CoinGeckoSource::new(&mock_coingecko(), None);
+
+        let price = source
+            .get_historical_price(chains::ETHEREUM_CHAIN_ID, &None, 1699999850)
+            .await
+            .unwrap();
+        assert_eq!(price, Some(2995.0));
+    }
+
+    #[actix_web::test]
+    async fn price_service_falls_back_to_next_source_and_reports_source() {
+        let base_url = mock_coingecko();
+        let sources: Vec<Box<dyn PriceSource>> = vec![
+            // 第一个来源的所有请求都返回500
+            Box::new(CoinGeckoSource::new(&format!("{}/broken", base_url), None)),
+            Box::new(CoinGeckoSource::new(&base_url, None)),
+        ];
+        let service = PriceService::with_sources(offline_redis(), sources);
+
+        let mut assets = vec![Asset {
+            chain_id: chains::ETHEREUM_CHAIN_ID,
+            asset_type: "token".to_string(),
+            symbol: "USDC".to_string(),
+            name: "USD Coin".to_string(),
+            contract_address: Some("0xA0b86991c6218b36c1d19d4a2e9Eb0cE3606eB48".to_string()),
+            balance: Some(100.0),
+            decimals: Some(6),
+            price_usd: None,
+            value_usd: None,
+            price_source: None,
+            price_updated_at: None,
+            logo_url: None,
+            created_at: None,
+            updated_at: None,
+        }];
+        service.apply_prices(&mut assets).await;
+
+        assert_eq!(assets[0].price_usd, Some(0.9998));
+        assert_eq!(assets[0].value_usd, Some(99.98));
+        assert_eq!(assets[0].price_source.as_deref(), Some(SOURCE_COINGECKO));
+        assert_eq!(assets[0].price_updated_at, Some(1700000100));
+    }
+
+    #[actix_web::test]
+    async fn price_service_rejects_unknown_chain() {
+        let service = PriceService::with_sources(offline_redis(), Vec::new());
+
+        assert!(matches!(
+            service.get_prices(-1, &[None]).await,
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+}
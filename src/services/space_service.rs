@@ -0,0 +1,286 @@
+use crate::models::rbatis_entities::{GatedSpaceEntity, SpaceMemberEntity};
+use crate::services::asset_service::{chain_id_by_code, wallet_scope, AssetService};
+use crate::services::badge_service::{get_user_wallet, holding_amount};
+use crate::utils::error::ServiceError;
+use rbatis::rbdc::datetime::DateTime;
+use rbatis::RBatis;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// 空间门槛类型
+pub const REQUIREMENT_VALUE: &str = "value"; // 资产总价值（美元）
+pub const REQUIREMENT_TOKEN: &str = "token"; // 指定代币数量
+pub const REQUIREMENT_NFT: &str = "nft"; // 指定集合的NFT个数
+
+// 成员资格检查结果的有效期（秒），同时作为定时重新检查的间隔，通过 SPACE_RECHECK_INTERVAL_SECS 配置
+const DEFAULT_RECHECK_INTERVAL_SECS: u64 = 6 * 3600;
+
+/// 创建空间的参数
+pub struct NewSpace<'a> {
+    pub tag: &'a str,
+    pub name: &'a str,
+    pub description: Option<String>,
+    pub requirement_type: &'a str,
+    // SOL或EVM链简称，token和nft类型必填
+    pub chain: Option<&'a str>,
+    // 代币或NFT合约地址，token类型为空表示原生币
+    pub contract_address: Option<String>,
+    pub min_amount: f64,
+}
+
+/// 资产门槛空间服务，只有满足持仓要求的钱包才能在对应标签下发帖和评论
+pub struct SpaceService {
+    db: Arc<RBatis>,
+    asset_service: Arc<AssetService>,
+}
+
+impl SpaceService {
+    pub fn new(db: Arc<RBatis>, asset_service: Arc<AssetService>) -> Self {
+        Self { db, asset_service }
+    }
+
+    /// 创建资产门槛空间
+    /// 已有帖子的标签只有管理员能设置门槛，避免他人占用热门标签；创建者自身必须满足门槛
+    pub async fn create_space(
+        &self,
+        creator_id: &str,
+        is_admin: bool,
+        space: NewSpace<'_>,
+    ) -> Result<GatedSpaceEntity, ServiceError> {
+        let NewSpace {
+            tag,
+            name,
+            description,
+            requirement_type,
+            chain,
+            contract_address,
+            min_amount,
+        } = space;
+        let tag = tag.trim();
+        if tag.is_empty() || name.trim().is_empty() {
+            return Err(ServiceError::BadRequest("标签和名称不能为空".into()));
+        }
+        if !min_amount.is_finite() || min_amount <= 0.0 {
+            return Err(ServiceError::BadRequest("门槛数量必须大于0".into()));
+        }
+
+        let chain_id = match requirement_type {
+            REQUIREMENT_VALUE => None,
+            REQUIREMENT_TOKEN | REQUIREMENT_NFT => {
+                let chain = chain.ok_or_else(|| ServiceError::BadRequest("代币和NFT门槛需要指定链".into()))?;
+                Some(chain_id_by_code(chain).ok_or_else(|| ServiceError::BadRequest("不支持的链类型".into()))?)
+            }
+            _ => return Err(ServiceError::BadRequest("门槛类型应为 value、token 或 nft".into())),
+        };
+        if requirement_type == REQUIREMENT_NFT && contract_address.is_none() {
+            return Err(ServiceError::BadRequest("NFT门槛需要指定集合地址".into()));
+        }
+        if self.get_space_by_tag(tag).await?.is_some() {
+            return Err(ServiceError::BadRequest("该标签已创建空间".into()));
+        }
+        if !is_admin && self.tag_has_posts(tag).await? {
+            return Err(ServiceError::Unauthorized("该标签下已有帖子，只有管理员可以创建空间".into()));
+        }
+
+        let space = GatedSpaceEntity {
+            id: Uuid::new_v4(),
+            tag: tag.to_string(),
+            name: name.trim().to_string(),
+            description,
+            creator_id: creator_id.to_string(),
+            requirement_type: requirement_type.to_string(),
+            chain_id,
+            // value类型不需要合约地址
+            contract_address: contract_address.filter(|_| chain_id.is_some()),
+            min_amount,
+            created_at: DateTime::now(),
+        };
+
+        if self.holding_amount(creator_id, &space).await? < space.min_amount {
+            return Err(ServiceError::Unauthorized("创建者不满足该空间的持仓要求".into()));
+        }
+
+        GatedSpaceEntity::insert(self.db.as_ref(), &space)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        self.save_member(&space, creator_id, true).await?;
+
+        Ok(space)
+    }
+
+    // 标签下是否已有帖子
+    async fn tag_has_posts(&self, tag: &str) -> Result<bool, ServiceError> {
+        let count: i64 = self
+            .db
+            .query_decode(
+                "SELECT COUNT(*) FROM posts WHERE ? = ANY(tags)",
+                vec![rbs::to_value!(tag)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(count > 0)
+    }
+
+    /// 获取所有空间
+    pub async fn list_spaces(&self) -> Result<Vec<GatedSpaceEntity>, ServiceError> {
+        self.db
+            .query_decode("SELECT * FROM gated_spaces ORDER BY created_at DESC", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+
+    /// 按标签获取空间
+    pub async fn get_space_by_tag(&self, tag: &str) -> Result<Option<GatedSpaceEntity>, ServiceError> {
+        let spaces: Vec<GatedSpaceEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM gated_spaces WHERE tag = ?",
+                vec![rbs::to_value!(tag)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(spaces.into_iter().next())
+    }
+
+    /// 检查用户是否满足空间门槛，有效期内的检查结果直接复用
+    pub async fn check_eligibility(
+        &self,
+        user_id: &str,
+        space: &GatedSpaceEntity,
+    ) -> Result<SpaceMemberEntity, ServiceError> {
+        let members: Vec<SpaceMemberEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM space_members WHERE space_id = ?::uuid AND user_id = ?",
+                vec![rbs::to_value!(space.id.to_string()), rbs::to_value!(user_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        if let Some(member) = members.into_iter().next() {
+            let age = chrono::Utc::now().timestamp() - member.checked_at.unix_timestamp();
+            if age < recheck_interval_secs() as i64 {
+                return Ok(member);
+            }
+        }
+
+        self.evaluate_member(user_id, space).await
+    }
+
+    /// 在带有门槛空间标签的帖子下发帖或评论前检查资格
+    pub async fn require_tag_access(&self, user_id: &str, tags: &[String]) -> Result<(), ServiceError> {
+        for tag in tags {
+            let space = match self.get_space_by_tag(tag).await? {
+                Some(space) => space,
+                None => continue,
+            };
+
+            if !self.check_eligibility(user_id, &space).await?.eligible {
+                return Err(ServiceError::Unauthorized(format!(
+                    "不满足空间「{}」的持仓要求",
+                    space.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 重新检查所有成员的资格，单个成员失败不影响其他成员
+    pub async fn recheck_all(&self) -> Result<usize, ServiceError> {
+        let spaces: HashMap<Uuid, GatedSpaceEntity> = self
+            .list_spaces()
+            .await?
+            .into_iter()
+            .map(|space| (space.id, space))
+            .collect();
+        let members: Vec<SpaceMemberEntity> = self
+            .db
+            .query_decode("SELECT * FROM space_members", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut count = 0;
+        for member in &members {
+            let space = match spaces.get(&member.space_id) {
+                Some(space) => space,
+                None => continue,
+            };
+            match self.evaluate_member(&member.user_id, space).await {
+                Ok(_) => count += 1,
+                Err(e) => log::warn!("检查用户{}空间{}资格失败: {}", member.user_id, space.tag, e),
+            }
+        }
+
+        Ok(count)
+    }
+
+    // 按当前资产检查资格并保存结果
+    async fn evaluate_member(
+        &self,
+        user_id: &str,
+        space: &GatedSpaceEntity,
+    ) -> Result<SpaceMemberEntity, ServiceError> {
+        let eligible = self.holding_amount(user_id, space).await? >= space.min_amount;
+
+        self.save_member(space, user_id, eligible).await
+    }
+
+    // 用户钱包中与空间门槛对应的持仓
+    async fn holding_amount(&self, user_id: &str, space: &GatedSpaceEntity) -> Result<f64, ServiceError> {
+        let wallet = get_user_wallet(&self.db, user_id).await?;
+        let assets = self
+            .asset_service
+            .get_wallet_assets(&wallet.wallet_address, wallet_scope(&wallet.wallet_chain))
+            .await?;
+
+        Ok(match (space.requirement_type.as_str(), space.chain_id) {
+            (REQUIREMENT_VALUE, _) => assets.total_value_usd,
+            (_, Some(chain_id)) => holding_amount(&assets, chain_id, space.contract_address.as_deref()),
+            _ => 0.0,
+        })
+    }
+
+    async fn save_member(
+        &self,
+        space: &GatedSpaceEntity,
+        user_id: &str,
+        eligible: bool,
+    ) -> Result<SpaceMemberEntity, ServiceError> {
+        let member = SpaceMemberEntity {
+            space_id: space.id,
+            user_id: user_id.to_string(),
+            eligible,
+            checked_at: DateTime::now(),
+        };
+
+        self.db
+            .exec(
+                "INSERT INTO space_members (space_id, user_id, eligible, checked_at) VALUES (?::uuid, ?, ?, ?) \
+                 ON CONFLICT (space_id, user_id) DO UPDATE SET eligible = EXCLUDED.eligible, checked_at = EXCLUDED.checked_at",
+                vec![
+                    rbs::to_value!(member.space_id.to_string()),
+                    rbs::to_value!(&member.user_id),
+                    rbs::to_value!(member.eligible),
+                    rbs::to_value!(&member.checked_at),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(member)
+    }
+}
+
+/// 重新检查成员资格的间隔（秒）
+pub fn recheck_interval_secs() -> u64 {
+    env::var("SPACE_RECHECK_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_RECHECK_INTERVAL_SECS)
+}
@@ -1,168 +1,1337 @@
-use crate::config::Config;
+use crate::models::rbatis_entities::StorageOutboxEntity;
+use crate::models::storage::{ContentProof, ContentVerification, IpfsProof};
+use crate::utils::ans104::DataItem;
+use crate::utils::arweave::{arweave_client, Tag, TxStatus};
+use crate::utils::envelope::{content_aad, keyring, ENVELOPE_ALG, ENVELOPE_CONTENT_TYPE};
 use crate::utils::error::ServiceError;
-use std::sync::Arc;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use crate::utils::storage_backend::{
+    create_backend, legacy_backend_names, media_backend_name, record_backend_name, StorageBackend, BACKEND_ARWEAVE,
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rbatis::executor::Executor;
+use rbatis::rbdc::datetime::DateTime;
 use rbatis::RBatis;
+use redis::Client as RedisClient;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// 内容的永久存储状态
+pub const STORAGE_STATUS_NONE: &str = "none"; // 不需要上链，如转发
+pub const STORAGE_STATUS_PENDING: &str = "pending";
+pub const STORAGE_STATUS_SUBMITTED: &str = "submitted";
+pub const STORAGE_STATUS_CONFIRMED: &str = "confirmed";
+pub const STORAGE_STATUS_FAILED: &str = "failed";
+
+// 完整性证明的校验结果
+pub const PROOF_STATUS_VERIFIED: &str = "verified"; // 取回的内容与记录一致
+pub const PROOF_STATUS_PENDING: &str = "pending"; // 仍在上传或等待确认
+pub const PROOF_STATUS_MISMATCH: &str = "mismatch"; // 内容与记录的哈希不一致
+pub const PROOF_STATUS_UNAVAILABLE: &str = "unavailable"; // 已上链的内容暂时无法取回
+pub const PROOF_STATUS_NOT_ARCHIVED: &str = "not_archived"; // 内容不需要上链，如转发
+
+// 发件箱记录对应的内容表
+pub const CONTENT_TABLE_POSTS: &str = "posts";
+pub const CONTENT_TABLE_COMMENTS: &str = "comments";
+
+// 发件箱记录的内容类型，分别写入媒体和记录存储后端
+const OUTBOX_KIND_MEDIA: &str = "media";
+const OUTBOX_KIND_RECORD: &str = "record";
+
+// 作者签名标签，签名的规范消息包含明文哈希，内容加密时不发布
+const SIGNATURE_TAG_NAMES: [&str; 3] = ["Author-Signature", "Signature-Version", "Signed-Message"];
+
+// 每次处理的发件箱记录数
+const OUTBOX_BATCH_SIZE: u64 = 200;
+// 上传失败后的重试间隔从30秒开始翻倍，最长1小时；超过 STORAGE_OUTBOX_MAX_ATTEMPTS 次后标记为失败
+const DEFAULT_MAX_ATTEMPTS: i32 = 8;
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 3600;
+// 达到 ARWEAVE_CONFIRMATION_DEPTH 个确认后视为永久保存
+const DEFAULT_CONFIRMATION_DEPTH: u64 = 10;
+// 提交后超过 ARWEAVE_RESUBMIT_SECS 仍查不到交易时重新上传
+const DEFAULT_RESUBMIT_SECS: i64 = 3600;
+// 打包上限，分别通过 ARWEAVE_BUNDLE_MAX_ITEMS、ARWEAVE_BUNDLE_MAX_BYTES 配置
+const DEFAULT_BUNDLE_MAX_ITEMS: usize = 50;
+const DEFAULT_BUNDLE_MAX_BYTES: usize = 10 * 1024 * 1024;
+// 完整性证明的缓存时间（秒），通过 CONTENT_PROOF_CACHE_SECS 配置
+const DEFAULT_PROOF_CACHE_SECS: u64 = 300;
+// 每次轮换的数据密钥数量
+const KEY_ROTATION_BATCH_SIZE: u64 = 500;
+// 后台任务间隔（秒），分别通过 STORAGE_OUTBOX_INTERVAL_SECS、ARWEAVE_CONFIRM_INTERVAL_SECS、CONTENT_KEY_ROTATION_INTERVAL_SECS 配置
+const DEFAULT_OUTBOX_INTERVAL_SECS: u64 = 15;
+const DEFAULT_CONFIRM_INTERVAL_SECS: u64 = 300;
+const DEFAULT_KEY_ROTATION_INTERVAL_SECS: u64 = 3600;
+
+// 内容记录的加密信息
+#[derive(Debug, Deserialize)]
+struct ContentKeyRow {
+    id: String,
+    content_key_id: Option<String>,
+    content_wrapped_key: Option<String>,
+}
+
+// 校验时读取的内容记录
+#[derive(Debug, Deserialize)]
+struct StoredContentRow {
+    id: String,
+    content: String,
+    content_hash: Option<String>,
+    storage_status: String,
+    storage_ref: Option<String>,
+    arweave_tx_id: Option<String>,
+    content_key_id: Option<String>,
+    content_wrapped_key: Option<String>,
+}
 
-/// 存储服务，处理IPFS和Arweave去中心化存储
+// 帖子引用的媒体文件
+#[derive(Debug, Deserialize)]
+struct PostImagesRow {
+    images_ipfs_cids: Option<Vec<String>>,
+}
+
+// 媒体文件写入时使用的存储后端
+#[derive(Debug, Deserialize)]
+struct MediaBackendRow {
+    expected_ref: Option<String>,
+    backend: String,
+}
+
+// 发件箱记录对应的内容
+#[derive(Debug, Deserialize)]
+struct OutboxContentRow {
+    content_table: String,
+    content_id: String,
+}
+
+/// 存储服务，媒体文件和帖子、评论正文分别写入配置的存储后端（IPFS、Arweave、本地文件、S3兼容存储）
+/// 帖子和评论先写入发件箱，由后台任务上传、重试并跟踪确认，发帖不再等待上链
 pub struct StorageService {
-    config: Arc<Config>,
-    http_client: Client,
-    db: Option<Arc<RBatis>>,
+    db: Arc<RBatis>,
+    redis: Arc<RedisClient>,
+    media: Arc<dyn StorageBackend>,
+    records: Arc<dyn StorageBackend>,
+    // 已配置的全部后端，按名称查找发件箱记录和媒体文件写入时使用的后端
+    backends: HashMap<&'static str, Arc<dyn StorageBackend>>,
 }
 
 impl StorageService {
-    pub fn new(config: Arc<Config>, db: Option<Arc<RBatis>>) -> Self {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+    // 按 STORAGE_MEDIA_BACKEND、STORAGE_RECORD_BACKEND 选择存储后端，默认分别为IPFS和Arweave
+    // 配置了内容加密主密钥但无法加载时直接退出，避免内容以明文写入永久存储
+    pub fn new(db: Arc<RBatis>, redis: Arc<RedisClient>) -> Self {
+        if let Err(e) = keyring() {
+            panic!("{}", e);
+        }
+        let (media_name, record_name) = (media_backend_name(), record_backend_name());
+        let media = create_backend(&media_name).expect("媒体存储后端配置错误");
+        let records = if record_name == media_name {
+            media.clone()
+        } else {
+            create_backend(&record_name).expect("记录存储后端配置错误")
+        };
+
+        let mut service = Self::with_backends(db, redis, media, records);
+        for name in legacy_backend_names() {
+            if !service.backends.contains_key(name.as_str()) {
+                let backend = create_backend(&name).expect("历史存储后端配置错误");
+                service.backends.insert(backend.name(), backend);
+            }
+        }
+        service
+    }
+
+    /// 使用指定的存储后端，如测试时使用内存存储
+    pub fn with_backends(
+        db: Arc<RBatis>,
+        redis: Arc<RedisClient>,
+        media: Arc<dyn StorageBackend>,
+        records: Arc<dyn StorageBackend>,
+    ) -> Self {
+        let backends = [media.clone(), records.clone()]
+            .into_iter()
+            .map(|backend| (backend.name(), backend))
+            .collect();
 
         Self {
-            config,
-            http_client,
             db,
+            redis,
+            media,
+            records,
+            backends,
         }
     }
 
-    /// 上传内容到IPFS
-    pub async fn upload_to_ipfs(&self, data: &[u8]) -> Result<String, ServiceError> {
-        #[derive(Serialize)]
-        struct IPFSUploadRequest<'a> {
-            file: &'a [u8],
+    /// 上传媒体文件，返回内容引用（IPFS为本地校验过的CIDv1）
+    pub async fn upload_media(&self, data: &[u8], content_type: &str) -> Result<String, ServiceError> {
+        self.media
+            .put(data, content_type, &[])
+            .await
+            .map_err(ServiceError::ExternalService)
+    }
+
+    /// 将媒体文件加入发件箱，立即返回本地计算的内容引用，由后台任务上传
+    /// 无法预先计算引用的后端（Arweave）直接上传；executor为写入内容记录的事务
+    pub async fn enqueue_media(
+        &self,
+        executor: &dyn Executor,
+        content_table: &str,
+        content_id: &str,
+        data: &[u8],
+        content_type: &str,
+    ) -> Result<String, ServiceError> {
+        let reference = match self.media.reference_for(data) {
+            Some(reference) => reference,
+            None => return self.upload_media(data, content_type).await,
+        };
+
+        let mut row = outbox_row(
+            content_table,
+            content_id,
+            OUTBOX_KIND_MEDIA,
+            self.media.name(),
+            data,
+            content_type,
+            Vec::new(),
+        )?;
+        row.expected_ref = Some(reference.clone());
+        insert_outbox(executor, &row).await?;
+
+        Ok(reference)
+    }
+
+    /// 将帖子、评论正文加入发件箱，由后台任务写入记录存储后端；Arweave签名为ANS-104数据项后打包上链
+    /// 配置了主密钥时内容先做信封加密，主密钥ID和数据密钥保存到内容记录
+    /// 自动添加App-Name和Content-Type标签，tags为作者钱包、帖子ID等附加标签
+    /// 内容加密时去掉作者签名标签，签名仍保存在数据库中
+    /// executor为写入内容记录的事务，内容记录和发件箱记录一起提交
+    pub async fn enqueue_record(
+        &self,
+        executor: &dyn Executor,
+        content_table: &str,
+        content_id: &str,
+        data: &[u8],
+        content_type: &str,
+        tags: Vec<Tag>,
+    ) -> Result<(), ServiceError> {
+        let keyring = keyring().map_err(|e| {
+            log::error!("加密{}/{}失败: {}", content_table, content_id, e);
+            ServiceError::InternalServerError
+        })?;
+        let sealed = match keyring {
+            Some(keyring) => Some(keyring.seal(data, &content_aad(content_table, content_id)).map_err(|e| {
+                log::error!("加密{}/{}失败: {}", content_table, content_id, e);
+                ServiceError::InternalServerError
+            })?),
+            None => None,
+        };
+
+        let mut all_tags = vec![Tag::new("App-Name", arweave_client().app_name())];
+        let (payload, payload_type) = match &sealed {
+            // 加密内容不写明文哈希，否则可据此比对猜测内容
+            Some(sealed) => {
+                all_tags.push(Tag::new("Content-Type", ENVELOPE_CONTENT_TYPE));
+                all_tags.push(Tag::new("Content-Encryption", ENVELOPE_ALG));
+                (sealed.envelope.as_slice(), ENVELOPE_CONTENT_TYPE)
+            }
+            // 明文内容的哈希，与数据库中记录的content_hash一致，供审计比对
+            None => {
+                all_tags.push(Tag::new("Content-SHA256", &content_hash(data)));
+                all_tags.push(Tag::new("Content-Type", content_type));
+                (data, content_type)
+            }
+        };
+        all_tags.extend(
+            tags.into_iter()
+                .filter(|tag| sealed.is_none() || !SIGNATURE_TAG_NAMES.contains(&tag.name.as_str())),
+        );
+
+        let row = outbox_row(
+            content_table,
+            content_id,
+            OUTBOX_KIND_RECORD,
+            self.records.name(),
+            payload,
+            payload_type,
+            all_tags,
+        )?;
+        insert_outbox(executor, &row).await?;
+
+        if let Some(sealed) = sealed {
+            save_content_key(executor, content_table, content_id, None, &sealed.key_id, &sealed.wrapped_key).await?;
         }
 
-        #[derive(Deserialize)]
-        struct IPFSUploadResponse {
-            cid: String,
+        Ok(())
+    }
+
+    /// 上传到期的发件箱记录，返回成功提交的数量
+    pub async fn process_outbox(&self) -> Result<usize, ServiceError> {
+        let rows: Vec<StorageOutboxEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM storage_outbox WHERE status = ? AND next_attempt_at <= ? ORDER BY created_at LIMIT ?",
+                vec![
+                    rbs::to_value!(STORAGE_STATUS_PENDING),
+                    rbs::to_value!(now_secs()),
+                    rbs::to_value!(OUTBOX_BATCH_SIZE),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        // Arweave上的正文批量打包并跟踪确认，其他内容逐条写入
+        let (arweave_rows, other_rows): (Vec<_>, Vec<_>) = rows
+            .into_iter()
+            .partition(|row| row.kind == OUTBOX_KIND_RECORD && row.backend == BACKEND_ARWEAVE);
+
+        let mut submitted = 0;
+        for row in &other_rows {
+            if self.upload_row(row).await? {
+                submitted += 1;
+            }
         }
+        submitted += self.upload_arweave_rows(arweave_rows).await?;
 
-        // 使用IPFS HTTP API上传
-        let response = self.http_client
-            .post(&self.config.ipfs.api_url)
-            .header("X-API-KEY", &self.config.ipfs.api_key)
-            .json(&IPFSUploadRequest { file: data })
-            .send()
+        Ok(submitted)
+    }
+
+    /// 查询已提交交易的确认数，达到确认深度后标记为已确认，长时间未上链的重新上传
+    /// 返回本次确认的记录数
+    pub async fn check_confirmations(&self) -> Result<usize, ServiceError> {
+        let rows: Vec<StorageOutboxEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM storage_outbox WHERE status = ? AND backend = ? AND tx_id IS NOT NULL",
+                vec![
+                    rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                    rbs::to_value!(BACKEND_ARWEAVE),
+                ],
+            )
             .await
-            .map_err(|e| ServiceError::ExternalService(format!("IPFS服务错误: {}", e)))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
-            return Err(ServiceError::ExternalService(format!("IPFS上传失败: {}", error_text)));
+        let mut by_tx: HashMap<String, Vec<StorageOutboxEntity>> = HashMap::new();
+        for row in rows {
+            if let Some(tx_id) = row.tx_id.clone() {
+                by_tx.entry(tx_id).or_default().push(row);
+            }
         }
 
-        let upload_response = response.json::<IPFSUploadResponse>().await
-            .map_err(|e| ServiceError::ExternalService(format!("解析IPFS响应失败: {}", e)))?;
+        let depth = confirmation_depth();
+        let mut confirmed = 0;
+        for (tx_id, rows) in by_tx {
+            let status = match arweave_client().get_status(&tx_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    log::warn!("查询Arweave交易{}状态失败: {}", tx_id, e);
+                    continue;
+                }
+            };
 
-        Ok(upload_response.cid)
-    }
+            match status {
+                TxStatus::Confirmed { confirmations, .. } if confirmations >= depth => {
+                    self.db
+                        .exec(
+                            "UPDATE storage_outbox SET status = ?, confirmations = ?, confirmed_at = ?, payload = NULL, \
+                             updated_at = NOW() WHERE tx_id = ? AND status = ?",
+                            vec![
+                                rbs::to_value!(STORAGE_STATUS_CONFIRMED),
+                                rbs::to_value!(confirmations as i32),
+                                rbs::to_value!(now_secs()),
+                                rbs::to_value!(&tx_id),
+                                rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                            ],
+                        )
+                        .await
+                        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+                    for row in &rows {
+                        self.update_content(row, None, STORAGE_STATUS_CONFIRMED).await?;
+                    }
+                    confirmed += rows.len();
+                }
+                TxStatus::Confirmed { confirmations, .. } => {
+                    self.db
+                        .exec(
+                            "UPDATE storage_outbox SET confirmations = ?, updated_at = NOW() WHERE tx_id = ? AND status = ?",
+                            vec![
+                                rbs::to_value!(confirmations as i32),
+                                rbs::to_value!(&tx_id),
+                                rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                            ],
+                        )
+                        .await
+                        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+                }
+                TxStatus::Pending => {}
+                TxStatus::NotFound => {
+                    let submitted_at = rows.iter().filter_map(|row| row.submitted_at).min().unwrap_or(0);
+                    if now_secs() - submitted_at < resubmit_secs() {
+                        continue;
+                    }
 
-    /// 上传内容到Arweave
-    pub async fn upload_to_arweave(&self, data: &[u8]) -> Result<String, ServiceError> {
-        #[derive(Serialize)]
-        struct ArweaveUploadRequest<'a> {
-            data: &'a [u8],
-            tags: Vec<ArweaveTag>,
+                    // 交易被丢弃，重新签名打包上传
+                    log::warn!("Arweave交易{}提交后长时间未上链，重新上传{}条内容", tx_id, rows.len());
+                    self.db
+                        .exec(
+                            "UPDATE storage_outbox SET status = ?, tx_id = NULL, item_id = NULL, confirmations = 0, \
+                             next_attempt_at = ?, updated_at = NOW() WHERE tx_id = ? AND status = ?",
+                            vec![
+                                rbs::to_value!(STORAGE_STATUS_PENDING),
+                                rbs::to_value!(now_secs()),
+                                rbs::to_value!(&tx_id),
+                                rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                            ],
+                        )
+                        .await
+                        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+                    for row in &rows {
+                        self.update_content(row, None, STORAGE_STATUS_PENDING).await?;
+                    }
+                }
+            }
         }
 
-        #[derive(Serialize)]
-        struct ArweaveTag {
-            name: String,
-            value: String,
+        Ok(confirmed)
+    }
+
+    /// 用当前主密钥重新加密旧主密钥加密的数据密钥，返回更新的记录数
+    /// 上链信封中的数据密钥无法修改，旧主密钥仍需保留，直到数据库中不再引用
+    pub async fn rotate_content_keys(&self) -> Result<usize, ServiceError> {
+        let keyring = keyring().map_err(|e| {
+            log::error!("轮换数据密钥失败: {}", e);
+            ServiceError::InternalServerError
+        })?;
+        let keyring = match keyring {
+            Some(keyring) => keyring,
+            None => return Ok(0),
+        };
+
+        let mut rotated = 0;
+        for table in [CONTENT_TABLE_POSTS, CONTENT_TABLE_COMMENTS] {
+            let rows: Vec<ContentKeyRow> = self
+                .db
+                .query_decode(
+                    &format!(
+                        "SELECT id::text AS id, content_key_id, content_wrapped_key FROM {} \
+                         WHERE content_key_id IS NOT NULL AND content_key_id <> ? LIMIT ?",
+                        table
+                    ),
+                    vec![
+                        rbs::to_value!(keyring.active_id()),
+                        rbs::to_value!(KEY_ROTATION_BATCH_SIZE),
+                    ],
+                )
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+            for row in rows {
+                let (key_id, wrapped_key) = match (&row.content_key_id, &row.content_wrapped_key) {
+                    (Some(key_id), Some(wrapped_key)) => (key_id, wrapped_key),
+                    _ => continue,
+                };
+                match keyring.rewrap(key_id, wrapped_key) {
+                    Ok((new_key_id, new_wrapped_key)) => {
+                        save_content_key(
+                            self.db.as_ref(),
+                            table,
+                            &row.id,
+                            Some(key_id),
+                            &new_key_id,
+                            &new_wrapped_key,
+                        )
+                        .await?;
+                        rotated += 1;
+                    }
+                    Err(e) => log::error!("轮换{}/{}的数据密钥失败: {}", table, row.id, e),
+                }
+            }
         }
 
-        #[derive(Deserialize)]
-        struct ArweaveUploadResponse {
-            id: String,
+        Ok(rotated)
+    }
+
+    /// 重新下载上链内容，解密后与数据库中的内容比对
+    pub async fn verify_content(
+        &self,
+        content_table_name: &str,
+        content_id: &str,
+    ) -> Result<ContentVerification, ServiceError> {
+        let (table, row) = self.load_content(content_table_name, content_id).await?;
+        let outbox = self.record_outbox(table, &row.id).await?;
+
+        let mut verification = ContentVerification {
+            content_table: table.to_string(),
+            content_id: row.id.clone(),
+            storage_ref: row.storage_ref.clone(),
+            arweave_tx_id: row.arweave_tx_id.clone(),
+            key_id: row.content_key_id.clone(),
+            matches: false,
+            error: None,
+        };
+        let result = match &row.storage_ref {
+            Some(reference) => self.download_plaintext(table, &row, outbox.as_ref(), reference).await,
+            None => Err("内容尚未上链".to_string()),
+        };
+        match result {
+            Ok(plaintext) if plaintext == row.content.as_bytes() => verification.matches = true,
+            Ok(_) => verification.error = Some("上链内容与数据库不一致".to_string()),
+            Err(e) => verification.error = Some(e),
         }
 
-        // 创建标签，表明内容类型和应用来源
-        let tags = vec![
-            ArweaveTag {
-                name: "Content-Type".to_string(),
-                value: "text/plain".to_string(),
+        Ok(verification)
+    }
+
+    /// 生成内容的完整性证明：重新从存储后端取回正文和媒体文件，与发帖时记录的哈希和引用比对
+    pub async fn content_proof(
+        &self,
+        content_table_name: &str,
+        content_id: &str,
+    ) -> Result<ContentProof, ServiceError> {
+        let (table, row) = self.load_content(content_table_name, content_id).await?;
+        let image_cids = if table == CONTENT_TABLE_POSTS {
+            self.post_image_cids(&row.id).await?
+        } else {
+            Vec::new()
+        };
+        let outbox = self.record_outbox(table, &row.id).await?;
+        let record_backend = self.record_backend(outbox.as_ref());
+
+        let mut proof = ContentProof {
+            content_table: table.to_string(),
+            content_id: row.id.clone(),
+            content_hash: row.content_hash.clone(),
+            storage_status: row.storage_status.clone(),
+            storage_backend: outbox.as_ref().map(|o| o.backend.clone()),
+            storage_ref: row.storage_ref.clone(),
+            storage_url: match (&row.storage_ref, &record_backend) {
+                (Some(reference), Ok(backend)) => Some(backend.url(reference)),
+                _ => None,
             },
-            ArweaveTag {
-                name: "App-Name".to_string(),
-                value: "Web3Social".to_string(),
+            arweave_tx_id: row.arweave_tx_id.clone(),
+            arweave_url: row.arweave_tx_id.as_ref().map(|id| arweave_client().get_url(id)),
+            bundle_tx_id: outbox.as_ref().and_then(|o| o.tx_id.clone()),
+            confirmations: outbox.as_ref().map_or(0, |o| o.confirmations),
+            encrypted: row.content_key_id.is_some(),
+            archived_hash: None,
+            ipfs: Vec::with_capacity(image_cids.len()),
+            status: PROOF_STATUS_VERIFIED.to_string(),
+            error: None,
+            checked_at: now_secs(),
+        };
+
+        // 数据库中的内容应与发帖时记录的哈希一致
+        let expected_hash = content_hash(row.content.as_bytes());
+        if row.content_hash.as_deref().is_some_and(|hash| hash != expected_hash) {
+            proof.status = PROOF_STATUS_MISMATCH.to_string();
+            proof.error = Some("数据库中的内容与发帖时记录的哈希不一致".to_string());
+        }
+
+        // 媒体文件从写入时的后端取回，切换配置前上传的文件仍可校验
+        let media_backends = self.media_backends(table, &row.id).await?;
+        let backends: Vec<Result<Arc<dyn StorageBackend>, String>> = image_cids
+            .iter()
+            .map(|cid| match media_backends.get(cid) {
+                Some(name) => self.backend(name),
+                None => Ok(self.media.clone()),
+            })
+            .collect();
+        let media_results = futures::future::join_all(image_cids.iter().zip(&backends).map(
+            |(cid, backend)| async move {
+                match backend {
+                    Ok(backend) => backend.get(cid).await,
+                    Err(e) => Err(e.clone()),
+                }
             },
-        ];
-
-        // 使用Arweave HTTP API或代理服务上传
-        let response = self.http_client
-            .post(&self.config.arweave.api_url)
-            .header("X-API-KEY", &self.config.arweave.api_key)
-            .json(&ArweaveUploadRequest { data, tags })
-            .send()
+        ))
+        .await;
+        for ((cid, backend), result) in image_cids.iter().zip(&backends).zip(media_results) {
+            proof.ipfs.push(IpfsProof {
+                cid: cid.clone(),
+                url: backend.as_ref().map(|backend| backend.url(cid)).unwrap_or_default(),
+                verified: result.is_ok(),
+                error: result.err(),
+            });
+        }
+
+        let archived = match (&row.storage_ref, row.storage_status.as_str()) {
+            (Some(reference), _) => Some(match &record_backend {
+                Ok(backend) => self.download_from(backend.as_ref(), table, &row, reference).await,
+                Err(e) => Err(e.clone()),
+            }),
+            (None, STORAGE_STATUS_NONE) => None,
+            (None, _) => Some(Err("内容尚未上链".to_string())),
+        };
+        if let Some(archived) = archived {
+            match archived {
+                Ok(data) => proof.archived_hash = Some(content_hash(&data)),
+                Err(e) => proof.error = proof.error.take().or(Some(e)),
+            }
+        }
+
+        if proof.status != PROOF_STATUS_MISMATCH {
+            let ipfs_ok = proof.ipfs.iter().all(|ipfs| ipfs.verified);
+            let status = match &proof.archived_hash {
+                Some(hash) if *hash != expected_hash => {
+                    proof.error = Some("存储后端中的内容与数据库不一致".to_string());
+                    PROOF_STATUS_MISMATCH
+                }
+                Some(_) if ipfs_ok => PROOF_STATUS_VERIFIED,
+                Some(_) => PROOF_STATUS_UNAVAILABLE,
+                None if row.storage_status == STORAGE_STATUS_NONE => {
+                    if !ipfs_ok {
+                        PROOF_STATUS_UNAVAILABLE
+                    } else if proof.ipfs.is_empty() {
+                        PROOF_STATUS_NOT_ARCHIVED
+                    } else {
+                        PROOF_STATUS_VERIFIED
+                    }
+                }
+                // 已确认的内容取不到视为不可用，仍在上传或等待确认的视为处理中
+                None => match row.storage_status.as_str() {
+                    STORAGE_STATUS_CONFIRMED | STORAGE_STATUS_FAILED => PROOF_STATUS_UNAVAILABLE,
+                    _ => PROOF_STATUS_PENDING,
+                },
+            };
+            proof.status = status.to_string();
+        }
+
+        Ok(proof)
+    }
+
+    /// 带缓存的完整性证明，同一内容在 CONTENT_PROOF_CACHE_SECS 内复用上次的结果，避免公开接口反复下载内容
+    /// Redis不可用时直接生成
+    pub async fn cached_content_proof(
+        &self,
+        content_table_name: &str,
+        content_id: &str,
+    ) -> Result<ContentProof, ServiceError> {
+        let key = proof_cache_key(content_table_name, content_id);
+        let mut con = self.redis.get_async_connection().await.ok();
+        if let Some(con) = con.as_mut() {
+            let cached: Option<String> = redis::cmd("GET").arg(&key).query_async(con).await.unwrap_or(None);
+            if let Some(proof) = cached.and_then(|json| serde_json::from_str::<ContentProof>(&json).ok()) {
+                return Ok(proof);
+            }
+        }
+
+        let proof = self.content_proof(content_table_name, content_id).await?;
+        if let (Some(con), Ok(json)) = (con.as_mut(), serde_json::to_string(&proof)) {
+            let result: redis::RedisResult<()> = redis::cmd("SETEX")
+                .arg(&key)
+                .arg(proof_cache_secs())
+                .arg(json)
+                .query_async(con)
+                .await;
+            if let Err(e) = result {
+                log::warn!("写入完整性证明缓存失败: {}", e);
+            }
+        }
+
+        Ok(proof)
+    }
+
+    /// 校验最近上链的内容
+    pub async fn verify_recent(&self, limit: u64) -> Result<Vec<ContentVerification>, ServiceError> {
+        let rows: Vec<OutboxContentRow> = self
+            .db
+            .query_decode(
+                "SELECT content_table, content_id FROM storage_outbox WHERE kind = ? AND status IN (?, ?) \
+                 ORDER BY updated_at DESC LIMIT ?",
+                vec![
+                    rbs::to_value!(OUTBOX_KIND_RECORD),
+                    rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                    rbs::to_value!(STORAGE_STATUS_CONFIRMED),
+                    rbs::to_value!(limit),
+                ],
+            )
             .await
-            .map_err(|e| ServiceError::ExternalService(format!("Arweave服务错误: {}", e)))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            results.push(self.verify_content(&row.content_table, &row.content_id).await?);
+        }
+
+        Ok(results)
+    }
+
+    /// 生成媒体文件访问地址
+    pub fn media_url(&self, reference: &str) -> String {
+        self.media.url(reference)
+    }
+
+    // 发件箱记录按写入时的后端上传和读取，切换配置后旧记录需通过 STORAGE_LEGACY_BACKENDS 保留原后端
+    fn backend(&self, name: &str) -> Result<Arc<dyn StorageBackend>, String> {
+        self.backends
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("存储后端{}未配置", name))
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
-            return Err(ServiceError::ExternalService(format!("Arweave上传失败: {}", error_text)));
+    // 正文所在的存储后端，没有发件箱记录时使用当前配置
+    fn record_backend(&self, outbox: Option<&StorageOutboxEntity>) -> Result<Arc<dyn StorageBackend>, String> {
+        match outbox {
+            Some(outbox) => self.backend(&outbox.backend),
+            None => Ok(self.records.clone()),
         }
+    }
+
+    // 内容最近一条正文发件箱记录
+    async fn record_outbox(
+        &self,
+        table: &str,
+        content_id: &str,
+    ) -> Result<Option<StorageOutboxEntity>, ServiceError> {
+        Ok(self
+            .db
+            .query_decode::<Vec<StorageOutboxEntity>>(
+                "SELECT * FROM storage_outbox WHERE content_table = ? AND content_id = ? AND kind = ? \
+                 ORDER BY created_at DESC LIMIT 1",
+                vec![
+                    rbs::to_value!(table),
+                    rbs::to_value!(content_id),
+                    rbs::to_value!(OUTBOX_KIND_RECORD),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .next())
+    }
 
-        let upload_response = response.json::<ArweaveUploadResponse>().await
-            .map_err(|e| ServiceError::ExternalService(format!("解析Arweave响应失败: {}", e)))?;
+    // 内容引用的媒体文件对应的存储后端
+    async fn media_backends(&self, table: &str, content_id: &str) -> Result<HashMap<String, String>, ServiceError> {
+        let rows: Vec<MediaBackendRow> = self
+            .db
+            .query_decode(
+                "SELECT expected_ref, backend FROM storage_outbox WHERE content_table = ? AND content_id = ? AND kind = ?",
+                vec![
+                    rbs::to_value!(table),
+                    rbs::to_value!(content_id),
+                    rbs::to_value!(OUTBOX_KIND_MEDIA),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        Ok(upload_response.id)
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.expected_ref.map(|reference| (reference, row.backend)))
+            .collect())
     }
 
-    /// 从IPFS获取内容
-    pub async fn get_from_ipfs(&self, cid: &str) -> Result<Vec<u8>, ServiceError> {
-        // 构建IPFS网关URL
-        let url = format!("{}/ipfs/{}", self.config.ipfs.gateway_url, cid);
+    // 读取内容记录，表名只允许已知的内容表
+    async fn load_content(
+        &self,
+        content_table_name: &str,
+        content_id: &str,
+    ) -> Result<(&'static str, StoredContentRow), ServiceError> {
+        let table = content_table(content_table_name)
+            .ok_or_else(|| ServiceError::BadRequest(format!("无效的内容类型: {}", content_table_name)))?;
+        let row = self
+            .db
+            .query_decode::<Vec<StoredContentRow>>(
+                &format!(
+                    "SELECT id::text AS id, content, content_hash, storage_status, storage_ref, arweave_tx_id, content_key_id, \
+                     content_wrapped_key FROM {} WHERE id::text = ?",
+                    table
+                ),
+                vec![rbs::to_value!(content_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ServiceError::NotFound("内容不存在".into()))?;
+
+        Ok((table, row))
+    }
 
-        // 获取内容
-        let response = self.http_client
-            .get(&url)
-            .send()
+    async fn post_image_cids(&self, post_id: &str) -> Result<Vec<String>, ServiceError> {
+        let rows: Vec<PostImagesRow> = self
+            .db
+            .query_decode(
+                "SELECT images_ipfs_cids FROM posts WHERE id::text = ?",
+                vec![rbs::to_value!(post_id)],
+            )
             .await
-            .map_err(|e| ServiceError::ExternalService(format!("IPFS获取错误: {}", e)))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
-            return Err(ServiceError::ExternalService(format!("IPFS内容获取失败: {}", error_text)));
+        Ok(rows
+            .into_iter()
+            .next()
+            .and_then(|row| row.images_ipfs_cids)
+            .unwrap_or_default())
+    }
+
+    // 下载正文，加密内容使用数据库中的数据密钥解密
+    async fn download_plaintext(
+        &self,
+        table: &str,
+        row: &StoredContentRow,
+        outbox: Option<&StorageOutboxEntity>,
+        reference: &str,
+    ) -> Result<Vec<u8>, String> {
+        let backend = self.record_backend(outbox)?;
+        self.download_from(backend.as_ref(), table, row, reference).await
+    }
+
+    async fn download_from(
+        &self,
+        backend: &dyn StorageBackend,
+        table: &str,
+        row: &StoredContentRow,
+        reference: &str,
+    ) -> Result<Vec<u8>, String> {
+        let data = backend.get(reference).await?;
+        let key_id = match &row.content_key_id {
+            Some(key_id) => key_id,
+            None => return Ok(data),
+        };
+
+        let keyring = keyring()?.ok_or("未配置内容加密主密钥，无法解密")?;
+        let wrapped = row
+            .content_wrapped_key
+            .as_deref()
+            .map(|wrapped_key| (key_id.as_str(), wrapped_key));
+        keyring.open(&data, &content_aad(table, &row.id), wrapped)
+    }
+
+    // 逐条写入的内容写入后即可访问，不需要跟踪确认
+    async fn upload_row(&self, row: &StorageOutboxEntity) -> Result<bool, ServiceError> {
+        let result = async {
+            let backend = self.backend(&row.backend)?;
+            backend
+                .put(&decode_payload(row)?, &row.content_type, &decode_tags(row)?)
+                .await
         }
+        .await;
 
-        let bytes = response.bytes().await
-            .map_err(|e| ServiceError::ExternalService(format!("读取IPFS响应失败: {}", e)))?;
+        match result {
+            Ok(reference) if row.expected_ref.as_deref().is_none_or(|expected| expected == reference) => {
+                self.db
+                    .exec(
+                        "UPDATE storage_outbox SET status = ?, item_id = ?, submitted_at = ?, confirmed_at = ?, \
+                         payload = NULL, last_error = NULL, updated_at = NOW() WHERE id::text = ?",
+                        vec![
+                            rbs::to_value!(STORAGE_STATUS_CONFIRMED),
+                            rbs::to_value!(&reference),
+                            rbs::to_value!(now_secs()),
+                            rbs::to_value!(now_secs()),
+                            rbs::to_value!(row.id.to_string()),
+                        ],
+                    )
+                    .await
+                    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+                if row.kind == OUTBOX_KIND_RECORD {
+                    self.update_content(row, Some(&reference), STORAGE_STATUS_CONFIRMED).await?;
+                }
+                Ok(true)
+            }
+            Ok(reference) => {
+                let error = format!(
+                    "{}返回的引用 {} 与发帖时的 {:?} 不一致",
+                    row.backend, reference, row.expected_ref
+                );
+                self.record_failure(row, &error).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                self.record_failure(row, &e).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    // 签名数据项并按大小和数量分批打包上传
+    async fn upload_arweave_rows(&self, rows: Vec<StorageOutboxEntity>) -> Result<usize, ServiceError> {
+        let mut signed: Vec<(StorageOutboxEntity, DataItem)> = Vec::with_capacity(rows.len());
+        for row in rows {
+            match create_data_item(&row) {
+                Ok(item) => signed.push((row, item)),
+                Err(e) => self.record_failure(&row, &e).await?,
+            }
+        }
+
+        let (max_items, max_bytes) = (bundle_max_items(), bundle_max_bytes());
+        let mut submitted = 0;
+        let mut batch: Vec<(StorageOutboxEntity, DataItem)> = Vec::new();
+        let mut batch_bytes = 0;
+        for (row, item) in signed {
+            if !batch.is_empty() && (batch.len() >= max_items || batch_bytes + item.size() > max_bytes) {
+                submitted += self.post_bundle(std::mem::take(&mut batch)).await?;
+                batch_bytes = 0;
+            }
+            batch_bytes += item.size();
+            batch.push((row, item));
+        }
+        if !batch.is_empty() {
+            submitted += self.post_bundle(batch).await?;
+        }
 
-        Ok(bytes.to_vec())
+        Ok(submitted)
     }
 
-    /// 从Arweave获取内容
-    pub async fn get_from_arweave(&self, id: &str) -> Result<Vec<u8>, ServiceError> {
-        // 构建Arweave网关URL
-        let url = format!("{}/{}", self.config.arweave.gateway_url, id);
+    async fn post_bundle(&self, batch: Vec<(StorageOutboxEntity, DataItem)>) -> Result<usize, ServiceError> {
+        let items: Vec<DataItem> = batch.iter().map(|(_, item)| item.clone()).collect();
+        let tx_id = match arweave_client().post_bundle(&items).await {
+            Ok(tx_id) => tx_id,
+            Err(e) => {
+                log::error!("上传Arweave bundle失败: {}", e);
+                for (row, _) in &batch {
+                    self.record_failure(row, &e).await?;
+                }
+                return Ok(0);
+            }
+        };
+        log::info!("已上传Arweave bundle {}，包含{}个数据项", tx_id, batch.len());
 
-        // 获取内容
-        let response = self.http_client
-            .get(&url)
-            .send()
+        for (row, item) in &batch {
+            self.db
+                .exec(
+                    "UPDATE storage_outbox SET status = ?, item_id = ?, tx_id = ?, submitted_at = ?, confirmations = 0, \
+                     last_error = NULL, updated_at = NOW() WHERE id::text = ?",
+                    vec![
+                        rbs::to_value!(STORAGE_STATUS_SUBMITTED),
+                        rbs::to_value!(&item.id),
+                        rbs::to_value!(&tx_id),
+                        rbs::to_value!(now_secs()),
+                        rbs::to_value!(row.id.to_string()),
+                    ],
+                )
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+            // 数据项打包上链后可通过网关按数据项ID访问
+            self.update_content(row, Some(&item.id), STORAGE_STATUS_SUBMITTED).await?;
+        }
+
+        Ok(batch.len())
+    }
+
+    // 记录失败并安排重试，超过最大次数后标记为失败
+    async fn record_failure(&self, row: &StorageOutboxEntity, error: &str) -> Result<(), ServiceError> {
+        let attempts = row.attempts + 1;
+        let failed = attempts >= max_attempts();
+        let delay = (RETRY_BASE_SECS << (attempts - 1).min(20)).min(RETRY_MAX_SECS);
+        log::warn!(
+            "上传{}内容{}/{}失败（第{}次）: {}",
+            row.backend,
+            row.content_table,
+            row.content_id,
+            attempts,
+            error
+        );
+
+        self.db
+            .exec(
+                "UPDATE storage_outbox SET status = ?, attempts = ?, next_attempt_at = ?, last_error = ?, updated_at = NOW() \
+                 WHERE id::text = ?",
+                vec![
+                    rbs::to_value!(if failed { STORAGE_STATUS_FAILED } else { STORAGE_STATUS_PENDING }),
+                    rbs::to_value!(attempts),
+                    rbs::to_value!(now_secs() + delay),
+                    rbs::to_value!(error),
+                    rbs::to_value!(row.id.to_string()),
+                ],
+            )
             .await
-            .map_err(|e| ServiceError::ExternalService(format!("Arweave获取错误: {}", e)))?;
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
-            return Err(ServiceError::ExternalService(format!("Arweave内容获取失败: {}", error_text)));
+        if failed && row.kind == OUTBOX_KIND_RECORD {
+            self.update_content(row, None, STORAGE_STATUS_FAILED).await?;
         }
 
-        let bytes = response.bytes().await
-            .map_err(|e| ServiceError::ExternalService(format!("读取Arweave响应失败: {}", e)))?;
+        Ok(())
+    }
 
-        Ok(bytes.to_vec())
+    // 同步内容表的存储状态，reference为空时保留原值；Arweave的数据项ID同时记录在arweave_tx_id
+    async fn update_content(
+        &self,
+        row: &StorageOutboxEntity,
+        reference: Option<&str>,
+        status: &str,
+    ) -> Result<(), ServiceError> {
+        let table = match content_table(&row.content_table) {
+            Some(table) => table,
+            None => {
+                log::warn!("发件箱记录{}的内容表{}无效", row.id, row.content_table);
+                return Ok(());
+            }
+        };
+        let arweave_tx_id = reference.filter(|_| row.backend == BACKEND_ARWEAVE);
+
+        let sql = format!(
+            "UPDATE {} SET storage_ref = COALESCE(?, storage_ref), arweave_tx_id = COALESCE(?, arweave_tx_id), \
+             storage_status = ?, updated_at = NOW() WHERE id::text = ?",
+            table
+        );
+        self.db
+            .exec(
+                &sql,
+                vec![
+                    rbs::to_value!(reference),
+                    rbs::to_value!(arweave_tx_id),
+                    rbs::to_value!(status),
+                    rbs::to_value!(&row.content_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
     }
-    
-    /// 生成IPFS内容URL
-    pub fn get_ipfs_url(&self, cid: &str) -> String {
-        format!("{}/ipfs/{}", self.config.ipfs.gateway_url, cid)
+}
+
+/// 内容哈希：正文UTF-8字节的SHA-256，十六进制编码
+pub fn content_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn proof_cache_key(content_table: &str, content_id: &str) -> String {
+    format!("content_proof:{}:{}", content_table, content_id)
+}
+
+fn proof_cache_secs() -> u64 {
+    env::var("CONTENT_PROOF_CACHE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_PROOF_CACHE_SECS)
+}
+
+// 表名不能作为参数绑定，只允许已知的内容表
+fn content_table(name: &str) -> Option<&'static str> {
+    match name {
+        CONTENT_TABLE_POSTS => Some(CONTENT_TABLE_POSTS),
+        CONTENT_TABLE_COMMENTS => Some(CONTENT_TABLE_COMMENTS),
+        _ => None,
     }
-    
-    /// 生成Arweave内容URL
-    pub fn get_arweave_url(&self, id: &str) -> String {
-        format!("{}/{}", self.config.arweave.gateway_url, id)
+}
+
+// 保存内容的主密钥ID和数据密钥，轮换时只更新仍使用旧主密钥的记录
+async fn save_content_key(
+    executor: &dyn Executor,
+    content_table_name: &str,
+    content_id: &str,
+    old_key_id: Option<&str>,
+    key_id: &str,
+    wrapped_key: &str,
+) -> Result<(), ServiceError> {
+    let table = content_table(content_table_name)
+        .ok_or_else(|| ServiceError::BadRequest(format!("无效的内容类型: {}", content_table_name)))?;
+
+    let sql = format!(
+        "UPDATE {} SET content_key_id = ?, content_wrapped_key = ? \
+         WHERE id::text = ? AND (?::text IS NULL OR content_key_id = ?)",
+        table
+    );
+    executor
+        .exec(
+            &sql,
+            vec![
+                rbs::to_value!(key_id),
+                rbs::to_value!(wrapped_key),
+                rbs::to_value!(content_id),
+                rbs::to_value!(old_key_id),
+                rbs::to_value!(old_key_id),
+            ],
+        )
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+async fn insert_outbox(executor: &dyn Executor, row: &StorageOutboxEntity) -> Result<(), ServiceError> {
+    StorageOutboxEntity::insert(executor, row)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+fn outbox_row(
+    content_table: &str,
+    content_id: &str,
+    kind: &str,
+    backend: &str,
+    data: &[u8],
+    content_type: &str,
+    tags: Vec<Tag>,
+) -> Result<StorageOutboxEntity, ServiceError> {
+    let tags: Vec<(String, String)> = tags.into_iter().map(|tag| (tag.name, tag.value)).collect();
+
+    Ok(StorageOutboxEntity {
+        id: Uuid::new_v4(),
+        content_table: content_table.to_string(),
+        content_id: content_id.to_string(),
+        kind: kind.to_string(),
+        backend: backend.to_string(),
+        payload: Some(STANDARD.encode(data)),
+        content_type: content_type.to_string(),
+        tags: serde_json::to_string(&tags).map_err(|e| ServiceError::BadRequest(e.to_string()))?,
+        expected_ref: None,
+        status: STORAGE_STATUS_PENDING.to_string(),
+        attempts: 0,
+        next_attempt_at: now_secs(),
+        last_error: None,
+        item_id: None,
+        tx_id: None,
+        confirmations: 0,
+        submitted_at: None,
+        confirmed_at: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    })
+}
+
+fn decode_payload(row: &StorageOutboxEntity) -> Result<Vec<u8>, String> {
+    let payload = row.payload.as_deref().ok_or("发件箱内容已清除")?;
+    STANDARD
+        .decode(payload)
+        .map_err(|e| format!("发件箱内容解码失败: {}", e))
+}
+
+fn decode_tags(row: &StorageOutboxEntity) -> Result<Vec<Tag>, String> {
+    let tags: Vec<(String, String)> =
+        serde_json::from_str(&row.tags).map_err(|e| format!("发件箱标签解析失败: {}", e))?;
+
+    Ok(tags.iter().map(|(name, value)| Tag::new(name, value)).collect())
+}
+
+fn create_data_item(row: &StorageOutboxEntity) -> Result<DataItem, String> {
+    arweave_client().create_data_item(&decode_payload(row)?, &decode_tags(row)?)
+}
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn max_attempts() -> i32 {
+    env::var("STORAGE_OUTBOX_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse::<i32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+fn confirmation_depth() -> u64 {
+    env::var("ARWEAVE_CONFIRMATION_DEPTH")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CONFIRMATION_DEPTH)
+}
+
+fn resubmit_secs() -> i64 {
+    env::var("ARWEAVE_RESUBMIT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_RESUBMIT_SECS)
+}
+
+fn bundle_max_items() -> usize {
+    env::var("ARWEAVE_BUNDLE_MAX_ITEMS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_BUNDLE_MAX_ITEMS)
+}
+
+fn bundle_max_bytes() -> usize {
+    env::var("ARWEAVE_BUNDLE_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_BUNDLE_MAX_BYTES)
+}
+
+/// 发件箱上传任务的间隔（秒）
+pub fn outbox_interval_secs() -> u64 {
+    env::var("STORAGE_OUTBOX_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_OUTBOX_INTERVAL_SECS)
+}
+
+/// Arweave确认检查任务的间隔（秒）
+pub fn confirm_interval_secs() -> u64 {
+    env::var("ARWEAVE_CONFIRM_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_CONFIRM_INTERVAL_SECS)
+}
+
+/// 数据密钥轮换任务的间隔（秒）
+pub fn key_rotation_interval_secs() -> u64 {
+    env::var("CONTENT_KEY_ROTATION_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_KEY_ROTATION_INTERVAL_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::storage_backend::{sha256_hex, MemoryStorage, BACKEND_MEMORY};
+    use rbatis::executor::Executor;
+    use std::sync::OnceLock;
+
+    // 需要已执行迁移的PostgreSQL测试库，通过 TEST_DATABASE_URL 配置
+    // 以 cargo test -- --ignored 运行
+    async fn test_db() -> Arc<RBatis> {
+        let url = env::var("TEST_DATABASE_URL")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .expect("未配置TEST_DATABASE_URL");
+        crate::config::rbatis_config::init_rbatis(&url).await
+    }
+
+    // 各测试共用同一个内存存储，后台处理到其他测试的记录时内容仍可取回
+    fn service(db: Arc<RBatis>) -> StorageService {
+        static MEMORY: OnceLock<Arc<MemoryStorage>> = OnceLock::new();
+        let memory = MEMORY.get_or_init(|| Arc::new(MemoryStorage::new())).clone();
+        let redis = Arc::new(RedisClient::open("redis://127.0.0.1:1").unwrap());
+
+        StorageService::with_backends(db, redis, memory.clone(), memory)
+    }
+
+    // 创建测试用户和帖子，返回帖子ID
+    async fn insert_post(executor: &dyn Executor, content: &str) -> String {
+        let (user_id, post_id) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        executor
+            .exec(
+                "INSERT INTO users (id, username, wallet_address, wallet_chain) VALUES (?::uuid, ?, ?, 'ethereum')",
+                vec![
+                    rbs::to_value!(&user_id),
+                    rbs::to_value!(format!("outbox-{}", &user_id[..8])),
+                    rbs::to_value!(format!("0x{}", &user_id.replace('-', "")[..32])),
+                ],
+            )
+            .await
+            .unwrap();
+        executor
+            .exec(
+                "INSERT INTO posts (id, user_id, content, content_hash) VALUES (?::uuid, ?::uuid, ?, ?)",
+                vec![
+                    rbs::to_value!(&post_id),
+                    rbs::to_value!(&user_id),
+                    rbs::to_value!(content),
+                    rbs::to_value!(content_hash(content.as_bytes())),
+                ],
+            )
+            .await
+            .unwrap();
+
+        post_id
     }
-}
\ No newline at end of file
+
+    async fn outbox_rows(db: &RBatis, content_id: &str) -> Vec<StorageOutboxEntity> {
+        db.query_decode(
+            "SELECT * FROM storage_outbox WHERE content_id = ? ORDER BY kind",
+            vec![rbs::to_value!(content_id)],
+        )
+        .await
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    #[ignore = "需要PostgreSQL测试库"]
+    async fn record_round_trip() {
+        let db = test_db().await;
+        let storage = service(db.clone());
+        let content = format!("outbox round trip {}", Uuid::new_v4());
+        let post_id = insert_post(db.as_ref(), &content).await;
+
+        storage
+            .enqueue_record(
+                db.as_ref(),
+                CONTENT_TABLE_POSTS,
+                &post_id,
+                content.as_bytes(),
+                "text/plain",
+                vec![Tag::new("Signed-Message", "signed")],
+            )
+            .await
+            .unwrap();
+        let rows = outbox_rows(&db, &post_id).await;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].backend, BACKEND_MEMORY);
+        assert_eq!(decode_payload(&rows[0]).unwrap(), content.as_bytes());
+        let tags = decode_tags(&rows[0]).unwrap();
+        assert!(tags.contains(&Tag::new("Content-SHA256", &content_hash(content.as_bytes()))));
+        // 未加密的内容发布签名标签
+        assert!(tags.contains(&Tag::new("Signed-Message", "signed")));
+
+        storage.process_outbox().await.unwrap();
+
+        // 确认后清除内容副本，引用写入storage_ref，不占用arweave_tx_id
+        let rows = outbox_rows(&db, &post_id).await;
+        assert_eq!(rows[0].status, STORAGE_STATUS_CONFIRMED);
+        assert!(rows[0].payload.is_none());
+        let reference = sha256_hex(content.as_bytes());
+        assert_eq!(rows[0].item_id.as_deref(), Some(reference.as_str()));
+
+        let verification = storage.verify_content(CONTENT_TABLE_POSTS, &post_id).await.unwrap();
+        assert!(verification.matches, "{:?}", verification.error);
+        assert_eq!(verification.storage_ref.as_deref(), Some(reference.as_str()));
+        assert_eq!(verification.arweave_tx_id, None);
+
+        let proof = storage.cached_content_proof(CONTENT_TABLE_POSTS, &post_id).await.unwrap();
+        assert_eq!(proof.status, PROOF_STATUS_VERIFIED);
+        assert_eq!(proof.storage_status, STORAGE_STATUS_CONFIRMED);
+        assert_eq!(proof.storage_backend.as_deref(), Some(BACKEND_MEMORY));
+        assert_eq!(proof.storage_url, Some(format!("memory://{}", reference)));
+        assert_eq!(proof.arweave_url, None);
+        assert_eq!(proof.archived_hash, proof.content_hash);
+    }
+
+    #[actix_web::test]
+    #[ignore = "需要PostgreSQL测试库"]
+    async fn media_round_trip() {
+        let db = test_db().await;
+        let storage = service(db.clone());
+        let data = Uuid::new_v4().as_bytes().repeat(100);
+        let post_id = insert_post(db.as_ref(), "outbox media").await;
+
+        let reference = storage
+            .enqueue_media(db.as_ref(), CONTENT_TABLE_POSTS, &post_id, &data, "image/png")
+            .await
+            .unwrap();
+        assert_eq!(reference, sha256_hex(&data));
+
+        storage.process_outbox().await.unwrap();
+
+        let rows = outbox_rows(&db, &post_id).await;
+        assert_eq!(rows[0].kind, OUTBOX_KIND_MEDIA);
+        assert_eq!(rows[0].status, STORAGE_STATUS_CONFIRMED);
+        assert_eq!(storage.media.get(&reference).await.unwrap(), data);
+    }
+
+    #[actix_web::test]
+    #[ignore = "需要PostgreSQL测试库"]
+    async fn rollback_discards_outbox_row() {
+        let db = test_db().await;
+        let storage = service(db.clone());
+
+        let tx = db.acquire_begin().await.unwrap();
+        let post_id = insert_post(&tx, "outbox rollback").await;
+        storage
+            .enqueue_record(&tx, CONTENT_TABLE_POSTS, &post_id, b"outbox rollback", "text/plain", Vec::new())
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert!(outbox_rows(&db, &post_id).await.is_empty());
+        assert!(matches!(
+            storage.verify_content(CONTENT_TABLE_POSTS, &post_id).await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+}
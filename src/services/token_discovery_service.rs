@@ -0,0 +1,314 @@
+use crate::blockchain::chains::{self, EvmChain};
+use crate::blockchain::ethereum;
+use crate::models::asset::Asset;
+use crate::models::rbatis_entities::{TokenScanCheckpointEntity, WalletTokenEntity};
+use crate::utils::error::ServiceError;
+use rbatis::RBatis;
+use std::env;
+use std::sync::Arc;
+
+// 查询持仓时最多扫描的区块数，新钱包从最新区块往前扫描这么多区块，
+// 通过 {链简称}_DISCOVERY_RECENT_BLOCKS 配置，如 ETH_DISCOVERY_RECENT_BLOCKS
+const DEFAULT_RECENT_BLOCKS: u64 = 100_000;
+// 后台回溯时每个钱包每次最多扫描的区块数
+const MAX_BACKFILL_BLOCKS_PER_RUN: u64 = 500_000;
+// 后台回溯每次处理的钱包数
+const BACKFILL_BATCH_SIZE: i64 = 50;
+// 后台回溯的间隔（秒），通过 TOKEN_BACKFILL_INTERVAL_SECS 配置
+const DEFAULT_BACKFILL_INTERVAL_SECS: u64 = 300;
+
+/// 代币发现服务，通过Transfer日志和常见代币列表找出钱包在各EVM链上的全部ERC20持仓
+pub struct TokenDiscoveryService {
+    db: Arc<RBatis>,
+}
+
+impl TokenDiscoveryService {
+    pub fn new(db: Arc<RBatis>) -> Self {
+        Self { db }
+    }
+
+    /// 获取钱包在指定链上的原生币和ERC20持仓
+    pub async fn get_chain_holdings(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<Asset>, ServiceError> {
+        let mut assets = Vec::new();
+
+        if let Some(native) = ethereum::get_native_asset(chain_id, wallet_address)
+            .await
+            .map_err(ServiceError::ExternalService)?
+        {
+            assets.push(native);
+        }
+        assets.extend(self.get_erc20_holdings(chain_id, wallet_address).await?);
+
+        Ok(assets)
+    }
+
+    /// 获取钱包在指定链上完整的ERC20持仓
+    pub async fn get_erc20_holdings(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<Asset>, ServiceError> {
+        let wallet_address = wallet_address.to_lowercase();
+        let tokens = self.discover_tokens(chain_id, &wallet_address).await?;
+
+        ethereum::get_erc20_assets(chain_id, &wallet_address, &tokens)
+            .await
+            .map_err(ServiceError::ExternalService)
+    }
+
+    /// 从上次的检查点继续扫描Transfer日志，返回已发现代币与常见代币的并集
+    pub async fn discover_tokens(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<String>, ServiceError> {
+        let chain = chains::require_chain(chain_id).map_err(ServiceError::BadRequest)?;
+        let latest_block = ethereum::get_block_number(chain_id)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+
+        // 新钱包先扫描最近的区块，更早的区块由后台任务回溯；已有检查点时继续向后扫描
+        let window = recent_blocks(chain);
+        let checkpoint = self.get_checkpoint(chain_id, wallet_address).await?;
+        let (from_block, to_block) = match &checkpoint {
+            Some(checkpoint) => {
+                let from_block = checkpoint.last_scanned_block as u64 + 1;
+                (from_block, latest_block.min(from_block + window - 1))
+            }
+            None => {
+                let from_block = latest_block.saturating_sub(window - 1).max(discovery_start_block(chain));
+                (from_block, latest_block)
+            }
+        };
+
+        if from_block <= to_block {
+            self.scan_and_save(chain_id, wallet_address, from_block, to_block).await?;
+            match &checkpoint {
+                Some(_) => self.update_last_scanned(chain_id, wallet_address, to_block).await?,
+                None => {
+                    let complete = from_block <= discovery_start_block(chain);
+                    self.insert_checkpoint(chain_id, wallet_address, from_block, to_block, complete)
+                        .await?
+                }
+            }
+        }
+
+        let mut tokens = ethereum::curated_tokens(chain_id);
+        for token in self.get_wallet_tokens(chain_id, wallet_address).await? {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// 获取钱包已发现的代币合约
+    async fn get_wallet_tokens(&self, chain_id: i32, wallet_address: &str) -> Result<Vec<String>, ServiceError> {
+        let entities: Vec<WalletTokenEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM wallet_tokens WHERE wallet_address = ? AND chain_id = ?",
+                vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(entities.into_iter().map(|e| e.contract_address).collect())
+    }
+
+    async fn save_wallet_token(&self, chain_id: i32, wallet_address: &str, token: &str) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "INSERT INTO wallet_tokens (wallet_address, chain_id, contract_address, created_at) \
+                 VALUES (?, ?, ?, NOW()) ON CONFLICT DO NOTHING",
+                vec![
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_id),
+                    rbs::to_value!(token),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get_checkpoint(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+    ) -> Result<Option<TokenScanCheckpointEntity>, ServiceError> {
+        let checkpoints: Vec<TokenScanCheckpointEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM token_scan_checkpoints WHERE wallet_address = ? AND chain_id = ?",
+                vec![rbs::to_value!(wallet_address), rbs::to_value!(chain_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(checkpoints.into_iter().next())
+    }
+
+    /// 后台回溯：对最近扫描过但尚未回溯到起始区块的钱包，向前扫描更早的区块
+    pub async fn backfill(&self) -> Result<usize, ServiceError> {
+        let checkpoints: Vec<TokenScanCheckpointEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM token_scan_checkpoints WHERE NOT backfill_complete ORDER BY updated_at LIMIT ?",
+                vec![rbs::to_value!(BACKFILL_BATCH_SIZE)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let mut processed = 0;
+        for checkpoint in checkpoints {
+            let Some(chain) = chains::get_chain(checkpoint.chain_id).filter(|c| c.is_configured()) else {
+                continue;
+            };
+            let start_block = discovery_start_block(chain);
+            let first_block = checkpoint.first_scanned_block as u64;
+
+            let result = if first_block <= start_block {
+                Ok(start_block)
+            } else {
+                let from_block = first_block.saturating_sub(MAX_BACKFILL_BLOCKS_PER_RUN).max(start_block);
+                self.scan_and_save(checkpoint.chain_id, &checkpoint.wallet_address, from_block, first_block - 1)
+                    .await
+                    .map(|_| from_block)
+            };
+
+            // 单个钱包失败不影响其他钱包，下次继续
+            match result {
+                Ok(from_block) => {
+                    self.update_first_scanned(
+                        checkpoint.chain_id,
+                        &checkpoint.wallet_address,
+                        from_block,
+                        from_block <= start_block,
+                    )
+                    .await?;
+                    processed += 1;
+                }
+                Err(e) => log::warn!(
+                    "回溯钱包{}在{}上的代币失败: {}",
+                    checkpoint.wallet_address,
+                    chain.name,
+                    e
+                ),
+            }
+        }
+
+        Ok(processed)
+    }
+
+    // 扫描区间内的Transfer日志并保存发现的代币
+    async fn scan_and_save(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<(), ServiceError> {
+        let found = ethereum::scan_transfer_tokens(chain_id, wallet_address, from_block, to_block)
+            .await
+            .map_err(ServiceError::ExternalService)?;
+
+        for token in found {
+            self.save_wallet_token(chain_id, wallet_address, &token).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn insert_checkpoint(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+        first_scanned_block: u64,
+        last_scanned_block: u64,
+        backfill_complete: bool,
+    ) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "INSERT INTO token_scan_checkpoints \
+                 (wallet_address, chain_id, first_scanned_block, last_scanned_block, backfill_complete, updated_at) \
+                 VALUES (?, ?, ?, ?, ?, NOW()) ON CONFLICT DO NOTHING",
+                vec![
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_id),
+                    rbs::to_value!(first_scanned_block as i64),
+                    rbs::to_value!(last_scanned_block as i64),
+                    rbs::to_value!(backfill_complete),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn update_last_scanned(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+        last_scanned_block: u64,
+    ) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "UPDATE token_scan_checkpoints SET last_scanned_block = GREATEST(last_scanned_block, ?), \
+                 updated_at = NOW() WHERE wallet_address = ? AND chain_id = ?",
+                vec![
+                    rbs::to_value!(last_scanned_block as i64),
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn update_first_scanned(
+        &self,
+        chain_id: i32,
+        wallet_address: &str,
+        first_scanned_block: u64,
+        backfill_complete: bool,
+    ) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "UPDATE token_scan_checkpoints SET first_scanned_block = LEAST(first_scanned_block, ?), \
+                 backfill_complete = ?, updated_at = NOW() WHERE wallet_address = ? AND chain_id = ?",
+                vec![
+                    rbs::to_value!(first_scanned_block as i64),
+                    rbs::to_value!(backfill_complete),
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// 后台回溯代币的间隔（秒）
+pub fn backfill_interval_secs() -> u64 {
+    env::var("TOKEN_BACKFILL_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_BACKFILL_INTERVAL_SECS)
+}
+
+// 查询持仓时扫描的最近区块数
+fn recent_blocks(chain: &EvmChain) -> u64 {
+    env::var(format!("{}_DISCOVERY_RECENT_BLOCKS", chain.code))
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&v| v > 0)
+        .unwrap_or(DEFAULT_RECENT_BLOCKS)
+}
+
+// 回溯的起始区块，通过 {链简称}_DISCOVERY_START_BLOCK 配置，如 ETH_DISCOVERY_START_BLOCK
+fn discovery_start_block(chain: &EvmChain) -> u64 {
+    env::var(format!("{}_DISCOVERY_START_BLOCK", chain.code))
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,315 @@
+use crate::blockchain::{chains, ethereum, solana};
+use crate::models::asset::{AssetMovement, BalanceChange, TransactionVerification};
+use crate::models::rbatis_entities::TradeProofEntity;
+use crate::services::price_service::PriceService;
+use crate::utils::error::{is_unique_violation, ServiceError};
+use rbatis::rbdc::datetime::DateTime;
+use rbatis::RBatis;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// 交易凭证状态
+pub const TRADE_STATUS_VERIFIED: &str = "verified";
+pub const TRADE_STATUS_FLAGGED: &str = "flagged";
+
+/// 交易凭证服务，验证帖子引用的链上交易并解析为结构化交易信息
+pub struct TradeService {
+    db: Arc<RBatis>,
+    price_service: Arc<PriceService>,
+}
+
+impl TradeService {
+    pub fn new(db: Arc<RBatis>, price_service: Arc<PriceService>) -> Self {
+        Self { db, price_service }
+    }
+
+    /// 验证交易属于作者钱包，并生成交易凭证
+    pub async fn verify_trade(
+        &self,
+        user_id: &str,
+        wallet_address: &str,
+        chain: &str,
+        tx_hash: &str,
+    ) -> Result<TradeProofEntity, ServiceError> {
+        // 同一交易哈希的不同写法按同一交易处理
+        let tx_hash = normalize_tx_hash(chain, tx_hash)?;
+        let tx_hash = tx_hash.as_str();
+
+        // 已验证过的交易直接复用，但仍需确认属于当前用户
+        if let Some(proof) = self.get_trade_proof(chain, tx_hash).await? {
+            return owned_proof(proof, user_id);
+        }
+
+        let verification = match chain {
+            "SOL" => solana::verify_transaction(tx_hash)
+                .await
+                .map_err(ServiceError::ExternalService)?,
+            _ => {
+                // 其余按EVM链简称查找，如 ETH、ARB、BASE
+                let evm_chain = chains::get_chain_by_code(chain)
+                    .ok_or_else(|| ServiceError::BadRequest("不支持的链类型".into()))?;
+                ethereum::verify_transaction(evm_chain.chain_id, tx_hash)
+                    .await
+                    .map_err(ServiceError::ExternalService)?
+            }
+        };
+
+        if verification.status != "success" {
+            return Err(ServiceError::BadRequest("交易执行失败，无法作为交易凭证".into()));
+        }
+
+        // 只能引用自己钱包发起的交易
+        if !is_same_address(chain, &verification.from_address, wallet_address) {
+            return Err(ServiceError::BadRequest("该交易不是由当前用户的钱包发起".into()));
+        }
+
+        let mut proof = self.build_trade_proof(user_id, &verification);
+        proof.value_usd = self.trade_value_usd(&proof).await;
+
+        if let Err(e) = TradeProofEntity::insert(self.db.as_ref(), &proof).await {
+            let message = e.to_string();
+            if !is_unique_violation(&message) {
+                return Err(ServiceError::DatabaseError(message));
+            }
+            // 并发验证同一交易时，以先写入的凭证为准
+            return match self.get_trade_proof(&proof.chain, &proof.tx_hash).await? {
+                Some(existing) => owned_proof(existing, user_id),
+                None => Err(ServiceError::DatabaseError(message)),
+            };
+        }
+
+        Ok(proof)
+    }
+
+    /// 查询交易凭证
+    pub async fn get_trade_proof(
+        &self,
+        chain: &str,
+        tx_hash: &str,
+    ) -> Result<Option<TradeProofEntity>, ServiceError> {
+        let proofs: Vec<TradeProofEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM trade_proofs WHERE chain = ? AND tx_hash = ?",
+                vec![rbs::to_value!(chain), rbs::to_value!(tx_hash)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(proofs.into_iter().next())
+    }
+
+    /// 将交易验证结果解析为结构化交易
+    fn build_trade_proof(
+        &self,
+        user_id: &str,
+        verification: &TransactionVerification,
+    ) -> TradeProofEntity {
+        let mut proof = TradeProofEntity {
+            id: Uuid::new_v4(),
+            user_id: user_id.to_string(),
+            chain: verification.chain.clone(),
+            tx_hash: verification.transaction_hash.clone(),
+            from_address: verification.from_address.clone(),
+            to_address: verification.to_address.clone(),
+            block_number: verification.block_number as i64,
+            block_time: verification.timestamp,
+            token_in: None,
+            token_in_symbol: None,
+            amount_in: None,
+            token_out: None,
+            token_out_symbol: None,
+            amount_out: None,
+            dex: verification.dex.clone(),
+            value_usd: None, // 解析出交易资产后再按价格计算
+            status: TRADE_STATUS_VERIFIED.to_string(),
+            created_at: DateTime::now(),
+        };
+
+        // 按兑换路径的顺序，发起钱包最先转出的资产为卖出资产，最后收到的资产为买入资产
+        let flows = if verification.balance_changes.is_empty() {
+            wallet_flows(
+                &verification.chain,
+                &verification.movements,
+                &verification.from_address,
+            )
+        } else {
+            wallet_flows_from_changes(&verification.balance_changes, &verification.from_address)
+        };
+        if let Some(outflow) = flows.iter().find(|f| f.sent > f.received) {
+            proof.token_in = outflow.token_address.clone();
+            proof.token_in_symbol = outflow.token_symbol.clone();
+            proof.amount_in = Some(outflow.sent - outflow.received);
+        }
+        if let Some(inflow) = flows.iter().rev().find(|f| f.received > f.sent) {
+            proof.token_out = inflow.token_address.clone();
+            proof.token_out_symbol = inflow.token_symbol.clone();
+            proof.amount_out = Some(inflow.received - inflow.sent);
+        }
+
+        // 路由合约通过内部交易转出的原生币等在日志中不可见，按首个和最后一个兑换补全
+        if proof.amount_in.is_none() {
+            if let Some(swap) = verification.swaps.first().filter(|s| s.amount_in.is_some()) {
+                proof.token_in = swap.token_in.clone();
+                proof.token_in_symbol = swap.token_in_symbol.clone();
+                proof.amount_in = swap.amount_in;
+            }
+        }
+        if proof.amount_out.is_none() {
+            if let Some(swap) = verification.swaps.last().filter(|s| s.amount_out.is_some()) {
+                proof.token_out = swap.token_out.clone();
+                proof.token_out_symbol = swap.token_out_symbol.clone();
+                proof.amount_out = swap.amount_out;
+            }
+        }
+
+        // 无法解析出任何资产变动的交易仅标记，不作为已验证交易展示
+        if proof.amount_in.is_none() && proof.amount_out.is_none() {
+            proof.status = TRADE_STATUS_FLAGGED.to_string();
+        }
+
+        proof
+    }
+
+    /// 按卖出资产（没有时按买入资产）在交易区块时间的价格估算交易的美元价值
+    async fn trade_value_usd(&self, proof: &TradeProofEntity) -> Option<f64> {
+        let chain_id = if proof.chain == "SOL" {
+            solana::SOLANA_CHAIN_ID
+        } else {
+            chains::get_chain_by_code(&proof.chain)?.chain_id
+        };
+        let (token, amount) = match (proof.amount_in, proof.amount_out) {
+            (Some(amount), _) => (proof.token_in.as_deref(), amount),
+            (None, Some(amount)) => (proof.token_out.as_deref(), amount),
+            (None, None) => return None,
+        };
+
+        match self
+            .price_service
+            .get_historical_price(chain_id, token, proof.block_time)
+            .await
+        {
+            Ok(price) => price.map(|p| p * amount),
+            Err(e) => {
+                log::warn!("计算交易{}美元价值失败: {}", proof.tx_hash, e);
+                None
+            }
+        }
+    }
+}
+
+// 钱包在交易中某一资产的流入流出汇总
+struct WalletFlow {
+    token_address: Option<String>,
+    token_symbol: Option<String>,
+    sent: f64,
+    received: f64,
+}
+
+// 按资产汇总钱包的流入流出，保持资产首次出现的顺序
+fn wallet_flows(chain: &str, movements: &[AssetMovement], wallet: &str) -> Vec<WalletFlow> {
+    let mut flows: Vec<WalletFlow> = Vec::new();
+
+    for movement in movements {
+        let amount = match movement.amount {
+            Some(amount) => amount,
+            None => continue,
+        };
+        let is_out = is_same_address(chain, &movement.from_address, wallet);
+        let is_in = is_same_address(chain, &movement.to_address, wallet);
+        if is_out == is_in {
+            continue;
+        }
+
+        let index = match flows
+            .iter()
+            .position(|f| f.token_address == movement.token_address)
+        {
+            Some(index) => index,
+            None => {
+                flows.push(WalletFlow {
+                    token_address: movement.token_address.clone(),
+                    token_symbol: movement.token_symbol.clone(),
+                    sent: 0.0,
+                    received: 0.0,
+                });
+                flows.len() - 1
+            }
+        };
+
+        if is_out {
+            flows[index].sent += amount;
+        } else {
+            flows[index].received += amount;
+        }
+    }
+
+    flows
+}
+
+// 按账户余额变动汇总钱包的流入流出（Solana）
+fn wallet_flows_from_changes(changes: &[BalanceChange], wallet: &str) -> Vec<WalletFlow> {
+    let mut flows: Vec<WalletFlow> = Vec::new();
+
+    for change in changes.iter().filter(|c| c.owner == wallet) {
+        let index = match flows.iter().position(|f| f.token_address == change.mint) {
+            Some(index) => index,
+            None => {
+                flows.push(WalletFlow {
+                    token_address: change.mint.clone(),
+                    token_symbol: change.token_symbol.clone(),
+                    sent: 0.0,
+                    received: 0.0,
+                });
+                flows.len() - 1
+            }
+        };
+
+        if change.delta < 0.0 {
+            flows[index].sent += -change.delta;
+        } else {
+            flows[index].received += change.delta;
+        }
+    }
+
+    flows
+}
+
+// 已有凭证只能由引用该交易的用户复用
+fn owned_proof(proof: TradeProofEntity, user_id: &str) -> Result<TradeProofEntity, ServiceError> {
+    if proof.user_id != user_id {
+        return Err(ServiceError::BadRequest("该交易已被其他用户引用".into()));
+    }
+    Ok(proof)
+}
+
+// 规范化交易哈希：EVM为0x开头的小写十六进制，Solana为base58签名
+fn normalize_tx_hash(chain: &str, tx_hash: &str) -> Result<String, ServiceError> {
+    let tx_hash = tx_hash.trim();
+    let invalid = || ServiceError::BadRequest("无效的交易哈希".into());
+
+    if chain == "SOL" {
+        return Signature::from_str(tx_hash)
+            .map(|s| s.to_string())
+            .map_err(|_| invalid());
+    }
+
+    let hex = tx_hash
+        .strip_prefix("0x")
+        .or_else(|| tx_hash.strip_prefix("0X"))
+        .unwrap_or(tx_hash);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    Ok(format!("0x{}", hex.to_lowercase()))
+}
+
+// 比较地址，EVM地址不区分大小写
+fn is_same_address(chain: &str, a: &str, b: &str) -> bool {
+    match chain {
+        "SOL" => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::models::rbatis_entities::{UserEntity, UserProfileEntity};
+use crate::models::auth::Challenge;
+use crate::models::rbatis_entities::{AuthChallengeEntity, UserEntity, UserProfileEntity};
+use crate::utils::authorship;
+use crate::utils::crypto;
 use crate::utils::error::ServiceError;
 use crate::utils::jwt;
 use chrono::{DateTime as ChronoDateTime, Utc};
@@ -7,6 +10,9 @@ use rbatis::RBatis;
 use std::sync::Arc;
 use uuid::Uuid;
 
+// 登录消息的首行，与作者签名的规范消息区分，内容签名不能用于登录
+const LOGIN_MESSAGE_HEADER: &str = "Web3Social login";
+
 /// 用户服务，处理用户身份和资料管理
 pub struct UserService {
     db: Arc<RBatis>,
@@ -17,16 +23,72 @@ impl UserService {
         Self { db }
     }
 
-    /// 使用钱包签名登录或注册
+    /// 生成一次性登录挑战，返回挑战和钱包需要签名的登录消息
+    pub async fn create_login_challenge(
+        &self,
+        wallet_address: String,
+        chain_type: String,
+    ) -> Result<(Challenge, String), ServiceError> {
+        check_chain_type(&chain_type)?;
+        if wallet_address.trim().is_empty() || wallet_address.len() > 100 {
+            return Err(ServiceError::BadRequest("无效的钱包地址".into()));
+        }
+
+        // 顺带清理过期的挑战
+        self.db
+            .exec("DELETE FROM login_challenges WHERE expires_at < NOW()", vec![])
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let challenge = Challenge::new(wallet_address, chain_type, crypto::generate_nonce());
+        self.db
+            .exec(
+                "INSERT INTO login_challenges (id, wallet_address, wallet_chain, nonce, created_at, expires_at) \
+                 VALUES (?::uuid, ?, ?, ?, to_timestamp(?), to_timestamp(?))",
+                vec![
+                    rbs::to_value!(challenge.id.to_string()),
+                    rbs::to_value!(&challenge.wallet_address),
+                    rbs::to_value!(&challenge.wallet_chain),
+                    rbs::to_value!(&challenge.nonce),
+                    rbs::to_value!(challenge.created_at.timestamp()),
+                    rbs::to_value!(challenge.expires_at.timestamp()),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        let message = login_message(&challenge.wallet_address, &challenge.wallet_chain, &challenge.nonce);
+        Ok((challenge, message))
+    }
+
+    /// 使用钱包签名登录或注册，签名的消息必须是服务端签发的登录挑战，且每个挑战只能使用一次
     pub async fn wallet_login(
         &self,
         wallet_address: String,
         chain_type: String,
         signature: String,
+        challenge_id: String,
         message: String,
     ) -> Result<String, ServiceError> {
+        check_chain_type(&chain_type)?;
+        if message.starts_with(authorship::MESSAGE_HEADER) {
+            return Err(ServiceError::BadRequest("内容签名不能用于登录".into()));
+        }
+
+        let challenge = self
+            .find_challenge(&challenge_id, &wallet_address, &chain_type)
+            .await?
+            .ok_or_else(|| ServiceError::AuthenticationError("登录挑战无效或已过期".into()))?;
+        if message != login_message(&challenge.wallet_address, &challenge.wallet_chain, &challenge.nonce) {
+            return Err(ServiceError::AuthenticationError("签名消息与登录挑战不一致".into()));
+        }
         // 验证签名
         self.verify_wallet_signature(&wallet_address, &signature, &message, &chain_type)?;
+        // 签名通过后消费挑战，并发请求中只有一个能成功
+        if !self.consume_challenge(&challenge_id).await? {
+            return Err(ServiceError::AuthenticationError("登录挑战已被使用".into()));
+        }
+
         // 检查用户是否存在，不存在则创建
         let user: UserEntity = self
             .find_or_create_user(&wallet_address, &chain_type)
@@ -37,6 +99,44 @@ impl UserService {
         Ok(token)
     }
 
+    // 查询未过期且属于该钱包的登录挑战
+    async fn find_challenge(
+        &self,
+        challenge_id: &str,
+        wallet_address: &str,
+        chain_type: &str,
+    ) -> Result<Option<AuthChallengeEntity>, ServiceError> {
+        let challenges: Vec<AuthChallengeEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM login_challenges WHERE id::text = ? AND wallet_address = ? AND wallet_chain = ? \
+                 AND expires_at > NOW()",
+                vec![
+                    rbs::to_value!(challenge_id),
+                    rbs::to_value!(wallet_address),
+                    rbs::to_value!(chain_type),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(challenges.into_iter().next())
+    }
+
+    // 删除登录挑战，返回是否由本次请求删除
+    async fn consume_challenge(&self, challenge_id: &str) -> Result<bool, ServiceError> {
+        let result = self
+            .db
+            .exec(
+                "DELETE FROM login_challenges WHERE id::text = ? AND expires_at > NOW()",
+                vec![rbs::to_value!(challenge_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(result.rows_affected > 0)
+    }
+
     /// 验证钱包签名
     fn verify_wallet_signature(
         &self,
@@ -45,18 +145,10 @@ impl UserService {
         message: &str,
         wallet_chain: &str,
     ) -> Result<bool, ServiceError> {
-        match wallet_chain {
-            "ethereum" => {
-                // 使用ethers-rs验证以太坊签名
-                // 实际实现会更复杂，这里简化处理
-                Ok(true)
-            }
-            "solana" => {
-                // 使用solana-client验证Solana签名
-                // 实际实现会更复杂，这里简化处理
-                Ok(true)
-            }
-            _ => Err(ServiceError::BadRequest("不支持的链类型".into())),
+        if crypto::verify_wallet_signature(wallet_chain, message, signature, address) {
+            Ok(true)
+        } else {
+            Err(ServiceError::AuthenticationError("钱包签名验证失败".into()))
         }
     }
 
@@ -66,34 +158,36 @@ impl UserService {
         wallet_address_val: &String,
         wallet_chain_val: &str,
     ) -> Result<UserEntity, ServiceError> {
-        // 使用self.db而不是创建新的RBatis实例
-        let rb: RBatis = RBatis::new();
-        let user_entity: Option<UserEntity> = UserEntity::select_by_column(&rb, "wallet_address", wallet_address_val)
+        let users: Vec<UserEntity> = self
+            .db
+            .query_decode(
+                "SELECT * FROM users WHERE wallet_address = ? AND wallet_chain = ?",
+                vec![rbs::to_value!(wallet_address_val), rbs::to_value!(wallet_chain_val)],
+            )
             .await
-            .map_err(|_| ServiceError::InternalServerError)?
-            .first()
-            .cloned();
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
 
-        if let Some(entity) = user_entity {
-            Ok(entity)
-        } else {
-            let new_user_entity = UserEntity {
-                id: Uuid::new_v4(),
-                username: "".to_string(),
-                nickname: Some("".to_string()),
-                wallet_address: wallet_address_val.clone(),
-                wallet_chain: wallet_chain_val.to_string(),
-                avatar_ipfs_cid: Some("".to_string()),
-                created_at: DateTime::now(),
-                updated_at: DateTime::now(),
-            };
-
-            UserEntity::insert(&rb, &new_user_entity)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?;
-
-            Ok(new_user_entity)
+        if let Some(entity) = users.into_iter().next() {
+            return Ok(entity);
         }
+
+        // 用户名唯一，新用户默认使用钱包地址
+        let new_user_entity = UserEntity {
+            id: Uuid::new_v4(),
+            username: wallet_address_val.clone(),
+            nickname: None,
+            wallet_address: wallet_address_val.clone(),
+            wallet_chain: wallet_chain_val.to_string(),
+            avatar_ipfs_cid: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        };
+
+        UserEntity::insert(self.db.as_ref(), &new_user_entity)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(new_user_entity)
     }
 
     /// 更新用户资料
@@ -105,24 +199,38 @@ impl UserService {
         avatar_cid: Option<String>,
     ) -> Result<String, ServiceError> {
         if let Some(username_val) = &username {
-            let rb: RBatis = RBatis::new();
-            let profile: Vec<UserProfileEntity> =
-                UserProfileEntity::select_by_column(&rb, "username", &username_val)
-                    .await
-                    .map_err(|_| ServiceError::InternalServerError)?;
-            let profile_entity = profile.first().cloned().unwrap();
-            if profile.first().is_none() {
-                return Err(ServiceError::BadRequest("用户名已存在".into()));
+            if username_val.trim().is_empty() {
+                return Err(ServiceError::BadRequest("用户名不能为空".into()));
             }
-            UserProfileEntity::update_by_column(&rb, &profile_entity, "nickname")
+            let taken: i64 = self
+                .db
+                .query_decode(
+                    "SELECT COUNT(*) AS count FROM users WHERE username = ? AND id::text <> ?",
+                    vec![rbs::to_value!(username_val), rbs::to_value!(&user_id)],
+                )
                 .await
-                .map_err(|_| ServiceError::InternalServerError)?;
-
-            Ok(String::from("修改成功！"))
-        } else {
-            // 处理没有提供用户名的情况
-            Err(ServiceError::BadRequest("用户名不能为空".into()))
+                .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+            if taken > 0 {
+                return Err(ServiceError::BadRequest("用户名已存在".into()));
+            }
         }
+
+        // 未提供的字段保持不变
+        self.db
+            .exec(
+                "UPDATE users SET username = COALESCE(?, username), nickname = COALESCE(?, nickname), \
+                 avatar_ipfs_cid = COALESCE(?, avatar_ipfs_cid) WHERE id::text = ?",
+                vec![
+                    rbs::to_value!(username),
+                    rbs::to_value!(nickname),
+                    rbs::to_value!(avatar_cid),
+                    rbs::to_value!(user_id),
+                ],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(String::from("修改成功！"))
     }
 
     /// 获取用户资料
@@ -130,13 +238,7 @@ impl UserService {
         &self,
         user_id_val: String,
     ) -> Result<UserProfileEntity, ServiceError> {
-        // 使用rbatis查询用户资料
-        let rb: RBatis = RBatis::new();
-        let profile: Vec<UserProfileEntity> =
-            UserProfileEntity::select_by_column(&rb, "user_id", &user_id_val)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?;
-        Ok(profile.first().cloned().unwrap())
+        self.find_profile("id::text", &user_id_val).await
     }
 
     /// 通过用户名获取用户资料
@@ -144,13 +246,7 @@ impl UserService {
         &self,
         username_val: &str,
     ) -> Result<UserProfileEntity, ServiceError> {
-        // 使用rbatis查询用户资料
-        let rb: RBatis = RBatis::new();
-        let profile = UserProfileEntity::select_by_column(&rb, "username", username_val)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?;
-
-        Ok(profile.first().cloned().unwrap())
+        self.find_profile("username", username_val).await
     }
 
     /// 通过钱包地址获取用户资料
@@ -158,14 +254,60 @@ impl UserService {
         &self,
         wallet_address_val: &str,
     ) -> Result<UserProfileEntity, ServiceError> {
-        // 使用rbatis查询用户资料
-        let rb: RBatis = RBatis::new();
-        let profile: Vec<UserProfileEntity> =
-            UserProfileEntity::select_by_column(&rb, "wallet_address", wallet_address_val)
-                .await
-                .map_err(|_| ServiceError::InternalServerError)?;
+        self.find_profile("wallet_address", wallet_address_val).await
+    }
 
-        Ok(profile.first().cloned().unwrap())
+    // 按指定列查询用户资料，column只能是内部传入的常量
+    async fn find_profile(&self, column: &str, value: &str) -> Result<UserProfileEntity, ServiceError> {
+        let profiles: Vec<UserProfileEntity> = self
+            .db
+            .query_decode(
+                &format!(
+                    "SELECT id AS user_id, username, nickname, wallet_address, avatar_ipfs_cid AS avatar_cid, \
+                     created_at, updated_at FROM users WHERE {} = ?",
+                    column
+                ),
+                vec![rbs::to_value!(value)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        profiles
+            .into_iter()
+            .next()
+            .ok_or(ServiceError::NotFound("用户不存在".into()))
+    }
+
+    /// 关注用户，重复关注不报错
+    pub async fn follow_user(&self, follower_id: &str, followee_id: &str) -> Result<(), ServiceError> {
+        if follower_id == followee_id {
+            return Err(ServiceError::BadRequest("不能关注自己".into()));
+        }
+        // 被关注的用户必须存在
+        self.get_profile(followee_id.to_string()).await?;
+
+        self.db
+            .exec(
+                "INSERT INTO follows (follower_id, followee_id) VALUES (?::uuid, ?::uuid) ON CONFLICT DO NOTHING",
+                vec![rbs::to_value!(follower_id), rbs::to_value!(followee_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 取消关注
+    pub async fn unfollow_user(&self, follower_id: &str, followee_id: &str) -> Result<(), ServiceError> {
+        self.db
+            .exec(
+                "DELETE FROM follows WHERE follower_id::text = ? AND followee_id::text = ?",
+                vec![rbs::to_value!(follower_id), rbs::to_value!(followee_id)],
+            )
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+        Ok(())
     }
 
     /// 通过用户ID获取钱包地址
@@ -173,16 +315,23 @@ impl UserService {
         &self,
         user_id_val: String,
     ) -> Result<String, ServiceError> {
-        // 解析UUID
-        let user_id_uuid = uuid::Uuid::parse_str(&user_id_val)
-            .map_err(|_| ServiceError::BadRequest("无效的用户ID".into()))?;
-        let rb: RBatis = RBatis::new();
-        // 使用rbatis查询用户
+        let profile = self.get_profile(user_id_val).await?;
 
-        let user_entity: Vec<UserEntity> = UserEntity::select_by_column(&rb, "id", user_id_uuid)
-            .await
-            .map_err(|_| ServiceError::InternalServerError)?;
+        Ok(profile.wallet_address)
+    }
+}
 
-        Ok(user_entity.first().unwrap().wallet_address.clone())
+fn check_chain_type(chain_type: &str) -> Result<(), ServiceError> {
+    match chain_type {
+        "ethereum" | "solana" => Ok(()),
+        _ => Err(ServiceError::BadRequest("不支持的链类型".into())),
     }
 }
+
+/// 钱包需要签名的登录消息，nonce由服务端生成
+pub fn login_message(wallet_address: &str, chain_type: &str, nonce: &str) -> String {
+    format!(
+        "{}\nwallet: {}\nchain: {}\nnonce: {}",
+        LOGIN_MESSAGE_HEADER, wallet_address, chain_type, nonce
+    )
+}
@@ -0,0 +1,189 @@
+use crate::utils::arweave::{deep_hash, ArweaveWallet, DeepHashChunk, Tag};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+// ANS-104数据项，使用Arweave钱包签名（签名类型1，RSA-PSS 4096位）
+const SIGNATURE_TYPE_ARWEAVE: u16 = 1;
+const ARWEAVE_SIGNATURE_LEN: usize = 512;
+const ARWEAVE_OWNER_LEN: usize = 512;
+
+/// 打包交易的标签
+pub const BUNDLE_FORMAT_TAGS: [(&str, &str); 2] = [("Bundle-Format", "binary"), ("Bundle-Version", "2.0.0")];
+
+/// 已签名的数据项
+#[derive(Debug, Clone)]
+pub struct DataItem {
+    pub id: String, // base64url编码，打包上链后可通过网关按该ID访问
+    raw_id: [u8; 32],
+    bytes: Vec<u8>,
+}
+
+impl DataItem {
+    /// 创建并签名数据项
+    pub fn new(wallet: &ArweaveWallet, data: &[u8], tags: &[Tag]) -> Result<Self, String> {
+        let owner = wallet.owner();
+        if owner.len() != ARWEAVE_OWNER_LEN {
+            return Err(format!("Arweave钱包公钥长度应为{}字节", ARWEAVE_OWNER_LEN));
+        }
+        let tag_bytes = encode_tags(tags);
+
+        let signature_data = deep_hash(&DeepHashChunk::List(vec![
+            DeepHashChunk::Blob(b"dataitem"),
+            DeepHashChunk::Blob(b"1"),
+            DeepHashChunk::Blob(SIGNATURE_TYPE_ARWEAVE.to_string().as_bytes()),
+            DeepHashChunk::Blob(owner),
+            DeepHashChunk::Blob(&[]), // target
+            DeepHashChunk::Blob(&[]), // anchor
+            DeepHashChunk::Blob(&tag_bytes),
+            DeepHashChunk::Blob(data),
+        ]));
+        let signature = wallet.sign(&signature_data)?;
+        if signature.len() != ARWEAVE_SIGNATURE_LEN {
+            return Err(format!("Arweave签名长度应为{}字节", ARWEAVE_SIGNATURE_LEN));
+        }
+
+        let mut bytes = Vec::with_capacity(2 + signature.len() + owner.len() + 18 + tag_bytes.len() + data.len());
+        bytes.extend_from_slice(&SIGNATURE_TYPE_ARWEAVE.to_le_bytes());
+        bytes.extend_from_slice(&signature);
+        bytes.extend_from_slice(owner);
+        bytes.push(0); // 无target
+        bytes.push(0); // 无anchor
+        bytes.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(tag_bytes.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&tag_bytes);
+        bytes.extend_from_slice(data);
+
+        let raw_id: [u8; 32] = Sha256::digest(&signature).into();
+
+        Ok(Self {
+            id: URL_SAFE_NO_PAD.encode(raw_id),
+            raw_id,
+            bytes,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+/// 将多个数据项打包为二进制bundle，作为一笔Arweave交易的数据上链
+pub fn create_bundle(items: &[DataItem]) -> Vec<u8> {
+    let total: usize = items.iter().map(|item| item.bytes.len()).sum();
+    let mut bundle = Vec::with_capacity(32 + items.len() * 64 + total);
+
+    bundle.extend_from_slice(&u256_le(items.len() as u64));
+    for item in items {
+        bundle.extend_from_slice(&u256_le(item.bytes.len() as u64));
+        bundle.extend_from_slice(&item.raw_id);
+    }
+    for item in items {
+        bundle.extend_from_slice(&item.bytes);
+    }
+
+    bundle
+}
+
+// 标签按Avro数组编码：数量、各标签的name和value（均为bytes），以0结尾；没有标签时为空
+fn encode_tags(tags: &[Tag]) -> Vec<u8> {
+    let mut out = Vec::new();
+    if tags.is_empty() {
+        return out;
+    }
+
+    write_zigzag(&mut out, tags.len() as i64);
+    for tag in tags {
+        write_zigzag(&mut out, tag.name.len() as i64);
+        out.extend_from_slice(tag.name.as_bytes());
+        write_zigzag(&mut out, tag.value.len() as i64);
+        out.extend_from_slice(tag.value.as_bytes());
+    }
+    out.push(0);
+    out
+}
+
+fn write_zigzag(out: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn u256_le(value: u64) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[..8].copy_from_slice(&value.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pss::{Signature, VerifyingKey};
+    use rsa::signature::Verifier;
+    use rsa::{BigUint, RsaPublicKey};
+
+    // 仅用于测试的4096位钱包；期望值由按arbundles格式独立实现的脚本计算
+    const TEST_WALLET: &str = include_str!("../../tests/fixtures/arweave/test_wallet.json");
+    const TAGS_HEX: &str = "0418436f6e74656e742d5479706514746578742f706c61696e104170702d4e616d651457656233536f6369616c00";
+    const SIGNATURE_DATA_HEX: &str =
+        "3626e97a73b868c9f640f907982fd4bc6b9fa73ab1e8516afb2bfe6e15c74d27cfdc5336de06d86e42684cc985b582b4";
+
+    fn tags() -> Vec<Tag> {
+        vec![Tag::new("Content-Type", "text/plain"), Tag::new("App-Name", "Web3Social")]
+    }
+
+    fn verify(wallet: &ArweaveWallet, message: &[u8], signature: &[u8]) {
+        let key = RsaPublicKey::new(BigUint::from_bytes_be(wallet.owner()), BigUint::from(65537u32)).unwrap();
+        VerifyingKey::<Sha256>::new(key)
+            .verify(message, &Signature::try_from(signature).unwrap())
+            .expect("签名校验失败");
+    }
+
+    #[test]
+    fn tag_encoding() {
+        assert_eq!(hex::encode(encode_tags(&tags())), TAGS_HEX);
+        assert!(encode_tags(&[]).is_empty());
+    }
+
+    #[test]
+    fn data_item_bytes() {
+        let wallet = ArweaveWallet::from_jwk(TEST_WALLET).unwrap();
+        let data = b"hello arweave";
+        let item = DataItem::new(&wallet, data, &tags()).unwrap();
+        let bytes = &item.bytes;
+        let tag_bytes = hex::decode(TAGS_HEX).unwrap();
+
+        assert_eq!(&bytes[..2], &[1, 0]);
+        let signature = &bytes[2..514];
+        assert_eq!(&bytes[514..1026], wallet.owner());
+        assert_eq!(&bytes[1026..1028], &[0, 0]);
+        assert_eq!(&bytes[1028..1036], &2u64.to_le_bytes());
+        assert_eq!(&bytes[1036..1044], &(tag_bytes.len() as u64).to_le_bytes());
+        assert_eq!(&bytes[1044..1044 + tag_bytes.len()], tag_bytes.as_slice());
+        assert_eq!(&bytes[1044 + tag_bytes.len()..], data);
+        assert_eq!(item.size(), 1044 + tag_bytes.len() + data.len());
+
+        assert_eq!(item.raw_id.as_slice(), Sha256::digest(signature).as_slice());
+        assert_eq!(item.id, URL_SAFE_NO_PAD.encode(item.raw_id));
+        verify(&wallet, &hex::decode(SIGNATURE_DATA_HEX).unwrap(), signature);
+    }
+
+    #[test]
+    fn bundle_layout() {
+        let wallet = ArweaveWallet::from_jwk(TEST_WALLET).unwrap();
+        let first = DataItem::new(&wallet, b"first", &[]).unwrap();
+        let second = DataItem::new(&wallet, b"second", &tags()).unwrap();
+        let bundle = create_bundle(&[first.clone(), second.clone()]);
+
+        assert_eq!(&bundle[..32], &u256_le(2));
+        assert_eq!(&bundle[32..64], &u256_le(first.size() as u64));
+        assert_eq!(&bundle[64..96], &first.raw_id);
+        assert_eq!(&bundle[96..128], &u256_le(second.size() as u64));
+        assert_eq!(&bundle[128..160], &second.raw_id);
+        assert_eq!(&bundle[160..160 + first.size()], first.bytes.as_slice());
+        assert_eq!(&bundle[160 + first.size()..], second.bytes.as_slice());
+    }
+}
@@ -1,11 +1,609 @@
-use reqwest::Client;
+use crate::utils::ans104::{create_bundle, DataItem, BUNDLE_FORMAT_TAGS};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use lazy_static::lazy_static;
+use reqwest::{Client, StatusCode};
+use rsa::pss::BlindedSigningKey;
+use rsa::signature::{RandomizedSigner, SignatureEncoding};
+use rsa::{BigUint, RsaPrivateKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384};
 use std::env;
+use std::time::Duration;
 
-// Arweave API响应结构
-#[derive(Debug, Serialize, Deserialize)]
-struct ArweavePostResponse {
-    id: String,
+// 数据分块大小，与Arweave节点要求一致
+const MAX_CHUNK_SIZE: usize = 256 * 1024;
+const MIN_CHUNK_SIZE: usize = 32 * 1024;
+// 只有一个分块时数据直接放在交易中上传，否则分块上传
+const MAX_CHUNKS_IN_BODY: usize = 1;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    // 全局Arweave客户端，按环境变量初始化
+    static ref ARWEAVE_CLIENT: ArweaveClient = ArweaveClient::from_env();
+}
+
+/// 交易标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub value: String,
+}
+
+impl Tag {
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Arweave钱包，从JWK格式的RSA私钥加载
+pub struct ArweaveWallet {
+    signing_key: BlindedSigningKey<Sha256>,
+    owner: Vec<u8>, // RSA模数n，即交易中的owner
+}
+
+impl ArweaveWallet {
+    pub fn from_jwk(jwk: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Jwk {
+            n: String,
+            e: String,
+            d: String,
+            p: String,
+            q: String,
+        }
+
+        let jwk: Jwk = serde_json::from_str(jwk).map_err(|e| format!("解析Arweave钱包失败: {}", e))?;
+        let owner = b64_decode(&jwk.n)?;
+        let component = |value: &str| b64_decode(value).map(|bytes| BigUint::from_bytes_be(&bytes));
+
+        let key = RsaPrivateKey::from_components(
+            BigUint::from_bytes_be(&owner),
+            component(&jwk.e)?,
+            component(&jwk.d)?,
+            vec![component(&jwk.p)?, component(&jwk.q)?],
+        )
+        .map_err(|e| format!("Arweave钱包私钥无效: {}", e))?;
+
+        Ok(Self {
+            signing_key: BlindedSigningKey::<Sha256>::new(key),
+            owner,
+        })
+    }
+
+    // ARWEAVE_WALLET_JWK：JWK内容；ARWEAVE_WALLET_PATH：JWK文件路径；都未配置时返回None
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let jwk = match env::var("ARWEAVE_WALLET_JWK").ok().filter(|v| !v.is_empty()) {
+            Some(jwk) => jwk,
+            None => match env::var("ARWEAVE_WALLET_PATH").ok().filter(|v| !v.is_empty()) {
+                Some(path) => std::fs::read_to_string(&path)
+                    .map_err(|e| format!("读取Arweave钱包文件{}失败: {}", path, e))?,
+                None => return Ok(None),
+            },
+        };
+
+        Self::from_jwk(&jwk).map(Some)
+    }
+
+    pub fn owner(&self) -> &[u8] {
+        &self.owner
+    }
+
+    /// RSA-PSS（SHA-256，盐长32字节）签名
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        let signature = self
+            .signing_key
+            .try_sign_with_rng(&mut rsa::rand_core::OsRng, message)
+            .map_err(|e| format!("Arweave签名失败: {}", e))?;
+
+        Ok(signature.to_vec())
+    }
+}
+
+/// deep hash的输入
+pub enum DeepHashChunk<'a> {
+    Blob(&'a [u8]),
+    List(Vec<DeepHashChunk<'a>>),
+}
+
+/// Arweave deep hash（SHA-384），交易和数据项都对其结果签名
+pub fn deep_hash(chunk: &DeepHashChunk) -> Vec<u8> {
+    match chunk {
+        DeepHashChunk::Blob(data) => {
+            let tag = sha384(&[b"blob", data.len().to_string().as_bytes()]);
+            sha384(&[&tag, &sha384(&[data])])
+        }
+        DeepHashChunk::List(items) => {
+            let mut acc = sha384(&[b"list", items.len().to_string().as_bytes()]);
+            for item in items {
+                acc = sha384(&[&acc, &deep_hash(item)]);
+            }
+            acc
+        }
+    }
+}
+
+// 数据分块
+struct Chunk {
+    data_hash: Vec<u8>,
+    min_byte_range: usize,
+    max_byte_range: usize,
+}
+
+// Merkle树节点
+enum MerkleNode {
+    Leaf {
+        id: Vec<u8>,
+        data_hash: Vec<u8>,
+        max_byte_range: usize,
+    },
+    Branch {
+        id: Vec<u8>,
+        byte_range: usize,
+        max_byte_range: usize,
+        left: Box<MerkleNode>,
+        right: Box<MerkleNode>,
+    },
+}
+
+impl MerkleNode {
+    fn id(&self) -> &[u8] {
+        match self {
+            MerkleNode::Leaf { id, .. } | MerkleNode::Branch { id, .. } => id,
+        }
+    }
+
+    fn max_byte_range(&self) -> usize {
+        match self {
+            MerkleNode::Leaf { max_byte_range, .. } | MerkleNode::Branch { max_byte_range, .. } => *max_byte_range,
+        }
+    }
+}
+
+// 数据的分块、data_root和每个分块的证明路径
+struct ChunkedData {
+    data_root: Vec<u8>,
+    chunks: Vec<Chunk>,
+    proofs: Vec<Vec<u8>>,
+}
+
+/// 已签名的v2格式交易
+#[derive(Debug, Clone, Serialize)]
+pub struct Transaction {
+    pub format: u8,
+    pub id: String,
+    pub last_tx: String,
+    pub owner: String,
+    pub tags: Vec<EncodedTag>,
+    pub target: String,
+    pub quantity: String,
+    pub data: String,
+    pub data_size: String,
+    pub data_root: String,
+    pub reward: String,
+    pub signature: String,
+}
+
+/// 交易中base64url编码的标签
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodedTag {
+    pub name: String,
+    pub value: String,
+}
+
+/// 交易确认状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TxStatus {
+    NotFound,
+    Pending,
+    Confirmed { block_height: u64, confirmations: u64 },
+}
+
+/// Arweave客户端，构建、签名和上传交易，并将小内容打包为ANS-104 bundle
+pub struct ArweaveClient {
+    node_url: String,
+    app_name: String,
+    wallet: Option<ArweaveWallet>,
+    client: Client,
+}
+
+impl ArweaveClient {
+    // ARWEAVE_NODE_URL：节点或网关地址；ARWEAVE_APP_NAME：App-Name标签
+    pub fn from_env() -> Self {
+        let wallet = match ArweaveWallet::from_env() {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                log::error!("加载Arweave钱包失败，将无法上传内容: {}", e);
+                None
+            }
+        };
+
+        Self {
+            node_url: get_arweave_node_url().trim_end_matches('/').to_string(),
+            app_name: env::var("ARWEAVE_APP_NAME").unwrap_or_else(|_| "Web3Social".to_string()),
+            wallet,
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+
+    /// App-Name标签的值
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+
+    /// 创建已签名的数据项
+    pub fn create_data_item(&self, data: &[u8], tags: &[Tag]) -> Result<DataItem, String> {
+        DataItem::new(self.wallet()?, data, tags)
+    }
+
+    /// 将数据项打包为一笔交易上传，返回交易ID
+    pub async fn post_bundle(&self, items: &[DataItem]) -> Result<String, String> {
+        let mut tags: Vec<Tag> = BUNDLE_FORMAT_TAGS
+            .iter()
+            .map(|(name, value)| Tag::new(name, value))
+            .collect();
+        tags.push(Tag::new("App-Name", &self.app_name));
+
+        self.post_transaction(&create_bundle(items), &tags).await
+    }
+
+    /// 构建、签名并上传交易，超过一个分块的数据分块上传，返回交易ID
+    pub async fn post_transaction(&self, data: &[u8], tags: &[Tag]) -> Result<String, String> {
+        let wallet = self.wallet()?;
+        let chunked = chunk_data(data);
+        let (reward, last_tx) = futures::join!(self.get_price(data.len()), self.get_anchor());
+        let mut tx = build_transaction(wallet, data, &chunked, tags, &reward?, &last_tx?)?;
+
+        let upload_chunks = chunked.chunks.len() > MAX_CHUNKS_IN_BODY;
+        if !upload_chunks {
+            tx.data = URL_SAFE_NO_PAD.encode(data);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/tx", self.node_url))
+            .json(&tx)
+            .send()
+            .await
+            .map_err(|e| format!("上传Arweave交易失败: {}", e))?;
+        check_response(response, "上传Arweave交易失败").await?;
+
+        if upload_chunks {
+            for (chunk, proof) in chunked.chunks.iter().zip(&chunked.proofs) {
+                self.post_chunk(&tx, data, chunk, proof).await?;
+            }
+        }
+
+        Ok(tx.id)
+    }
+
+    /// 查询交易确认状态
+    pub async fn get_status(&self, tx_id: &str) -> Result<TxStatus, String> {
+        #[derive(Deserialize)]
+        struct StatusResponse {
+            block_height: u64,
+            number_of_confirmations: u64,
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/tx/{}/status", self.node_url, tx_id))
+            .send()
+            .await
+            .map_err(|e| format!("查询Arweave交易状态失败: {}", e))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(TxStatus::NotFound),
+            StatusCode::ACCEPTED => Ok(TxStatus::Pending),
+            status if status.is_success() => {
+                let status: StatusResponse = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("解析Arweave交易状态失败: {}", e))?;
+                Ok(TxStatus::Confirmed {
+                    block_height: status.block_height,
+                    confirmations: status.number_of_confirmations,
+                })
+            }
+            status => Err(format!("查询Arweave交易状态失败: {}", status)),
+        }
+    }
+
+    /// 从网关读取交易或数据项的内容
+    pub async fn get_data(&self, id: &str) -> Result<Vec<u8>, String> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.node_url, id))
+            .send()
+            .await
+            .map_err(|e| format!("Arweave获取错误: {}", e))?;
+        let response = check_response(response, "Arweave内容获取失败").await?;
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("读取Arweave响应失败: {}", e))?;
+        Ok(bytes.to_vec())
+    }
+
+    /// 生成内容访问地址
+    pub fn get_url(&self, id: &str) -> String {
+        format!("{}/{}", self.node_url, id)
+    }
+
+    fn wallet(&self) -> Result<&ArweaveWallet, String> {
+        self.wallet
+            .as_ref()
+            .ok_or_else(|| "未配置Arweave钱包（ARWEAVE_WALLET_JWK 或 ARWEAVE_WALLET_PATH）".to_string())
+    }
+
+    // 上传费用（winston）
+    async fn get_price(&self, data_size: usize) -> Result<String, String> {
+        self.get_text(&format!("price/{}", data_size)).await
+    }
+
+    // 交易锚点，作为last_tx
+    async fn get_anchor(&self) -> Result<String, String> {
+        self.get_text("tx_anchor").await
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String, String> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.node_url, path))
+            .send()
+            .await
+            .map_err(|e| format!("请求Arweave节点失败: {}", e))?;
+        let response = check_response(response, "请求Arweave节点失败").await?;
+
+        response
+            .text()
+            .await
+            .map(|text| text.trim().to_string())
+            .map_err(|e| format!("读取Arweave响应失败: {}", e))
+    }
+
+    async fn post_chunk(&self, tx: &Transaction, data: &[u8], chunk: &Chunk, proof: &[u8]) -> Result<(), String> {
+        let body = serde_json::json!({
+            "data_root": tx.data_root,
+            "data_size": tx.data_size,
+            "data_path": URL_SAFE_NO_PAD.encode(proof),
+            "offset": (chunk.max_byte_range - 1).to_string(),
+            "chunk": URL_SAFE_NO_PAD.encode(&data[chunk.min_byte_range..chunk.max_byte_range]),
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/chunk", self.node_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("上传Arweave分块失败: {}", e))?;
+        check_response(response, "上传Arweave分块失败").await?;
+
+        Ok(())
+    }
+}
+
+// 构建并签名交易，data字段由调用方决定是否填充
+fn build_transaction(
+    wallet: &ArweaveWallet,
+    data: &[u8],
+    chunked: &ChunkedData,
+    tags: &[Tag],
+    reward: &str,
+    last_tx: &str,
+) -> Result<Transaction, String> {
+    let last_tx_bytes = b64_decode(last_tx)?;
+    let data_size = data.len().to_string();
+    let tag_chunks = tags
+        .iter()
+        .map(|tag| {
+            DeepHashChunk::List(vec![
+                DeepHashChunk::Blob(tag.name.as_bytes()),
+                DeepHashChunk::Blob(tag.value.as_bytes()),
+            ])
+        })
+        .collect();
+
+    let signature_data = deep_hash(&DeepHashChunk::List(vec![
+        DeepHashChunk::Blob(b"2"),
+        DeepHashChunk::Blob(wallet.owner()),
+        DeepHashChunk::Blob(&[]), // target
+        DeepHashChunk::Blob(b"0"), // quantity
+        DeepHashChunk::Blob(reward.as_bytes()),
+        DeepHashChunk::Blob(&last_tx_bytes),
+        DeepHashChunk::List(tag_chunks),
+        DeepHashChunk::Blob(data_size.as_bytes()),
+        DeepHashChunk::Blob(&chunked.data_root),
+    ]));
+    let signature = wallet.sign(&signature_data)?;
+
+    Ok(Transaction {
+        format: 2,
+        id: URL_SAFE_NO_PAD.encode(Sha256::digest(&signature)),
+        last_tx: last_tx.to_string(),
+        owner: URL_SAFE_NO_PAD.encode(wallet.owner()),
+        tags: tags
+            .iter()
+            .map(|tag| EncodedTag {
+                name: URL_SAFE_NO_PAD.encode(&tag.name),
+                value: URL_SAFE_NO_PAD.encode(&tag.value),
+            })
+            .collect(),
+        target: String::new(),
+        quantity: "0".to_string(),
+        data: String::new(),
+        data_size,
+        data_root: URL_SAFE_NO_PAD.encode(&chunked.data_root),
+        reward: reward.to_string(),
+        signature: URL_SAFE_NO_PAD.encode(&signature),
+    })
+}
+
+// 分块并计算data_root和证明路径；最后一块过小时与前一块平分，避免产生小于32KiB的分块
+fn chunk_data(data: &[u8]) -> ChunkedData {
+    if data.is_empty() {
+        return ChunkedData {
+            data_root: Vec::new(),
+            chunks: Vec::new(),
+            proofs: Vec::new(),
+        };
+    }
+
+    let mut chunks = Vec::new();
+    let mut cursor = 0;
+    let mut rest = data;
+    while rest.len() >= MAX_CHUNK_SIZE {
+        let mut chunk_size = MAX_CHUNK_SIZE;
+        let next_chunk_size = rest.len() - MAX_CHUNK_SIZE;
+        if next_chunk_size > 0 && next_chunk_size < MIN_CHUNK_SIZE {
+            chunk_size = rest.len().div_ceil(2);
+        }
+
+        chunks.push(Chunk {
+            data_hash: sha256(&rest[..chunk_size]),
+            min_byte_range: cursor,
+            max_byte_range: cursor + chunk_size,
+        });
+        cursor += chunk_size;
+        rest = &rest[chunk_size..];
+    }
+    chunks.push(Chunk {
+        data_hash: sha256(rest),
+        min_byte_range: cursor,
+        max_byte_range: cursor + rest.len(),
+    });
+
+    let mut layer: Vec<MerkleNode> = chunks
+        .iter()
+        .map(|chunk| MerkleNode::Leaf {
+            id: sha256_concat(&[&sha256(&chunk.data_hash), &sha256(&note(chunk.max_byte_range))]),
+            data_hash: chunk.data_hash.clone(),
+            max_byte_range: chunk.max_byte_range,
+        })
+        .collect();
+    while layer.len() > 1 {
+        let mut next = Vec::with_capacity(layer.len().div_ceil(2));
+        let mut nodes = layer.into_iter();
+        while let Some(left) = nodes.next() {
+            next.push(match nodes.next() {
+                Some(right) => hash_branch(left, right),
+                None => left,
+            });
+        }
+        layer = next;
+    }
+    let root = layer.pop().expect("Merkle树至少包含一个节点");
+
+    let mut proofs = Vec::with_capacity(chunks.len());
+    resolve_proofs(&root, Vec::new(), &mut proofs);
+
+    // 数据恰好是分块大小的整数倍时，最后一个空分块只参与data_root计算，不需要上传
+    if chunks.len() > 1 && chunks.last().map(|c| c.min_byte_range == c.max_byte_range).unwrap_or(false) {
+        chunks.pop();
+        proofs.pop();
+    }
+
+    ChunkedData {
+        data_root: root.id().to_vec(),
+        chunks,
+        proofs,
+    }
+}
+
+fn hash_branch(left: MerkleNode, right: MerkleNode) -> MerkleNode {
+    let byte_range = left.max_byte_range();
+    MerkleNode::Branch {
+        id: sha256_concat(&[&sha256(left.id()), &sha256(right.id()), &sha256(&note(byte_range))]),
+        byte_range,
+        max_byte_range: right.max_byte_range(),
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+// 按分块顺序生成每个分块的证明路径
+fn resolve_proofs(node: &MerkleNode, proof: Vec<u8>, proofs: &mut Vec<Vec<u8>>) {
+    match node {
+        MerkleNode::Leaf {
+            data_hash,
+            max_byte_range,
+            ..
+        } => {
+            let mut proof = proof;
+            proof.extend_from_slice(data_hash);
+            proof.extend_from_slice(&note(*max_byte_range));
+            proofs.push(proof);
+        }
+        MerkleNode::Branch {
+            byte_range,
+            left,
+            right,
+            ..
+        } => {
+            let mut partial = proof;
+            partial.extend_from_slice(left.id());
+            partial.extend_from_slice(right.id());
+            partial.extend_from_slice(&note(*byte_range));
+            resolve_proofs(left, partial.clone(), proofs);
+            resolve_proofs(right, partial, proofs);
+        }
+    }
+}
+
+// 32字节大端整数
+fn note(value: usize) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    out
+}
+
+fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+fn sha256_concat(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn sha384(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha384::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn b64_decode(value: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|e| format!("base64url解码失败: {}", e))
+}
+
+async fn check_response(response: reqwest::Response, action: &str) -> Result<reqwest::Response, String> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
+    Err(format!("{}({}): {}", action, status, error_text))
+}
+
+// 获取全局Arweave客户端
+pub fn arweave_client() -> &'static ArweaveClient {
+    &ARWEAVE_CLIENT
 }
 
 // 获取Arweave节点URL
@@ -13,50 +611,109 @@ pub fn get_arweave_node_url() -> String {
     env::var("ARWEAVE_NODE_URL").unwrap_or_else(|_| "https://arweave.net".to_string())
 }
 
-// 上传内容到Arweave（简化版）
-// 注意：实际操作需要钱包私钥来签名交易，这里是简化实现
-// 在生产环境应该使用arweave-js的Rust移植版或通过HTTP接口与钱包集成
-pub async fn upload_to_arweave(data: &str, tags: Option<Vec<(String, String)>>) -> Result<String, String> {
-    let client = Client::new();
-    let arweave_url = get_arweave_node_url();
-    
-    // 构建上传数据
-    // 注意：这是简化示例，实际应该使用arweave-js等库进行交易构建和签名
-    // 这段代码在实际环境中无法工作，只作为代码结构示例
-    let response = client
-        .post(format!("{}/tx", arweave_url))
-        .json(&serde_json::json!({
-            "data": data,
-            "tags": tags.unwrap_or_default()
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to upload to Arweave: {}", e))?;
-    
-    // 解析响应
-    let arweave_response = response
-        .json::<ArweavePostResponse>()
-        .await
-        .map_err(|e| format!("Failed to parse Arweave response: {}", e))?;
-    
-    Ok(arweave_response.id)
-}
-
-// 从Arweave获取内容
-pub async fn get_from_arweave(tx_id: &str) -> Result<String, String> {
-    let client = Client::new();
-    let arweave_url = get_arweave_node_url();
-    
-    let response = client
-        .get(format!("{}/{}", arweave_url, tx_id))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch from Arweave: {}", e))?;
-    
-    let content = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to parse Arweave content: {}", e))?;
-    
-    Ok(content)
-} 
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 期望值由按arweave-js算法（deepHash、chunkData/generateLeaves/buildLayers）独立实现的脚本计算
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    // 数据长度、data_root、各分块的字节范围
+    type DataRootCase = (usize, &'static str, &'static [(usize, usize)]);
+
+    #[test]
+    fn deep_hash_vectors() {
+        let cases = [
+            (
+                DeepHashChunk::Blob(b""),
+                "fbf00cc444f5fea9dc3bedf62a13fba8ae87e7445fc910567a23bec4eb82fadb1143c433069314d8362983dc3c2e4a38",
+            ),
+            (
+                DeepHashChunk::Blob(b"hello"),
+                "33ab2407a6c328c0bc1bbe5971f49af5c1908985f83c3d2bd89a9e221dd8b068dc61ce968ba3f9ab12d5361ba3944382",
+            ),
+            (
+                DeepHashChunk::List(vec![]),
+                "a69e7d37fdc7f040a9ec16aae84de24fab4a653dac4de0bd247e36bab9fe45d9289c5a04a893c95285812f5cefc9707a",
+            ),
+            (
+                DeepHashChunk::List(vec![
+                    DeepHashChunk::Blob(b"a"),
+                    DeepHashChunk::List(vec![DeepHashChunk::Blob(b"b"), DeepHashChunk::Blob(b"c")]),
+                    DeepHashChunk::Blob(b""),
+                ]),
+                "041610a481af67a00b33e0e2197bf20e92747db97a47197e2181648ea1f571d7d11537859258d953eebf88a170abccb0",
+            ),
+        ];
+        for (chunk, expected) in cases {
+            assert_eq!(hex::encode(deep_hash(&chunk)), expected);
+        }
+    }
+
+    #[test]
+    fn data_root_vectors() {
+        let cases: [DataRootCase; 6] = [
+            (1000, "OBN0lHZnrFrskv9s1HKU7VRRDXrzQ3sOgMniX5cGavM", &[(0, 1000)]),
+            // 恰好一个分块时末尾的空分块参与计算但不上传
+            (MAX_CHUNK_SIZE, "gty7KB2baLFp7OGxuV2wBeX3NippS1tNVlMOZryIq5o", &[(0, 262144)]),
+            // 剩余不足32KiB时与前一块平分
+            (
+                MAX_CHUNK_SIZE + 1,
+                "kJKkN6QWAcUM_WQUxeNXnnwKy0fp7myUNNob6XNlOPA",
+                &[(0, 131073), (131073, 262145)],
+            ),
+            (
+                2 * MAX_CHUNK_SIZE,
+                "H2bNmvftzAlIQXVYCetFsJ9f0uoC4C2FbfivZTRSyqI",
+                &[(0, 262144), (262144, 524288)],
+            ),
+            (
+                600 * 1024,
+                "N_gS_f6fPYsmpog4LZJLEwgKVekMLOLMrdCXsi2Z_oc",
+                &[(0, 262144), (262144, 524288), (524288, 614400)],
+            ),
+            (
+                5 * MAX_CHUNK_SIZE + 1000,
+                "t5Jxby3akgtXVPVOfr6kn6pQBtdd_kdeyv_7cuFPtu4",
+                &[
+                    (0, 262144),
+                    (262144, 524288),
+                    (524288, 786432),
+                    (786432, 1048576),
+                    (1048576, 1180148),
+                    (1180148, 1311720),
+                ],
+            ),
+        ];
+
+        for (len, root, ranges) in cases {
+            let data = pattern(len);
+            let chunked = chunk_data(&data);
+            assert_eq!(URL_SAFE_NO_PAD.encode(&chunked.data_root), root, "data_root of {} bytes", len);
+
+            let actual: Vec<(usize, usize)> = chunked
+                .chunks
+                .iter()
+                .map(|c| (c.min_byte_range, c.max_byte_range))
+                .collect();
+            assert_eq!(actual, ranges, "chunks of {} bytes", len);
+            assert_eq!(chunked.proofs.len(), chunked.chunks.len());
+
+            // 每个证明以分块哈希和偏移结尾
+            for (chunk, proof) in chunked.chunks.iter().zip(&chunked.proofs) {
+                let tail = &proof[proof.len() - 64..];
+                assert_eq!(&tail[..32], chunk.data_hash.as_slice());
+                assert_eq!(&tail[32..], &note(chunk.max_byte_range));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_data_has_no_root() {
+        let chunked = chunk_data(&[]);
+        assert!(chunked.data_root.is_empty());
+        assert!(chunked.chunks.is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+// 作者签名的规范消息，客户端用钱包对该文本签名（以太坊为personal_sign，Solana为signMessage）
+// 消息与签名一起写入Arweave标签，数据库丢失后仍可独立验证作者身份
+pub const SIGNATURE_VERSION: &str = "1";
+pub const MESSAGE_HEADER: &str = "Web3Social signed content";
+// ANS-104单个标签值的上限
+pub const MAX_SIGNED_MESSAGE_LEN: usize = 3072;
+
+/// 客户端提交的作者签名
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthorSignature {
+    pub signature: String,
+    pub signed_at: i64, // 签名时的Unix时间戳（秒），也是规范消息的一部分
+}
+
+/// 参与签名的内容字段
+pub struct SignedContent<'a> {
+    pub kind: &'a str, // post、quote、comment
+    pub author: &'a str,
+    pub content_hash: &'a str, // 正文的SHA-256，十六进制
+    pub tags: &'a [String],
+    pub parent: Option<&'a str>, // 引用的帖子，或评论所属的帖子/父评论
+    pub signed_at: i64,
+}
+
+impl SignedContent<'_> {
+    /// 生成规范消息：固定顺序的键值行，标签去重排序后用逗号连接
+    /// 例如：
+    /// Web3Social signed content
+    /// version: 1
+    /// kind: post
+    /// author: 0xabc...
+    /// content-sha256: 9f86d0...
+    /// tags: defi,eth
+    /// parent:
+    /// timestamp: 1697000000
+    pub fn canonical_message(&self) -> String {
+        let mut tags: Vec<String> = self.tags.iter().map(|t| t.trim().to_lowercase()).collect();
+        tags.sort();
+        tags.dedup();
+
+        format!(
+            "{}\nversion: {}\nkind: {}\nauthor: {}\ncontent-sha256: {}\ntags: {}\nparent: {}\ntimestamp: {}",
+            MESSAGE_HEADER,
+            SIGNATURE_VERSION,
+            self.kind,
+            self.author,
+            self.content_hash,
+            tags.join(","),
+            self.parent.unwrap_or(""),
+            self.signed_at
+        )
+    }
+}
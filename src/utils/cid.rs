@@ -0,0 +1,386 @@
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+// 本地计算CID，参数与Kubo默认一致：256KiB固定分块、平衡DAG、每个节点最多174个链接
+// CIDv1使用raw叶子节点，CIDv0使用dag-pb叶子节点
+const CHUNK_SIZE: usize = 262_144;
+const MAX_LINKS: usize = 174;
+
+const CODEC_RAW: u64 = 0x55;
+const CODEC_DAG_PB: u64 = 0x70;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+const SHA2_256_LEN: usize = 32;
+
+// UnixFS文件类型
+const UNIXFS_FILE: u64 = 2;
+
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// 内容标识，只支持sha2-256
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cid {
+    pub version: u64,
+    pub codec: u64,
+    pub digest: Vec<u8>,
+}
+
+impl Cid {
+    /// 解析CIDv0（Qm开头的base58）或base32编码的CIDv1（b开头）
+    pub fn parse(cid: &str) -> Result<Self, String> {
+        let cid = cid.trim();
+        if cid.len() == 46 && cid.starts_with("Qm") {
+            let bytes = base58_decode(cid)?;
+            let (digest, rest) = read_multihash(&bytes)?;
+            if !rest.is_empty() {
+                return Err(format!("CID格式错误: {}", cid));
+            }
+            return Ok(Self {
+                version: 0,
+                codec: CODEC_DAG_PB,
+                digest,
+            });
+        }
+
+        let encoded = cid
+            .strip_prefix('b')
+            .ok_or_else(|| format!("不支持的CID编码: {}", cid))?;
+        let bytes = base32_decode(encoded)?;
+        let (version, rest) = read_varint(&bytes)?;
+        if version != 1 {
+            return Err(format!("不支持的CID版本: {}", version));
+        }
+        let (codec, rest) = read_varint(rest)?;
+        let (digest, rest) = read_multihash(rest)?;
+        if !rest.is_empty() {
+            return Err(format!("CID格式错误: {}", cid));
+        }
+
+        Ok(Self { version, codec, digest })
+    }
+
+    /// CID的二进制形式，用于dag-pb链接
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if self.version != 0 {
+            write_varint(&mut bytes, self.version);
+            write_varint(&mut bytes, self.codec);
+        }
+        write_varint(&mut bytes, MULTIHASH_SHA2_256);
+        write_varint(&mut bytes, self.digest.len() as u64);
+        bytes.extend_from_slice(&self.digest);
+        bytes
+    }
+}
+
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.version == 0 {
+            write!(f, "{}", base58_encode(&self.to_bytes()))
+        } else {
+            write!(f, "b{}", base32_encode(&self.to_bytes()))
+        }
+    }
+}
+
+/// 计算内容的CIDv1，与 `ipfs add --cid-version=1` 的结果一致
+pub fn compute_cid_v1(data: &[u8]) -> String {
+    build_dag(data, 1).cid.to_string()
+}
+
+/// 校验内容是否与CID匹配
+pub fn verify_cid(cid: &str, data: &[u8]) -> Result<(), String> {
+    let expected = Cid::parse(cid)?;
+    let actual = match (expected.version, expected.codec) {
+        (0, _) => build_dag(data, 0).cid,
+        (1, CODEC_RAW) | (1, CODEC_DAG_PB) => build_dag(data, 1).cid,
+        (_, codec) => return Err(format!("不支持的CID编码类型: 0x{:x}", codec)),
+    };
+
+    if actual.digest == expected.digest {
+        Ok(())
+    } else {
+        Err(format!("内容与CID不匹配: 期望{}，实际{}", cid, actual))
+    }
+}
+
+// DAG节点
+struct DagNode {
+    cid: Cid,
+    tsize: u64,    // 节点及所有子节点编码后的总大小
+    filesize: u64, // 节点包含的文件内容大小
+}
+
+// 按平衡布局自底向上构建DAG，返回根节点
+fn build_dag(data: &[u8], version: u64) -> DagNode {
+    let mut nodes: Vec<DagNode> = if data.is_empty() {
+        vec![leaf_node(data, version)]
+    } else {
+        data.chunks(CHUNK_SIZE).map(|chunk| leaf_node(chunk, version)).collect()
+    };
+
+    while nodes.len() > 1 {
+        nodes = nodes.chunks(MAX_LINKS).map(|children| parent_node(children, version)).collect();
+    }
+
+    nodes.pop().expect("DAG至少包含一个节点")
+}
+
+fn leaf_node(chunk: &[u8], version: u64) -> DagNode {
+    if version == 1 {
+        return DagNode {
+            cid: sha256_cid(1, CODEC_RAW, chunk),
+            tsize: chunk.len() as u64,
+            filesize: chunk.len() as u64,
+        };
+    }
+
+    let encoded = encode_pb_node(&[], &encode_unixfs(chunk, chunk.len() as u64, &[]));
+    DagNode {
+        cid: sha256_cid(0, CODEC_DAG_PB, &encoded),
+        tsize: encoded.len() as u64,
+        filesize: chunk.len() as u64,
+    }
+}
+
+fn parent_node(children: &[DagNode], version: u64) -> DagNode {
+    let filesize = children.iter().map(|c| c.filesize).sum();
+    let blocksizes: Vec<u64> = children.iter().map(|c| c.filesize).collect();
+    let encoded = encode_pb_node(children, &encode_unixfs(&[], filesize, &blocksizes));
+
+    DagNode {
+        cid: sha256_cid(version, CODEC_DAG_PB, &encoded),
+        tsize: encoded.len() as u64 + children.iter().map(|c| c.tsize).sum::<u64>(),
+        filesize,
+    }
+}
+
+fn sha256_cid(version: u64, codec: u64, bytes: &[u8]) -> Cid {
+    Cid {
+        version,
+        codec,
+        digest: Sha256::digest(bytes).to_vec(),
+    }
+}
+
+// dag-pb节点：先写链接（字段2），再写数据（字段1）
+fn encode_pb_node(links: &[DagNode], data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for link in links {
+        let mut encoded = Vec::new();
+        write_bytes_field(&mut encoded, 1, &link.cid.to_bytes());
+        write_bytes_field(&mut encoded, 2, b"");
+        write_varint_field(&mut encoded, 3, link.tsize);
+        write_bytes_field(&mut out, 2, &encoded);
+    }
+    write_bytes_field(&mut out, 1, data);
+    out
+}
+
+// UnixFS文件节点数据，blocksizes按proto2非压缩方式逐个写入
+fn encode_unixfs(data: &[u8], filesize: u64, blocksizes: &[u64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint_field(&mut out, 1, UNIXFS_FILE);
+    if !data.is_empty() {
+        write_bytes_field(&mut out, 2, data);
+    }
+    write_varint_field(&mut out, 3, filesize);
+    for size in blocksizes {
+        write_varint_field(&mut out, 4, *size);
+    }
+    out
+}
+
+fn write_varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(out, field << 3);
+    write_varint(out, value);
+}
+
+fn write_bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_varint(out, (field << 3) | 2);
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), String> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &bytes[i + 1..]));
+        }
+    }
+    Err("CID格式错误: varint无效".to_string())
+}
+
+fn read_multihash(bytes: &[u8]) -> Result<(Vec<u8>, &[u8]), String> {
+    let (code, rest) = read_varint(bytes)?;
+    if code != MULTIHASH_SHA2_256 {
+        return Err(format!("不支持的哈希算法: 0x{:x}", code));
+    }
+    let (len, rest) = read_varint(rest)?;
+    if len as usize != SHA2_256_LEN || rest.len() < SHA2_256_LEN {
+        return Err("CID格式错误: 哈希长度无效".to_string());
+    }
+    Ok((rest[..SHA2_256_LEN].to_vec(), &rest[SHA2_256_LEN..]))
+}
+
+// RFC 4648 base32，小写、无填充
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in encoded.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_lowercase())
+            .ok_or_else(|| format!("无效的base32字符: {}", c as char))?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    std::iter::repeat_n('1', zeros)
+        .chain(digits.iter().rev().map(|d| BASE58_ALPHABET[*d as usize] as char))
+        .collect()
+}
+
+fn base58_decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in encoded.bytes() {
+        let mut carry = BASE58_ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .ok_or_else(|| format!("无效的base58字符: {}", c as char))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+
+    let zeros = encoded.bytes().take_while(|c| *c == b'1').count();
+    Ok(std::iter::repeat_n(0, zeros).chain(bytes.into_iter().rev()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 空内容与 "hello world" 为 `ipfs add` 的公开结果；
+    // 多分块用例按同样参数由独立脚本计算（该脚本对前述公开用例结果一致）
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn cid_v0(data: &[u8]) -> String {
+        build_dag(data, 0).cid.to_string()
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(compute_cid_v1(b""), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+        assert_eq!(cid_v0(b""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+    }
+
+    #[test]
+    fn small_content() {
+        assert_eq!(
+            compute_cid_v1(b"hello world"),
+            "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"
+        );
+        assert_eq!(cid_v0(b"hello world"), "Qmf412jQZiuVUtdgnB36FXFX7xg5V6KEbSJ4dpQuhkLyfD");
+        assert_eq!(cid_v0(b"hello world\n"), "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o");
+    }
+
+    #[test]
+    fn single_chunk() {
+        let data = pattern(CHUNK_SIZE);
+        assert_eq!(compute_cid_v1(&data), "bafkreibruh455iawsviqslif5c7uurdcfdemh22mtnytyzvnzn75kpejxy");
+        assert_eq!(cid_v0(&data), "QmeqfRyS3vkku7n6krqC3DgGMex3x2sCpSeKMDmrG13QQq");
+    }
+
+    #[test]
+    fn two_chunks() {
+        let data = pattern(CHUNK_SIZE + 1);
+        assert_eq!(compute_cid_v1(&data), "bafybeiexg2oqkfnj56l7fcmawswqbijt5shq4b5rg6a546uwpkqqzwjioi");
+        assert_eq!(cid_v0(&data), "QmUSjGawaz4ptvREcMKSMJneWCa5j8dAz2wSAAvHtW2rnB");
+    }
+
+    #[test]
+    fn more_than_max_links() {
+        // 175个分块，根节点下有两层
+        let data = pattern(CHUNK_SIZE * (MAX_LINKS + 1));
+        assert_eq!(compute_cid_v1(&data), "bafybeie73j3heycdgkdsehpoe6cxh2y3iywtf6djpi3dzqrywevvjmazny");
+        assert_eq!(cid_v0(&data), "Qmbp67kThKoJFnWu7pUgwCu81WttemMnD13oG4uj9DiY5E");
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        for cid in [
+            "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku",
+            "bafybeiexg2oqkfnj56l7fcmawswqbijt5shq4b5rg6a546uwpkqqzwjioi",
+            "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+        ] {
+            assert_eq!(Cid::parse(cid).unwrap().to_string(), cid);
+        }
+        assert!(Cid::parse("zdj7WWeQ43G6JJvLWQWZpyHuAMq6uYWRjkBXFad11vE2LHhQ7").is_err());
+    }
+
+    #[test]
+    fn verify_against_content() {
+        let data = pattern(CHUNK_SIZE + 1);
+        assert!(verify_cid("bafybeiexg2oqkfnj56l7fcmawswqbijt5shq4b5rg6a546uwpkqqzwjioi", &data).is_ok());
+        assert!(verify_cid("QmUSjGawaz4ptvREcMKSMJneWCa5j8dAz2wSAAvHtW2rnB", &data).is_ok());
+        assert!(verify_cid("QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o", b"hello world").is_err());
+    }
+}
@@ -1,6 +1,4 @@
 use ethers::prelude::*;
-use sha2::{Digest, Sha256};
-use std::convert::TryFrom;
 use std::str::FromStr;
 
 // 生成随机的挑战码
@@ -10,50 +8,43 @@ pub fn generate_nonce() -> String {
     hex::encode(&random_bytes)
 }
 
-// 验证以太坊签名
-pub async fn verify_eth_signature(message: &str, signature: &str, wallet_address: &str) -> bool {
-    // 创建前缀消息
-    let prefixed_message = format!("\x19Ethereum Signed Message:\n{}{}", message.len(), message);
-
-    // 计算前缀消息的哈希
-    let mut hasher = Sha256::new();
-    hasher.update(prefixed_message.as_bytes());
-    let message_hash = hasher.finalize();
-
-    // 解析签名
-    let signature_bytes = match hex::decode(signature.trim_start_matches("0x")) {
-        Ok(bytes) => bytes,
+// 验证以太坊签名（EIP-191 personal_sign）
+pub fn verify_eth_signature(message: &str, signature: &str, wallet_address: &str) -> bool {
+    // 解析签名，v 可以是 0/1 或 27/28
+    let signature = match Signature::from_str(signature.trim()) {
+        Ok(sig) => sig,
         Err(_) => return false,
     };
 
-    let signature = match Signature::try_from(signature_bytes.as_slice()) {
-        Ok(sig) => sig,
+    let address = match Address::from_str(wallet_address.trim()) {
+        Ok(addr) => addr,
         Err(_) => return false,
     };
 
-    // 从签名恢复地址
-    let recovered_address = match signature.recover(H256::from_slice(&message_hash)) {
-        Ok(addr) => addr,
+    // 按前缀消息的keccak256哈希恢复地址并比较
+    signature.verify(message, address).is_ok()
+}
+
+// 验证Solana签名（ed25519，签名和地址均为base58编码）
+pub fn verify_sol_signature(message: &str, signature: &str, wallet_address: &str) -> bool {
+    let signature = match solana_sdk::signature::Signature::from_str(signature.trim()) {
+        Ok(sig) => sig,
         Err(_) => return false,
     };
 
-    // 比较恢复的地址和提供的地址
-    let provided_address = match Address::from_str(wallet_address.trim_start_matches("0x")) {
-        Ok(addr) => addr,
+    let pubkey = match solana_sdk::pubkey::Pubkey::from_str(wallet_address.trim()) {
+        Ok(pubkey) => pubkey,
         Err(_) => return false,
     };
 
-    recovered_address == provided_address
+    signature.verify(pubkey.as_ref(), message.as_bytes())
 }
 
-// 验证Solana签名 (简化版，实际应使用solana库进行验证)
-pub async fn verify_sol_signature(message: &str, signature: &str, wallet_address: &str) -> bool {
-    // 在实际实现中，应该使用solana-sdk进行正确的签名验证
-    // 这里仅作为示例占位符
-
-    // 1. 应该使用bs58解码签名和公钥
-    // 2. 使用ed25519-dalek验证签名
-    // 3. 返回验证结果
-
-    false // 暂时返回false，需要实际实现
+// 按钱包所在链验证签名，登录和内容签名共用
+pub fn verify_wallet_signature(wallet_chain: &str, message: &str, signature: &str, wallet_address: &str) -> bool {
+    if wallet_chain.eq_ignore_ascii_case("solana") || wallet_chain.eq_ignore_ascii_case("SOL") {
+        verify_sol_signature(message, signature, wallet_address)
+    } else {
+        verify_eth_signature(message, signature, wallet_address)
+    }
 }
@@ -0,0 +1,205 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+// 信封加密：每条内容使用随机数据密钥（AES-256-GCM）加密，数据密钥再用主密钥加密后随内容保存
+// 主密钥按ID区分，轮换时新增主密钥并设为active，旧主密钥保留用于解密
+const ENVELOPE_VERSION: u8 = 1;
+pub const ENVELOPE_ALG: &str = "A256GCM";
+pub const ENVELOPE_CONTENT_TYPE: &str = "application/json";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+lazy_static! {
+    // 全局主密钥，未配置时为None，内容以明文上传；配置了但无法加载时保留错误，不能退回明文
+    static ref KEYRING: Result<Option<Keyring>, String> = Keyring::from_env();
+}
+
+// 主密钥文件格式：{"active": "2023-10", "keys": {"2023-10": "<base64编码的32字节密钥>"}}
+#[derive(Deserialize)]
+struct KeyringFile {
+    active: String,
+    keys: HashMap<String, String>,
+}
+
+/// 上链的加密内容，所有二进制字段均为base64编码
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    v: u8,
+    alg: String,
+    kid: String,         // 加密数据密钥的主密钥ID
+    wrapped_key: String, // 主密钥加密后的数据密钥
+    nonce: String,
+    ciphertext: String,
+}
+
+/// 加密结果，key_id和wrapped_key与内容一起保存到数据库，轮换主密钥时重新加密wrapped_key
+#[derive(Debug, Clone)]
+pub struct SealedContent {
+    pub key_id: String,
+    pub wrapped_key: String,
+    pub envelope: Vec<u8>,
+}
+
+/// 内容加密主密钥
+pub struct Keyring {
+    active: String,
+    keys: HashMap<String, [u8; KEY_LEN]>,
+}
+
+impl Keyring {
+    // CONTENT_KEYRING_PATH：主密钥文件路径；CONTENT_KEYRING：直接配置主密钥JSON，两者都未配置时返回None
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let json = match env::var("CONTENT_KEYRING_PATH").ok().filter(|v| !v.is_empty()) {
+            Some(path) => fs::read_to_string(&path).map_err(|e| format!("读取主密钥文件{}失败: {}", path, e))?,
+            None => match env::var("CONTENT_KEYRING").ok().filter(|v| !v.is_empty()) {
+                Some(json) => json,
+                None => return Ok(None),
+            },
+        };
+
+        Self::from_json(&json).map(Some)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: KeyringFile = serde_json::from_str(json).map_err(|e| format!("主密钥格式错误: {}", e))?;
+
+        let mut keys = HashMap::with_capacity(file.keys.len());
+        for (id, encoded) in file.keys {
+            let key: [u8; KEY_LEN] = STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| format!("主密钥{}应为base64编码的{}字节", id, KEY_LEN))?;
+            keys.insert(id, key);
+        }
+        if !keys.contains_key(&file.active) {
+            return Err(format!("当前主密钥{}不存在", file.active));
+        }
+
+        Ok(Self {
+            active: file.active,
+            keys,
+        })
+    }
+
+    /// 当前用于加密的主密钥ID
+    pub fn active_id(&self) -> &str {
+        &self.active
+    }
+
+    /// 使用新的数据密钥加密内容，aad绑定内容所属的记录，防止密文被挪用到其他记录
+    pub fn seal(&self, data: &[u8], aad: &str) -> Result<SealedContent, String> {
+        let data_key = Aes256Gcm::generate_key(&mut OsRng);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&data_key)
+            .encrypt(&nonce, Payload { msg: data, aad: aad.as_bytes() })
+            .map_err(|_| "内容加密失败".to_string())?;
+        let wrapped_key = STANDARD.encode(self.wrap(&self.active, &data_key)?);
+
+        let envelope = Envelope {
+            v: ENVELOPE_VERSION,
+            alg: ENVELOPE_ALG.to_string(),
+            kid: self.active.clone(),
+            wrapped_key: wrapped_key.clone(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+
+        Ok(SealedContent {
+            key_id: self.active.clone(),
+            wrapped_key,
+            envelope: serde_json::to_vec(&envelope).map_err(|e| e.to_string())?,
+        })
+    }
+
+    /// 解密内容；wrapped为数据库中保存的(key_id, wrapped_key)，轮换后优先使用，未提供时使用信封中的数据密钥
+    pub fn open(&self, envelope: &[u8], aad: &str, wrapped: Option<(&str, &str)>) -> Result<Vec<u8>, String> {
+        let envelope: Envelope = serde_json::from_slice(envelope).map_err(|e| format!("加密内容格式错误: {}", e))?;
+        if envelope.v != ENVELOPE_VERSION || envelope.alg != ENVELOPE_ALG {
+            return Err(format!("不支持的加密格式: v{} {}", envelope.v, envelope.alg));
+        }
+
+        let (key_id, wrapped_key) = wrapped.unwrap_or((envelope.kid.as_str(), envelope.wrapped_key.as_str()));
+        let data_key = self.unwrap(key_id, &decode(wrapped_key)?)?;
+        let nonce = decode(&envelope.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err("加密内容格式错误: nonce长度无效".to_string());
+        }
+
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key))
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &decode(&envelope.ciphertext)?,
+                    aad: aad.as_bytes(),
+                },
+            )
+            .map_err(|_| "内容解密失败：密钥错误或内容被篡改".to_string())
+    }
+
+    /// 用当前主密钥重新加密数据密钥，返回新的(key_id, wrapped_key)
+    pub fn rewrap(&self, key_id: &str, wrapped_key: &str) -> Result<(String, String), String> {
+        let data_key = self.unwrap(key_id, &decode(wrapped_key)?)?;
+        let wrapped = self.wrap(&self.active, &data_key)?;
+
+        Ok((self.active.clone(), STANDARD.encode(wrapped)))
+    }
+
+    // 加密数据密钥，格式为nonce || 密文，主密钥ID作为aad
+    fn wrap(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher(key_id)?
+            .encrypt(&nonce, Payload { msg: data_key, aad: key_id.as_bytes() })
+            .map_err(|_| "数据密钥加密失败".to_string())?;
+
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    fn unwrap(&self, key_id: &str, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+        if wrapped.len() <= NONCE_LEN {
+            return Err("数据密钥格式错误".to_string());
+        }
+        let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+        let data_key = self
+            .cipher(key_id)?
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: key_id.as_bytes() })
+            .map_err(|_| format!("无法用主密钥{}解密数据密钥", key_id))?;
+        if data_key.len() != KEY_LEN {
+            return Err("数据密钥长度无效".to_string());
+        }
+
+        Ok(data_key)
+    }
+
+    fn cipher(&self, key_id: &str) -> Result<Aes256Gcm, String> {
+        let key = self.keys.get(key_id).ok_or_else(|| format!("主密钥{}不存在", key_id))?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(value).map_err(|e| format!("base64解码失败: {}", e))
+}
+
+/// 内容记录的aad，格式为 表名/ID
+pub fn content_aad(content_table: &str, content_id: &str) -> String {
+    format!("{}/{}", content_table, content_id)
+}
+
+// 获取全局主密钥，未配置时返回None，配置错误时返回错误
+pub fn keyring() -> Result<Option<&'static Keyring>, String> {
+    match KEYRING.as_ref() {
+        Ok(keyring) => Ok(keyring.as_ref()),
+        Err(e) => Err(format!("加载内容加密主密钥失败: {}", e)),
+    }
+}
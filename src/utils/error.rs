@@ -26,3 +26,8 @@ impl fmt::Display for ServiceError {
 }
 
 impl std::error::Error for ServiceError {}
+
+// Postgres唯一约束冲突（SQLSTATE 23505）
+pub fn is_unique_violation(message: &str) -> bool {
+    message.starts_with("23505")
+}
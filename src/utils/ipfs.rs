@@ -1,64 +1,214 @@
-// Add futures::StreamExt import
-use futures::StreamExt;
-use ipfs_api_backend_hyper::{IpfsApi, IpfsClient};
+use crate::utils::cid::{compute_cid_v1, verify_cid};
+use lazy_static::lazy_static;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::Deserialize;
 use std::env;
-use std::io::Cursor;
-
-// IPFS客户端单例
-pub fn get_ipfs_client() -> IpfsClient {
-    let ipfs_api_url = env::var("IPFS_API_URL").unwrap_or_else(|_| "http://localhost:5001".to_string());
-    let url_parts: Vec<&str> = ipfs_api_url.split("://").collect();
-    
-    if url_parts.len() != 2 {
-        panic!("Invalid IPFS API URL format");
+use std::time::Duration;
+
+// 与 utils::cid 的本地计算参数保持一致
+const ADD_PARAMS: &[(&str, &str)] = &[
+    ("cid-version", "1"),
+    ("raw-leaves", "true"),
+    ("chunker", "size-262144"),
+    ("pin", "true"),
+];
+// 请求超时（秒），通过 IPFS_TIMEOUT_SECS 配置
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+lazy_static! {
+    // 全局IPFS后端，按环境变量初始化
+    static ref IPFS_BACKEND: IpfsBackend = IpfsBackend::from_env();
+}
+
+// 远程固定服务（IPFS Pinning Service API）
+struct PinningService {
+    endpoint: String,
+    token: String,
+}
+
+/// 远程固定状态
+#[derive(Debug, Deserialize)]
+pub struct PinStatus {
+    pub requestid: String,
+    pub status: String, // queued、pinning、pinned、failed
+}
+
+/// IPFS后端，通过Kubo HTTP API上传（同时固定）和读取内容，可选同步固定到远程固定服务
+/// 上传前在本地计算CID并与节点返回的结果比对，读取的内容校验与请求的CID一致
+pub struct IpfsBackend {
+    api_url: String,
+    api_auth: Option<String>,
+    gateway_url: String,
+    pinning: Option<PinningService>,
+    client: Client,
+}
+
+impl IpfsBackend {
+    // IPFS_API_URL：Kubo API地址；IPFS_API_AUTH：Authorization请求头（如 "Bearer xxx" 或 "Basic xxx"）
+    // IPFS_PINNING_SERVICE_URL、IPFS_PINNING_SERVICE_TOKEN：远程固定服务，不配置时只固定在本地节点
+    pub fn from_env() -> Self {
+        let api_url = env::var("IPFS_API_URL").unwrap_or_else(|_| "http://localhost:5001".to_string());
+        let api_auth = env::var("IPFS_API_AUTH").ok().filter(|v| !v.is_empty());
+        let pinning = match (
+            env::var("IPFS_PINNING_SERVICE_URL").ok().filter(|v| !v.is_empty()),
+            env::var("IPFS_PINNING_SERVICE_TOKEN").ok().filter(|v| !v.is_empty()),
+        ) {
+            (Some(endpoint), Some(token)) => Some(PinningService {
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                token,
+            }),
+            _ => None,
+        };
+        let timeout = env::var("IPFS_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_auth,
+            gateway_url: get_ipfs_gateway_url().trim_end_matches('/').to_string(),
+            pinning,
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout))
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
     }
-    
-    let (protocol, rest) = (url_parts[0], url_parts[1]);
-    let host_parts: Vec<&str> = rest.split(':').collect();
-    
-    if host_parts.len() != 2 {
-        panic!("Invalid IPFS API URL format");
+
+    /// 上传内容并固定，返回CIDv1
+    pub async fn add(&self, data: &[u8]) -> Result<String, String> {
+        #[derive(Deserialize)]
+        struct AddResponse {
+            #[serde(rename = "Hash")]
+            hash: String,
+        }
+
+        let expected = compute_cid_v1(data);
+        let form = Form::new().part("file", Part::bytes(data.to_vec()).file_name("file"));
+        let response = self
+            .api_request("add")
+            .query(ADD_PARAMS)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("IPFS上传失败: {}", e))?;
+        let added: AddResponse = parse_response(response).await?;
+
+        if added.hash != expected {
+            return Err(format!("IPFS节点返回的CID {} 与本地计算的 {} 不一致", added.hash, expected));
+        }
+
+        if self.pinning.is_some() {
+            if let Err(e) = self.pin_remote(&expected, None).await {
+                log::warn!("远程固定{}失败: {}", expected, e);
+            }
+        }
+
+        Ok(expected)
     }
-    
-    let (host, port_str) = (host_parts[0], host_parts[1]);
-    let port = port_str.parse::<u16>().expect("Invalid IPFS API port");
-    
-    match protocol {
-        "http" => IpfsClient::default(),
-        "https" => IpfsClient::default(),
-        _ => panic!("Unsupported IPFS API protocol: {}", protocol),
+
+    /// 提交到远程固定服务
+    pub async fn pin_remote(&self, cid: &str, name: Option<&str>) -> Result<PinStatus, String> {
+        let pinning = self.pinning.as_ref().ok_or("未配置远程固定服务")?;
+
+        let mut body = serde_json::json!({ "cid": cid });
+        if let Some(name) = name {
+            body["name"] = serde_json::json!(name);
+        }
+        let response = self
+            .client
+            .post(format!("{}/pins", pinning.endpoint))
+            .bearer_auth(&pinning.token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("远程固定服务错误: {}", e))?;
+
+        parse_response(response).await
     }
-}
 
-// 获取IPFS网关URL
-pub fn get_ipfs_gateway_url() -> String {
-    env::var("IPFS_GATEWAY_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
+    /// 读取内容并校验CID，本地节点不可用时从网关读取
+    pub async fn cat(&self, cid: &str) -> Result<Vec<u8>, String> {
+        let data = match self.cat_from_node(cid).await {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("从IPFS节点读取{}失败，改用网关: {}", cid, e);
+                self.cat_from_gateway(cid).await?
+            }
+        };
+
+        verify_cid(cid, &data)?;
+
+        Ok(data)
+    }
+
+    /// 生成网关访问地址
+    pub fn gateway_url(&self, cid: &str) -> String {
+        format!("{}/ipfs/{}", self.gateway_url, cid)
+    }
+
+    async fn cat_from_node(&self, cid: &str) -> Result<Vec<u8>, String> {
+        let response = self
+            .api_request("cat")
+            .query(&[("arg", cid)])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        read_bytes(response).await
+    }
+
+    async fn cat_from_gateway(&self, cid: &str) -> Result<Vec<u8>, String> {
+        let response = self
+            .client
+            .get(self.gateway_url(cid))
+            .send()
+            .await
+            .map_err(|e| format!("IPFS网关错误: {}", e))?;
+
+        read_bytes(response).await
+    }
+
+    // Kubo API只接受POST请求
+    fn api_request(&self, command: &str) -> reqwest::RequestBuilder {
+        let request = self.client.post(format!("{}/api/v0/{}", self.api_url, command));
+        match &self.api_auth {
+            Some(auth) => request.header(reqwest::header::AUTHORIZATION, auth),
+            None => request,
+        }
+    }
 }
 
-// 上传文件到IPFS
-pub async fn upload_to_ipfs(data: Vec<u8>) -> Result<String, String> {
-    let client: IpfsClient = get_ipfs_client();
-    let cursor = Cursor::new(data);
-    
-    match client.add(cursor).await {
-        Ok(res) => Ok(res.hash),
-        Err(e) => Err(format!("Failed to upload to IPFS: {}", e)),
+async fn parse_response<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, String> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
+        return Err(format!("IPFS请求失败({}): {}", status, error_text));
     }
+
+    response.json::<T>().await.map_err(|e| format!("解析IPFS响应失败: {}", e))
 }
 
-// 从IPFS获取内容
-pub async fn get_from_ipfs(cid: &str) -> Result<Vec<u8>, String> {
-    let client: IpfsClient = get_ipfs_client();
-    
-    // Properly handle the stream response
-    let mut stream = client.cat(cid);
-        //.map_err(|e| format!("Failed to start IPFS cat: {}", e))?;
-    
-    let mut bytes = Vec::new();
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
-        bytes.extend_from_slice(&chunk);
+async fn read_bytes(response: reqwest::Response) -> Result<Vec<u8>, String> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
+        return Err(format!("IPFS内容获取失败({}): {}", status, error_text));
     }
-    
-    Ok(bytes)
-}
\ No newline at end of file
+
+    let bytes = response.bytes().await.map_err(|e| format!("读取IPFS响应失败: {}", e))?;
+    Ok(bytes.to_vec())
+}
+
+// 获取全局IPFS后端
+pub fn ipfs_backend() -> &'static IpfsBackend {
+    &IPFS_BACKEND
+}
+
+// 获取IPFS网关URL
+pub fn get_ipfs_gateway_url() -> String {
+    env::var("IPFS_GATEWAY_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
+}
+
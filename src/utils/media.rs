@@ -0,0 +1,558 @@
+use image::imageops::FilterType;
+use image::ImageFormat;
+use std::io::Cursor;
+
+// 单个图片最大10MB
+pub const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
+// GIF动图最大15MB
+pub const MAX_GIF_SIZE: usize = 15 * 1024 * 1024;
+// 短视频最大50MB
+pub const MAX_VIDEO_SIZE: usize = 50 * 1024 * 1024;
+// 缩略图最大边长
+const THUMBNAIL_SIZE: u32 = 320;
+// 识别媒体类型所需的文件头长度
+pub const MEDIA_SNIFF_LEN: usize = 256;
+
+// 接受的MP4品牌；HEIC/AVIF等图片容器和QuickTime(MOV)同样以ftyp开头，需排除
+const MP4_BRANDS: [&[u8; 4]; 11] = [
+    b"isom", b"iso2", b"iso3", b"iso4", b"iso5", b"iso6", b"mp41", b"mp42", b"avc1", b"dash",
+    b"M4V ",
+];
+const REJECTED_BRANDS: [&[u8; 4]; 11] = [
+    b"qt  ", b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1", b"msf1", b"avif",
+    b"avis",
+];
+
+/// 支持的媒体类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Jpeg,
+    Png,
+    Gif,
+    Mp4,
+    WebM,
+}
+
+impl MediaKind {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            MediaKind::Jpeg => "image/jpeg",
+            MediaKind::Png => "image/png",
+            MediaKind::Gif => "image/gif",
+            MediaKind::Mp4 => "video/mp4",
+            MediaKind::WebM => "video/webm",
+        }
+    }
+
+    /// 媒体分类：image、gif、video
+    pub fn category(&self) -> &'static str {
+        match self {
+            MediaKind::Jpeg | MediaKind::Png => "image",
+            MediaKind::Gif => "gif",
+            MediaKind::Mp4 | MediaKind::WebM => "video",
+        }
+    }
+
+    pub fn max_size(&self) -> usize {
+        match self {
+            MediaKind::Jpeg | MediaKind::Png => MAX_IMAGE_SIZE,
+            MediaKind::Gif => MAX_GIF_SIZE,
+            MediaKind::Mp4 | MediaKind::WebM => MAX_VIDEO_SIZE,
+        }
+    }
+}
+
+// 通过文件头魔数识别媒体类型，不信任客户端提供的Content-Type
+pub fn detect_media_kind(data: &[u8]) -> Option<MediaKind> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(MediaKind::Jpeg)
+    } else if data.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some(MediaKind::Png)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(MediaKind::Gif)
+    } else if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        is_mp4_ftyp(data).then_some(MediaKind::Mp4)
+    } else if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some(MediaKind::WebM)
+    } else {
+        None
+    }
+}
+
+// 检查ftyp中的主品牌和兼容品牌：至少有一个MP4品牌，且不含图片或QuickTime品牌
+fn is_mp4_ftyp(data: &[u8]) -> bool {
+    let box_size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    if box_size < 16 {
+        return false;
+    }
+    // 只读取已收到的部分，兼容品牌列表可能被截断
+    let compatible = data.get(16..box_size.min(data.len())).unwrap_or(&[]);
+    let brands = std::iter::once(&data[8..12])
+        .chain(compatible.chunks_exact(4))
+        .collect::<Vec<_>>();
+
+    brands
+        .iter()
+        .any(|brand| MP4_BRANDS.iter().any(|b| b.as_slice() == *brand))
+        && !brands
+            .iter()
+            .any(|brand| REJECTED_BRANDS.iter().any(|b| b.as_slice() == *brand))
+}
+
+// 校验媒体类型和大小
+pub fn validate_media(data: &[u8]) -> Result<MediaKind, String> {
+    let kind = detect_media_kind(data).ok_or_else(|| "不支持的媒体类型".to_string())?;
+
+    if data.len() > kind.max_size() {
+        return Err(format!(
+            "文件过大，{}最大支持{}MB",
+            kind.category(),
+            kind.max_size() / 1024 / 1024
+        ));
+    }
+
+    Ok(kind)
+}
+
+// 去除媒体中的EXIF等元数据（可能包含GPS位置、设备信息）
+// 图片直接按段/块过滤，视频原位覆盖为填充块，都不重新编码，不损失画质
+pub fn strip_metadata(kind: MediaKind, data: &[u8]) -> Result<Vec<u8>, String> {
+    match kind {
+        MediaKind::Jpeg => strip_jpeg_metadata(data),
+        MediaKind::Png => strip_png_metadata(data),
+        MediaKind::Gif => strip_gif_metadata(data),
+        MediaKind::Mp4 => strip_mp4_metadata(data),
+        MediaKind::WebM => strip_webm_metadata(data),
+    }
+}
+
+// 去除JPEG中的APP1(EXIF/XMP)、APP13(IPTC)和COM段
+fn strip_jpeg_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[0..2]);
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err("JPEG数据格式错误".to_string());
+        }
+        let marker = data[pos + 1];
+
+        // SOS之后是压缩数据，直接保留剩余部分
+        if marker == 0xDA {
+            output.extend_from_slice(&data[pos..]);
+            return Ok(output);
+        }
+
+        let segment_len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment_end = pos + 2 + segment_len;
+        if segment_end > data.len() {
+            return Err("JPEG数据格式错误".to_string());
+        }
+
+        let is_metadata = marker == 0xE1 || marker == 0xED || marker == 0xFE;
+        if !is_metadata {
+            output.extend_from_slice(&data[pos..segment_end]);
+        }
+        pos = segment_end;
+    }
+
+    Err("JPEG数据不完整".to_string())
+}
+
+// 去除PNG中的eXIf和文本块
+fn strip_png_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&data[0..8]);
+
+    let mut pos = 8;
+    while pos + 12 <= data.len() {
+        let chunk_len =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let chunk_type = &data[pos + 4..pos + 8];
+        // 长度 + 类型 + 数据 + CRC
+        let chunk_end = pos + 12 + chunk_len;
+        if chunk_end > data.len() {
+            return Err("PNG数据格式错误".to_string());
+        }
+
+        let is_metadata = matches!(chunk_type, b"eXIf" | b"tEXt" | b"iTXt" | b"zTXt" | b"tIME");
+        if !is_metadata {
+            output.extend_from_slice(&data[pos..chunk_end]);
+        }
+
+        if chunk_type == b"IEND" {
+            return Ok(output);
+        }
+        pos = chunk_end;
+    }
+
+    Err("PNG数据不完整".to_string())
+}
+
+// 去除GIF中的注释扩展和应用扩展（XMP等），保留控制循环播放的NETSCAPE扩展
+fn strip_gif_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+    let err = || "GIF数据格式错误".to_string();
+    if data.len() < 13 {
+        return Err(err());
+    }
+
+    // 文件头 + 逻辑屏幕描述符 + 全局颜色表
+    let mut pos = 13;
+    if data[10] & 0x80 != 0 {
+        pos += 3 << ((data[10] & 0x07) + 1);
+    }
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(data.get(..pos).ok_or_else(err)?);
+
+    while let Some(&block) = data.get(pos) {
+        let start = pos;
+        match block {
+            // 扩展块：标签 + 子块序列
+            0x21 => {
+                let label = *data.get(pos + 1).ok_or_else(err)?;
+                let is_looping = label == 0xFF
+                    && data.get(pos + 2) == Some(&11)
+                    && matches!(
+                        data.get(pos + 3..pos + 14),
+                        Some(b"NETSCAPE2.0") | Some(b"ANIMEXTS1.0")
+                    );
+                pos = skip_gif_sub_blocks(data, pos + 2).ok_or_else(err)?;
+                let is_metadata = label == 0xFE || (label == 0xFF && !is_looping);
+                if !is_metadata {
+                    output.extend_from_slice(&data[start..pos]);
+                }
+            }
+            // 图像描述符 + 局部颜色表 + LZW最小码长 + 图像数据子块
+            0x2C => {
+                let packed = *data.get(pos + 9).ok_or_else(err)?;
+                pos += 10;
+                if packed & 0x80 != 0 {
+                    pos += 3 << ((packed & 0x07) + 1);
+                }
+                pos = skip_gif_sub_blocks(data, pos + 1).ok_or_else(err)?;
+                output.extend_from_slice(&data[start..pos]);
+            }
+            0x3B => {
+                output.push(0x3B);
+                return Ok(output);
+            }
+            _ => return Err(err()),
+        }
+    }
+
+    Err("GIF数据不完整".to_string())
+}
+
+// 跳过GIF子块序列，返回结束符之后的位置
+fn skip_gif_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *data.get(pos)? as usize;
+        pos += 1 + len;
+        if len == 0 {
+            return if pos <= data.len() { Some(pos) } else { None };
+        }
+    }
+}
+
+// 将MP4中的udta/meta/uuid(XMP)盒子原位改为free盒子并清零，
+// 不改变文件长度，stco/co64中的偏移无需调整
+fn strip_mp4_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = data.to_vec();
+    blank_mp4_boxes(&mut output, 0, data.len())?;
+    Ok(output)
+}
+
+fn blank_mp4_boxes(data: &mut [u8], mut pos: usize, end: usize) -> Result<(), String> {
+    let err = || "MP4数据格式错误".to_string();
+    while pos < end {
+        if pos + 8 > end {
+            return Err(err());
+        }
+        let size =
+            u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as u64;
+        let (box_size, header_len) = match size {
+            // 盒子延伸到文件末尾
+            0 => ((end - pos) as u64, 8),
+            // 64位扩展长度
+            1 => {
+                let large = data.get(pos + 8..pos + 16).ok_or_else(err)?;
+                (u64::from_be_bytes(large.try_into().unwrap()), 16)
+            }
+            size => (size, 8),
+        };
+        if box_size < header_len as u64 || box_size > (end - pos) as u64 {
+            return Err(err());
+        }
+        let box_end = pos + box_size as usize;
+
+        match &data[pos + 4..pos + 8] {
+            b"udta" | b"meta" | b"uuid" => {
+                data[pos + 4..pos + 8].copy_from_slice(b"free");
+                data[pos + header_len..box_end].fill(0);
+            }
+            b"moov" | b"trak" | b"mdia" => blank_mp4_boxes(data, pos + header_len, box_end)?,
+            _ => {}
+        }
+        pos = box_end;
+    }
+    Ok(())
+}
+
+const EBML_SEGMENT: u32 = 0x1853_8067;
+const EBML_INFO: u32 = 0x1549_A966;
+const EBML_TAGS: u32 = 0x1254_C367;
+const EBML_ATTACHMENTS: u32 = 0x1941_A469;
+const EBML_TITLE: u32 = 0x7BA9;
+const EBML_DATE_UTC: u32 = 0x4461;
+
+// 将WebM中的Tags、Attachments以及Info下的标题和日期原位改为Void元素，
+// 不改变元素位置，SeekHead和Cues中的偏移无需调整
+fn strip_webm_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+    let err = || "WebM数据格式错误".to_string();
+    let mut output = data.to_vec();
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let (id, id_len) = read_ebml_vint(data, pos, false).ok_or_else(err)?;
+        let id = id.unwrap_or_default() as u32;
+        let (size, size_len) = read_ebml_vint(data, pos + id_len, true).ok_or_else(err)?;
+        let data_start = pos + id_len + size_len;
+
+        // Segment、Info以及长度未知的元素（如流式录制的Cluster）进入子元素继续扫描
+        let Some(size) = size else {
+            pos = data_start;
+            continue;
+        };
+        if id == EBML_SEGMENT || id == EBML_INFO {
+            pos = data_start;
+            continue;
+        }
+
+        let end = data_start
+            .checked_add(size as usize)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(err)?;
+        if matches!(
+            id,
+            EBML_TAGS | EBML_ATTACHMENTS | EBML_TITLE | EBML_DATE_UTC
+        ) {
+            write_ebml_void(&mut output[pos..end]);
+        }
+        pos = end;
+    }
+
+    Ok(output)
+}
+
+// 读取EBML变长整数；作为长度时全1表示未知长度，返回None
+fn read_ebml_vint(data: &[u8], pos: usize, is_size: bool) -> Option<(Option<u64>, usize)> {
+    let first = *data.get(pos)?;
+    if first == 0 {
+        return None;
+    }
+    let len = first.leading_zeros() as usize + 1;
+    let bytes = data.get(pos..pos + len)?;
+
+    // ID保留长度标记位，长度值去掉标记位
+    let mut value = if is_size {
+        first as u64 & (0xFF >> len)
+    } else {
+        first as u64
+    };
+    for &byte in &bytes[1..] {
+        value = (value << 8) | byte as u64;
+    }
+
+    let unknown = is_size && value == (1u64 << (7 * len)) - 1;
+    Some((if unknown { None } else { Some(value) }, len))
+}
+
+// 用同样长度的Void元素（ID 0xEC）覆盖
+fn write_ebml_void(element: &mut [u8]) {
+    let size_len = (element.len() - 1).min(8);
+    let data_len = (element.len() - 1 - size_len) as u64;
+
+    element.fill(0);
+    element[0] = 0xEC;
+    let size = data_len | (1u64 << (7 * size_len));
+    for i in 0..size_len {
+        element[1 + i] = (size >> (8 * (size_len - 1 - i))) as u8;
+    }
+}
+
+// 生成JPEG缩略图，视频暂不生成
+pub fn generate_thumbnail(kind: MediaKind, data: &[u8]) -> Result<Option<Vec<u8>>, String> {
+    let format = match kind {
+        MediaKind::Jpeg => ImageFormat::Jpeg,
+        MediaKind::Png => ImageFormat::Png,
+        // GIF取第一帧
+        MediaKind::Gif => ImageFormat::Gif,
+        MediaKind::Mp4 | MediaKind::WebM => return Ok(None),
+    };
+
+    let img = image::load_from_memory_with_format(data, format)
+        .map_err(|e| format!("解析图片失败: {}", e))?;
+    let thumbnail = img
+        .resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle)
+        .to_rgb8();
+
+    let mut buffer = Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut buffer, ImageFormat::Jpeg)
+        .map_err(|e| format!("生成缩略图失败: {}", e))?;
+
+    Ok(Some(buffer.into_inner()))
+}
+
+// 读取图片尺寸
+pub fn image_dimensions(kind: MediaKind, data: &[u8]) -> Option<(u32, u32)> {
+    let format = match kind {
+        MediaKind::Jpeg => ImageFormat::Jpeg,
+        MediaKind::Png => ImageFormat::Png,
+        MediaKind::Gif => ImageFormat::Gif,
+        MediaKind::Mp4 | MediaKind::WebM => return None,
+    };
+
+    image::load_from_memory_with_format(data, format)
+        .ok()
+        .map(|img| (img.width(), img.height()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(major: &[u8; 4], compatible: &[&[u8; 4]]) -> Vec<u8> {
+        let mut data = ((16 + 4 * compatible.len()) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(b"ftyp");
+        data.extend_from_slice(major);
+        data.extend_from_slice(&[0, 0, 2, 0]);
+        for brand in compatible {
+            data.extend_from_slice(*brand);
+        }
+        data
+    }
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = ((8 + body.len()) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn detects_mp4_by_brand() {
+        assert_eq!(
+            detect_media_kind(&ftyp(b"isom", &[b"isom", b"avc1"])),
+            Some(MediaKind::Mp4)
+        );
+        assert_eq!(
+            detect_media_kind(&ftyp(b"mp42", &[b"mp41", b"isom"])),
+            Some(MediaKind::Mp4)
+        );
+    }
+
+    #[test]
+    fn rejects_image_and_quicktime_containers() {
+        // HEIC、AVIF、MOV
+        assert_eq!(detect_media_kind(&ftyp(b"heic", &[b"mif1", b"heic"])), None);
+        assert_eq!(
+            detect_media_kind(&ftyp(b"avif", &[b"avif", b"mif1", b"miaf"])),
+            None
+        );
+        assert_eq!(detect_media_kind(&ftyp(b"qt  ", &[b"qt  "])), None);
+        // 兼容品牌中声明了图片品牌
+        assert_eq!(detect_media_kind(&ftyp(b"isom", &[b"isom", b"avif"])), None);
+        // 未知品牌
+        assert_eq!(detect_media_kind(&ftyp(b"3gp4", &[b"3gp4"])), None);
+    }
+
+    #[test]
+    fn blanks_mp4_metadata_in_place() {
+        let udta = mp4_box(b"udta", &mp4_box(b"\xa9xyz", b"+39.9+116.4/"));
+        let trak = mp4_box(b"trak", &mp4_box(b"tkhd", &[1; 12]));
+        let moov = mp4_box(
+            b"moov",
+            &[mp4_box(b"mvhd", &[2; 12]), trak.clone(), udta.clone()].concat(),
+        );
+        let data = [ftyp(b"isom", &[b"isom"]), moov, mp4_box(b"mdat", &[3; 32])].concat();
+
+        let stripped = strip_metadata(MediaKind::Mp4, &data).unwrap();
+        assert_eq!(stripped.len(), data.len());
+        assert!(!stripped.windows(4).any(|w| w == b"udta"));
+        assert!(!stripped.windows(12).any(|w| w == b"+39.9+116.4/"));
+        // 媒体数据和轨道信息保持原位
+        assert_eq!(&stripped[stripped.len() - 40..], &data[data.len() - 40..]);
+        assert!(stripped.windows(trak.len()).any(|w| w == trak.as_slice()));
+    }
+
+    #[test]
+    fn rejects_truncated_mp4() {
+        let mut data = ftyp(b"isom", &[b"isom"]);
+        data.extend_from_slice(&mp4_box(b"moov", &[0; 16])[..12]);
+        assert!(strip_metadata(MediaKind::Mp4, &data).is_err());
+    }
+
+    #[test]
+    fn voids_webm_tags() {
+        let header = [&[0x1A, 0x45, 0xDF, 0xA3, 0x84][..], b"webm"].concat();
+        let title = [&[0x7B, 0xA9, 0x85][..], b"Title"].concat();
+        let info = [
+            &[0x15, 0x49, 0xA9, 0x66, 0x80 | title.len() as u8][..],
+            &title,
+        ]
+        .concat();
+        let tags = [
+            &[0x12, 0x54, 0xC3, 0x67, 0x87][..],
+            b"GPS+39.9"[..7].as_ref(),
+        ]
+        .concat();
+        // 长度未知的Segment和Cluster
+        let cluster = [
+            &[
+                0x1F, 0x43, 0xB6, 0x75, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            ][..],
+            &[0xA3, 0x83, 1, 2, 3],
+        ]
+        .concat();
+        let segment = [&[0x18, 0x53, 0x80, 0x67, 0xFF][..], &info, &cluster, &tags].concat();
+        let data = [header, segment].concat();
+
+        let stripped = strip_metadata(MediaKind::WebM, &data).unwrap();
+        assert_eq!(stripped.len(), data.len());
+        assert!(!stripped.windows(5).any(|w| w == b"Title"));
+        assert!(!stripped.windows(3).any(|w| w == b"GPS"));
+        // 12字节的Tags变为8字节长度的Void元素
+        assert_eq!(
+            &stripped[stripped.len() - tags.len()..],
+            &[0xEC, 1, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0]
+        );
+        assert!(stripped.windows(5).any(|w| w == [0xA3, 0x83, 1, 2, 3]));
+    }
+
+    #[test]
+    fn strips_gif_comments_and_keeps_looping() {
+        let mut data = b"GIF89a".to_vec();
+        // 1x1，2色全局颜色表
+        data.extend_from_slice(&[1, 0, 1, 0, 0x80, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF]);
+        let looping = [&[0x21, 0xFF, 11][..], b"NETSCAPE2.0", &[3, 1, 0, 0, 0]].concat();
+        let xmp = [
+            &[0x21, 0xFF, 11][..],
+            b"XMP DataXMP",
+            &[4, b'g', b'p', b's', b'!', 0],
+        ]
+        .concat();
+        let comment = [&[0x21, 0xFE, 5][..], b"hello", &[0]].concat();
+        let image = [0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x4C, 0x01, 0];
+        data.extend_from_slice(&looping);
+        data.extend_from_slice(&xmp);
+        data.extend_from_slice(&comment);
+        data.extend_from_slice(&image);
+        data.push(0x3B);
+
+        let stripped = strip_metadata(MediaKind::Gif, &data).unwrap();
+        let expected = [&data[..19], &looping[..], &image[..], &[0x3B]].concat();
+        assert_eq!(stripped, expected);
+    }
+}
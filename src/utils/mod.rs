@@ -1,6 +1,14 @@
 pub mod crypto;
 pub mod jwt;
+pub mod cid;
 pub mod ipfs;
 pub mod arweave;
+pub mod ans104;
+pub mod envelope;
+pub mod storage_backend;
+pub mod s3;
+pub mod authorship;
 pub mod pagination;
-pub mod error; 
\ No newline at end of file
+pub mod error;
+pub mod media;
+pub mod nft_metadata;
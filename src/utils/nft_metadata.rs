@@ -0,0 +1,416 @@
+use crate::utils::arweave::get_arweave_node_url;
+use crate::utils::ipfs::get_ipfs_gateway_url;
+use base64::Engine;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+// 元数据请求超时
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+// 元数据JSON的最大大小
+const MAX_METADATA_SIZE: usize = 1024 * 1024;
+// 最多跟随的重定向次数，每次跳转都重新校验目标地址
+const MAX_REDIRECTS: usize = 3;
+
+/// NFT元数据（ERC721 / ERC1155 / Metaplex通用字段）
+#[derive(Debug, Clone, Default)]
+pub struct NftMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub attributes: Option<serde_json::Value>,
+}
+
+// 将ipfs://、ar://等URI转换为可访问的HTTP地址，data: URI和无法识别的协议返回None
+pub fn resolve_uri(uri: &str) -> Option<String> {
+    let uri = uri.trim();
+
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        // 兼容 ipfs://ipfs/<cid> 的写法
+        let path = path.strip_prefix("ipfs/").unwrap_or(path);
+        return Some(format!("{}/ipfs/{}", get_ipfs_gateway_url().trim_end_matches('/'), path));
+    }
+    if let Some(id) = uri.strip_prefix("ar://") {
+        return Some(format!("{}/{}", get_arweave_node_url().trim_end_matches('/'), id));
+    }
+    if uri.starts_with("http://") || uri.starts_with("https://") {
+        return Some(uri.to_string());
+    }
+
+    None
+}
+
+// 解析 data: URI 的内容，支持base64和明文两种编码，超过元数据大小上限时返回None
+pub fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    let (header, data) = uri.strip_prefix("data:")?.split_once(',')?;
+
+    let body = if header.ends_with(";base64") {
+        // 先按编码长度估算，避免解码过大的内容
+        if data.trim().trim_end_matches('=').len() / 4 * 3 > MAX_METADATA_SIZE {
+            return None;
+        }
+        base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?
+    } else {
+        data.as_bytes().to_vec()
+    };
+
+    (body.len() <= MAX_METADATA_SIZE).then_some(body)
+}
+
+// 获取并解析NFT元数据
+pub async fn fetch_metadata(uri: &str) -> Result<NftMetadata, String> {
+    let body = if uri.starts_with("data:") {
+        decode_data_uri(uri).ok_or_else(|| "Invalid data URI".to_string())?
+    } else {
+        let url = resolve_uri(uri).ok_or_else(|| format!("Unsupported metadata URI: {}", uri))?;
+        fetch_bytes(&url, &[get_ipfs_gateway_url(), get_arweave_node_url()]).await?
+    };
+
+    let json: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| format!("Invalid metadata JSON: {}", e))?;
+
+    Ok(parse_metadata(&json))
+}
+
+// 从元数据JSON中提取通用字段，图片地址统一转换为HTTP地址
+pub fn parse_metadata(json: &serde_json::Value) -> NftMetadata {
+    let text = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    // 链上SVG等 data: 图片直接保留
+    let image_url = text("image")
+        .or_else(|| text("image_url"))
+        .and_then(|image| {
+            if image.starts_with("data:") {
+                Some(image)
+            } else {
+                resolve_uri(&image)
+            }
+        })
+        .or_else(|| {
+            text("image_data").map(|svg| {
+                format!(
+                    "data:image/svg+xml;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(svg)
+                )
+            })
+        });
+
+    NftMetadata {
+        name: text("name"),
+        description: text("description"),
+        image_url,
+        attributes: json.get("attributes").filter(|v| v.is_array()).cloned(),
+    }
+}
+
+// 元数据地址由NFT合约提供，只允许访问公网地址，重定向逐跳校验，响应体按上限流式读取
+// gateways为配置的IPFS网关和Arweave节点，可能部署在内网，不做限制
+async fn fetch_bytes(url: &str, gateways: &[String]) -> Result<Vec<u8>, String> {
+    let mut url = Url::parse(url).map_err(|e| format!("Invalid metadata URL: {}", e))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let client = public_client(&url, gateways).await?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch metadata: {}", e))?;
+
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| "Failed to fetch metadata: redirect without location".to_string())?;
+            url = url
+                .join(location)
+                .map_err(|e| format!("Invalid metadata redirect: {}", e))?;
+            continue;
+        }
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch metadata: HTTP {}", response.status()));
+        }
+
+        return read_limited(response).await;
+    }
+
+    Err("Failed to fetch metadata: too many redirects".to_string())
+}
+
+// 创建只能访问该地址的客户端：域名解析结果必须全部为公网IP，并固定使用校验过的IP，防止DNS重绑定
+async fn public_client(url: &Url, gateways: &[String]) -> Result<reqwest::Client, String> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("Unsupported metadata URL scheme: {}", url.scheme()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| "Metadata URL has no host".to_string())?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let mut builder = reqwest::Client::builder()
+        .timeout(METADATA_TIMEOUT)
+        .redirect(Policy::none());
+
+    if !is_gateway(url, gateways) {
+        let literal_ip = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok();
+        let addrs: Vec<SocketAddr> = match literal_ip {
+            Some(ip) => vec![SocketAddr::new(ip, port)],
+            None => tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+                .collect(),
+        };
+        if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+            return Err(format!("Metadata host {} is not a public address", host));
+        }
+        if literal_ip.is_none() {
+            builder = builder.resolve_to_addrs(host, &addrs);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn is_gateway(url: &Url, gateways: &[String]) -> bool {
+    gateways.iter().any(|gateway| {
+        Url::parse(gateway)
+            .map(|gateway| {
+                gateway.host_str() == url.host_str()
+                    && gateway.port_or_known_default() == url.port_or_known_default()
+            })
+            .unwrap_or(false)
+    })
+}
+
+// 按块读取响应体，超过上限立即中止
+async fn read_limited(mut response: reqwest::Response) -> Result<Vec<u8>, String> {
+    if response.content_length().is_some_and(|len| len > MAX_METADATA_SIZE as u64) {
+        return Err("Metadata too large".to_string());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read metadata: {}", e))?
+    {
+        if body.len() + chunk.len() > MAX_METADATA_SIZE {
+            return Err("Metadata too large".to_string());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+// 排除回环、私有、链路本地、运营商NAT、保留等非公网地址
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // 100.64.0.0/10
+        || (a == 192 && b == 0 && c == 0) // 192.0.0.0/24
+        || (a == 198 && (b == 18 || b == 19)) // 198.18.0.0/15
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00 // fc00::/7 唯一本地地址
+        || (first & 0xffc0) == 0xfe80 // fe80::/10 链路本地地址
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8) // 2001:db8::/32 文档地址
+        || (first == 0x0064 && ip.segments()[1] == 0xff9b)) // 64:ff9b::/96 NAT64，可能映射到内网IPv4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+
+    // 在本地随机端口启动元数据服务，返回服务地址；该地址作为网关传入，第一跳不受公网限制
+    fn mock_metadata_server() -> String {
+        let server = HttpServer::new(|| {
+            App::new()
+                .route(
+                    "/metadata.json",
+                    web::get().to(|| async { HttpResponse::Ok().json(serde_json::json!({ "name": "Token #1" })) }),
+                )
+                .route(
+                    "/to-loopback",
+                    web::get().to(|| async {
+                        HttpResponse::Found()
+                            .insert_header(("Location", "http://localhost:9/metadata.json"))
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/to-metadata-service",
+                    web::get().to(|| async {
+                        HttpResponse::Found()
+                            .insert_header(("Location", "http://169.254.169.254/latest/meta-data/"))
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/to-mapped",
+                    web::get().to(|| async {
+                        HttpResponse::Found()
+                            .insert_header(("Location", "http://[::ffff:10.0.0.1]/metadata.json"))
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/same-host",
+                    web::get().to(|| async {
+                        HttpResponse::Found().insert_header(("Location", "/metadata.json")).finish()
+                    }),
+                )
+                .route(
+                    "/loop",
+                    web::get().to(|| async { HttpResponse::Found().insert_header(("Location", "/loop")).finish() }),
+                )
+                .route(
+                    "/large",
+                    web::get().to(|| async { HttpResponse::Ok().body(vec![b' '; MAX_METADATA_SIZE + 1]) }),
+                )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("failed to bind mock server");
+        let address = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+
+        format!("http://{}", address)
+    }
+
+    fn ip(addr: &str) -> IpAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn rejects_non_public_ipv4() {
+        for addr in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "198.18.0.1",
+            "240.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip(addr)), "{} should not be public", addr);
+        }
+        assert!(is_public_ip(ip("8.8.8.8")));
+        assert!(is_public_ip(ip("172.32.0.1")));
+    }
+
+    #[test]
+    fn rejects_non_public_ipv6() {
+        for addr in ["::1", "::", "fe80::1", "fc00::1", "fd12:3456::1", "ff02::1", "2001:db8::1", "64:ff9b::a00:1"] {
+            assert!(!is_public_ip(ip(addr)), "{} should not be public", addr);
+        }
+        assert!(is_public_ip(ip("2606:4700:4700::1111")));
+    }
+
+    #[test]
+    fn checks_ipv4_mapped_ipv6_as_ipv4() {
+        for addr in ["::ffff:127.0.0.1", "::ffff:10.0.0.1", "::ffff:169.254.169.254", "::ffff:192.168.0.1"] {
+            assert!(!is_public_ip(ip(addr)), "{} should not be public", addr);
+        }
+        assert!(is_public_ip(ip("::ffff:8.8.8.8")));
+    }
+
+    #[actix_web::test]
+    async fn rejects_private_hosts() {
+        for url in [
+            "http://127.0.0.1:9/metadata.json",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]:9/metadata.json",
+            "http://[::ffff:192.168.0.1]/metadata.json",
+            "http://localhost:9/metadata.json",
+        ] {
+            let err = fetch_bytes(url, &[]).await.unwrap_err();
+            assert!(err.contains("not a public address"), "{}: {}", url, err);
+        }
+        assert!(fetch_bytes("file:///etc/passwd", &[]).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn rechecks_each_redirect() {
+        let server = mock_metadata_server();
+        let gateways = [server.clone()];
+
+        let body = fetch_bytes(&format!("{}/metadata.json", server), &gateways).await.unwrap();
+        assert_eq!(body, br#"{"name":"Token #1"}"#);
+        // 同一网关内的跳转允许
+        let body = fetch_bytes(&format!("{}/same-host", server), &gateways).await.unwrap();
+        assert_eq!(body, br#"{"name":"Token #1"}"#);
+
+        for path in ["/to-loopback", "/to-metadata-service", "/to-mapped"] {
+            let err = fetch_bytes(&format!("{}{}", server, path), &gateways).await.unwrap_err();
+            assert!(err.contains("not a public address"), "{}: {}", path, err);
+        }
+
+        let err = fetch_bytes(&format!("{}/loop", server), &gateways).await.unwrap_err();
+        assert!(err.contains("too many redirects"), "{}", err);
+        let err = fetch_bytes(&format!("{}/large", server), &gateways).await.unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn decodes_data_uris() {
+        assert_eq!(decode_data_uri("data:application/json,{}").unwrap(), b"{}");
+        assert_eq!(
+            decode_data_uri("data:application/json;base64,eyJuYW1lIjoiYSJ9").unwrap(),
+            br#"{"name":"a"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_data_uris() {
+        assert!(decode_data_uri("data:application/json;base64,!!!not-base64").is_none());
+        assert!(decode_data_uri("data:application/json").is_none());
+        assert!(decode_data_uri("https://example.com/metadata.json").is_none());
+    }
+
+    #[test]
+    fn rejects_oversized_data_uris() {
+        let plain = format!("data:application/json,{}", " ".repeat(MAX_METADATA_SIZE + 1));
+        assert!(decode_data_uri(&plain).is_none());
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(vec![b' '; MAX_METADATA_SIZE + 1]);
+        assert!(decode_data_uri(&format!("data:application/json;base64,{}", encoded)).is_none());
+        let encoded = base64::engine::general_purpose::STANDARD.encode(vec![b' '; MAX_METADATA_SIZE]);
+        assert!(decode_data_uri(&format!("data:application/json;base64,{}", encoded)).is_some());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::utils::arweave::Tag;
+use crate::utils::storage_backend::{is_sha256_hex, sha256_hex, verify_sha256, StorageBackend, BACKEND_S3};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, Url};
+use sha2::Sha256;
+use std::env;
+use std::time::Duration;
+
+// 兼容S3 API的对象存储（AWS S3、MinIO等），使用路径风格地址和SigV4签名
+const DEFAULT_REGION: &str = "us-east-1";
+const SERVICE: &str = "s3";
+// 请求超时（秒），通过 S3_TIMEOUT_SECS 配置
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// S3兼容存储，对象键为可选前缀加内容的SHA-256，读取时校验内容
+pub struct S3Storage {
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    prefix: String,
+    public_url: Option<String>,
+    client: Client,
+}
+
+impl S3Storage {
+    // S3_ENDPOINT：服务地址（如 http://localhost:9000）；S3_BUCKET：存储桶；S3_REGION：区域
+    // S3_ACCESS_KEY_ID、S3_SECRET_ACCESS_KEY：访问密钥；S3_PREFIX：对象键前缀
+    // S3_PUBLIC_URL：公开访问地址（如CDN），不配置时使用 endpoint/bucket
+    pub fn from_env() -> Result<Self, String> {
+        let required = |name: &str| {
+            env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| format!("未配置{}", name))
+        };
+
+        let endpoint = required("S3_ENDPOINT")?;
+        let endpoint = Url::parse(endpoint.trim_end_matches('/')).map_err(|e| format!("S3_ENDPOINT无效: {}", e))?;
+        if endpoint.host_str().is_none() {
+            return Err("S3_ENDPOINT缺少主机名".to_string());
+        }
+        let timeout = env::var("S3_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+        Ok(Self {
+            endpoint,
+            bucket: required("S3_BUCKET")?,
+            region: env::var("S3_REGION")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| DEFAULT_REGION.to_string()),
+            access_key: required("S3_ACCESS_KEY_ID")?,
+            secret_key: required("S3_SECRET_ACCESS_KEY")?,
+            prefix: env::var("S3_PREFIX")
+                .unwrap_or_default()
+                .trim_matches('/')
+                .to_string(),
+            public_url: env::var("S3_PUBLIC_URL")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.trim_end_matches('/').to_string()),
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout))
+                .build()
+                .expect("Failed to create HTTP client"),
+        })
+    }
+
+    fn object_key(&self, reference: &str) -> String {
+        if self.prefix.is_empty() {
+            reference.to_string()
+        } else {
+            format!("{}/{}", self.prefix, reference)
+        }
+    }
+
+    // 路径风格：/bucket/key
+    fn object_path(&self, reference: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket),
+            uri_encode(&self.object_key(reference))
+        )
+    }
+
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+
+    // 构造带SigV4签名的请求
+    fn signed_request(&self, method: Method, reference: &str, body: &[u8]) -> reqwest::RequestBuilder {
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(body);
+        let path = self.object_path(reference);
+        let host = self.host();
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, SERVICE);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+
+        let signing_key = [self.region.as_str(), SERVICE, "aws4_request"]
+            .iter()
+            .fold(hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes()), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        );
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+        self.client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Storage {
+    fn name(&self) -> &'static str {
+        BACKEND_S3
+    }
+
+    fn reference_for(&self, data: &[u8]) -> Option<String> {
+        Some(sha256_hex(data))
+    }
+
+    async fn put(&self, data: &[u8], content_type: &str, _tags: &[Tag]) -> Result<String, String> {
+        let reference = sha256_hex(data);
+        let response = self
+            .signed_request(Method::PUT, &reference, data)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(data.to_vec())
+            .send()
+            .await
+            .map_err(|e| format!("S3上传失败: {}", e))?;
+        check_response(response, "S3上传失败").await?;
+
+        Ok(reference)
+    }
+
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String> {
+        if !is_sha256_hex(reference) {
+            return Err(format!("无效的内容引用: {}", reference));
+        }
+
+        let response = self
+            .signed_request(Method::GET, reference, &[])
+            .send()
+            .await
+            .map_err(|e| format!("S3读取失败: {}", e))?;
+        let response = check_response(response, "S3读取失败").await?;
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| format!("读取S3响应失败: {}", e))?
+            .to_vec();
+        verify_sha256(reference, &data)?;
+
+        Ok(data)
+    }
+
+    fn url(&self, reference: &str) -> String {
+        match &self.public_url {
+            Some(public_url) => format!("{}/{}", public_url, self.object_key(reference)),
+            None => {
+                let mut url = self.endpoint.clone();
+                url.set_path(&self.object_path(reference));
+                url.to_string()
+            }
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC可以接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// SigV4的URI编码：保留未保留字符和路径分隔符
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+async fn check_response(response: reqwest::Response, action: &str) -> Result<reqwest::Response, String> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_else(|_| "未知错误".into());
+        return Err(format!("{}({}): {}", action, status, error_text));
+    }
+    Ok(response)
+}
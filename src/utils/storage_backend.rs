@@ -0,0 +1,334 @@
+use crate::utils::arweave::{arweave_client, Tag};
+use crate::utils::cid::compute_cid_v1;
+use crate::utils::ipfs::ipfs_backend;
+use crate::utils::s3::S3Storage;
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// 存储后端名称，记录在发件箱中，通过 STORAGE_MEDIA_BACKEND、STORAGE_RECORD_BACKEND 选择
+pub const BACKEND_IPFS: &str = "ipfs";
+pub const BACKEND_ARWEAVE: &str = "arweave";
+pub const BACKEND_LOCAL: &str = "local";
+pub const BACKEND_S3: &str = "s3";
+pub const BACKEND_MEMORY: &str = "memory";
+
+// 默认媒体文件存IPFS，帖子和评论正文存Arweave
+const DEFAULT_MEDIA_BACKEND: &str = BACKEND_IPFS;
+const DEFAULT_RECORD_BACKEND: &str = BACKEND_ARWEAVE;
+// 本地存储目录和访问地址，分别通过 STORAGE_LOCAL_DIR、STORAGE_LOCAL_BASE_URL 配置
+const DEFAULT_LOCAL_DIR: &str = "./data/storage";
+const DEFAULT_LOCAL_BASE_URL: &str = "/storage";
+
+/// 内容存储后端，媒体文件和永久记录都通过该接口读写
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// 后端名称
+    fn name(&self) -> &'static str;
+
+    /// 上传前即可确定的内容引用，按内容寻址的后端返回Some，用于发帖时立即返回
+    fn reference_for(&self, data: &[u8]) -> Option<String>;
+
+    /// 保存内容，返回内容引用（CID、交易ID或对象键）
+    async fn put(&self, data: &[u8], content_type: &str, tags: &[Tag]) -> Result<String, String>;
+
+    /// 按引用读取内容，按内容寻址的后端会校验内容与引用一致
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String>;
+
+    /// 内容的访问地址
+    fn url(&self, reference: &str) -> String;
+}
+
+/// IPFS存储，使用全局IPFS后端，引用为CIDv1
+pub struct IpfsStorage;
+
+#[async_trait]
+impl StorageBackend for IpfsStorage {
+    fn name(&self) -> &'static str {
+        BACKEND_IPFS
+    }
+
+    fn reference_for(&self, data: &[u8]) -> Option<String> {
+        Some(compute_cid_v1(data))
+    }
+
+    async fn put(&self, data: &[u8], _content_type: &str, _tags: &[Tag]) -> Result<String, String> {
+        ipfs_backend().add(data).await
+    }
+
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String> {
+        ipfs_backend().cat(reference).await
+    }
+
+    fn url(&self, reference: &str) -> String {
+        ipfs_backend().gateway_url(reference)
+    }
+}
+
+/// Arweave存储，单条内容签名为数据项后打包上传，引用为数据项ID
+/// 发件箱中的记录由存储服务批量打包并跟踪确认，不经过put
+pub struct ArweaveStorage;
+
+#[async_trait]
+impl StorageBackend for ArweaveStorage {
+    fn name(&self) -> &'static str {
+        BACKEND_ARWEAVE
+    }
+
+    fn reference_for(&self, _data: &[u8]) -> Option<String> {
+        None
+    }
+
+    async fn put(&self, data: &[u8], content_type: &str, tags: &[Tag]) -> Result<String, String> {
+        let client = arweave_client();
+        let mut all_tags = Vec::with_capacity(tags.len() + 2);
+        if !tags.iter().any(|tag| tag.name == "App-Name") {
+            all_tags.push(Tag::new("App-Name", client.app_name()));
+        }
+        if !tags.iter().any(|tag| tag.name == "Content-Type") {
+            all_tags.push(Tag::new("Content-Type", content_type));
+        }
+        all_tags.extend(tags.iter().cloned());
+
+        let item = client.create_data_item(data, &all_tags)?;
+        client.post_bundle(std::slice::from_ref(&item)).await?;
+
+        Ok(item.id)
+    }
+
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String> {
+        arweave_client().get_data(reference).await
+    }
+
+    fn url(&self, reference: &str) -> String {
+        arweave_client().get_url(reference)
+    }
+}
+
+/// 本地文件存储，适用于开发和单机部署，引用为内容的SHA-256
+/// 文件按引用前两位分目录保存，访问地址需要由反向代理或静态文件服务提供
+pub struct LocalFsStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalFsStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let root = env::var("STORAGE_LOCAL_DIR")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_LOCAL_DIR.to_string());
+        let base_url = env::var("STORAGE_LOCAL_BASE_URL")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_LOCAL_BASE_URL.to_string());
+
+        Self::new(root, &base_url)
+    }
+
+    // 引用只允许SHA-256十六进制，避免路径穿越
+    fn path_for(&self, reference: &str) -> Result<PathBuf, String> {
+        if !is_sha256_hex(reference) {
+            return Err(format!("无效的内容引用: {}", reference));
+        }
+        Ok(self.root.join(&reference[..2]).join(reference))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalFsStorage {
+    fn name(&self) -> &'static str {
+        BACKEND_LOCAL
+    }
+
+    fn reference_for(&self, data: &[u8]) -> Option<String> {
+        Some(sha256_hex(data))
+    }
+
+    async fn put(&self, data: &[u8], _content_type: &str, _tags: &[Tag]) -> Result<String, String> {
+        let reference = sha256_hex(data);
+        let path = self.path_for(&reference)?;
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(reference);
+        }
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| format!("创建存储目录失败: {}", e))?;
+        }
+        // 先写临时文件再重命名，避免读到写了一半的内容
+        let tmp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp_path, data)
+            .await
+            .map_err(|e| format!("写入本地存储失败: {}", e))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| format!("写入本地存储失败: {}", e))?;
+
+        Ok(reference)
+    }
+
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let data = tokio::fs::read(self.path_for(reference)?)
+            .await
+            .map_err(|e| format!("读取本地存储{}失败: {}", reference, e))?;
+        verify_sha256(reference, &data)?;
+
+        Ok(data)
+    }
+
+    // 与文件的分目录保存方式一致
+    fn url(&self, reference: &str) -> String {
+        match reference.get(..2) {
+            Some(prefix) => format!("{}/{}/{}", self.base_url, prefix, reference),
+            None => format!("{}/{}", self.base_url, reference),
+        }
+    }
+}
+
+/// 内存存储，进程退出后内容丢失，仅用于测试和本地调试，引用为内容的SHA-256
+#[derive(Default)]
+pub struct MemoryStorage {
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    fn name(&self) -> &'static str {
+        BACKEND_MEMORY
+    }
+
+    fn reference_for(&self, data: &[u8]) -> Option<String> {
+        Some(sha256_hex(data))
+    }
+
+    async fn put(&self, data: &[u8], _content_type: &str, _tags: &[Tag]) -> Result<String, String> {
+        let reference = sha256_hex(data);
+        self.blobs
+            .lock()
+            .map_err(|_| "内存存储锁已损坏".to_string())?
+            .insert(reference.clone(), data.to_vec());
+
+        Ok(reference)
+    }
+
+    async fn get(&self, reference: &str) -> Result<Vec<u8>, String> {
+        self.blobs
+            .lock()
+            .map_err(|_| "内存存储锁已损坏".to_string())?
+            .get(reference)
+            .cloned()
+            .ok_or_else(|| format!("内容{}不存在", reference))
+    }
+
+    fn url(&self, reference: &str) -> String {
+        format!("memory://{}", reference)
+    }
+}
+
+/// 按名称创建存储后端
+pub fn create_backend(name: &str) -> Result<Arc<dyn StorageBackend>, String> {
+    match name {
+        BACKEND_IPFS => Ok(Arc::new(IpfsStorage)),
+        BACKEND_ARWEAVE => Ok(Arc::new(ArweaveStorage)),
+        BACKEND_LOCAL => Ok(Arc::new(LocalFsStorage::from_env())),
+        BACKEND_S3 => Ok(Arc::new(S3Storage::from_env()?)),
+        BACKEND_MEMORY => Ok(Arc::new(MemoryStorage::new())),
+        _ => Err(format!("未知的存储后端: {}", name)),
+    }
+}
+
+/// 媒体文件（图片、缩略图）使用的存储后端
+pub fn media_backend_name() -> String {
+    env::var("STORAGE_MEDIA_BACKEND")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_MEDIA_BACKEND.to_string())
+}
+
+/// 帖子和评论正文等永久记录使用的存储后端
+pub fn record_backend_name() -> String {
+    env::var("STORAGE_RECORD_BACKEND")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_RECORD_BACKEND.to_string())
+}
+
+/// 切换配置前使用过的存储后端，逗号分隔，旧的发件箱记录和媒体文件仍从这些后端读取
+pub fn legacy_backend_names() -> Vec<String> {
+    env::var("STORAGE_LEGACY_BACKENDS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+pub(crate) fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+// 校验按SHA-256寻址的内容
+pub(crate) fn verify_sha256(reference: &str, data: &[u8]) -> Result<(), String> {
+    let actual = sha256_hex(data);
+    if actual != reference {
+        return Err(format!("内容哈希{}与引用{}不一致", actual, reference));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn local_round_trip() {
+        let root = env::temp_dir().join(format!("storage-test-{}", uuid::Uuid::new_v4()));
+        let storage = LocalFsStorage::new(&root, "https://cdn.example.com/storage/");
+        let data = b"local storage";
+
+        let reference = storage.put(data, "text/plain", &[]).await.unwrap();
+        assert_eq!(Some(reference.clone()), storage.reference_for(data));
+        assert!(root.join(&reference[..2]).join(&reference).exists());
+        assert_eq!(storage.get(&reference).await.unwrap(), data);
+        // 访问地址与文件的分目录一致
+        assert_eq!(
+            storage.url(&reference),
+            format!("https://cdn.example.com/storage/{}/{}", &reference[..2], reference)
+        );
+        assert!(storage.get("../etc/passwd").await.is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn memory_round_trip() {
+        let storage = MemoryStorage::new();
+        let reference = storage.put(b"memory", "text/plain", &[]).await.unwrap();
+
+        assert_eq!(reference, sha256_hex(b"memory"));
+        assert_eq!(storage.get(&reference).await.unwrap(), b"memory");
+        assert!(storage.get(&sha256_hex(b"missing")).await.is_err());
+    }
+}